@@ -1,23 +1,43 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
+    middleware,
     response::Json,
     routing::{get, post},
     Router,
 };
 use fluxe_core::{
-    data_structures::{IngressReceipt, ExitReceipt},
-    server_verifier::{ServerVerifier, TransactionBuilder, TransactionData},
+    crypto::{AttestationRegistry, PedersenCommitment, SchnorrSignature, SignedAttestation},
+    data_structures::{CallbackInvocation, IngressReceipt, ExitReceipt, Note},
+    server_verifier::{claimed_new_roots, ServerVerifier, TransactionBuilder, TransactionData, VerifiedTransaction},
+    state_manager::{MaintenanceConfig, MaintenanceReport, StateManager},
     types::*,
 };
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use crate::idempotency::{replay_idempotent, IdempotencyCache};
+use crate::rate_limit::{enforce_rate_limit, RateLimiter};
 
 /// Main Fluxe API service implementing section 12.5 endpoints
 pub struct FluxeApi {
     /// Server verifier for batch processing
     pub verifier: Arc<Mutex<ServerVerifier>>,
+    
+    /// Registered attestation providers and accepted attestations
+    pub attestations: Arc<Mutex<AttestationRegistry>>,
+    
+    /// Periodic verifier maintenance run by `serve` (None to disable)
+    pub maintenance: Option<MaintenanceConfig>,
+    
+    /// Per-client request throttling applied to every route (None to disable)
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    
+    /// Responses replayed for retried submissions carrying an Idempotency-Key (None to disable)
+    pub idempotency: Option<Arc<IdempotencyCache>>,
 }
 
 /// API response wrapper
@@ -61,6 +81,8 @@ pub struct SubmitBurnRequest {
     pub asset_type: AssetType,
     pub amount: u64,
     pub nullifier: String, // Hex-encoded
+    pub consumed_cm: String, // Hex-encoded commitment of the burned note
+    pub exit_destination: String, // Hex-encoded, must be in EXIT_ALLOW_ROOT
     pub proof: Vec<u8>,
     pub public_inputs: Vec<String>,
 }
@@ -80,25 +102,64 @@ pub struct SubmitObjectUpdateRequest {
     pub proof: Vec<u8>,
     pub public_inputs: Vec<String>,
     pub callback_operations: Vec<SerializableCallbackOp>,
+    #[serde(default)]
+    pub owner: Option<String>, // Hex-encoded, indexes a not-yet-indexed owner's object; must be the proof's owner input
+}
+
+/// Attestation pushed to drive a compliance update of an object
+#[derive(Deserialize)]
+pub struct SubmitAttestationRequest {
+    pub attestation: String, // Hex-encoded canonical SignedAttestation
+    pub object_cm: String, // Hex-encoded, the object whose state the attestation updates; must be its user_cm
+    /// Object update applying the attested state change, queued once the attestation verifies
+    pub object_update: Option<SubmitObjectUpdateRequest>,
+}
+
+/// Any submission tagged with its transaction type
+#[derive(Deserialize)]
+#[serde(tag = "tx_type", rename_all = "snake_case")]
+pub enum TypedSubmission {
+    Mint(SubmitMintRequest),
+    Burn(SubmitBurnRequest),
+    Transfer(SubmitTransferRequest),
+    ObjectUpdate(SubmitObjectUpdateRequest),
+}
+
+/// Ordered batch of submissions, validated and queued all-or-nothing
+#[derive(Deserialize)]
+pub struct SubmitBatchRequest {
+    pub transactions: Vec<TypedSubmission>,
 }
 
 /// Serializable versions of core types for API
 #[derive(Serialize, Deserialize)]
 pub struct SerializableNote {
     pub asset_type: AssetType,
+    pub v_comm: String, // Hex-encoded compressed value commitment
     pub owner_addr: String, // Hex-encoded
     pub psi: [u8; 32],
     pub chain_hint: ChainHint,
     pub pool_id: PoolId,
+    // Hex-encoded; omitted hashes are zero, as in `Note::new`
+    #[serde(default)]
+    pub compliance_hash: Option<String>,
+    #[serde(default)]
+    pub lineage_hash: Option<String>,
+    #[serde(default)]
+    pub callbacks_hash: Option<String>,
+    #[serde(default)]
+    pub memo_hash: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SerializableCallbackOp {
-    pub op_type: String, // "add" or "process"
-    pub ticket: Option<String>, // Hex-encoded
+    pub op_type: String, // "add", "process" or "freeze"
+    pub ticket: Option<String>, // Hex-encoded, for "add" and "process"
     pub payload: Option<Vec<u8>>,
     pub timestamp: Option<Time>,
-    pub signature: Option<Vec<u8>>,
+    pub signature: Option<Vec<u8>>, // Compressed SchnorrSignature
+    #[serde(default)]
+    pub owner: Option<String>, // Hex-encoded, for "freeze"
 }
 
 /// State query responses
@@ -112,6 +173,25 @@ pub struct StateRootsResponse {
     pub exit_root: String,
     pub sanctions_root: String,
     pub pool_rules_root: String,
+    pub exit_allow_root: String,
+    pub authorized_minters_root: String,
+}
+
+impl From<&StateRoots> for StateRootsResponse {
+    fn from(roots: &StateRoots) -> Self {
+        Self {
+            cmt_root: field_to_hex(&roots.cmt_root),
+            nft_root: field_to_hex(&roots.nft_root),
+            obj_root: field_to_hex(&roots.obj_root),
+            cb_root: field_to_hex(&roots.cb_root),
+            ingress_root: field_to_hex(&roots.ingress_root),
+            exit_root: field_to_hex(&roots.exit_root),
+            sanctions_root: field_to_hex(&roots.sanctions_root),
+            pool_rules_root: field_to_hex(&roots.pool_rules_root),
+            exit_allow_root: field_to_hex(&roots.exit_allow_root),
+            authorized_minters_root: field_to_hex(&roots.authorized_minters_root),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -122,6 +202,57 @@ pub struct SupplyResponse {
     pub current_supply: u64,
 }
 
+#[derive(Serialize)]
+pub struct StateStatsResponse {
+    pub spent_nullifiers: usize,
+    pub nft_leaf_count: usize,
+    pub nft_utilization: f64,
+}
+
+#[derive(Serialize)]
+pub struct BatchItemResult {
+    /// Private transaction id, if the submission could be built
+    pub tx_id: Option<String>,
+    pub accepted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SubmitBatchResponse {
+    /// True if every item passed and the whole batch was queued
+    pub queued: bool,
+    pub results: Vec<BatchItemResult>,
+}
+
+#[derive(Serialize)]
+pub struct SubmitAttestationResponse {
+    /// Attested KYC level of the user after this attestation
+    pub level: u8,
+    /// Private id of the queued object update, if one was submitted
+    pub tx_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TxStatusResponse {
+    pub tx_id: String,
+    pub pending: bool,
+}
+
+/// Inclusion receipt of a batched transaction, field elements hex-encoded
+#[derive(Serialize)]
+pub struct InclusionReceiptResponse {
+    pub batch_id: u64,
+    pub tx_private_id: String,
+    pub index: usize,
+    pub path: Vec<String>, // Siblings from the leaf to the batch's tx root
+}
+
+#[derive(Serialize)]
+pub struct ObjectByOwnerResponse {
+    pub owner_addr: String,
+    pub object_cm: String, // Hex-encoded latest object commitment
+}
+
 #[derive(Serialize)]
 pub struct ProofResponse {
     pub exists: bool,
@@ -134,23 +265,80 @@ impl FluxeApi {
     pub fn new(verifier: ServerVerifier) -> Self {
         Self {
             verifier: Arc::new(Mutex::new(verifier)),
+            attestations: Arc::new(Mutex::new(AttestationRegistry::new())),
+            maintenance: None,
+            rate_limiter: None,
+            idempotency: None,
         }
     }
     
+    /// Accept attestations from the providers registered in `registry`
+    pub fn with_attestation_registry(mut self, registry: AttestationRegistry) -> Self {
+        self.attestations = Arc::new(Mutex::new(registry));
+        self
+    }
+    
+    /// Run `ServerVerifier::maintenance` every `config.interval` while serving
+    pub fn with_maintenance(mut self, config: MaintenanceConfig) -> Self {
+        self.maintenance = Some(config);
+        self
+    }
+    
+    /// Throttle clients with `limiter`; requests over its limits get 429
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(Arc::new(limiter));
+        self
+    }
+    
+    /// Answer a POST repeating an earlier Idempotency-Key with the first response, without re-running it
+    pub fn with_idempotency(mut self, cache: IdempotencyCache) -> Self {
+        self.idempotency = Some(Arc::new(cache));
+        self
+    }
+    
+    /// Spawn the periodic maintenance task on the current runtime
+    /// The returned receiver always holds the latest pass's report
+    pub fn spawn_maintenance(
+        &self,
+        config: MaintenanceConfig,
+    ) -> (tokio::task::JoinHandle<()>, watch::Receiver<MaintenanceReport>) {
+        let verifier = self.verifier.clone();
+        let (reports, receiver) = watch::channel(MaintenanceReport::default());
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.interval);
+            // The first tick completes immediately; skip it so startup is not slowed
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let report = verifier.lock().unwrap().maintenance(&config);
+                // Kept even when no one is watching
+                reports.send_replace(report);
+            }
+        });
+        (handle, receiver)
+    }
+    
     /// Create the Axum router with all endpoints
     pub fn router(self) -> Router {
+        let rate_limiter = self.rate_limiter.clone();
+        let idempotency = self.idempotency.clone();
         let shared_state = Arc::new(self);
         
-        Router::new()
+        let router = Router::new()
             // Transaction submission endpoints
             .route("/submit/mint", post(submit_mint))
             .route("/submit/burn", post(submit_burn))
             .route("/submit/transfer", post(submit_transfer))
             .route("/submit/object_update", post(submit_object_update))
+            .route("/submit/validate", post(submit_validate))
+            .route("/submit/batch", post(submit_batch))
+            .route("/attestations/submit", post(submit_attestation))
             
             // State query endpoints
             .route("/state/roots", get(get_roots))
+            .route("/state/empty_roots", get(get_empty_roots))
             .route("/state/supply/:asset_type", get(get_supply))
+            .route("/state/stats", get(get_stats))
             
             // Proof query endpoints
             .route("/proofs/commitment/:cm", get(get_commitment_proof))
@@ -158,24 +346,44 @@ impl FluxeApi {
             .route("/proofs/object/:obj", get(get_object_proof))
             .route("/proofs/sanctions/:addr", get(get_sanctions_proof))
             
+            // Object queries
+            .route("/objects/by-owner/:addr", get(get_object_by_owner))
+            
             // Batch processing
             .route("/batch/process", post(process_batch))
             .route("/batch/status", get(get_batch_status))
+            .route("/tx/status/:id", get(get_tx_status))
+            .route("/tx/:private_id/receipt", get(get_tx_receipt))
             
             // Health and info
             .route("/health", get(health_check))
-            .route("/info", get(get_info))
-            
-            .with_state(shared_state)
+            .route("/info", get(get_info));
+        
+        // Applied per matched route so keys and limits can be scoped by route path;
+        // replays sit inside the rate limiter and still count against it
+        let router = match idempotency {
+            Some(cache) => router.route_layer(middleware::from_fn_with_state(cache, replay_idempotent)),
+            None => router,
+        };
+        let router = match rate_limiter {
+            Some(limiter) => router.route_layer(middleware::from_fn_with_state(limiter, enforce_rate_limit)),
+            None => router,
+        };
+        
+        router.with_state(shared_state)
     }
     
     /// Start the API server
     pub async fn serve(self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(config) = self.maintenance.clone() {
+            self.spawn_maintenance(config);
+        }
         let app = self.router();
         let listener = TcpListener::bind(addr).await?;
         
         println!("Fluxe API server starting on {}", addr);
-        axum::serve(listener, app).await?;
+        // Peer addresses key the rate limiter for anonymous clients
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
         Ok(())
     }
 }
@@ -184,10 +392,10 @@ impl FluxeApi {
 async fn submit_mint(
     State(api): State<Arc<FluxeApi>>,
     Json(req): Json<SubmitMintRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> (StatusCode, Json<ApiResponse<String>>) {
     match handle_submit_mint(api, req).await {
-        Ok(tx_id) => Ok(Json(ApiResponse::success(tx_id))),
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Ok(tx_id) => (StatusCode::OK, Json(ApiResponse::success(tx_id))),
+        Err(e) => submission_error(e),
     }
 }
 
@@ -195,6 +403,21 @@ async fn handle_submit_mint(
     api: Arc<FluxeApi>,
     req: SubmitMintRequest,
 ) -> Result<String, FluxeError> {
+    let old_roots = api.verifier.lock().unwrap().pending_roots();
+    let tx = build_mint_transaction(req, old_roots)?;
+    let tx_id = field_to_hex(&tx.private_id());
+    
+    // Add to verifier
+    let mut verifier = api.verifier.lock().unwrap();
+    verifier.add_transaction(tx)?;
+    
+    Ok(tx_id)
+}
+
+fn build_mint_transaction(
+    req: SubmitMintRequest,
+    old_roots: StateRoots,
+) -> Result<VerifiedTransaction, FluxeError> {
     // Parse proof and public inputs (simplified)
     let proof = parse_proof_from_bytes(&req.proof)?;
     let public_inputs = parse_public_inputs(&req.public_inputs)?;
@@ -206,44 +429,36 @@ async fn handle_submit_mint(
     let ingress_receipt = IngressReceipt::new(
         req.asset_type,
         req.amount.into(), // Convert u64 to Amount
-        compute_notes_commitment(&notes_out),
+        IngressReceipt::beneficiary_commitment(&notes_out),
         0, // Would use actual nonce
     );
     
-    // Build transaction
-    let verifier = api.verifier.lock().unwrap();
-    let old_roots = verifier.get_current_roots().clone();
-    drop(verifier);
+    let transaction_data = TransactionData::Mint {
+        asset_type: req.asset_type,
+        amount: req.amount.into(), // Convert u64 to Amount
+        notes_out,
+        ingress_receipt,
+    };
     
-    // For new roots, we'd need to compute what they would be after this transaction
-    // For now, use old roots as placeholder
-    let new_roots = old_roots.clone();
+    // New roots are the ones the proof commits to
+    let new_roots = claimed_new_roots(&old_roots, &public_inputs, &transaction_data)?;
     
     let tx = TransactionBuilder::new_mint(old_roots, new_roots).build(
         proof,
         public_inputs,
-        TransactionData::Mint {
-            asset_type: req.asset_type,
-            amount: req.amount.into(), // Convert u64 to Amount
-            notes_out,
-            ingress_receipt,
-        },
+        transaction_data,
     );
     
-    // Add to verifier
-    let mut verifier = api.verifier.lock().unwrap();
-    verifier.add_transaction(tx)?;
-    
-    Ok(format!("mint_tx_{}", req.asset_type))
+    Ok(tx)
 }
 
 async fn submit_burn(
     State(api): State<Arc<FluxeApi>>,
     Json(req): Json<SubmitBurnRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> (StatusCode, Json<ApiResponse<String>>) {
     match handle_submit_burn(api, req).await {
-        Ok(tx_id) => Ok(Json(ApiResponse::success(tx_id))),
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Ok(tx_id) => (StatusCode::OK, Json(ApiResponse::success(tx_id))),
+        Err(e) => submission_error(e),
     }
 }
 
@@ -251,47 +466,59 @@ async fn handle_submit_burn(
     api: Arc<FluxeApi>,
     req: SubmitBurnRequest,
 ) -> Result<String, FluxeError> {
+    let old_roots = api.verifier.lock().unwrap().pending_roots();
+    let tx = build_burn_transaction(req, old_roots)?;
+    let tx_id = field_to_hex(&tx.private_id());
+    
+    let mut verifier = api.verifier.lock().unwrap();
+    verifier.add_transaction(tx)?;
+    
+    Ok(tx_id)
+}
+
+fn build_burn_transaction(
+    req: SubmitBurnRequest,
+    old_roots: StateRoots,
+) -> Result<VerifiedTransaction, FluxeError> {
     let proof = parse_proof_from_bytes(&req.proof)?;
     let public_inputs = parse_public_inputs(&req.public_inputs)?;
     let nullifier = parse_field_from_hex(&req.nullifier)?;
+    let consumed_cm = parse_field_from_hex(&req.consumed_cm)?;
+    let exit_destination = parse_field_from_hex(&req.exit_destination)?;
     
     let exit_receipt = ExitReceipt::new(
         req.asset_type,
         req.amount.into(), // Convert u64 to Amount
         nullifier,
         0, // Would use actual nonce
-    );
+    ).with_destination(exit_destination);
     
-    let verifier = api.verifier.lock().unwrap();
-    let old_roots = verifier.get_current_roots().clone();
-    drop(verifier);
+    let transaction_data = TransactionData::Burn {
+        asset_type: req.asset_type,
+        amount: req.amount.into(), // Convert u64 to Amount
+        nullifier,
+        consumed_cm,
+        exit_receipt,
+    };
     
-    let new_roots = old_roots.clone(); // Placeholder
+    let new_roots = claimed_new_roots(&old_roots, &public_inputs, &transaction_data)?;
     
     let tx = TransactionBuilder::new_burn(old_roots, new_roots).build(
         proof,
         public_inputs,
-        TransactionData::Burn {
-            asset_type: req.asset_type,
-            amount: req.amount.into(), // Convert u64 to Amount
-            nullifier,
-            exit_receipt,
-        },
+        transaction_data,
     );
     
-    let mut verifier = api.verifier.lock().unwrap();
-    verifier.add_transaction(tx)?;
-    
-    Ok(format!("burn_tx_{}", req.asset_type))
+    Ok(tx)
 }
 
 async fn submit_transfer(
     State(api): State<Arc<FluxeApi>>,
     Json(req): Json<SubmitTransferRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> (StatusCode, Json<ApiResponse<String>>) {
     match handle_submit_transfer(api, req).await {
-        Ok(tx_id) => Ok(Json(ApiResponse::success(tx_id))),
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Ok(tx_id) => (StatusCode::OK, Json(ApiResponse::success(tx_id))),
+        Err(e) => submission_error(e),
     }
 }
 
@@ -299,6 +526,20 @@ async fn handle_submit_transfer(
     api: Arc<FluxeApi>,
     req: SubmitTransferRequest,
 ) -> Result<String, FluxeError> {
+    let old_roots = api.verifier.lock().unwrap().pending_roots();
+    let tx = build_transfer_transaction(req, old_roots)?;
+    let tx_id = field_to_hex(&tx.private_id());
+    
+    let mut verifier = api.verifier.lock().unwrap();
+    verifier.add_transaction(tx)?;
+    
+    Ok(tx_id)
+}
+
+fn build_transfer_transaction(
+    req: SubmitTransferRequest,
+    old_roots: StateRoots,
+) -> Result<VerifiedTransaction, FluxeError> {
     let proof = parse_proof_from_bytes(&req.proof)?;
     let public_inputs = parse_public_inputs(&req.public_inputs)?;
     let nullifiers = req.nullifiers.iter()
@@ -306,34 +547,29 @@ async fn handle_submit_transfer(
         .collect::<Result<Vec<_>, _>>()?;
     let notes_out = convert_serializable_notes(&req.notes_out)?;
     
-    let verifier = api.verifier.lock().unwrap();
-    let old_roots = verifier.get_current_roots().clone();
-    drop(verifier);
+    let transaction_data = TransactionData::Transfer {
+        nullifiers,
+        notes_out,
+    };
     
-    let new_roots = old_roots.clone(); // Placeholder
+    let new_roots = claimed_new_roots(&old_roots, &public_inputs, &transaction_data)?;
     
     let tx = TransactionBuilder::new_transfer(old_roots, new_roots).build(
         proof,
         public_inputs,
-        TransactionData::Transfer {
-            nullifiers,
-            notes_out,
-        },
+        transaction_data,
     );
     
-    let mut verifier = api.verifier.lock().unwrap();
-    verifier.add_transaction(tx)?;
-    
-    Ok("transfer_tx".to_string())
+    Ok(tx)
 }
 
 async fn submit_object_update(
     State(api): State<Arc<FluxeApi>>,
     Json(req): Json<SubmitObjectUpdateRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> (StatusCode, Json<ApiResponse<String>>) {
     match handle_submit_object_update(api, req).await {
-        Ok(tx_id) => Ok(Json(ApiResponse::success(tx_id))),
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Ok(tx_id) => (StatusCode::OK, Json(ApiResponse::success(tx_id))),
+        Err(e) => submission_error(e),
     }
 }
 
@@ -341,32 +577,195 @@ async fn handle_submit_object_update(
     api: Arc<FluxeApi>,
     req: SubmitObjectUpdateRequest,
 ) -> Result<String, FluxeError> {
+    let old_roots = api.verifier.lock().unwrap().pending_roots();
+    let tx = build_object_update_transaction(req, old_roots)?;
+    let tx_id = field_to_hex(&tx.private_id());
+    
+    let mut verifier = api.verifier.lock().unwrap();
+    verifier.add_transaction(tx)?;
+    
+    Ok(tx_id)
+}
+
+fn build_object_update_transaction(
+    req: SubmitObjectUpdateRequest,
+    old_roots: StateRoots,
+) -> Result<VerifiedTransaction, FluxeError> {
     let proof = parse_proof_from_bytes(&req.proof)?;
     let public_inputs = parse_public_inputs(&req.public_inputs)?;
     let old_object_cm = parse_field_from_hex(&req.old_object_cm)?;
     let new_object_cm = parse_field_from_hex(&req.new_object_cm)?;
     let callback_ops = convert_serializable_callback_ops(&req.callback_operations)?;
+    let owner = req.owner.as_deref().map(parse_field_from_hex).transpose()?;
     
-    let verifier = api.verifier.lock().unwrap();
-    let old_roots = verifier.get_current_roots().clone();
-    drop(verifier);
+    let transaction_data = TransactionData::ObjectUpdate {
+        old_object_cm,
+        new_object_cm,
+        callback_ops,
+        owner,
+    };
     
-    let new_roots = old_roots.clone(); // Placeholder
+    let new_roots = claimed_new_roots(&old_roots, &public_inputs, &transaction_data)?;
     
     let tx = TransactionBuilder::new_transfer(old_roots, new_roots).build(
         proof,
         public_inputs,
-        TransactionData::ObjectUpdate {
-            old_object_cm,
-            new_object_cm,
-            callback_ops,
-        },
+        transaction_data,
     );
     
+    Ok(tx)
+}
+
+async fn submit_attestation(
+    State(api): State<Arc<FluxeApi>>,
+    Json(req): Json<SubmitAttestationRequest>,
+) -> Result<Json<ApiResponse<SubmitAttestationResponse>>, StatusCode> {
+    use ark_serialize::CanonicalDeserialize;
+    
+    // Malformed, unknown-provider or forged attestations are unprocessable
+    let bytes = hex::decode(req.attestation.trim_start_matches("0x"))
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    let attestation = SignedAttestation::deserialize_compressed(&*bytes)
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    api.attestations
+        .lock()
+        .unwrap()
+        .verify_attestation(&attestation)
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    
+    match handle_submit_attestation(api, attestation, req.object_cm, req.object_update).await {
+        Ok(response) => Ok(Json(ApiResponse::success(response))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+/// Record a verified attestation of `object_cm` together with the object update applying it:
+/// both registry and verifier stay locked, so either both land or neither does
+async fn handle_submit_attestation(
+    api: Arc<FluxeApi>,
+    attestation: SignedAttestation,
+    object_cm: String,
+    object_update: Option<SubmitObjectUpdateRequest>,
+) -> Result<SubmitAttestationResponse, FluxeError> {
+    let object_cm = parse_field_from_hex(&object_cm)?;
+    if attestation.user_cm != object_cm {
+        return Err(FluxeError::ComplianceViolation(
+            "Attestation is for a different object".to_string()
+        ));
+    }
+    
+    let mut registry = api.attestations.lock().unwrap();
+    registry.verify_attestation(&attestation)?;
+    
+    let tx_id = match object_update {
+        Some(update) => {
+            if parse_field_from_hex(&update.old_object_cm)? != object_cm {
+                return Err(FluxeError::Other(
+                    "Object update does not spend the attested object".to_string()
+                ));
+            }
+            let mut verifier = api.verifier.lock().unwrap();
+            let tx = build_object_update_transaction(update, verifier.pending_roots())?;
+            let tx_id = field_to_hex(&tx.private_id());
+            verifier.add_transaction(tx)?;
+            Some(tx_id)
+        }
+        None => None,
+    };
+    
+    // Verified above under the same lock, so recording can no longer fail
+    registry.add_attestation(attestation)?;
+    
+    Ok(SubmitAttestationResponse {
+        level: registry.compliance_level(object_cm),
+        tx_id,
+    })
+}
+
+async fn submit_validate(
+    State(api): State<Arc<FluxeApi>>,
+    Json(req): Json<TypedSubmission>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    match handle_submit_validate(api, req).await {
+        Ok(result) => Ok(Json(ApiResponse::success(result))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+/// Dry run: run all acceptance checks without queuing the transaction
+async fn handle_submit_validate(
+    api: Arc<FluxeApi>,
+    req: TypedSubmission,
+) -> Result<String, FluxeError> {
+    let verifier = api.verifier.lock().unwrap();
+    let tx = build_typed_transaction(req, verifier.pending_roots())?;
+    verifier.validate_only(&tx)?;
+    
+    Ok("valid".to_string())
+}
+
+async fn submit_batch(
+    State(api): State<Arc<FluxeApi>>,
+    Json(req): Json<SubmitBatchRequest>,
+) -> Result<Json<ApiResponse<SubmitBatchResponse>>, StatusCode> {
+    Ok(Json(ApiResponse::success(handle_submit_batch(api, req).await)))
+}
+
+/// Build every submission, then validate and queue them in order; a single failure
+/// (build or validation) leaves the pending batch untouched
+async fn handle_submit_batch(
+    api: Arc<FluxeApi>,
+    req: SubmitBatchRequest,
+) -> SubmitBatchResponse {
     let mut verifier = api.verifier.lock().unwrap();
-    verifier.add_transaction(tx)?;
     
-    Ok("object_update_tx".to_string())
+    // Each item builds on the roots the pending batch and the items before it leave behind
+    let mut next_roots = verifier.pending_roots();
+    let built: Vec<Result<VerifiedTransaction, FluxeError>> = req.transactions
+        .into_iter()
+        .map(|sub| {
+            let tx = build_typed_transaction(sub, next_roots.clone())?;
+            next_roots = tx.new_roots.clone();
+            Ok(tx)
+        })
+        .collect();
+    
+    let tx_ids: Vec<Option<String>> = built
+        .iter()
+        .map(|tx| tx.as_ref().ok().map(|tx| field_to_hex(&tx.private_id())))
+        .collect();
+    
+    let results: Vec<Result<(), FluxeError>> = if built.iter().all(Result::is_ok) {
+        let txs = built.into_iter().map(Result::unwrap).collect();
+        verifier.add_transactions(txs)
+    } else {
+        built.into_iter().map(|tx| tx.map(|_| ())).collect()
+    };
+    
+    SubmitBatchResponse {
+        queued: results.iter().all(Result::is_ok),
+        results: results
+            .into_iter()
+            .zip(tx_ids)
+            .map(|(r, tx_id)| BatchItemResult {
+                tx_id,
+                accepted: r.is_ok(),
+                error: r.err().map(|e| e.to_string()),
+            })
+            .collect(),
+    }
+}
+
+fn build_typed_transaction(
+    req: TypedSubmission,
+    old_roots: StateRoots,
+) -> Result<VerifiedTransaction, FluxeError> {
+    match req {
+        TypedSubmission::Mint(req) => build_mint_transaction(req, old_roots),
+        TypedSubmission::Burn(req) => build_burn_transaction(req, old_roots),
+        TypedSubmission::Transfer(req) => build_transfer_transaction(req, old_roots),
+        TypedSubmission::ObjectUpdate(req) => build_object_update_transaction(req, old_roots),
+    }
 }
 
 async fn get_roots(
@@ -375,18 +774,12 @@ async fn get_roots(
     let verifier = api.verifier.lock().unwrap();
     let roots = verifier.get_current_roots();
     
-    let response = StateRootsResponse {
-        cmt_root: field_to_hex(&roots.cmt_root),
-        nft_root: field_to_hex(&roots.nft_root),
-        obj_root: field_to_hex(&roots.obj_root),
-        cb_root: field_to_hex(&roots.cb_root),
-        ingress_root: field_to_hex(&roots.ingress_root),
-        exit_root: field_to_hex(&roots.exit_root),
-        sanctions_root: field_to_hex(&roots.sanctions_root),
-        pool_rules_root: field_to_hex(&roots.pool_rules_root),
-    };
-    
-    Ok(Json(ApiResponse::success(response)))
+    Ok(Json(ApiResponse::success(StateRootsResponse::from(&roots))))
+}
+
+/// Canonical initial roots, for conformance testing by other implementations
+async fn get_empty_roots() -> Result<Json<ApiResponse<StateRootsResponse>>, StatusCode> {
+    Ok(Json(ApiResponse::success(StateRootsResponse::from(&StateManager::empty_roots()))))
 }
 
 async fn get_supply(
@@ -407,6 +800,21 @@ async fn get_supply(
     Ok(Json(ApiResponse::success(response)))
 }
 
+async fn get_stats(
+    State(api): State<Arc<FluxeApi>>,
+) -> Result<Json<ApiResponse<StateStatsResponse>>, StatusCode> {
+    let verifier = api.verifier.lock().unwrap();
+    let stats = verifier.nft_stats();
+    
+    let response = StateStatsResponse {
+        spent_nullifiers: stats.entries,
+        nft_leaf_count: stats.leaf_count,
+        nft_utilization: stats.utilization,
+    };
+    
+    Ok(Json(ApiResponse::success(response)))
+}
+
 async fn get_commitment_proof(
     State(_api): State<Arc<FluxeApi>>,
     Path(_cm): Path<String>,
@@ -450,6 +858,28 @@ async fn get_object_proof(
     Ok(Json(ApiResponse::success(response)))
 }
 
+/// Current object commitment of an owner, for driving compliance updates
+async fn get_object_by_owner(
+    State(api): State<Arc<FluxeApi>>,
+    Path(addr): Path<String>,
+) -> Result<Json<ApiResponse<ObjectByOwnerResponse>>, StatusCode> {
+    let owner_addr = match parse_field_from_hex(&addr) {
+        Ok(a) => a,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    
+    let verifier = api.verifier.lock().unwrap();
+    match verifier.object_for_owner(owner_addr) {
+        Some(object_cm) => Ok(Json(ApiResponse::success(ObjectByOwnerResponse {
+            owner_addr: addr,
+            object_cm: field_to_hex(&object_cm),
+        }))),
+        None => Ok(Json(ApiResponse::error(
+            "No object indexed for owner".to_string()
+        ))),
+    }
+}
+
 async fn get_sanctions_proof(
     State(api): State<Arc<FluxeApi>>,
     Path(addr): Path<String>,
@@ -493,6 +923,53 @@ async fn get_batch_status(
     Ok(Json(ApiResponse::success(status)))
 }
 
+/// Poll a submission by the private id returned when it was submitted
+async fn get_tx_status(
+    State(api): State<Arc<FluxeApi>>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<TxStatusResponse>>, StatusCode> {
+    let tx_id = match parse_field_from_hex(&id) {
+        Ok(id) => id,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    
+    let verifier = api.verifier.lock().unwrap();
+    let response = TxStatusResponse {
+        pending: verifier.is_pending(&tx_id),
+        tx_id: id,
+    };
+    
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Inclusion receipt for a transaction once its batch has been processed
+async fn get_tx_receipt(
+    State(api): State<Arc<FluxeApi>>,
+    Path(private_id): Path<String>,
+) -> Result<Json<ApiResponse<InclusionReceiptResponse>>, StatusCode> {
+    let tx_id = match parse_field_from_hex(&private_id) {
+        Ok(id) => id,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    
+    let verifier = api.verifier.lock().unwrap();
+    let receipt = match verifier.inclusion_receipt(&tx_id) {
+        Some(receipt) => receipt,
+        None => return Ok(Json(ApiResponse::error(
+            "No inclusion receipt: transaction unknown or not yet batched".to_string()
+        ))),
+    };
+    
+    let response = InclusionReceiptResponse {
+        batch_id: receipt.batch_id,
+        tx_private_id: field_to_hex(&receipt.tx_private_id),
+        index: receipt.merkle_path_to_batch_txs.leaf_index,
+        path: receipt.merkle_path_to_batch_txs.siblings.iter().map(field_to_hex).collect(),
+    };
+    
+    Ok(Json(ApiResponse::success(response)))
+}
+
 async fn health_check() -> Result<Json<ApiResponse<String>>, StatusCode> {
     Ok(Json(ApiResponse::success("OK".to_string())))
 }
@@ -510,10 +987,31 @@ async fn get_info(
     Ok(Json(ApiResponse::success(info)))
 }
 
+/// Error envelope for a rejected submission; undecodable input is the client's fault (400),
+/// while a well-formed transaction the verifier refuses keeps the 200 error envelope
+fn submission_error(e: FluxeError) -> (StatusCode, Json<ApiResponse<String>>) {
+    let status = match e {
+        FluxeError::SerializationError(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::OK,
+    };
+    (status, Json(ApiResponse::error(e.to_string())))
+}
+
 // Utility functions for parsing and conversion
-fn parse_proof_from_bytes(_bytes: &[u8]) -> Result<ark_groth16::Proof<ark_bls12_381::Bls12_381>, FluxeError> {
-    // Placeholder - would deserialize actual Groth16 proof
-    Err(FluxeError::Other("Proof parsing not implemented".to_string()))
+
+/// Decode a compressed Groth16 proof, as produced by `serialize_proof_to_bytes`
+pub fn parse_proof_from_bytes(bytes: &[u8]) -> Result<ark_groth16::Proof<ark_bls12_381::Bls12_381>, FluxeError> {
+    use ark_serialize::CanonicalDeserialize;
+    ark_groth16::Proof::deserialize_compressed(bytes)
+        .map_err(|e| FluxeError::SerializationError(format!("Malformed proof: {}", e)))
+}
+
+/// Compressed encoding of a Groth16 proof, the `proof` field of submission requests
+pub fn serialize_proof_to_bytes(proof: &ark_groth16::Proof<ark_bls12_381::Bls12_381>) -> Vec<u8> {
+    use ark_serialize::CanonicalSerialize;
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+    bytes
 }
 
 fn parse_public_inputs(inputs: &[String]) -> Result<Vec<ark_bls12_381::Fr>, FluxeError> {
@@ -540,17 +1038,68 @@ fn field_to_hex(field: &ark_bls12_381::Fr) -> String {
     format!("0x{}", hex::encode(bytes))
 }
 
-fn convert_serializable_notes(_notes: &[SerializableNote]) -> Result<Vec<fluxe_core::data_structures::Note>, FluxeError> {
-    // Placeholder - would convert from API format to core format
-    Ok(Vec::new())
+/// Field a request must carry, hex-encoded
+fn required_field(value: &Option<String>, name: &str) -> Result<ark_bls12_381::Fr, FluxeError> {
+    match value {
+        Some(hex) => parse_field_from_hex(hex),
+        None => Err(FluxeError::SerializationError(format!("Missing {}", name))),
+    }
 }
 
-fn convert_serializable_callback_ops(_ops: &[SerializableCallbackOp]) -> Result<Vec<CallbackOperation>, FluxeError> {
-    // Placeholder - would convert callback operations
-    Ok(Vec::new())
+fn convert_serializable_notes(notes: &[SerializableNote]) -> Result<Vec<Note>, FluxeError> {
+    use ark_serialize::CanonicalDeserialize;
+    
+    let hash_or_zero = |hash: &Option<String>| match hash {
+        Some(hex) => parse_field_from_hex(hex),
+        None => Ok(ark_bls12_381::Fr::from(0u64)),
+    };
+    notes.iter()
+        .map(|note| {
+            let bytes = hex::decode(note.v_comm.trim_start_matches("0x"))
+                .map_err(|e| FluxeError::SerializationError(format!("Invalid hex: {}", e)))?;
+            let v_comm = PedersenCommitment::deserialize_compressed(&*bytes)
+                .map_err(|e| FluxeError::SerializationError(format!("Invalid value commitment: {}", e)))?;
+            Ok(Note {
+                asset_type: note.asset_type,
+                v_comm,
+                owner_addr: parse_field_from_hex(&note.owner_addr)?,
+                psi: note.psi,
+                chain_hint: note.chain_hint,
+                compliance_hash: hash_or_zero(&note.compliance_hash)?,
+                lineage_hash: hash_or_zero(&note.lineage_hash)?,
+                pool_id: note.pool_id,
+                callbacks_hash: hash_or_zero(&note.callbacks_hash)?,
+                memo_hash: hash_or_zero(&note.memo_hash)?,
+            })
+        })
+        .collect()
 }
 
-fn compute_notes_commitment(_notes: &[fluxe_core::data_structures::Note]) -> ark_bls12_381::Fr {
-    // Placeholder - would compute Merkle commitment of notes
-    ark_bls12_381::Fr::from(0)
-}
\ No newline at end of file
+fn convert_serializable_callback_ops(ops: &[SerializableCallbackOp]) -> Result<Vec<CallbackOperation>, FluxeError> {
+    use ark_serialize::CanonicalDeserialize;
+    
+    ops.iter()
+        .map(|op| match op.op_type.as_str() {
+            "add" => {
+                let timestamp = op.timestamp
+                    .ok_or_else(|| FluxeError::SerializationError("Missing timestamp".to_string()))?;
+                let mut invocation = CallbackInvocation::new(
+                    required_field(&op.ticket, "ticket")?,
+                    op.payload.clone().unwrap_or_default(),
+                    timestamp,
+                );
+                invocation.signature = op.signature
+                    .as_deref()
+                    .map(|bytes| {
+                        SchnorrSignature::deserialize_compressed(bytes)
+                            .map_err(|e| FluxeError::SerializationError(format!("Malformed signature: {}", e)))
+                    })
+                    .transpose()?;
+                Ok(CallbackOperation::Add(invocation))
+            }
+            "process" => Ok(CallbackOperation::Process(required_field(&op.ticket, "ticket")?)),
+            "freeze" => Ok(CallbackOperation::Freeze(required_field(&op.owner, "owner")?)),
+            other => Err(FluxeError::SerializationError(format!("Unknown callback operation: {}", other))),
+        })
+        .collect()
+}
\ No newline at end of file
@@ -0,0 +1,192 @@
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header::CONTENT_TYPE, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::api::ApiResponse;
+use crate::rate_limit::{peer_key, route_path};
+
+/// Request header naming a logical submission; retries reuse the same value
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Default number of recent keys remembered
+pub const DEFAULT_IDEMPOTENCY_CAPACITY: usize = 10_000;
+
+/// (route path, peer key, idempotency key)
+type Scope = (String, String, String);
+
+/// Accepted response for a key
+#[derive(Clone, Debug)]
+struct CachedResponse {
+    status: StatusCode,
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+}
+
+impl IntoResponse for CachedResponse {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, self.body).into_response();
+        if let Some(content_type) = self.content_type {
+            response.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+        response
+    }
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    responses: HashMap<Scope, CachedResponse>,
+    /// Insertion order, oldest first
+    order: VecDeque<Scope>,
+    /// Keys reserved by a request still being handled
+    in_flight: HashSet<Scope>,
+}
+
+/// What a request's key finds in the cache
+enum Lookup {
+    /// Accepted before: replay that response
+    Replay(CachedResponse),
+    /// Another request with the key is still being handled
+    InFlight,
+    /// First use: the key is now reserved for this request
+    Reserved(Reservation),
+}
+
+/// A key held by the request handling it, released on drop so a rejected or abandoned
+/// attempt can be retried
+struct Reservation {
+    cache: Arc<IdempotencyCache>,
+    scope: Scope,
+}
+
+impl Reservation {
+    /// Remember the response accepted under the key; dropping then releases the reservation
+    fn complete(self, response: CachedResponse) {
+        self.cache.insert(self.scope.clone(), response);
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.cache.entries.lock().unwrap().in_flight.remove(&self.scope);
+    }
+}
+
+/// Recent idempotency keys and the responses accepted under them
+/// Keys are scoped by route and client, the oldest evicted once `capacity` are held
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_CAPACITY)
+    }
+}
+
+impl IdempotencyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+    
+    /// Number of keys currently remembered
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().order.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Replay the key's response, or reserve the key unless another request holds it
+    fn reserve(self: &Arc<Self>, scope: Scope) -> Lookup {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(cached) = entries.responses.get(&scope) {
+            return Lookup::Replay(cached.clone());
+        }
+        if !entries.in_flight.insert(scope.clone()) {
+            return Lookup::InFlight;
+        }
+        Lookup::Reserved(Reservation { cache: self.clone(), scope })
+    }
+    
+    fn insert(&self, scope: Scope, response: CachedResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.responses.insert(scope.clone(), response).is_none() {
+            entries.order.push_back(scope);
+        }
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.responses.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Whether a response body reports an accepted submission: `success`, and for a batch
+/// `queued` as well. Rejections come back in a 200 error envelope too
+fn is_accepted(body: &[u8]) -> bool {
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(value) => value["success"] == true && value["data"]["queued"] != false,
+        Err(_) => false,
+    }
+}
+
+/// Middleware replaying the accepted response to a POST whose Idempotency-Key the same
+/// client already used on the route; a duplicate arriving while the first is still being
+/// handled gets 409. Requests without the header pass through
+pub async fn replay_idempotent(
+    State(cache): State<Arc<IdempotencyCache>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let key = match req.headers().get(IDEMPOTENCY_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(key) if req.method() == Method::POST => key.to_string(),
+        _ => return next.run(req).await,
+    };
+    let scope = (route_path(&req), peer_key(&req), key);
+    
+    let reservation = match cache.reserve(scope) {
+        Lookup::Replay(cached) => return cached.into_response(),
+        Lookup::InFlight => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error("A request with this idempotency key is in progress".to_string())),
+            ).into_response();
+        }
+        Lookup::Reserved(reservation) => reservation,
+    };
+    
+    // Only accepted submissions are remembered, so a rejected attempt can be retried
+    let response = next.run(req).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if is_accepted(&body) {
+        reservation.complete(CachedResponse {
+            status: parts.status,
+            content_type: parts.headers.get(CONTENT_TYPE).cloned(),
+            body: body.clone(),
+        });
+    }
+    Response::from_parts(parts, Body::from(body))
+}
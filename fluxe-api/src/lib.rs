@@ -1,3 +1,7 @@
 pub mod api;
+pub mod idempotency;
+pub mod rate_limit;
 
-pub use api::*;
\ No newline at end of file
+pub use api::*;
+pub use idempotency::IdempotencyCache;
+pub use rate_limit::{RateLimit, RateLimiter};
\ No newline at end of file
@@ -0,0 +1,195 @@
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::api::ApiResponse;
+
+/// Token bucket shape: up to `burst` requests at once, refilled at one token per `refill_every`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub refill_every: Duration,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, refill_every: Duration) -> Self {
+        Self { burst, refill_every }
+    }
+}
+
+/// Buckets held before `check` sweeps out the idle ones
+const SWEEP_THRESHOLD: usize = 1024;
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    /// Tokens held at `now`, capped at the burst
+    fn refilled(&self, limit: &RateLimit, now: Instant) -> f64 {
+        if limit.refill_every.is_zero() {
+            return limit.burst as f64;
+        }
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        (self.tokens + elapsed.as_secs_f64() / limit.refill_every.as_secs_f64()).min(limit.burst as f64)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    /// Keyed by (route path, client key)
+    by_client: HashMap<(String, String), Bucket>,
+    /// Bucket count at which `check` next sweeps
+    sweep_at: usize,
+}
+
+/// Per-client, per-endpoint token buckets
+/// Clients are keyed by their auth token when it is one of the authenticated tokens, else
+/// by IP, so sending fresh tokens doesn't buy fresh buckets. A bucket that has refilled to
+/// its burst is no different from a new one, so idle buckets are dropped
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    /// Limit for endpoints without their own entry (None = unlimited)
+    default_limit: Option<RateLimit>,
+
+    /// Limits keyed by route path, e.g. "/submit/mint"
+    endpoint_limits: HashMap<String, RateLimit>,
+
+    /// Bearer tokens issued to known clients
+    authenticated_tokens: HashSet<String>,
+
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit every endpoint that has no limit of its own
+    pub fn with_default_limit(mut self, limit: RateLimit) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// Limit one route, given as registered on the router (e.g. "/tx/status/:id")
+    pub fn with_endpoint_limit(mut self, endpoint: &str, limit: RateLimit) -> Self {
+        self.endpoint_limits.insert(endpoint.to_string(), limit);
+        self
+    }
+
+    /// Key clients presenting `token` as a bearer token by the token instead of their IP
+    pub fn with_authenticated_token(mut self, token: &str) -> Self {
+        self.authenticated_tokens.insert(token.to_string());
+        self
+    }
+
+    fn limit_for(&self, endpoint: &str) -> Option<RateLimit> {
+        self.endpoint_limits.get(endpoint).or(self.default_limit.as_ref()).copied()
+    }
+
+    /// Take a token for `client` on `endpoint` at time `now`; false when the bucket is empty
+    pub fn check(&self, endpoint: &str, client: &str, now: Instant) -> bool {
+        let limit = match self.limit_for(endpoint) {
+            Some(limit) => limit,
+            None => return true,
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.by_client.len() >= buckets.sweep_at {
+            self.sweep(&mut buckets, now);
+        }
+        let bucket = buckets
+            .by_client
+            .entry((endpoint.to_string(), client.to_string()))
+            .or_insert(Bucket { tokens: limit.burst as f64, last_refill: now });
+
+        bucket.tokens = bucket.refilled(&limit, now);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drop every bucket that has refilled to its burst by `now`, returning how many
+    pub fn prune_idle(&self, now: Instant) -> usize {
+        let mut buckets = self.buckets.lock().unwrap();
+        self.sweep(&mut buckets, now)
+    }
+
+    /// Number of buckets currently held
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.lock().unwrap().by_client.len()
+    }
+
+    /// Drop idle buckets; the next sweep waits until the survivors have doubled
+    fn sweep(&self, buckets: &mut Buckets, now: Instant) -> usize {
+        let before = buckets.by_client.len();
+        buckets.by_client.retain(|(endpoint, _), bucket| match self.limit_for(endpoint) {
+            Some(limit) => bucket.refilled(&limit, now) < limit.burst as f64,
+            None => false,
+        });
+        buckets.sweep_at = (2 * buckets.by_client.len()).max(SWEEP_THRESHOLD);
+        before - buckets.by_client.len()
+    }
+
+    /// Client identity for a request: its auth token if authenticated, else its peer IP
+    pub fn client_key(&self, req: &Request) -> String {
+        let token = req.headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim_start_matches("Bearer ").trim());
+        match token {
+            Some(token) if self.authenticated_tokens.contains(token) => format!("token:{}", token),
+            _ => peer_key(req),
+        }
+    }
+}
+
+/// Peer IP of a request, for clients without an authenticated token
+pub(crate) fn peer_key(req: &Request) -> String {
+    match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "anonymous".to_string(),
+    }
+}
+
+/// Route a request matched, as registered on the router, falling back to its URI path
+pub(crate) fn route_path(req: &Request) -> String {
+    req.extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string())
+}
+
+/// Middleware answering 429 once the client's bucket for the matched route is empty
+pub async fn enforce_rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let endpoint = route_path(&req);
+    let client = limiter.client_key(&req);
+
+    if !limiter.check(&endpoint, &client, Instant::now()) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::<()>::error(format!("Rate limit exceeded for {}", endpoint))),
+        ).into_response();
+    }
+    next.run(req).await
+}
+
@@ -0,0 +1,157 @@
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_groth16::Groth16;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use fluxe_api::FluxeApi;
+use fluxe_core::{
+    crypto::{AttestationProvider, AttestationRegistry, AttestationType, SchnorrSecretKey, SignedAttestation},
+    server_verifier::ServerVerifier,
+    state_manager::StateManager,
+};
+use rand::thread_rng;
+use tower::ServiceExt;
+
+/// Exposes an object update's [obj_old, obj_new, cb_root, current_time] and constrains nothing
+#[derive(Clone)]
+struct ObjectUpdateInputsCircuit([F; 4]);
+
+impl ConstraintSynthesizer<F> for ObjectUpdateInputsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        for input in self.0 {
+            cs.new_input_variable(|| Ok(input))?;
+        }
+        Ok(())
+    }
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).unwrap();
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn submit_request(attestation: &SignedAttestation, object_cm: F, object_update: serde_json::Value) -> Request<Body> {
+    let body = serde_json::json!({
+        "attestation": to_hex(attestation),
+        "object_cm": to_hex(&object_cm),
+        "object_update": object_update,
+    });
+    Request::post("/attestations/submit")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn provider_registry(provider_sk: &SchnorrSecretKey) -> AttestationRegistry {
+    let mut registry = AttestationRegistry::new();
+    registry.register_provider(AttestationProvider {
+        id: 1,
+        name: "KYC Provider".to_string(),
+        public_key: provider_sk.public_key(),
+        jurisdiction: 1,
+        trust_level: 90,
+    }).unwrap();
+    registry
+}
+
+fn kyc(user_cm: F, level: u8, provider_sk: &SchnorrSecretKey) -> SignedAttestation {
+    let mut attestation = SignedAttestation::new(
+        user_cm,
+        AttestationType::KYCComplete { level, document_hash: F::from(5u64) },
+        1000,
+        1,
+        vec![],
+    );
+    attestation.sign(provider_sk);
+    attestation
+}
+
+#[tokio::test]
+async fn test_submit_attestation() {
+    let mut rng = thread_rng();
+    let provider_sk = SchnorrSecretKey::random(&mut rng);
+    
+    let verifier = ServerVerifier::with_keys(StateManager::new(16), None, None, None, None);
+    let api = FluxeApi::new(verifier).with_attestation_registry(provider_registry(&provider_sk));
+    let attestations = api.attestations.clone();
+    let app = api.router();
+    
+    let object_cm = F::from(77u64);
+    
+    // A valid KYC attestation raises the account's level
+    let valid = kyc(object_cm, 2, &provider_sk);
+    let response = app.clone().oneshot(submit_request(&valid, object_cm, serde_json::Value::Null)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(attestations.lock().unwrap().compliance_level(object_cm), 2);
+    
+    // A forged attestation (signed by an unregistered key) is rejected
+    let forged = kyc(object_cm, 3, &SchnorrSecretKey::random(&mut rng));
+    let response = app.clone().oneshot(submit_request(&forged, object_cm, serde_json::Value::Null)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(attestations.lock().unwrap().compliance_level(object_cm), 2);
+    
+    // An attestation of another user can't be submitted against this object
+    let other = kyc(F::from(88u64), 3, &provider_sk);
+    let response = app.oneshot(submit_request(&other, object_cm, serde_json::Value::Null)).await.unwrap();
+    let response: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(response["success"], false);
+    assert_eq!(attestations.lock().unwrap().compliance_level(F::from(88u64)), 0);
+}
+
+#[tokio::test]
+async fn test_attestation_with_object_update() {
+    let mut rng = thread_rng();
+    let provider_sk = SchnorrSecretKey::random(&mut rng);
+    
+    // The update moves the attested object to its successor
+    let state = StateManager::new(16);
+    let (old_object_cm, new_object_cm) = (F::from(77u64), F::from(78u64));
+    let roots = state.get_roots();
+    let mut obj_tree = state.obj_tree.clone();
+    obj_tree.append(new_object_cm);
+    let inputs = [roots.obj_root, obj_tree.root(), roots.cb_root, F::from(1000u64)];
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(ObjectUpdateInputsCircuit(inputs), &mut rng).unwrap();
+    let proof = Groth16::<Bls12_381>::prove(&pk, ObjectUpdateInputsCircuit(inputs), &mut rng).unwrap();
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).unwrap();
+    let update = |inputs: &[F]| serde_json::json!({
+        "old_object_cm": to_hex(&old_object_cm),
+        "new_object_cm": to_hex(&new_object_cm),
+        "proof": proof_bytes,
+        "public_inputs": inputs.iter().map(to_hex).collect::<Vec<_>>(),
+        "callback_operations": [],
+    });
+    
+    let verifier = ServerVerifier::with_keys(state, None, None, None, Some(vk));
+    let api = FluxeApi::new(verifier).with_attestation_registry(provider_registry(&provider_sk));
+    let attestations = api.attestations.clone();
+    let verifier = api.verifier.clone();
+    let app = api.router();
+    
+    // An update that fails validation records nothing
+    let mut forged_inputs = inputs;
+    forged_inputs[3] = F::from(1001u64);
+    let attestation = kyc(old_object_cm, 2, &provider_sk);
+    let response = app.clone().oneshot(submit_request(&attestation, old_object_cm, update(&forged_inputs))).await.unwrap();
+    let response: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(response["success"], false);
+    assert_eq!(attestations.lock().unwrap().compliance_level(old_object_cm), 0);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 0);
+    
+    // A valid update is queued with the attestation recorded
+    let response = app.oneshot(submit_request(&attestation, old_object_cm, update(&inputs))).await.unwrap();
+    let response: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(response["success"], true);
+    assert_eq!(response["data"]["level"], 2);
+    assert!(response["data"]["tx_id"].is_string());
+    assert_eq!(attestations.lock().unwrap().compliance_level(old_object_cm), 2);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 1);
+    
+    let header = verifier.lock().unwrap().process_batch().unwrap();
+    assert_eq!(header.new_roots.obj_root, inputs[1]);
+}
@@ -0,0 +1,142 @@
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use fluxe_api::FluxeApi;
+use fluxe_core::{
+    crypto::{PedersenCommitment, PedersenParams},
+    data_structures::{IngressReceipt, Note},
+    server_verifier::ServerVerifier,
+    state_manager::StateManager,
+    types::Amount,
+};
+use rand::thread_rng;
+use tower::ServiceExt;
+
+/// Exposes a mint's ten public inputs and constrains nothing
+#[derive(Clone)]
+struct MintInputsCircuit([F; 10]);
+
+impl ConstraintSynthesizer<F> for MintInputsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        for input in self.0 {
+            cs.new_input_variable(|| Ok(input))?;
+        }
+        Ok(())
+    }
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).unwrap();
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// A note per mint, each mint chaining its receipt and note onto the roots the one before leaves
+fn chained_mints(state: &StateManager, count: usize) -> (VerifyingKey<Bls12_381>, Vec<Proof<Bls12_381>>, Vec<[F; 10]>, Vec<Note>) {
+    let mut rng = thread_rng();
+    let params = PedersenParams::setup_value_commitment();
+    let notes: Vec<Note> = (0..count)
+        .map(|i| {
+            let (v_comm, _) = PedersenCommitment::commit_with_rng(&params, 1000, &mut rng);
+            Note::new(1, v_comm, F::from(7u64), [i as u8 + 1; 32], 0)
+        })
+        .collect();
+
+    let mut scratch = state.clone();
+    let inputs: Vec<[F; 10]> = notes
+        .iter()
+        .map(|note| {
+            let receipt = IngressReceipt::new(1, Amount::from(1000u64), IngressReceipt::beneficiary_commitment(std::slice::from_ref(note)), 0);
+            let (cmt_old, ingress_old) = (scratch.cmt_tree.root(), scratch.ingress_tree.root());
+            scratch.ingress_tree.append(receipt.hash());
+            scratch.cmt_tree.append(note.commitment());
+            let mut inputs = [F::from(0u64); 10];
+            inputs[..4].copy_from_slice(&[cmt_old, scratch.cmt_tree.root(), ingress_old, scratch.ingress_tree.root()]);
+            inputs[9] = state.get_roots().authorized_minters_root;
+            inputs
+        })
+        .collect();
+
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(MintInputsCircuit(inputs[0]), &mut rng).unwrap();
+    let proofs = inputs
+        .iter()
+        .map(|inputs| Groth16::<Bls12_381>::prove(&pk, MintInputsCircuit(*inputs), &mut rng).unwrap())
+        .collect();
+    (vk, proofs, inputs, notes)
+}
+
+fn mint_item(proof: &Proof<Bls12_381>, inputs: &[F; 10], note: &Note) -> serde_json::Value {
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).unwrap();
+    serde_json::json!({
+        "tx_type": "mint",
+        "asset_type": 1,
+        "amount": 1000,
+        "proof": proof_bytes,
+        "public_inputs": inputs.iter().map(to_hex).collect::<Vec<_>>(),
+        "notes_out": [{
+            "asset_type": note.asset_type,
+            "v_comm": to_hex(&note.v_comm),
+            "owner_addr": to_hex(&note.owner_addr),
+            "psi": note.psi,
+            "chain_hint": note.chain_hint,
+            "pool_id": note.pool_id,
+        }],
+    })
+}
+
+fn batch_request(items: Vec<serde_json::Value>) -> Request<Body> {
+    let body = serde_json::json!({ "transactions": items });
+    Request::post("/submit/batch")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn submit(app: axum::Router, items: Vec<serde_json::Value>) -> serde_json::Value {
+    let response = app.oneshot(batch_request(items)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_batch_submission_lands_notes() {
+    let state = StateManager::new(16);
+    let (vk, proofs, inputs, notes) = chained_mints(&state, 2);
+
+    let verifier = ServerVerifier::with_keys(state, Some(vk), None, None, None);
+    let api = FluxeApi::new(verifier);
+    let verifier = api.verifier.clone();
+    let app = api.router();
+
+    // Replaying the first mint after it is stale fails, so neither item is queued
+    let response = submit(app.clone(), vec![
+        mint_item(&proofs[0], &inputs[0], &notes[0]),
+        mint_item(&proofs[0], &inputs[0], &notes[0]),
+    ]).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["data"]["queued"], false);
+    assert_eq!(response["data"]["results"][1]["accepted"], false);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 0);
+
+    // The chained pair is queued whole
+    let response = submit(app, vec![
+        mint_item(&proofs[0], &inputs[0], &notes[0]),
+        mint_item(&proofs[1], &inputs[1], &notes[1]),
+    ]).await;
+    assert_eq!(response["data"]["queued"], true);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 2);
+
+    // Applying it appends both notes, reaching the CMT root the second mint claims
+    let header = verifier.lock().unwrap().process_batch().unwrap();
+    assert_eq!(header.new_roots.cmt_root, inputs[1][1]);
+    assert_eq!(header.new_roots.ingress_root, inputs[1][3]);
+
+    println!("✓ Batch submission test passed");
+}
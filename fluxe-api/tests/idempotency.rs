@@ -0,0 +1,189 @@
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_groth16::{Groth16, Proof};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use ark_groth16::VerifyingKey;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    middleware,
+    routing::post,
+    Json, Router,
+};
+use fluxe_api::{idempotency::replay_idempotent, ApiResponse, FluxeApi, IdempotencyCache};
+use fluxe_core::{
+    data_structures::IngressReceipt,
+    server_verifier::ServerVerifier,
+    state_manager::StateManager,
+    types::Amount,
+};
+use rand::thread_rng;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+use tower::ServiceExt;
+
+/// Exposes a mint's ten public inputs and constrains nothing
+#[derive(Clone)]
+struct MintInputsCircuit([F; 10]);
+
+impl ConstraintSynthesizer<F> for MintInputsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        for input in self.0 {
+            cs.new_input_variable(|| Ok(input))?;
+        }
+        Ok(())
+    }
+}
+
+fn to_hex(field: &F) -> String {
+    let mut bytes = Vec::new();
+    field.serialize_compressed(&mut bytes).unwrap();
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn mint_request(proof: &Proof<Bls12_381>, inputs: &[F; 10], key: Option<&str>) -> Request<Body> {
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).unwrap();
+    let body = serde_json::json!({
+        "asset_type": 1,
+        "amount": 1000,
+        "proof": proof_bytes,
+        "public_inputs": inputs.iter().map(to_hex).collect::<Vec<_>>(),
+        "notes_out": [],
+    });
+    
+    let mut request = Request::post("/submit/mint").header("content-type", "application/json");
+    if let Some(key) = key {
+        request = request.header("idempotency-key", key);
+    }
+    request.body(Body::from(body.to_string())).unwrap()
+}
+
+/// `count` mints of the receipt the API builds, each appending it onto the ingress root
+/// the one before it leaves, by a minter in the (empty) authorized minters tree
+fn chained_mints(state: &StateManager, count: usize) -> (VerifyingKey<Bls12_381>, Vec<Proof<Bls12_381>>, Vec<[F; 10]>) {
+    let roots = state.get_roots();
+    let receipt = IngressReceipt::new(1, Amount::from(1000u64), IngressReceipt::beneficiary_commitment(&[]), 0);
+    let mut ingress_tree = state.ingress_tree.clone();
+    let inputs: Vec<[F; 10]> = (0..count)
+        .map(|_| {
+            let ingress_old = ingress_tree.root();
+            ingress_tree.append(receipt.hash());
+            let mut inputs = [F::from(0u64); 10];
+            inputs[..4].copy_from_slice(&[roots.cmt_root, roots.cmt_root, ingress_old, ingress_tree.root()]);
+            inputs[9] = roots.authorized_minters_root;
+            inputs
+        })
+        .collect();
+    
+    let mut rng = thread_rng();
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(MintInputsCircuit(inputs[0]), &mut rng).unwrap();
+    let proofs: Vec<_> = inputs
+        .iter()
+        .map(|inputs| Groth16::<Bls12_381>::prove(&pk, MintInputsCircuit(*inputs), &mut rng).unwrap())
+        .collect();
+    (vk, proofs, inputs)
+}
+
+#[tokio::test]
+async fn test_idempotent_submission() {
+    let state = StateManager::new(16);
+    let (vk, proofs, inputs) = chained_mints(&state, 3);
+    
+    let verifier = ServerVerifier::with_keys(state, Some(vk), None, None, None);
+    let api = FluxeApi::new(verifier).with_idempotency(IdempotencyCache::new(16));
+    let verifier = api.verifier.clone();
+    let app = api.router();
+    
+    // A retry with the same key replays the first response and queues nothing new
+    let first = app.clone().oneshot(mint_request(&proofs[0], &inputs[0], Some("mint-1"))).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    let first = to_bytes(first.into_body(), usize::MAX).await.unwrap();
+    
+    let retry = app.clone().oneshot(mint_request(&proofs[0], &inputs[0], Some("mint-1"))).await.unwrap();
+    assert_eq!(retry.status(), StatusCode::OK);
+    let retry = to_bytes(retry.into_body(), usize::MAX).await.unwrap();
+    
+    assert_eq!(first, retry);
+    let response: serde_json::Value = serde_json::from_slice(&first).unwrap();
+    assert_eq!(response["success"], true);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 1);
+    
+    // A new key, or no key at all, is a new submission
+    let response = app.clone().oneshot(mint_request(&proofs[1], &inputs[1], Some("mint-2"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.oneshot(mint_request(&proofs[2], &inputs[2], None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 3);
+    
+    // The queued chain applies as one batch
+    let header = verifier.lock().unwrap().process_batch().unwrap();
+    assert_eq!(header.new_roots.ingress_root, inputs[2][3]);
+    
+    println!("✓ Idempotent submission test passed");
+}
+
+#[tokio::test]
+async fn test_rejected_submission_not_replayed() {
+    let state = StateManager::new(16);
+    let (vk, proofs, inputs) = chained_mints(&state, 2);
+    
+    let verifier = ServerVerifier::with_keys(state, Some(vk), None, None, None);
+    let api = FluxeApi::new(verifier).with_idempotency(IdempotencyCache::new(16));
+    let verifier = api.verifier.clone();
+    let app = api.router();
+    
+    // The second mint's ingress root is not current yet, so it is rejected in a 200 envelope
+    let rejected = app.clone().oneshot(mint_request(&proofs[1], &inputs[1], Some("mint-1"))).await.unwrap();
+    assert_eq!(rejected.status(), StatusCode::OK);
+    let rejected: serde_json::Value = serde_json::from_slice(&to_bytes(rejected.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(rejected["success"], false);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 0);
+    
+    // The rejection was not remembered, so a corrected retry under the key goes through
+    let retry = app.oneshot(mint_request(&proofs[0], &inputs[0], Some("mint-1"))).await.unwrap();
+    let retry: serde_json::Value = serde_json::from_slice(&to_bytes(retry.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(retry["success"], true);
+    assert_eq!(verifier.lock().unwrap().pending_count(), 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_duplicate_conflicts() {
+    // A submission that announces it started, then holds until released
+    let (entered_tx, mut entered) = mpsc::unbounded_channel();
+    let release = Arc::new(Notify::new());
+    let held = release.clone();
+    let app = Router::new()
+        .route("/submit/mint", post(move || {
+            let entered_tx = entered_tx.clone();
+            let held = held.clone();
+            async move {
+                entered_tx.send(()).unwrap();
+                held.notified().await;
+                Json(ApiResponse::success("queued"))
+            }
+        }))
+        .route_layer(middleware::from_fn_with_state(Arc::new(IdempotencyCache::new(16)), replay_idempotent));
+    let request = || {
+        Request::post("/submit/mint")
+            .header("idempotency-key", "mint-1")
+            .body(Body::empty())
+            .unwrap()
+    };
+    
+    let first = tokio::spawn(app.clone().oneshot(request()));
+    entered.recv().await.unwrap();
+    
+    // A duplicate arriving while the first is handled is refused instead of run again
+    let duplicate = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(duplicate.status(), StatusCode::CONFLICT);
+    
+    release.notify_one();
+    assert_eq!(first.await.unwrap().unwrap().status(), StatusCode::OK);
+    
+    // Once accepted, the key replays without reaching the handler
+    let replay = app.oneshot(request()).await.unwrap();
+    assert_eq!(replay.status(), StatusCode::OK);
+    assert!(entered.try_recv().is_err());
+}
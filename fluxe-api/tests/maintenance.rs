@@ -0,0 +1,34 @@
+use fluxe_api::FluxeApi;
+use fluxe_core::{
+    server_verifier::ServerVerifier,
+    state_manager::{MaintenanceConfig, StateManager},
+};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_maintenance_reports_published() {
+    let verifier = ServerVerifier::with_keys(StateManager::new(16), None, None, None, None);
+    let api = FluxeApi::new(verifier);
+    let config = MaintenanceConfig {
+        interval: Duration::from_millis(20),
+        ..MaintenanceConfig::default()
+    };
+
+    let (handle, mut reports) = api.spawn_maintenance(config);
+
+    // Each pass hands its report to the caller instead of printing it
+    for _ in 0..2 {
+        tokio::time::timeout(Duration::from_secs(5), reports.changed())
+            .await
+            .expect("maintenance pass within the timeout")
+            .unwrap();
+        let report = reports.borrow_and_update().clone();
+        assert_eq!(report.events_pruned, 0);
+        assert_eq!(report.rejections_trimmed, 0);
+    }
+    assert!(!handle.is_finished());
+
+    handle.abort();
+
+    println!("✓ Maintenance report test passed");
+}
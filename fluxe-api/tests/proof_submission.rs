@@ -0,0 +1,112 @@
+use ark_bls12_381::Fr as F;
+use ark_serialize::CanonicalSerialize;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use fluxe_api::{parse_proof_from_bytes, serialize_proof_to_bytes, FluxeApi};
+use fluxe_circuits::{
+    circuits::FluxeCircuit,
+    mint::MintCircuit,
+    setup::{test_rng, CircuitType, SetupManager},
+};
+use fluxe_core::{
+    crypto::{compute_ec_public_key, owner_address_from_pk},
+    crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness},
+    data_structures::{IngressReceipt, Note},
+    merkle::{IncrementalTree, SortedTree},
+    server_verifier::ServerVerifier,
+    state_manager::StateManager,
+    types::*,
+};
+use tower::ServiceExt;
+
+fn field_to_hex(field: &F) -> String {
+    let mut bytes = Vec::new();
+    field.serialize_compressed(&mut bytes).unwrap();
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn submit_mint(proof: Vec<u8>, public_inputs: &[F]) -> Request<Body> {
+    let body = serde_json::json!({
+        "asset_type": 1,
+        "amount": 1000,
+        "proof": proof,
+        "public_inputs": public_inputs.iter().map(field_to_hex).collect::<Vec<_>>(),
+        "notes_out": [],
+    });
+    Request::post("/submit/mint")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_submitted_proof_round_trips() {
+    let mut rng = test_rng();
+    let mut manager = SetupManager::new();
+    manager.generate_setup(CircuitType::Mint, &mut rng).unwrap();
+
+    // A real mint proof against the verifier's empty trees
+    let value = 1000u64;
+    let randomness = F::from(7u64);
+    let v_comm = PedersenCommitment::commit(
+        &PedersenParams::setup_value_commitment(),
+        value,
+        &PedersenRandomness { r: randomness },
+    );
+    let note = Note::new(1, v_comm, F::from(99u64), [1u8; 32], 1);
+    let ingress = IngressReceipt::new(
+        1,
+        Amount::from(value as u128),
+        IngressReceipt::beneficiary_commitment(&[note.clone()]),
+        1,
+    );
+    let minter_sk = F::from(5u64);
+    let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+    let mut authorized_minters = SortedTree::new(16);
+    authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+    let circuit = MintCircuit::new(
+        vec![note],
+        vec![value],
+        vec![randomness],
+        ingress,
+        &mut IncrementalTree::new(16),
+        &mut IncrementalTree::new(16),
+    )
+    .with_minter(minter_sk, &authorized_minters)
+    .unwrap();
+    let public_inputs = circuit.public_inputs();
+    let proof = manager.get_setup(CircuitType::Mint).unwrap().prove_deterministic(circuit, 1).unwrap();
+
+    // Bytes decode to the same proof
+    let bytes = serialize_proof_to_bytes(&proof);
+    assert_eq!(parse_proof_from_bytes(&bytes).unwrap(), proof);
+
+    let mut state = StateManager::new(16);
+    state.update_authorized_minters_root(authorized_minters.root());
+    let mut verifier = ServerVerifier::with_keys(state, None, None, None, None);
+    verifier.rotate_keys(manager.verifying_keys());
+    let app = FluxeApi::new(verifier).router();
+
+    // The posted proof is decoded identically, so it verifies and is queued
+    let response = app.clone().oneshot(submit_mint(bytes.clone(), &public_inputs)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["success"], true, "Unexpected response: {}", body);
+
+    // Malformed bytes are a client error, not a panic
+    let mut truncated = bytes.clone();
+    truncated.truncate(bytes.len() / 2);
+    let response = app.clone().oneshot(submit_mint(truncated, &public_inputs)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["success"], false);
+
+    let response = app.oneshot(submit_mint(vec![0xff; bytes.len()], &public_inputs)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    println!("✓ Submitted proof round trip test passed");
+}
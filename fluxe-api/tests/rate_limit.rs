@@ -0,0 +1,81 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use fluxe_api::{FluxeApi, RateLimit, RateLimiter};
+use fluxe_core::{server_verifier::ServerVerifier, state_manager::StateManager};
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+fn empty_batch(token: &str) -> Request<Body> {
+    Request::post("/submit/batch")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {}", token))
+        .body(Body::from(serde_json::json!({ "transactions": [] }).to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_submissions_rate_limited() {
+    let refill = Duration::from_millis(200);
+    let limiter = RateLimiter::new()
+        .with_endpoint_limit("/submit/batch", RateLimit::new(2, refill))
+        .with_authenticated_token("alice")
+        .with_authenticated_token("bob");
+
+    let verifier = ServerVerifier::with_keys(StateManager::new(16), None, None, None, None);
+    let app = FluxeApi::new(verifier).with_rate_limiter(limiter).router();
+
+    // Rapid submissions beyond the burst are throttled
+    for _ in 0..2 {
+        let response = app.clone().oneshot(empty_batch("alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = app.clone().oneshot(empty_batch("alice")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Another client has its own bucket, and unlimited endpoints are untouched
+    let response = app.clone().oneshot(empty_batch("bob")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let health = Request::get("/health").body(Body::empty()).unwrap();
+    assert_eq!(app.clone().oneshot(health).await.unwrap().status(), StatusCode::OK);
+
+    // Unknown tokens are keyed by peer, so a fresh token per request shares one bucket
+    for token in ["mallory-1", "mallory-2"] {
+        let response = app.clone().oneshot(empty_batch(token)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = app.clone().oneshot(empty_batch("mallory-3")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Submissions spaced by the refill interval succeed
+    for _ in 0..3 {
+        tokio::time::sleep(refill + Duration::from_millis(20)).await;
+        let response = app.clone().oneshot(empty_batch("alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    println!("✓ Rate limit test passed");
+}
+
+#[test]
+fn test_idle_buckets_evicted() {
+    let refill = Duration::from_secs(1);
+    let limiter = RateLimiter::new().with_default_limit(RateLimit::new(2, refill));
+    let start = Instant::now();
+    for i in 0..10 {
+        assert!(limiter.check("/submit/batch", &format!("ip:10.0.0.{}", i), start));
+    }
+    assert!(limiter.check("/submit/batch", "ip:10.0.0.0", start + refill / 2));
+    assert_eq!(limiter.bucket_count(), 10);
+
+    // Buckets back at their burst are dropped; the busier one is still refilling
+    assert_eq!(limiter.prune_idle(start + refill), 9);
+    assert_eq!(limiter.bucket_count(), 1);
+    assert_eq!(limiter.prune_idle(start + 2 * refill), 1);
+
+    // A client whose bucket was dropped starts over with the full burst
+    assert!(limiter.check("/submit/batch", "ip:10.0.0.1", start + 2 * refill));
+    assert!(limiter.check("/submit/batch", "ip:10.0.0.1", start + 2 * refill));
+    assert!(!limiter.check("/submit/batch", "ip:10.0.0.1", start + 2 * refill));
+}
@@ -16,7 +16,8 @@ use fluxe_circuits::{
 use fluxe_core::{
     data_structures::{Note, IngressReceipt, ExitReceipt, ComplianceState, ZkObject},
     crypto::pedersen::{PedersenParams, PedersenCommitment, PedersenRandomness},
-    merkle::{IncrementalTree, MerklePath},
+    crypto::{compute_ec_public_key, owner_address_from_pk},
+    merkle::{IncrementalTree, MerklePath, SortedTree},
     types::*,
 };
 
@@ -85,6 +86,11 @@ fn create_mint_circuit<R: RngCore>(rng: &mut R) -> MintCircuit {
     let mut cmt_tree = IncrementalTree::new(16);
     let mut ingress_tree = IncrementalTree::new(16);
     
+    let minter_sk = F::rand(rng);
+    let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+    let mut authorized_minters = SortedTree::new(16);
+    authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+    
     MintCircuit::new(
         vec![note],
         vec![value],
@@ -93,6 +99,8 @@ fn create_mint_circuit<R: RngCore>(rng: &mut R) -> MintCircuit {
         &mut cmt_tree,
         &mut ingress_tree,
     )
+    .with_minter(minter_sk, &authorized_minters)
+    .unwrap()
 }
 
 fn create_burn_circuit<R: RngCore>(rng: &mut R) -> BurnCircuit {
@@ -124,14 +132,17 @@ fn create_burn_circuit<R: RngCore>(rng: &mut R) -> BurnCircuit {
         cm_path: path,
         nf_nonmembership: None, // Simplified for benchmarking
         exit_receipt,
+        exit_allow_proof: None, // Simplified for benchmarking
         cmt_root: F::rand(rng),
         nft_root_old: F::rand(rng),
         nft_root_new: F::rand(rng),
         exit_root_old: F::rand(rng),
         exit_root_new: F::rand(rng),
+        exit_allow_root: F::rand(rng),
         asset_type: 1,
         amount: Amount::from(value as u128),
         nf_in: F::rand(rng),
+        tree_config: None,
     }
 }
 
@@ -212,8 +223,29 @@ fn create_transfer_circuit<R: RngCore>(rng: &mut R, num_inputs: usize, num_outpu
         sanctions_root: F::rand(rng),
         pool_rules_root: F::rand(rng),
         nf_list,
+        n_out: cm_list.len() as u64,
         cm_list,
         fee: Amount::from(10u128),
+        consolidation: false,
+        checked_compliance: false,
+        compliance_states_in: Vec::new(),
+        require_fresh_review: false,
+        current_time: 0,
+        max_review_age: 0,
+        fee_note: None,
+        recent_cmt_roots: Vec::new(),
+        cm_path_roots: Vec::new(),
+        memos_out: Vec::new(),
+        derived_randomness: false,
+        min_denomination: None,
+        asset_fees: Vec::new(),
+        check_permitted_pools: false,
+        migration: None,
+        tree_config: None,
+        block_frozen_recipients: false,
+        recipient_compliance: Vec::new(),
+        owner_index_root: F::from(0u64),
+        expose_sanctions_fallbacks: false,
     }
 }
 
@@ -228,12 +260,14 @@ fn create_object_update_circuit<R: RngCore>(rng: &mut R) -> ObjectUpdateCircuit
         state_hash: state_old.hash(),
         serial: 100,
         cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
     };
     
     let obj_new = ZkObject {
         state_hash: state_new.hash(),
         serial: 101,
         cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
     };
     
     let obj_path_old = MerklePath {
@@ -257,6 +291,10 @@ fn create_object_update_circuit<R: RngCore>(rng: &mut R) -> ObjectUpdateCircuit
         obj_root_new: F::rand(rng),
         cb_root: F::rand(rng),
         current_time: 1000,
+        owner: None,
+        freeze: false,
+        callback_op_hashes: None,
+        tree_config: None,
     }
 }
 
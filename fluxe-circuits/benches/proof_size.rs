@@ -0,0 +1,367 @@
+//! Serialized proof size and public-input count per circuit shape, for on-chain cost estimates
+//!
+//! Groth16 over BLS12-381 gives a constant-size proof (A, C in G1 and B in G2) whatever the
+//! circuit: 192 bytes compressed, 384 uncompressed. Each public input is a 32-byte field element,
+//! so calldata grows with the input count (transfers grow with their input and output count)
+//! The sizes are printed once per shape before the serialization timings
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_groth16::{Groth16, Proof};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use ark_ff::UniformRand;
+use ark_std::rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use fluxe_circuits::{
+    mint::MintCircuit,
+    burn::BurnCircuit,
+    transfer::TransferCircuit,
+    object_update::ObjectUpdateCircuit,
+    circuits::FluxeCircuit,
+};
+use fluxe_core::{
+    data_structures::{Note, IngressReceipt, ExitReceipt, ComplianceState, ZkObject},
+    crypto::pedersen::{PedersenParams, PedersenCommitment, PedersenRandomness},
+    crypto::{compute_ec_public_key, owner_address_from_pk},
+    merkle::{IncrementalTree, MerklePath, SortedTree},
+    types::*,
+};
+
+/// Compressed size of every Groth16 proof over BLS12-381
+const COMPRESSED_PROOF_BYTES: usize = 192;
+/// Uncompressed size of every Groth16 proof over BLS12-381
+const UNCOMPRESSED_PROOF_BYTES: usize = 384;
+
+/// Sizes of one proof and its public inputs
+struct ProofSizes {
+    compressed: usize,
+    uncompressed: usize,
+    public_inputs: usize,
+    public_input_bytes: usize,
+}
+
+/// Prove `make_circuit()` twice with independent randomness and measure the result
+/// Panics if the two proofs of the same shape serialize to different sizes
+fn measure<C, M>(name: &str, make_circuit: M) -> (Proof<Bls12_381>, ProofSizes)
+where
+    C: FluxeCircuit + Clone,
+    M: Fn(&mut ChaCha20Rng) -> C,
+{
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let circuit = make_circuit(&mut rng);
+    let (pk, _) = Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng)
+        .expect("Setup failed");
+    
+    let public_inputs = circuit.public_inputs();
+    let first = Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).expect("Proof failed");
+    let second = Groth16::<Bls12_381>::prove(&pk, make_circuit(&mut rng), &mut rng).expect("Proof failed");
+    
+    let sizes = ProofSizes {
+        compressed: first.compressed_size(),
+        uncompressed: first.uncompressed_size(),
+        public_inputs: public_inputs.len(),
+        public_input_bytes: public_inputs.iter().map(|input| input.compressed_size()).sum(),
+    };
+    assert_eq!(sizes.compressed, second.compressed_size(), "{}: proof size varies", name);
+    assert_eq!(sizes.compressed, COMPRESSED_PROOF_BYTES, "{}: unexpected compressed size", name);
+    assert_eq!(sizes.uncompressed, UNCOMPRESSED_PROOF_BYTES, "{}: unexpected uncompressed size", name);
+    
+    println!(
+        "{}: proof {} bytes compressed ({} uncompressed), {} public inputs ({} bytes)",
+        name, sizes.compressed, sizes.uncompressed, sizes.public_inputs, sizes.public_input_bytes
+    );
+    (first, sizes)
+}
+
+/// Time compressed serialization of `proof`, which is what a submission carries
+fn bench_serialization(c: &mut Criterion, name: &str, proof: &Proof<Bls12_381>, sizes: &ProofSizes) {
+    c.bench_function(&format!("{}_proof_serialization", name), |b| {
+        b.iter(|| {
+            let mut bytes = Vec::with_capacity(sizes.compressed);
+            proof.serialize_compressed(&mut bytes).expect("Serialization failed");
+            black_box(bytes);
+        });
+    });
+}
+
+fn create_mint_circuit<R: RngCore>(rng: &mut R) -> MintCircuit {
+    let params = PedersenParams::setup_value_commitment();
+    let value = 1000u64;
+    let randomness = F::rand(rng);
+    
+    let v_comm = PedersenCommitment::commit(
+        &params,
+        value,
+        &PedersenRandomness { r: randomness },
+    );
+    
+    let note = Note::new(1, v_comm, F::rand(rng), [0u8; 32], 1);
+    let ingress = IngressReceipt::new(1, Amount::from(value as u128), note.commitment(), 1);
+    
+    let mut cmt_tree = IncrementalTree::new(16);
+    let mut ingress_tree = IncrementalTree::new(16);
+    
+    let minter_sk = F::rand(rng);
+    let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+    let mut authorized_minters = SortedTree::new(16);
+    authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+    
+    MintCircuit::new(
+        vec![note],
+        vec![value],
+        vec![randomness],
+        ingress,
+        &mut cmt_tree,
+        &mut ingress_tree,
+    )
+    .with_minter(minter_sk, &authorized_minters)
+    .unwrap()
+}
+
+fn create_burn_circuit<R: RngCore>(rng: &mut R) -> BurnCircuit {
+    let params = PedersenParams::setup_value_commitment();
+    let value = 500u64;
+    let randomness = F::rand(rng);
+    
+    let v_comm = PedersenCommitment::commit(
+        &params,
+        value,
+        &PedersenRandomness { r: randomness },
+    );
+    
+    let note_in = Note::new(1, v_comm, F::rand(rng), [0u8; 32], 1);
+    let exit_receipt = ExitReceipt::new(1, Amount::from(value as u128), F::rand(rng), 1);
+    
+    let path = MerklePath {
+        leaf_index: 0,
+        siblings: vec![F::from(0u64); 16],
+        leaf: note_in.commitment(),
+    };
+    
+    BurnCircuit {
+        note_in,
+        value_in: value,
+        value_randomness_in: randomness,
+        owner_sk: F::rand(rng),
+        nk: F::rand(rng),
+        cm_path: path,
+        nf_nonmembership: None, // Simplified for benchmarking
+        exit_receipt,
+        exit_allow_proof: None, // Simplified for benchmarking
+        cmt_root: F::rand(rng),
+        nft_root_old: F::rand(rng),
+        nft_root_new: F::rand(rng),
+        exit_root_old: F::rand(rng),
+        exit_root_new: F::rand(rng),
+        exit_allow_root: F::rand(rng),
+        asset_type: 1,
+        amount: Amount::from(value as u128),
+        nf_in: F::rand(rng),
+        tree_config: None,
+    }
+}
+
+fn create_transfer_circuit<R: RngCore>(rng: &mut R, num_inputs: usize, num_outputs: usize) -> TransferCircuit {
+    let params = PedersenParams::setup_value_commitment();
+    
+    // Create input notes
+    let mut notes_in = Vec::new();
+    let mut values_in = Vec::new();
+    let mut value_randomness_in = Vec::new();
+    let mut nks = Vec::new();
+    let mut cm_paths = Vec::new();
+    let mut nf_list = Vec::new();
+    
+    for _ in 0..num_inputs {
+        let value = 500u64;
+        let randomness = F::rand(rng);
+        let v_comm = PedersenCommitment::commit(
+            &params,
+            value,
+            &PedersenRandomness { r: randomness },
+        );
+        
+        let note = Note::new(1, v_comm, F::rand(rng), [0u8; 32], 1);
+        notes_in.push(note.clone());
+        values_in.push(value);
+        value_randomness_in.push(randomness);
+        nks.push(F::rand(rng));
+        
+        let path = MerklePath {
+            leaf_index: 0,
+            siblings: vec![F::from(0u64); 16],
+            leaf: note.commitment(),
+        };
+        cm_paths.push(path);
+        nf_list.push(F::rand(rng));
+    }
+    
+    // Create output notes
+    let mut notes_out = Vec::new();
+    let mut values_out = Vec::new();
+    let mut value_randomness_out = Vec::new();
+    let mut cm_list = Vec::new();
+    
+    let value_per_output = (values_in.iter().sum::<u64>() - 10) / num_outputs as u64;
+    
+    for _ in 0..num_outputs {
+        let randomness = F::rand(rng);
+        let v_comm = PedersenCommitment::commit(
+            &params,
+            value_per_output,
+            &PedersenRandomness { r: randomness },
+        );
+        
+        let note = Note::new(1, v_comm, F::rand(rng), [0u8; 32], 1);
+        notes_out.push(note.clone());
+        values_out.push(value_per_output);
+        value_randomness_out.push(randomness);
+        cm_list.push(note.commitment());
+    }
+    
+    TransferCircuit {
+        notes_in,
+        values_in,
+        value_randomness_in,
+        notes_out,
+        values_out,
+        value_randomness_out,
+        nks,
+        cm_paths,
+        nf_nonmembership_proofs: vec![None; num_inputs], // Simplified for benchmarking
+        sanctions_nm_proofs_in: vec![None; num_inputs],
+        sanctions_nm_proofs_out: vec![None; num_outputs],
+        cmt_root_old: F::rand(rng),
+        cmt_root_new: F::rand(rng),
+        nft_root_old: F::rand(rng),
+        nft_root_new: F::rand(rng),
+        sanctions_root: F::rand(rng),
+        pool_rules_root: F::rand(rng),
+        nf_list,
+        n_out: cm_list.len() as u64,
+        cm_list,
+        fee: Amount::from(10u128),
+        consolidation: false,
+        checked_compliance: false,
+        compliance_states_in: Vec::new(),
+        require_fresh_review: false,
+        current_time: 0,
+        max_review_age: 0,
+        fee_note: None,
+        recent_cmt_roots: Vec::new(),
+        cm_path_roots: Vec::new(),
+        memos_out: Vec::new(),
+        derived_randomness: false,
+        min_denomination: None,
+        asset_fees: Vec::new(),
+        check_permitted_pools: false,
+        migration: None,
+        tree_config: None,
+        block_frozen_recipients: false,
+        recipient_compliance: Vec::new(),
+        owner_index_root: F::from(0u64),
+        expose_sanctions_fallbacks: false,
+    }
+}
+
+fn create_object_update_circuit<R: RngCore>(rng: &mut R) -> ObjectUpdateCircuit {
+    let state_old = ComplianceState::new_verified(1);
+    let state_new = ComplianceState {
+        level: 2,
+        ..state_old.clone()
+    };
+    
+    let obj_old = ZkObject {
+        state_hash: state_old.hash(),
+        serial: 100,
+        cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
+    };
+    
+    let obj_new = ZkObject {
+        state_hash: state_new.hash(),
+        serial: 101,
+        cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
+    };
+    
+    let obj_path_old = MerklePath {
+        leaf_index: 0,
+        siblings: vec![F::from(0u64); 16],
+        leaf: F::rand(rng),
+    };
+    
+    ObjectUpdateCircuit {
+        obj_old,
+        state_old,
+        obj_new,
+        state_new,
+        callback_entry: None,
+        callback_invocation: None,
+        cb_path: None,
+        cb_nonmembership: None,
+        obj_path_old,
+        decrypt_key: None,
+        obj_root_old: F::rand(rng),
+        obj_root_new: F::rand(rng),
+        cb_root: F::rand(rng),
+        current_time: 1000,
+        owner: None,
+        freeze: false,
+        callback_op_hashes: None,
+        tree_config: None,
+    }
+}
+
+
+fn bench_mint_proof_size(c: &mut Criterion) {
+    let (proof, sizes) = measure("mint", |rng| create_mint_circuit(rng));
+    bench_serialization(c, "mint", &proof, &sizes);
+}
+
+fn bench_burn_proof_size(c: &mut Criterion) {
+    let (proof, sizes) = measure("burn", |rng| create_burn_circuit(rng));
+    bench_serialization(c, "burn", &proof, &sizes);
+}
+
+fn bench_transfer_proof_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transfer_proof_serialization");
+    
+    // Proof size is fixed; the public-input count grows with the shape
+    let mut input_counts = Vec::new();
+    for (num_inputs, num_outputs) in [(1, 1), (2, 2), (2, 4), (4, 4)] {
+        let name = format!("transfer_{}in_{}out", num_inputs, num_outputs);
+        let (proof, sizes) = measure(&name, |rng| create_transfer_circuit(rng, num_inputs, num_outputs));
+        input_counts.push(sizes.public_inputs);
+        
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}in_{}out", num_inputs, num_outputs)),
+            &proof,
+            |b, proof| {
+                b.iter(|| {
+                    let mut bytes = Vec::with_capacity(sizes.compressed);
+                    proof.serialize_compressed(&mut bytes).expect("Serialization failed");
+                    black_box(bytes);
+                });
+            }
+        );
+    }
+    assert!(input_counts.windows(2).all(|pair| pair[0] <= pair[1]));
+    group.finish();
+}
+
+fn bench_object_update_proof_size(c: &mut Criterion) {
+    let (proof, sizes) = measure("object_update", |rng| create_object_update_circuit(rng));
+    bench_serialization(c, "object_update", &proof, &sizes);
+}
+
+criterion_group!(
+    benches,
+    bench_mint_proof_size,
+    bench_burn_proof_size,
+    bench_transfer_proof_size,
+    bench_object_update_proof_size
+);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use ark_bls12_381::{Bls12_381, Fr as F};
-use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
 use ark_snark::SNARK;
 use ark_ff::UniformRand;
 use ark_std::rand::{RngCore, SeedableRng};
@@ -18,7 +18,9 @@ use fluxe_circuits::{
 use fluxe_core::{
     data_structures::{Note, IngressReceipt, ExitReceipt, ComplianceState, ZkObject},
     crypto::pedersen::{PedersenParams, PedersenCommitment, PedersenRandomness},
-    merkle::{IncrementalTree, MerklePath},
+    crypto::{compute_ec_public_key, owner_address_from_pk},
+    merkle::{IncrementalTree, MerklePath, SortedTree},
+    server_verifier::batch_verify_groth16,
     types::*,
 };
 
@@ -122,6 +124,11 @@ fn create_mint_circuit<R: RngCore>(rng: &mut R) -> MintCircuit {
     let mut cmt_tree = IncrementalTree::new(16);
     let mut ingress_tree = IncrementalTree::new(16);
     
+    let minter_sk = F::rand(rng);
+    let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+    let mut authorized_minters = SortedTree::new(16);
+    authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+    
     MintCircuit::new(
         vec![note],
         vec![value],
@@ -130,6 +137,8 @@ fn create_mint_circuit<R: RngCore>(rng: &mut R) -> MintCircuit {
         &mut cmt_tree,
         &mut ingress_tree,
     )
+    .with_minter(minter_sk, &authorized_minters)
+    .unwrap()
 }
 
 fn create_burn_circuit<R: RngCore>(rng: &mut R) -> BurnCircuit {
@@ -161,14 +170,17 @@ fn create_burn_circuit<R: RngCore>(rng: &mut R) -> BurnCircuit {
         cm_path: path,
         nf_nonmembership: None, // Simplified for benchmarking
         exit_receipt,
+        exit_allow_proof: None, // Simplified for benchmarking
         cmt_root: F::rand(rng),
         nft_root_old: F::rand(rng),
         nft_root_new: F::rand(rng),
         exit_root_old: F::rand(rng),
         exit_root_new: F::rand(rng),
+        exit_allow_root: F::rand(rng),
         asset_type: 1,
         amount: Amount::from(value as u128),
         nf_in: F::rand(rng),
+        tree_config: None,
     }
 }
 
@@ -249,8 +261,29 @@ fn create_transfer_circuit<R: RngCore>(rng: &mut R, num_inputs: usize, num_outpu
         sanctions_root: F::rand(rng),
         pool_rules_root: F::rand(rng),
         nf_list,
+        n_out: cm_list.len() as u64,
         cm_list,
         fee: Amount::from(10u128),
+        consolidation: false,
+        checked_compliance: false,
+        compliance_states_in: Vec::new(),
+        require_fresh_review: false,
+        current_time: 0,
+        max_review_age: 0,
+        fee_note: None,
+        recent_cmt_roots: Vec::new(),
+        cm_path_roots: Vec::new(),
+        memos_out: Vec::new(),
+        derived_randomness: false,
+        min_denomination: None,
+        asset_fees: Vec::new(),
+        check_permitted_pools: false,
+        migration: None,
+        tree_config: None,
+        block_frozen_recipients: false,
+        recipient_compliance: Vec::new(),
+        owner_index_root: F::from(0u64),
+        expose_sanctions_fallbacks: false,
     }
 }
 
@@ -265,12 +298,14 @@ fn create_object_update_circuit<R: RngCore>(rng: &mut R) -> ObjectUpdateCircuit
         state_hash: state_old.hash(),
         serial: 100,
         cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
     };
     
     let obj_new = ZkObject {
         state_hash: state_new.hash(),
         serial: 101,
         cb_head_hash: F::from(0),
+        owner_addr: F::from(0),
     };
     
     let obj_path_old = MerklePath {
@@ -294,6 +329,10 @@ fn create_object_update_circuit<R: RngCore>(rng: &mut R) -> ObjectUpdateCircuit
         obj_root_new: F::rand(rng),
         cb_root: F::rand(rng),
         current_time: 1000,
+        owner: None,
+        freeze: false,
+        callback_op_hashes: None,
+        tree_config: None,
     }
 }
 
@@ -453,6 +492,48 @@ fn bench_parallel_verification(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_accumulated_batch_verification(c: &mut Criterion) {
+    let setup = VerificationSetup::new();
+    let pvk = prepare_verifying_key(&setup.transfer_vk);
+    
+    // Naive: 3n Miller loops and n final exponentiations
+    // Accumulated: n + 2 Miller loops and a single final exponentiation
+    let mut group = c.benchmark_group("accumulated_batch_verification");
+    for batch_size in [1, 5, 10, 20, 50] {
+        let proofs: Vec<(&Proof<Bls12_381>, &[F])> = (0..batch_size)
+            .map(|_| (&setup.transfer_proof, setup.transfer_public_inputs.as_slice()))
+            .collect();
+        
+        group.bench_with_input(
+            BenchmarkId::new("naive", batch_size),
+            &proofs,
+            |b, proofs| {
+                b.iter(|| {
+                    let all_valid = proofs.iter().all(|(proof, inputs)| {
+                        Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, inputs, proof)
+                            .expect("Verification failed")
+                    });
+                    black_box(all_valid);
+                });
+            }
+        );
+        
+        group.bench_with_input(
+            BenchmarkId::new("accumulated", batch_size),
+            &proofs,
+            |b, proofs| {
+                let mut rng = ChaCha20Rng::seed_from_u64(7);
+                b.iter(|| {
+                    let all_valid = batch_verify_groth16(&pvk, proofs, &mut rng)
+                        .expect("Verification failed");
+                    black_box(all_valid);
+                });
+            }
+        );
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_mint_verification,
@@ -460,6 +541,7 @@ criterion_group!(
     bench_transfer_verification,
     bench_object_update_verification,
     bench_batch_verification,
-    bench_parallel_verification
+    bench_parallel_verification,
+    bench_accumulated_batch_verification
 );
 criterion_main!(benches);
\ No newline at end of file
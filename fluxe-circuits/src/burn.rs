@@ -5,13 +5,15 @@ use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use fluxe_core::{
     data_structures::{ExitReceipt, Note},
-    merkle::{MerklePath, RangePath, AppendWitness},
+    merkle::{MerklePath, RangePath, AppendWitness, SortedMembershipProof, IncrementalTree, SortedTree, TreeConfig, TreeError},
+    crypto::{compute_ec_public_key, owner_address_from_pk},
     types::*,
 };
 use crate::gadgets::sorted_insert::{SortedInsertWitness, SimtInsertVar};
 use crate::gadgets::merkle_append::ImtAppendProofVar;
+use crate::gadgets::sorted_tree::SortedMembershipVar;
 
-use crate::circuits::FluxeCircuit;
+use crate::circuits::{ConstraintSections, FluxeCircuit};
 use crate::gadgets::*;
 
 /// Burn circuit for withdrawals (boundary-out transactions)
@@ -52,6 +54,9 @@ pub struct BurnCircuit {
     /// Append witness for EXIT_ROOT update
     pub exit_append_witness: AppendWitness,
     
+    /// Membership proof of the exit destination in EXIT_ALLOW_ROOT
+    pub exit_allow_proof: Option<SortedMembershipProof>,
+    
     // Public inputs
     /// Commitment tree root
     pub cmt_root: MerkleRoot,
@@ -68,6 +73,9 @@ pub struct BurnCircuit {
     /// New exit root
     pub exit_root_new: MerkleRoot,
     
+    /// Root of the sorted tree of approved exit destinations
+    pub exit_allow_root: MerkleRoot,
+    
     /// Asset type being burned
     pub asset_type: AssetType,
     
@@ -76,6 +84,9 @@ pub struct BurnCircuit {
     
     /// Nullifier of input note
     pub nf_in: Nullifier,
+    
+    /// Configured tree heights the witness paths must match (None skips the check)
+    pub tree_config: Option<TreeConfig>,
 }
 
 impl BurnCircuit {
@@ -92,11 +103,13 @@ impl BurnCircuit {
         nf_insert_witness: Option<SortedInsertWitness>,
         exit_receipt: ExitReceipt,
         exit_append_witness: AppendWitness,
+        exit_allow_proof: Option<SortedMembershipProof>,
         cmt_root: MerkleRoot,
         nft_root_old: MerkleRoot,
         nft_root_new: MerkleRoot,
         exit_root_old: MerkleRoot,
         exit_root_new: MerkleRoot,
+        exit_allow_root: MerkleRoot,
     ) -> Self {
         let asset_type = note_in.asset_type;
         let amount = exit_receipt.amount;
@@ -115,20 +128,180 @@ impl BurnCircuit {
             nf_insert_witness,
             exit_receipt,
             exit_append_witness,
+            exit_allow_proof,
             cmt_root,
             nft_root_old,
             nft_root_new,
             exit_root_old,
             exit_root_new,
+            exit_allow_root,
             asset_type,
             amount,
             nf_in,
+            tree_config: None,
+        }
+    }
+    
+    /// Require the witnesses to be built for the trees in `config`
+    pub fn with_tree_config(mut self, config: TreeConfig) -> Self {
+        self.tree_config = Some(config);
+        self
+    }
+}
+
+/// Assembles a fully-witnessed BurnCircuit against live trees
+/// Building spends the note: its nullifier is inserted into the NFT and the
+/// exit receipt is appended to the exit tree, so the trees end at the circuit's new roots
+pub struct BurnBuilder<'a> {
+    cmt_tree: &'a mut IncrementalTree,
+    nft_tree: &'a mut SortedTree,
+    exit_tree: &'a mut IncrementalTree,
+    note_in: Note,
+    value_in: u64,
+    value_randomness_in: F,
+    owner_sk: F,
+    nk: F,
+    amount: Amount,
+    nonce: u64,
+    exit_destination: Option<(F, SortedMembershipProof, MerkleRoot)>,
+}
+
+impl<'a> BurnBuilder<'a> {
+    /// Burn the full value of `note_in`, which must already be in `cmt_tree`
+    pub fn new(
+        cmt_tree: &'a mut IncrementalTree,
+        nft_tree: &'a mut SortedTree,
+        exit_tree: &'a mut IncrementalTree,
+        note_in: Note,
+        value_in: u64,
+        value_randomness_in: F,
+        owner_sk: F,
+        nk: F,
+    ) -> Self {
+        Self {
+            cmt_tree,
+            nft_tree,
+            exit_tree,
+            note_in,
+            value_in,
+            value_randomness_in,
+            owner_sk,
+            nk,
+            amount: Amount::from(value_in),
+            nonce: 0,
+            exit_destination: None,
+        }
+    }
+    
+    /// Burn only part of the note's value
+    pub fn with_amount(mut self, amount: Amount) -> Self {
+        self.amount = amount;
+        self
+    }
+    
+    /// Set the exit receipt nonce
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+    
+    /// Exit to `destination`, which must be a key in `exit_allow_tree`
+    pub fn with_exit_destination(
+        mut self,
+        exit_allow_tree: &SortedTree,
+        destination: F,
+    ) -> Result<Self, FluxeError> {
+        let proof = exit_allow_tree
+            .prove_key_membership(destination)
+            .ok_or_else(|| FluxeError::Other("Exit destination not approved".to_string()))?;
+        self.exit_destination = Some((destination, proof, exit_allow_tree.root()));
+        Ok(self)
+    }
+    
+    /// Witness the burn and update the NFT and exit trees; the circuit is held to their heights
+    pub fn build(self) -> Result<BurnCircuit, FluxeError> {
+        let (exit_destination, exit_allow_proof, exit_allow_root) = self.exit_destination
+            .ok_or_else(|| FluxeError::Other("Exit destination not set".to_string()))?;
+        
+        if self.amount > Amount::from(self.value_in) {
+            return Err(FluxeError::InsufficientBalance);
         }
+        
+        // Owner authentication: the note must belong to owner_sk
+        let (owner_pk_x, owner_pk_y) = compute_ec_public_key(self.owner_sk);
+        if owner_address_from_pk(owner_pk_x, owner_pk_y) != self.note_in.owner_addr {
+            return Err(FluxeError::Other("Owner secret does not match note owner".to_string()));
+        }
+        
+        // Membership of the input note in CMT
+        let cm_path = self.cmt_tree
+            .get_proof(self.note_in.commitment())
+            .ok_or(FluxeError::InvalidMerklePath)?;
+        let cmt_root = self.cmt_tree.root();
+        
+        // Nullifier non-membership, then insertion into NFT
+        let nf_in = self.note_in.nullifier(&self.nk);
+        if self.nft_tree.contains(&nf_in) {
+            return Err(FluxeError::DoubleSpend(nf_in));
+        }
+        let nft_root_old = self.nft_tree.root();
+        let nf_nonmembership = self.nft_tree.prove_non_membership(nf_in)?;
+        let witness = self.nft_tree.insert_with_witness(nf_in)?;
+        let nft_root_new = self.nft_tree.root();
+        let nf_insert_witness = SortedInsertWitness {
+            target: witness.target,
+            range_proof: witness.range_proof,
+            new_leaf: witness.new_leaf,
+            updated_pred_leaf: witness.updated_pred_leaf,
+            new_leaf_path: witness.new_leaf_path,
+            pred_update_path: witness.pred_update_path,
+            height: witness.height,
+        };
+        
+        // Exit receipt appended to the exit tree
+        let exit_receipt = ExitReceipt::new(self.note_in.asset_type, self.amount, nf_in, self.nonce)
+            .with_destination(exit_destination);
+        let exit_root_old = self.exit_tree.root();
+        let exit_append_witness = self.exit_tree.generate_append_witness(exit_receipt.hash());
+        let exit_root_new = self.exit_tree.append_returning_root(exit_receipt.hash());
+        let tree_config = TreeConfig {
+            cmt: self.cmt_tree.height(),
+            nft: self.nft_tree.height(),
+            exit: self.exit_tree.height(),
+            ..TreeConfig::default()
+        };
+        
+        Ok(BurnCircuit::new(
+            self.note_in,
+            self.value_in,
+            self.value_randomness_in,
+            self.nk,
+            self.owner_sk,
+            owner_pk_x,
+            owner_pk_y,
+            cm_path,
+            Some(nf_nonmembership),
+            Some(nf_insert_witness),
+            exit_receipt,
+            exit_append_witness,
+            Some(exit_allow_proof),
+            cmt_root,
+            nft_root_old,
+            nft_root_new,
+            exit_root_old,
+            exit_root_new,
+            exit_allow_root,
+        ).with_tree_config(tree_config))
     }
 }
 
 impl ConstraintSynthesizer<F> for BurnCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Shape check: witnesses built for another tree height cannot be hashed to the roots
+        if self.check_tree_heights().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         // Witness private inputs
         let note_in_var = NoteVar::new_witness(
             cs.clone(),
@@ -150,9 +323,20 @@ impl ConstraintSynthesizer<F> for BurnCircuit {
         let asset_type_var = FpVar::new_input(cs.clone(), || Ok(F::from(self.asset_type as u64)))?;
         let amount_var = FpVar::new_input(cs.clone(), || Ok(self.amount.to_field()))?;
         let nf_in_var = FpVar::new_input(cs.clone(), || Ok(self.nf_in))?;
+        let exit_allow_root_var = FpVar::new_input(cs.clone(), || Ok(self.exit_allow_root))?;
+        let cm_in_var = FpVar::new_input(cs.clone(), || Ok(self.note_in.commitment()))?;
+        
+        // Constraint 0: Burned amount fits in RECEIPT_AMOUNT_BITS, before the
+        // comparison below relies on it; the exit receipt amount is bound to it
+        use crate::gadgets::range_proof::RangeProofGadget;
+        ConstraintSections::begin(&cs, "amount range");
+        RangeProofGadget::prove_range_bits(cs.clone(), &amount_var, RECEIPT_AMOUNT_BITS)?;
+        ConstraintSections::begin(&cs, "spend");
         
         // Constraint 1: Verify membership - input note is in CMT tree
+        // The burned note's commitment is public so a block cannot burn one note twice
         let cm_in = note_in_var.commitment()?;
+        cm_in.enforce_equal(&cm_in_var)?;
         cm_path_var.leaf.enforce_equal(&cm_in)?;
         cm_path_var.enforce_valid(&cmt_root_var)?;
         
@@ -168,7 +352,7 @@ impl ConstraintSynthesizer<F> for BurnCircuit {
         use crate::gadgets::auth::AuthGadget;
         let (derived_pk_x_fq, derived_pk_y_fq) = AuthGadget::scalar_mult_generator(cs.clone(), &owner_sk_var)?;
         
-        // Compute owner address from the derived public key: addr = H(pk_x, pk_y)
+        // Compute owner address from the derived public key: addr = H(DOM_OWNER_ADDR, pk_x, pk_y)
         let computed_owner_addr = AuthGadget::compute_owner_address_from_fq(cs.clone(), &derived_pk_x_fq, &derived_pk_y_fq)?;
         
         // Enforce that computed address matches note's owner
@@ -191,6 +375,15 @@ impl ConstraintSynthesizer<F> for BurnCircuit {
         exit_var.amount.enforce_equal(&amount_var)?;
         exit_var.burned_nf.enforce_equal(&nf_in_var)?;
         
+        // Constraint 5b: Exit destination is approved (member of EXIT_ALLOW_ROOT)
+        if let Some(ref allow_proof) = self.exit_allow_proof {
+            let allow_proof_var = SortedMembershipVar::new_witness(cs.clone(), || Ok(allow_proof.clone()))?;
+            allow_proof_var.enforce_member(&exit_var.exit_destination, &exit_allow_root_var)?;
+        } else {
+            // SECURITY: Without it, funds could exit to an arbitrary destination
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         // Constraint 6: Non-membership of nf_in in NFT_ROOT_old (S-IMT gap proof)
         // Verify the nullifier doesn't already exist (prevent double spend)
         if let Some(ref nm_proof) = self.nf_nonmembership {
@@ -212,11 +405,6 @@ impl ConstraintSynthesizer<F> for BurnCircuit {
         let compliance_not_frozen = note_in_var.compliance_hash.is_neq(&FpVar::zero())?;
         compliance_not_frozen.enforce_equal(&Boolean::TRUE)?;
         
-        // Check spending limits (simplified - would check against ComplianceState)
-        use crate::gadgets::range_proof::RangeProofGadget;
-        // Verify amount is within acceptable range (64-bit for monetary values)
-        RangeProofGadget::prove_range_bits(cs.clone(), &amount_var, 64)?;
-        
         // Constraint 8: Verify callback expiry (simplified)
         // In practice, would check if any callbacks in cb_head_hash have expired
         // For now, just ensure callbacks_hash is set (non-zero means no pending expired callbacks)
@@ -262,9 +450,8 @@ impl ConstraintSynthesizer<F> for BurnCircuit {
             self.exit_root_new,
         )?;
         
-        // Verify the append operation
-        let append_valid = exit_append_proof.verify()?;
-        append_valid.enforce_equal(&Boolean::TRUE)?;
+        // Verify the append operation and that EXIT_ROOT only grows
+        exit_append_proof.enforce_grows()?;
         
         // Verify the appended leaf is the exit receipt hash
         exit_append_proof.appended_leaf.enforce_equal(&exit_hash)?;
@@ -294,6 +481,8 @@ impl FluxeCircuit for BurnCircuit {
             F::from(self.asset_type as u64),
             self.amount.to_field(),
             self.nf_in,
+            self.exit_allow_root,
+            self.note_in.commitment(),
         ]
     }
     
@@ -308,6 +497,30 @@ impl FluxeCircuit for BurnCircuit {
             return Err(FluxeError::Other("Asset type mismatch".to_string()));
         }
         
+        // Verify the allowlist proof is for the receipt's destination
+        match &self.exit_allow_proof {
+            Some(proof) if proof.leaf.key == self.exit_receipt.exit_destination => {}
+            _ => return Err(FluxeError::Other("Exit destination not approved".to_string())),
+        }
+        
+        Ok(())
+    }
+    
+    /// Check every witness path has one sibling per level of its configured tree
+    /// The exit allowlist is set out-of-band and not part of the config
+    fn check_tree_heights(&self) -> Result<(), TreeError> {
+        let config = match &self.tree_config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        TreeConfig::check_siblings("CMT", config.cmt, self.cm_path.siblings.len())?;
+        if let Some(proof) = &self.nf_nonmembership {
+            TreeConfig::check_siblings("NFT", config.nft, proof.low_path.siblings.len())?;
+        }
+        if let Some(witness) = &self.nf_insert_witness {
+            witness.check_height("NFT", config.nft)?;
+        }
+        self.exit_append_witness.check_height("exit", config.exit)?;
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,10 +1,12 @@
 use ark_bls12_381::{Bls12_381, Fr as F};
 use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
 use ark_groth16::r1cs_to_qap::LibsnarkReduction;
-use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
 use ark_snark::SNARK;
+use fluxe_core::merkle::TreeError;
 use fluxe_core::types::*;
 use rand::{CryptoRng, RngCore};
+use std::any::{Any, TypeId};
 use std::marker::PhantomData;
 
 /// Common trait for all Fluxe circuits
@@ -14,6 +16,77 @@ pub trait FluxeCircuit: ConstraintSynthesizer<F> + Clone {
     
     /// Verify public inputs are valid
     fn verify_public_inputs(&self) -> Result<(), FluxeError>;
+    
+    /// Check every witness path has one sibling per level of its configured tree
+    /// Circuits without tree witnesses, or without a configuration, have nothing to check
+    fn check_tree_heights(&self) -> Result<(), TreeError> {
+        Ok(())
+    }
+    
+    /// Describe the first unsatisfied constraint by the labeled section it belongs to
+    /// Returns None when every constraint is satisfied
+    fn diagnose(cs: &ConstraintSystemRef<F>) -> Option<String> {
+        let trace = cs.which_is_unsatisfied().ok()??;
+        
+        // Without a ConstraintLayer the trace is just the constraint index
+        let index: usize = match trace.parse() {
+            Ok(index) => index,
+            Err(_) => return Some(trace),
+        };
+        
+        Some(match ConstraintSections::of(cs).label_for(index) {
+            Some(label) => format!("constraint {} unsatisfied in section '{}'", index, label),
+            None => format!("constraint {} unsatisfied (no section recorded)", index),
+        })
+    }
+}
+
+/// Labeled constraint ranges recorded on a constraint system during synthesis
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintSections {
+    /// (index of the section's first constraint, label) in synthesis order
+    pub starts: Vec<(usize, &'static str)>,
+}
+
+impl ConstraintSections {
+    /// Start a labeled section at the current constraint count
+    pub fn begin(cs: &ConstraintSystemRef<F>, label: &'static str) {
+        let start = cs.num_constraints();
+        if let Some(inner) = cs.borrow() {
+            let mut cache = inner.cache_map.borrow_mut();
+            let entry = cache
+                .entry(TypeId::of::<Self>())
+                .or_insert_with(|| Box::new(Self::default()) as Box<dyn Any>);
+            if let Some(sections) = entry.downcast_mut::<Self>() {
+                sections.starts.push((start, label));
+            }
+        }
+    }
+    
+    /// Sections recorded on a constraint system (empty if none)
+    pub fn of(cs: &ConstraintSystemRef<F>) -> Self {
+        let inner = match cs.borrow() {
+            Some(inner) => inner,
+            None => return Self::default(),
+        };
+        let cache = inner.cache_map.borrow();
+        let sections = cache
+            .get(&TypeId::of::<Self>())
+            .and_then(|entry| entry.downcast_ref::<Self>())
+            .cloned()
+            .unwrap_or_default();
+        sections
+    }
+    
+    /// Label of the section containing a constraint index
+    pub fn label_for(&self, index: usize) -> Option<&'static str> {
+        // Later starts win, so empty sections never claim a constraint
+        self.starts
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= index)
+            .map(|(_, label)| *label)
+    }
 }
 
 /// Circuit setup parameters
@@ -0,0 +1,216 @@
+use ark_bls12_381::Fr as F;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use fluxe_core::{
+    crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness},
+    data_structures::Note,
+    merkle::MerklePath,
+    types::*,
+};
+
+use crate::circuits::FluxeCircuit;
+use crate::gadgets::*;
+
+/// Note attributes a disclosure reveals; everything else stays private
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisclosedFields {
+    pub asset_type: bool,
+    pub value: bool,
+    pub owner_addr: bool,
+    pub pool_id: bool,
+}
+
+/// Selective disclosure circuit: proves a note is in CMT_ROOT and reveals the
+/// chosen attributes of that one note as public inputs
+#[derive(Clone)]
+pub struct DisclosureCircuit {
+    // Private inputs
+    /// Note being disclosed
+    pub note: Note,
+    
+    /// Value of the note
+    pub value: u64,
+    
+    /// Randomness for the note's value commitment
+    pub value_randomness: F,
+    
+    /// Merkle path for the note
+    pub cm_path: MerklePath,
+    
+    // Public inputs
+    /// Commitment tree root
+    pub cmt_root: MerkleRoot,
+    
+    /// Which attributes are public inputs, in the order asset_type, value, owner_addr, pool_id
+    pub disclosed: DisclosedFields,
+}
+
+impl DisclosureCircuit {
+    pub fn new(
+        note: Note,
+        value: u64,
+        value_randomness: F,
+        cm_path: MerklePath,
+        cmt_root: MerkleRoot,
+        disclosed: DisclosedFields,
+    ) -> Self {
+        Self {
+            note,
+            value,
+            value_randomness,
+            cm_path,
+            cmt_root,
+            disclosed,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<F> for DisclosureCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Witness private inputs
+        let note_var = NoteVar::new_witness(
+            cs.clone(),
+            || Ok(self.note.clone()),
+            self.value,
+            &self.value_randomness,
+        )?;
+        let cm_path_var = MerklePathVar::new_witness(cs.clone(), || Ok(self.cm_path.clone()))?;
+        
+        // Input public values
+        let cmt_root_var = FpVar::new_input(cs.clone(), || Ok(self.cmt_root))?;
+        
+        // Constraint 1: The note is in the CMT tree
+        let cm = note_var.commitment()?;
+        cm_path_var.leaf.enforce_equal(&cm)?;
+        cm_path_var.enforce_valid(&cmt_root_var)?;
+        
+        // Constraint 2: Disclosed attributes equal the note's
+        // NOTE: the value is bound to v_comm only natively (verify_public_inputs), as in NoteVar
+        let fields = [
+            (self.disclosed.asset_type, F::from(self.note.asset_type as u64), &note_var.asset_type),
+            (self.disclosed.value, F::from(self.value), &note_var.value),
+            (self.disclosed.owner_addr, self.note.owner_addr, &note_var.owner_addr),
+            (self.disclosed.pool_id, F::from(self.note.pool_id as u64), &note_var.pool_id),
+        ];
+        for (disclosed, value, var) in fields {
+            if disclosed {
+                let input = FpVar::new_input(cs.clone(), || Ok(value))?;
+                var.enforce_equal(&input)?;
+            }
+        }
+        
+        // Constraint 3: Disclosed value is a 64-bit amount
+        if self.disclosed.value {
+            note_var.verify_value_range()?;
+        }
+        
+        Ok(())
+    }
+}
+
+impl FluxeCircuit for DisclosureCircuit {
+    fn public_inputs(&self) -> Vec<F> {
+        let mut inputs = vec![self.cmt_root];
+        
+        if self.disclosed.asset_type {
+            inputs.push(F::from(self.note.asset_type as u64));
+        }
+        if self.disclosed.value {
+            inputs.push(F::from(self.value));
+        }
+        if self.disclosed.owner_addr {
+            inputs.push(self.note.owner_addr);
+        }
+        if self.disclosed.pool_id {
+            inputs.push(F::from(self.note.pool_id as u64));
+        }
+        
+        inputs
+    }
+    
+    fn verify_public_inputs(&self) -> Result<(), FluxeError> {
+        // Verify the path is for this note
+        if self.cm_path.leaf != self.note.commitment() {
+            return Err(FluxeError::InvalidMerklePath);
+        }
+        
+        // Verify the disclosed value opens the note's value commitment
+        if self.disclosed.value {
+            let params = PedersenParams::setup_value_commitment();
+            let randomness = PedersenRandomness { r: self.value_randomness };
+            if PedersenCommitment::commit(&params, self.value, &randomness) != self.note.v_comm {
+                return Err(FluxeError::Other("Disclosed value does not open the note's value commitment".to_string()));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::CircuitSetup;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::merkle::IncrementalTree;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_disclose_asset_type_and_value() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        
+        let value = 2500u64;
+        let randomness = F::rand(&mut rng);
+        let v_comm = PedersenCommitment::commit(&params, value, &PedersenRandomness { r: randomness });
+        let owner = F::rand(&mut rng);
+        let note = Note::new(3, v_comm, owner, [9u8; 32], 1);
+        
+        // The note sits among others in the commitment tree
+        let mut cmt_tree = IncrementalTree::new(16);
+        cmt_tree.append(F::rand(&mut rng));
+        let cm_path = cmt_tree.append(note.commitment());
+        cmt_tree.append(F::rand(&mut rng));
+        let cm_path = cmt_tree.get_path(cm_path.leaf_index).unwrap();
+        
+        let disclosed = DisclosedFields { asset_type: true, value: true, ..Default::default() };
+        let circuit = DisclosureCircuit::new(note, value, randomness, cm_path, cmt_tree.root(), disclosed);
+        
+        // Only the root, asset type and value are public; owner and psi are not
+        let public_inputs = circuit.public_inputs();
+        assert_eq!(public_inputs, vec![cmt_tree.root(), F::from(3u64), F::from(value)]);
+        assert!(!public_inputs.contains(&owner));
+        
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        
+        let setup = CircuitSetup::setup(circuit.clone(), &mut rng).unwrap();
+        let proof = setup.prove(circuit, &mut rng).unwrap();
+        assert!(setup.verify(&proof, &public_inputs).unwrap());
+        
+        // The same proof does not support a different value
+        let wrong = vec![public_inputs[0], public_inputs[1], F::from(value + 1)];
+        assert!(!setup.verify(&proof, &wrong).unwrap());
+    }
+    
+    #[test]
+    fn test_disclosure_rejects_wrong_value() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        
+        let randomness = F::rand(&mut rng);
+        let v_comm = PedersenCommitment::commit(&params, 100, &PedersenRandomness { r: randomness });
+        let note = Note::new(1, v_comm, F::rand(&mut rng), [2u8; 32], 1);
+        
+        let mut cmt_tree = IncrementalTree::new(16);
+        let cm_path = cmt_tree.append(note.commitment());
+        
+        // Claiming 200 for a note committed to 100
+        let disclosed = DisclosedFields { value: true, ..Default::default() };
+        let circuit = DisclosureCircuit::new(note, 200, randomness, cm_path, cmt_tree.root(), disclosed);
+        assert!(circuit.verify_public_inputs().is_err());
+    }
+}
@@ -15,9 +15,10 @@ use ark_r1cs_std::{
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 
 use crate::gadgets::poseidon::poseidon_hash_zk;
+use fluxe_core::crypto::{domain_sep_to_field, DOM_OWNER_ADDR};
 
 /// EC-based authentication gadget for Fluxe circuits
-/// Implements owner_addr = H(pk_x, pk_y) where pk is derived from sk
+/// Implements owner_addr = H(DOM_OWNER_ADDR, pk_x, pk_y) where pk is derived from sk
 pub struct AuthGadget;
 
 impl AuthGadget {
@@ -43,7 +44,7 @@ impl AuthGadget {
         // Derive public key from secret key: pk = sk * G
         let (pk_x_fq, pk_y_fq) = Self::scalar_mult_generator(cs.clone(), owner_sk)?;
         
-        // Compute owner address: addr = H(pk_x, pk_y)
+        // Compute owner address: addr = H(DOM_OWNER_ADDR, pk_x, pk_y)
         let computed_addr = Self::compute_owner_address_from_fq(cs, &pk_x_fq, &pk_y_fq)?;
         
         // Enforce that computed address matches expected
@@ -53,6 +54,7 @@ impl AuthGadget {
     }
     
     /// Compute owner address from Fq coordinates
+    /// Must match `fluxe_core::crypto::owner_address_from_pk` off-circuit
     pub fn compute_owner_address_from_fq(
         cs: ConstraintSystemRef<F>,
         pk_x_fq: &FqVar,
@@ -60,7 +62,8 @@ impl AuthGadget {
     ) -> Result<FpVar<F>, SynthesisError> {
         let pk_x = Self::fq_to_fr_constrained(cs.clone(), pk_x_fq)?;
         let pk_y = Self::fq_to_fr_constrained(cs, pk_y_fq)?;
-        poseidon_hash_zk(&[pk_x, pk_y])
+        let dom_owner = FpVar::constant(domain_sep_to_field(DOM_OWNER_ADDR));
+        poseidon_hash_zk(&[dom_owner, pk_x, pk_y])
     }
     
     /// Real scalar multiplication: pk = sk * G on Jubjub
@@ -172,6 +175,33 @@ impl AuthGadget {
         
         Ok(())
     }
+    
+    /// Check that all owner addresses are equal (e.g. a self-transfer)
+    /// Returns TRUE for an empty or single-element list
+    pub fn same_owner(owner_addrs: &[FpVar<F>]) -> Result<Boolean<F>, SynthesisError> {
+        let mut all_same = Boolean::TRUE;
+        if let Some((first, rest)) = owner_addrs.split_first() {
+            for addr in rest {
+                all_same = all_same.and(&addr.is_eq(first)?)?;
+            }
+        }
+        Ok(all_same)
+    }
+    
+    /// `same_owner` over the addresses not flagged as dummies
+    /// Returns TRUE when at most one address is real
+    pub fn same_real_owner(owner_addrs: &[(FpVar<F>, Boolean<F>)]) -> Result<Boolean<F>, SynthesisError> {
+        let mut all_same = Boolean::TRUE;
+        let mut seen = Boolean::FALSE;
+        let mut first = FpVar::zero();
+        for (addr, is_dummy) in owner_addrs {
+            let matches = addr.is_eq(&first)?.or(&seen.not())?;
+            all_same = all_same.and(&matches.or(is_dummy)?)?;
+            first = seen.or(is_dummy)?.select(&first, addr)?;
+            seen = seen.or(&is_dummy.not())?;
+        }
+        Ok(all_same)
+    }
 }
 
 // PublicKeyVar removed - it used the wrong field type (FpVar instead of FqVar)
@@ -187,7 +217,7 @@ pub struct AuthWitness {
 /// Supported authentication types
 #[derive(Clone, Copy, Debug)]
 pub enum AuthType {
-    /// Poseidon-based authentication: addr = H(pk_x, pk_y)
+    /// Poseidon-based authentication: addr = H(DOM_OWNER_ADDR, pk_x, pk_y)
     Poseidon,
     /// Ethereum-compatible authentication: addr = last20(Keccak256(pk))
     Ethereum,
@@ -355,4 +385,28 @@ mod tests {
         
         assert!(cs.is_satisfied().unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_owner_address_matches_native() {
+        use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+        let mut rng = thread_rng();
+        
+        for _ in 0..4 {
+            let owner_sk = F::rand(&mut rng);
+            let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
+            let native_addr = owner_address_from_pk(pk_x, pk_y);
+            
+            let cs = ConstraintSystem::<F>::new_ref();
+            let owner_sk_var = FpVar::new_witness(cs.clone(), || Ok(owner_sk)).unwrap();
+            let (pk_x_fq, pk_y_fq) = AuthGadget::scalar_mult_generator(cs.clone(), &owner_sk_var).unwrap();
+            let circuit_addr = AuthGadget::compute_owner_address_from_fq(cs.clone(), &pk_x_fq, &pk_y_fq).unwrap();
+            
+            assert_eq!(circuit_addr.value().unwrap(), native_addr);
+            assert!(cs.is_satisfied().unwrap());
+        }
+        
+        // The domain tag is part of the address
+        let (pk_x, pk_y) = compute_ec_public_key(F::from(7u64));
+        assert_ne!(owner_address_from_pk(pk_x, pk_y), fluxe_core::crypto::poseidon_hash(&[pk_x, pk_y]));
+    }
+}
@@ -54,6 +54,8 @@ impl CallbackEntryVar {
 pub struct CallbackInvocationVar {
     pub ticket: FpVar<F>,
     pub payload: Vec<FpVar<F>>, // Encrypted payload as field elements
+    pub payload_len: FpVar<F>,
+    pub payload_commitment: FpVar<F>,
     pub timestamp: FpVar<F>,
     pub has_signature: Boolean<F>,
     pub signature: Vec<FpVar<F>>, // Signature components if present
@@ -104,12 +106,67 @@ impl CallbackInvocationVar {
         Ok(Self {
             ticket: FpVar::new_witness(cs.clone(), || Ok(invocation.ticket))?,
             payload: payload_vars,
+            payload_len: FpVar::new_witness(cs.clone(), || Ok(F::from(invocation.payload.len() as u64)))?,
+            payload_commitment: FpVar::new_witness(cs.clone(), || Ok(invocation.payload_commitment))?,
             timestamp: FpVar::new_witness(cs, || Ok(F::from(invocation.timestamp)))?,
             has_signature,
             signature: signature_vars,
         })
     }
     
+    /// Enforce payload_commitment = H(len || chunk_1 || ... || chunk_n)
+    /// Matches CallbackInvocation::commit_payload; len is held to the chunks (see
+    /// `enforce_payload_len`), so it cannot claim bytes the payload doesn't hold
+    pub fn enforce_payload_commitment(&self) -> Result<(), SynthesisError> {
+        self.enforce_payload_len()?;
+        let mut input = vec![self.payload_len.clone()];
+        input.extend(self.payload.iter().cloned());
+        let computed = poseidon_hash_zk(&input)?;
+        computed.enforce_equal(&self.payload_commitment)
+    }
+    
+    /// Enforce payload_len against a used-byte mask over the last chunk: the used slots
+    /// form a non-empty prefix, their count plus the full chunks before it is payload_len,
+    /// and every byte past the end is zero
+    fn enforce_payload_len(&self) -> Result<(), SynthesisError> {
+        use ark_ff::PrimeField;
+        
+        let last = match self.payload.last() {
+            Some(last) => last,
+            None => return self.payload_len.enforce_equal(&FpVar::zero()),
+        };
+        let cs = self.payload_len.cs();
+        let full_bytes = 31 * (self.payload.len() as u64 - 1);
+        
+        let is_used = (0..31u64)
+            .map(|i| {
+                Boolean::new_witness(cs.clone(), || {
+                    let len = self.payload_len.value()?.into_bigint().as_ref()[0];
+                    Ok(len.saturating_sub(full_bytes) > i)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        is_used[0].enforce_equal(&Boolean::TRUE)?;
+        for pair in is_used.windows(2) {
+            pair[1].and(&pair[0].not())?.enforce_equal(&Boolean::FALSE)?;
+        }
+        
+        let mut len = FpVar::constant(F::from(full_bytes));
+        for used in &is_used {
+            len += FpVar::from(used.clone());
+        }
+        len.enforce_equal(&self.payload_len)?;
+        
+        // Bytes past the 31 a chunk holds have no slot, so they must be zero as well
+        for (i, byte) in last.to_bits_le()?.chunks(8).enumerate() {
+            let used = is_used.get(i).cloned().unwrap_or(Boolean::FALSE);
+            for bit in byte {
+                bit.and(&used.not())?.enforce_equal(&Boolean::FALSE)?;
+            }
+        }
+        Ok(())
+    }
+    
     /// Verify the signature on this invocation using Schnorr on Jubjub
     /// 
     /// Parameters:
@@ -126,17 +183,11 @@ impl CallbackInvocationVar {
         r_y_fq: &FqVar,
         s: &FpVar<F>,
     ) -> Result<Boolean<F>, SynthesisError> {
-        // Build message fields: [ticket, timestamp, payload_hash]
-        let payload_hash = if !self.payload.is_empty() {
-            poseidon_hash_zk(&self.payload)?
-        } else {
-            FpVar::zero()
-        };
-        
+        // Build message fields: [ticket, timestamp, payload_commitment]
         let msg_fields = vec![
             self.ticket.clone(),
             self.timestamp.clone(),
-            payload_hash,
+            self.payload_commitment.clone(),
         ];
         
         // Use SchnorrGadget with proper Fq coordinates
@@ -147,13 +198,8 @@ impl CallbackInvocationVar {
     pub fn hash(&self) -> Result<FpVar<F>, SynthesisError> {
         let mut hash_inputs = vec![self.ticket.clone(), self.timestamp.clone()];
         
-        // Hash payload
-        let payload_hash = if !self.payload.is_empty() {
-            poseidon_hash_zk(&self.payload)?
-        } else {
-            FpVar::zero()
-        };
-        hash_inputs.push(payload_hash);
+        // Bind the full payload via its commitment
+        hash_inputs.push(self.payload_commitment.clone());
         
         // Hash signature
         let sig_hash = if !self.signature.is_empty() {
@@ -165,4 +211,39 @@ impl CallbackInvocationVar {
         
         poseidon_hash_zk(&hash_inputs)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::crypto::poseidon_hash;
+    use fluxe_core::utils::bytes_to_field;
+
+    #[test]
+    fn test_payload_len_bound_to_chunks() {
+        let payload: Vec<u8> = (1..=40).collect();
+        let invocation = CallbackInvocation::new(F::from(1u64), payload.clone(), 5);
+        
+        // Claim `len` over the payload's chunks, with a commitment that matches the claim
+        let check = |len: u64| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let mut var = CallbackInvocationVar::new_witness(cs.clone(), || Ok(invocation.clone())).unwrap();
+            let mut input = vec![F::from(len)];
+            input.extend(payload.chunks(31).map(bytes_to_field));
+            var.payload_len = FpVar::new_witness(cs.clone(), || Ok(F::from(len))).unwrap();
+            var.payload_commitment = FpVar::new_witness(cs.clone(), || Ok(poseidon_hash(&input))).unwrap();
+            var.enforce_payload_commitment().unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        assert!(check(40));
+        
+        // Dropping bytes the last chunk still holds, an empty last chunk, more bytes than
+        // the chunks have room for, or no bytes at all are rejected
+        assert!(!check(39));
+        assert!(!check(31));
+        assert!(!check(63));
+        assert!(!check(0));
+    }
+}
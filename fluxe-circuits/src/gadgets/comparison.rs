@@ -1,4 +1,5 @@
 use ark_bls12_381::Fr as F;
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_r1cs_std::{
     boolean::Boolean,
     fields::fp::FpVar,
@@ -99,6 +100,56 @@ impl ComparisonGadget {
         gte_min.and(&lte_max)
     }
     
+    /// Check if a <= b for values that fit in `bits` bits
+    /// Both values are range checked, then b - a + 2^bits is decomposed into bits + 1
+    /// booleans; its top bit is set exactly when the difference did not underflow
+    pub fn is_le(
+        cs: ConstraintSystemRef<F>,
+        a: &FpVar<F>,
+        b: &FpVar<F>,
+        bits: usize,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        assert!(bits + 1 < F::MODULUS_BIT_SIZE as usize, "comparison width exceeds the field");
+        
+        Self::decompose(cs.clone(), a, bits)?;
+        Self::decompose(cs.clone(), b, bits)?;
+        
+        let offset = FpVar::constant(F::from(2u64).pow([bits as u64]));
+        let shifted = b - a + offset;
+        let shifted_bits = Self::decompose(cs, &shifted, bits + 1)?;
+        
+        Ok(shifted_bits[bits].clone())
+    }
+    
+    /// Enforce a <= b for values that fit in `bits` bits
+    pub fn enforce_le(
+        cs: ConstraintSystemRef<F>,
+        a: &FpVar<F>,
+        b: &FpVar<F>,
+        bits: usize,
+    ) -> Result<(), SynthesisError> {
+        Self::is_le(cs, a, b, bits)?.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Witness the low `bits` bits of a value and enforce that they recompose to it
+    fn decompose(
+        cs: ConstraintSystemRef<F>,
+        value: &FpVar<F>,
+        bits: usize,
+    ) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let native = value.value().ok().map(|v| v.into_bigint().to_bits_le());
+        let value_bits = (0..bits)
+            .map(|i| {
+                Boolean::new_witness(cs.clone(), || {
+                    native.as_ref().map(|b| b[i]).ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Boolean::le_bits_to_fp_var(&value_bits)?.enforce_equal(value)?;
+        Ok(value_bits)
+    }
+    
     /// Pad bits to desired length
     fn pad_bits(mut bits: Vec<Boolean<F>>, target_len: usize) -> Vec<Boolean<F>> {
         while bits.len() < target_len {
@@ -197,6 +248,38 @@ mod tests {
         assert!(cs.is_satisfied().unwrap());
     }
     
+    #[test]
+    fn test_is_le() {
+        let check = |a: u64, b: u64| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let a = FpVar::new_witness(cs.clone(), || Ok(F::from(a))).unwrap();
+            let b = FpVar::new_witness(cs.clone(), || Ok(F::from(b))).unwrap();
+            let le = ComparisonGadget::is_le(cs.clone(), &a, &b, 64).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            le.value().unwrap()
+        };
+        
+        assert!(check(100, 200));
+        assert!(check(200, 200));
+        assert!(!check(201, 200));
+        assert!(check(0, u64::MAX));
+        assert!(!check(u64::MAX, u64::MAX - 1));
+        
+        // enforce_le is unsatisfiable once a exceeds b
+        let enforce = |a: u64, b: u64| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let a = FpVar::new_witness(cs.clone(), || Ok(F::from(a))).unwrap();
+            let b = FpVar::new_witness(cs.clone(), || Ok(F::from(b))).unwrap();
+            ComparisonGadget::enforce_le(cs.clone(), &a, &b, 16).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        assert!(enforce(999, 1000));
+        assert!(!enforce(1001, 1000));
+        
+        // Values outside the declared width cannot be compared
+        assert!(!enforce(1, 1 << 16));
+    }
+    
     #[test]
     fn test_64bit_comparison() {
         let cs = ConstraintSystem::<F>::new_ref();
@@ -4,7 +4,13 @@
 use ark_bls12_381::Fr as F;
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::prelude::*;
-use ark_relations::r1cs::SynthesisError;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use fluxe_core::crypto::{domain_sep_to_field, DOM_OWNER_OBJECT};
+
+use super::comparison::ComparisonGadget;
+use super::poseidon::poseidon_hash_zk;
+use super::sorted_tree::SortedMembershipVar;
 
 // Re-export from other modules to maintain compatibility
 pub use super::zk_object::{ZkObjectVar, ComplianceStateVar};
@@ -31,4 +37,243 @@ pub fn verify_compliance_gates(
     let not_frozen = frozen.not();
     let within_limit = verify_limit_not_exceeded(amount, daily_limit)?;
     not_frozen.and(&within_limit)
+}
+
+/// Bind a note's compliance_hash to a witnessed ComplianceState and gate on it
+/// Enforces compliance_hash == state.hash(), state not frozen, state.level >= min_level
+pub fn enforce_checked_compliance(
+    cs: ConstraintSystemRef<F>,
+    compliance_hash: &FpVar<F>,
+    state: &ComplianceStateVar,
+    min_level: u8,
+) -> Result<(), SynthesisError> {
+    compliance_hash.enforce_equal(&state.hash()?)?;
+    state.frozen.enforce_equal(&Boolean::FALSE)?;
+    
+    let level_ok = ComparisonGadget::is_greater_than_or_equal(
+        cs,
+        &state.level,
+        &FpVar::constant(F::from(min_level as u64)),
+    )?;
+    level_ok.enforce_equal(&Boolean::TRUE)
+}
+
+/// Require `pool_id` to be set in the state's permitted-pools bitmap
+/// Pools outside the 64-bit bitmap match no bit and are rejected
+pub fn enforce_pool_permitted(
+    state: &ComplianceStateVar,
+    pool_id: &FpVar<F>,
+) -> Result<(), SynthesisError> {
+    // The bitmap is bound by the state hash to a u64, so its low 64 bits are all of it
+    let bits = state.permitted_pools.to_bits_le()?;
+    let mut permitted = FpVar::zero();
+    for (i, bit) in bits.iter().take(64).enumerate() {
+        let is_pool = pool_id.is_eq(&FpVar::constant(F::from(i as u64)))?;
+        permitted += FpVar::from(is_pool.and(bit)?);
+    }
+    permitted.enforce_equal(&FpVar::one())
+}
+
+/// Block a state whose last review is stale
+/// Enforces last_review_time <= current_time, current_time - last_review_time <= max_review_age
+pub fn enforce_fresh_review(
+    cs: ConstraintSystemRef<F>,
+    state: &ComplianceStateVar,
+    current_time: &FpVar<F>,
+    max_review_age: &FpVar<F>,
+) -> Result<(), SynthesisError> {
+    // Reviews from the future would wrap the subtraction below
+    let not_future = ComparisonGadget::is_less_than_or_equal(
+        cs.clone(),
+        &state.last_review_time,
+        current_time,
+    )?;
+    not_future.enforce_equal(&Boolean::TRUE)?;
+    
+    let review_age = current_time - &state.last_review_time;
+    let fresh = ComparisonGadget::is_less_than_or_equal(cs, &review_age, max_review_age)?;
+    fresh.enforce_equal(&Boolean::TRUE)
+}
+
+/// Whether a recipient may receive funds: `compliance_hash` is the witnessed state's hash,
+/// the state is not frozen, and it is the state of `owner_addr`'s current zk-object, proven
+/// by its `ZkObject::owner_index_key` in the owner index at `owner_index_root` and by the
+/// owner the object commits to, so an index entry naming another owner's object vouches for nothing
+pub fn verify_recipient_not_frozen(
+    compliance_hash: &FpVar<F>,
+    owner_addr: &FpVar<F>,
+    state: &ComplianceStateVar,
+    object: &ZkObjectVar,
+    owner_proof: &SortedMembershipVar,
+    owner_index_root: &FpVar<F>,
+) -> Result<Boolean<F>, SynthesisError> {
+    let state_hash = state.hash()?;
+    let bound = compliance_hash.is_eq(&state_hash)?;
+    let held = object.state_hash.is_eq(&state_hash)?;
+    let key = poseidon_hash_zk(&[
+        FpVar::constant(domain_sep_to_field(DOM_OWNER_OBJECT)),
+        owner_addr.clone(),
+        object.commitment()?,
+    ])?;
+    let indexed = owner_proof.verify(&key, owner_index_root)?;
+    let owned = object.owner_addr.is_eq(owner_addr)?;
+    
+    bound.and(&held)?.and(&indexed)?.and(&owned)?.and(&state.frozen.not())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::data_structures::ComplianceState;
+
+    fn check(claimed_hash: F, state: ComplianceState, min_level: u8) -> bool {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let hash_var = FpVar::new_witness(cs.clone(), || Ok(claimed_hash)).unwrap();
+        let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state)).unwrap();
+        enforce_checked_compliance(cs.clone(), &hash_var, &state_var, min_level).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_checked_compliance_consistent_state_passes() {
+        let state = ComplianceState::new_verified(2);
+        assert!(check(state.hash(), state, 1));
+    }
+
+    #[test]
+    fn test_checked_compliance_rejects_mismatched_hash() {
+        let state = ComplianceState::new_verified(2);
+        let other = ComplianceState::new_verified(3);
+        assert!(!check(other.hash(), state, 1));
+    }
+
+    #[test]
+    fn test_checked_compliance_applies_gates() {
+        // Frozen state fails even with a matching hash
+        let mut frozen = ComplianceState::new_verified(2);
+        frozen.freeze();
+        assert!(!check(frozen.hash(), frozen, 1));
+        
+        // Level below the minimum fails
+        let unverified = ComplianceState::new();
+        assert!(!check(unverified.hash(), unverified, 1));
+    }
+
+    fn check_pool(permitted_pools: u64, pool_id: u32) -> bool {
+        let mut state = ComplianceState::new_verified(2);
+        state.permitted_pools = permitted_pools;
+        
+        let cs = ConstraintSystem::<F>::new_ref();
+        let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state.clone())).unwrap();
+        let pool_var = FpVar::new_witness(cs.clone(), || Ok(F::from(pool_id as u64))).unwrap();
+        enforce_pool_permitted(&state_var, &pool_var).unwrap();
+        let satisfied = cs.is_satisfied().unwrap();
+        assert_eq!(satisfied, state.is_pool_permitted(pool_id));
+        satisfied
+    }
+
+    #[test]
+    fn test_pool_permitted_by_compliance_state() {
+        // Permitted pools pass
+        assert!(check_pool(0b1010, 1));
+        assert!(check_pool(0b1010, 3));
+        assert!(check_pool(u64::MAX, 63));
+        
+        // Pools outside the bitmap are rejected
+        assert!(!check_pool(0b1010, 0));
+        assert!(!check_pool(0b1010, 2));
+        assert!(!check_pool(u64::MAX, 64));
+    }
+
+    fn check_review(last_review_time: u64, current_time: u64, max_review_age: u64) -> bool {
+        let mut state = ComplianceState::new_verified(2);
+        state.last_review_time = last_review_time;
+        assert_eq!(
+            state.review_is_fresh(current_time, max_review_age),
+            last_review_time <= current_time && current_time - last_review_time <= max_review_age,
+        );
+        
+        let cs = ConstraintSystem::<F>::new_ref();
+        let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state)).unwrap();
+        let now_var = FpVar::new_input(cs.clone(), || Ok(F::from(current_time))).unwrap();
+        let max_age_var = FpVar::new_input(cs.clone(), || Ok(F::from(max_review_age))).unwrap();
+        enforce_fresh_review(cs.clone(), &state_var, &now_var, &max_age_var).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_fresh_review_passes() {
+        assert!(check_review(9_000, 10_000, 1_000)); // exactly at the limit
+        assert!(check_review(9_500, 10_000, 1_000));
+        assert!(check_review(10_000, 10_000, 0));
+    }
+
+    #[test]
+    fn test_stale_review_blocked() {
+        assert!(!check_review(8_999, 10_000, 1_000));
+        assert!(!check_review(0, 10_000, 1_000));
+        
+        // A review dated in the future cannot wrap around to look fresh
+        assert!(!check_review(10_001, 10_000, 1_000));
+    }
+
+    #[test]
+    fn test_recipient_not_frozen() {
+        use fluxe_core::crypto::poseidon_hash;
+        use fluxe_core::data_structures::ZkObject;
+        use fluxe_core::merkle::SortedTree;
+        
+        let active = ComplianceState::new_verified(2);
+        let mut frozen = ComplianceState::new_verified(2);
+        frozen.freeze();
+        
+        // Owner index keys bind each owner to the in-circuit commitment of their object
+        let owners = [F::from(101u64), F::from(102u64)];
+        let mut objects: Vec<ZkObject> = [&active, &frozen]
+            .iter()
+            .zip(owners)
+            .map(|(s, owner)| ZkObject::new(s).with_owner(owner))
+            .collect();
+        
+        // A forged entry indexing owner 1 to owner 0's unfrozen object
+        objects.push(objects[0].clone());
+        let claimed = [owners[0], owners[1], owners[1]];
+        let key = |i: usize| {
+            let obj = &objects[i];
+            ZkObject::owner_index_key(claimed[i], poseidon_hash(&[obj.state_hash, F::from(obj.serial), obj.cb_head_hash, obj.owner_addr]))
+        };
+        let mut owner_index = SortedTree::new(8);
+        for i in 0..3 {
+            owner_index.insert(key(i)).unwrap();
+        }
+        
+        let check = |claimed_hash: F, owner: F, state: &ComplianceState, index: usize| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let hash_var = FpVar::new_witness(cs.clone(), || Ok(claimed_hash)).unwrap();
+            let owner_var = FpVar::new_witness(cs.clone(), || Ok(owner)).unwrap();
+            let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state.clone())).unwrap();
+            let obj_var = ZkObjectVar::new_witness(cs.clone(), || Ok(objects[index].clone())).unwrap();
+            let proof_var = SortedMembershipVar::new_witness(cs.clone(), || {
+                Ok(owner_index.prove_key_membership(key(index)).unwrap())
+            }).unwrap();
+            let root_var = FpVar::new_input(cs.clone(), || Ok(owner_index.root())).unwrap();
+            let ok = verify_recipient_not_frozen(&hash_var, &owner_var, &state_var, &obj_var, &proof_var, &root_var).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            ok.value().unwrap()
+        };
+        
+        assert!(check(active.hash(), owners[0], &active, 0));
+        assert!(!check(frozen.hash(), owners[1], &frozen, 1));
+        
+        // An unfrozen state must be the one the recipient's object and note commit to
+        assert!(!check(frozen.hash(), owners[0], &active, 0));
+        assert!(!check(active.hash(), owners[1], &active, 1));
+        
+        // Another owner's unfrozen object does not vouch for this recipient
+        assert!(!check(active.hash(), owners[1], &active, 0));
+        
+        // Not even through an index entry that claims it for them
+        assert!(!check(active.hash(), owners[1], &active, 2));
+    }
 }
\ No newline at end of file
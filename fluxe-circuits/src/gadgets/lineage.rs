@@ -0,0 +1,194 @@
+use ark_bls12_381::Fr as F;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use fluxe_core::types::Time;
+
+use super::comparison::ComparisonGadget;
+use super::poseidon::poseidon_hash_zk;
+use super::range_proof::RangeProofGadget;
+
+/// Opening of a timed lineage hash: the split lineage and the note's creation time
+/// Matches fluxe_core::crypto::compute_timed_lineage_hash
+#[derive(Clone)]
+pub struct TimedLineageVar {
+    pub split_lineage: FpVar<F>,
+    pub created_at: FpVar<F>,
+}
+
+impl TimedLineageVar {
+    pub fn new_witness(
+        cs: ConstraintSystemRef<F>,
+        split_lineage: F,
+        created_at: Time,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            split_lineage: FpVar::new_witness(cs.clone(), || Ok(split_lineage))?,
+            created_at: FpVar::new_witness(cs, || Ok(F::from(created_at)))?,
+        })
+    }
+
+    /// H(split_lineage || created_at)
+    pub fn lineage_hash(&self) -> Result<FpVar<F>, SynthesisError> {
+        poseidon_hash_zk(&[self.split_lineage.clone(), self.created_at.clone()])
+    }
+}
+
+/// Gadget for split provenance in lineage hashes
+/// Matches fluxe_core::crypto::compute_split_lineage_hash
+pub struct LineageGadget;
+
+impl LineageGadget {
+    /// Compute H(parent_1 || ... || parent_n || position || split_count)
+    /// Position and count are circuit constants, so the prover cannot choose them
+    pub fn split_lineage_hash(
+        parent_lineages: &[FpVar<F>],
+        position: usize,
+        split_count: usize,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        if position >= split_count {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut input = parent_lineages.to_vec();
+        input.push(FpVar::constant(F::from(position as u64)));
+        input.push(FpVar::constant(F::from(split_count as u64)));
+        poseidon_hash_zk(&input)
+    }
+
+    /// As `split_lineage_hash`, with the split count a variable (e.g. the number of
+    /// non-padding outputs); the caller must ensure position < split_count
+    pub fn split_lineage_hash_with_count(
+        parent_lineages: &[FpVar<F>],
+        position: usize,
+        split_count: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let mut input = parent_lineages.to_vec();
+        input.push(FpVar::constant(F::from(position as u64)));
+        input.push(split_count.clone());
+        poseidon_hash_zk(&input)
+    }
+
+    /// Enforce that an output's lineage encodes its position within the split
+    pub fn enforce_split_lineage(
+        lineage_hash: &FpVar<F>,
+        parent_lineages: &[FpVar<F>],
+        position: usize,
+        split_count: usize,
+    ) -> Result<(), SynthesisError> {
+        let expected = Self::split_lineage_hash(parent_lineages, position, split_count)?;
+        lineage_hash.enforce_equal(&expected)
+    }
+
+    /// Enforce that each opening matches its lineage hash and that the output
+    /// was created no earlier than any parent: out.created_at >= max(parent.created_at)
+    pub fn enforce_created_after(
+        cs: ConstraintSystemRef<F>,
+        out_lineage: &FpVar<F>,
+        out: &TimedLineageVar,
+        parent_lineages: &[FpVar<F>],
+        parents: &[TimedLineageVar],
+    ) -> Result<(), SynthesisError> {
+        if parent_lineages.len() != parents.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        out_lineage.enforce_equal(&out.lineage_hash()?)?;
+        RangeProofGadget::prove_range_bits(cs.clone(), &out.created_at, 64)?;
+
+        for (lineage, parent) in parent_lineages.iter().zip(parents) {
+            lineage.enforce_equal(&parent.lineage_hash()?)?;
+            RangeProofGadget::prove_range_bits(cs.clone(), &parent.created_at, 64)?;
+            let before_parent = ComparisonGadget::is_less_than_64bit(cs.clone(), &out.created_at, &parent.created_at)?;
+            before_parent.enforce_equal(&Boolean::FALSE)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::crypto::{compute_split_lineage_hash, SplitProvenance};
+
+    #[test]
+    fn test_split_lineage_matches_native() {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let parent = F::from(77u64);
+        let parent_var = FpVar::new_witness(cs.clone(), || Ok(parent)).unwrap();
+
+        for i in 0..3 {
+            let native = compute_split_lineage_hash(&[parent], SplitProvenance::new(i as u32, 3).unwrap());
+            let lineage_var = FpVar::new_witness(cs.clone(), || Ok(native)).unwrap();
+            LineageGadget::enforce_split_lineage(&lineage_var, &[parent_var.clone()], i, 3).unwrap();
+        }
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_split_lineage_rejects_wrong_position() {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let parent = F::from(77u64);
+        let parent_var = FpVar::new_witness(cs.clone(), || Ok(parent)).unwrap();
+
+        // Middle note's lineage claimed at the first position
+        let middle = compute_split_lineage_hash(&[parent], SplitProvenance::new(1, 3).unwrap());
+        let lineage_var = FpVar::new_witness(cs.clone(), || Ok(middle)).unwrap();
+        LineageGadget::enforce_split_lineage(&lineage_var, &[parent_var], 0, 3).unwrap();
+
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    /// Two parents created at 100 and 150 and one output created at `created_at`
+    fn created_after_satisfied(created_at: Time) -> bool {
+        use fluxe_core::crypto::compute_timed_lineage_hash;
+
+        let cs = ConstraintSystem::<F>::new_ref();
+        let mut parent_lineages = Vec::new();
+        let mut parents = Vec::new();
+        for (i, time) in [100u64, 150u64].into_iter().enumerate() {
+            let split = F::from(i as u64 + 1);
+            let lineage = compute_timed_lineage_hash(split, time);
+            parent_lineages.push(FpVar::new_witness(cs.clone(), || Ok(lineage)).unwrap());
+            parents.push(TimedLineageVar::new_witness(cs.clone(), split, time).unwrap());
+        }
+
+        let parent_values: Vec<F> = parent_lineages.iter().map(|l| l.value().unwrap()).collect();
+        let split_out = compute_split_lineage_hash(&parent_values, SplitProvenance::new(0, 1).unwrap());
+        let out_lineage = compute_timed_lineage_hash(split_out, created_at);
+        let out_lineage_var = FpVar::new_witness(cs.clone(), || Ok(out_lineage)).unwrap();
+        let out = TimedLineageVar::new_witness(cs.clone(), split_out, created_at).unwrap();
+
+        LineageGadget::enforce_split_lineage(&out.split_lineage, &parent_lineages, 0, 1).unwrap();
+        LineageGadget::enforce_created_after(cs.clone(), &out_lineage_var, &out, &parent_lineages, &parents).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_created_after_parents() {
+        assert!(created_after_satisfied(200));
+        assert!(created_after_satisfied(150));
+        // Earlier than the latest parent, though later than the other
+        assert!(!created_after_satisfied(120));
+        assert!(!created_after_satisfied(50));
+    }
+
+    #[test]
+    fn test_created_after_rejects_wrong_opening() {
+        use fluxe_core::crypto::compute_timed_lineage_hash;
+
+        let cs = ConstraintSystem::<F>::new_ref();
+        let parent_lineage = compute_timed_lineage_hash(F::from(1u64), 300);
+        let parent_lineage_var = FpVar::new_witness(cs.clone(), || Ok(parent_lineage)).unwrap();
+        // Parent opened at an earlier time than it committed to
+        let parent = TimedLineageVar::new_witness(cs.clone(), F::from(1u64), 100).unwrap();
+
+        let out_lineage = compute_timed_lineage_hash(F::from(2u64), 200);
+        let out_lineage_var = FpVar::new_witness(cs.clone(), || Ok(out_lineage)).unwrap();
+        let out = TimedLineageVar::new_witness(cs.clone(), F::from(2u64), 200).unwrap();
+
+        LineageGadget::enforce_created_after(cs.clone(), &out_lineage_var, &out, &[parent_lineage_var], &[parent]).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -4,6 +4,7 @@ use ark_r1cs_std::{
     prelude::*,
 };
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use fluxe_core::crypto::{MAX_MEMO_BYTES, MEMO_CHUNKS};
 
 use super::poseidon::poseidon_hash_zk;
 
@@ -41,4 +42,97 @@ impl MemoVar {
             poseidon_hash_zk(&self.encrypted_chunks)
         }
     }
+}
+
+/// Memo ciphertext padded to MEMO_CHUNKS chunks, so the circuit shape does not depend on the memo
+/// Mirrors `MemoEncryption::compute_circuit_memo_hash`
+#[derive(Clone)]
+pub struct PaddedMemoVar {
+    pub chunks: Vec<FpVar<F>>,
+    pub length: FpVar<F>,
+}
+
+impl PaddedMemoVar {
+    /// Allocate a ciphertext (None allocates an empty memo)
+    pub fn new_witness(
+        cs: ConstraintSystemRef<F>,
+        ciphertext: Option<&[u8]>,
+    ) -> Result<Self, SynthesisError> {
+        use ark_ff::PrimeField;
+        
+        let ciphertext = ciphertext.unwrap_or(&[]);
+        if ciphertext.len() > MAX_MEMO_BYTES {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        let mut values = vec![F::from(0u64); MEMO_CHUNKS];
+        for (slot, chunk) in values.iter_mut().zip(ciphertext.chunks(31)) {
+            let mut bytes = [0u8; 32];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *slot = F::from_le_bytes_mod_order(&bytes);
+        }
+        
+        let chunks = values
+            .into_iter()
+            .map(|v| FpVar::new_witness(cs.clone(), || Ok(v)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let length = FpVar::new_witness(cs, || Ok(F::from(ciphertext.len() as u64)))?;
+        
+        Ok(Self { chunks, length })
+    }
+    
+    /// Poseidon over the padded chunks followed by the length
+    pub fn hash(&self) -> Result<FpVar<F>, SynthesisError> {
+        let mut input = self.chunks.clone();
+        input.push(self.length.clone());
+        poseidon_hash_zk(&input)
+    }
+    
+    /// Enforce a note's memo_hash: 0 when has_memo is false, otherwise the hash of
+    /// this (non-empty) ciphertext, so a note cannot carry a memo while claiming none
+    pub fn enforce_memo_hash(
+        &self,
+        has_memo: &Boolean<F>,
+        memo_hash: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        let is_empty = self.length.is_zero()?;
+        is_empty.conditional_enforce_equal(&Boolean::FALSE, has_memo)?;
+        
+        let expected = has_memo.select(&self.hash()?, &FpVar::zero())?;
+        memo_hash.enforce_equal(&expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::crypto::MemoEncryption;
+
+    #[test]
+    fn test_memo_hash_binding() {
+        let ciphertext = b"opaque memo ciphertext bytes".to_vec();
+        let native = MemoEncryption::compute_circuit_memo_hash(&ciphertext).unwrap();
+        
+        let check = |memo: Option<&[u8]>, has_memo: bool, memo_hash: F| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let memo_var = PaddedMemoVar::new_witness(cs.clone(), memo).unwrap();
+            let flag = Boolean::new_witness(cs.clone(), || Ok(has_memo)).unwrap();
+            let hash_var = FpVar::new_witness(cs.clone(), || Ok(memo_hash)).unwrap();
+            memo_var.enforce_memo_hash(&flag, &hash_var).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // No memo with a zero hash, or a real memo with its hash
+        assert!(check(None, false, F::from(0u64)));
+        assert!(check(Some(ciphertext.as_slice()), true, native));
+        
+        // Claiming no memo while carrying a memo hash is rejected
+        assert!(!check(None, false, native));
+        assert!(!check(Some(ciphertext.as_slice()), false, native));
+        
+        // A claimed memo must be non-empty and match its ciphertext
+        assert!(!check(None, true, native));
+        assert!(!check(Some(&b"other ciphertext"[..]), true, native));
+    }
 }
\ No newline at end of file
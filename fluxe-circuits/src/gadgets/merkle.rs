@@ -3,7 +3,7 @@ use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::boolean::Boolean;
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use fluxe_core::merkle::MerklePath;
+use fluxe_core::merkle::{MerklePath, MultiProof};
 
 use crate::gadgets::poseidon::poseidon_hash_zk;
 
@@ -73,6 +73,16 @@ impl MerklePathVar {
         is_valid.enforce_equal(&Boolean::TRUE)
     }
     
+    /// Enforce that this path is valid for `root` and that `root` is one of the accepted `window`
+    pub fn enforce_valid_in_window(&self, root: &FpVar<F>, window: &[FpVar<F>]) -> Result<(), SynthesisError> {
+        self.enforce_valid(root)?;
+        let mut in_window = Boolean::FALSE;
+        for accepted in window {
+            in_window = in_window.or(&root.is_eq(accepted)?)?;
+        }
+        in_window.enforce_equal(&Boolean::TRUE)
+    }
+    
     /// Compute the root with a specific leaf at this path's position
     pub fn compute_root_with_leaf(&self, leaf: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
         let mut current = leaf.clone();
@@ -95,6 +105,79 @@ impl MerklePathVar {
     }
 }
 
+/// Multiproof variable for several leaves of one tree
+/// The index set fixes the circuit shape and is not hidden; leaves and nodes are witnesses
+#[derive(Clone)]
+pub struct MultiProofVar {
+    pub shape: Vec<Vec<bool>>,
+    pub indices: Vec<usize>,
+    pub leaves: Vec<FpVar<F>>,
+    pub nodes: Vec<FpVar<F>>,
+}
+
+impl MultiProofVar {
+    /// Allocate the proof's nodes as witnesses over already allocated leaves (in `indices` order)
+    pub fn new_witness(
+        cs: ConstraintSystemRef<F>,
+        proof: &MultiProof,
+        leaves: Vec<FpVar<F>>,
+    ) -> Result<Self, SynthesisError> {
+        if leaves.len() != proof.indices.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        let nodes = proof.nodes
+            .iter()
+            .map(|n| FpVar::new_witness(cs.clone(), || Ok(*n)))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(Self {
+            shape: proof.shape(),
+            indices: proof.indices.clone(),
+            leaves,
+            nodes,
+        })
+    }
+    
+    /// Compute the root following the proof's shape
+    pub fn compute_root(&self) -> Result<FpVar<F>, SynthesisError> {
+        let mut known: Vec<(usize, FpVar<F>)> = self.indices.iter().copied().zip(self.leaves.iter().cloned()).collect();
+        let mut supplied = self.nodes.iter();
+        
+        for steps in &self.shape {
+            let mut parents = Vec::with_capacity(steps.len());
+            let mut i = 0;
+            for paired in steps {
+                let (index, node) = &known[i];
+                let parent = if *paired {
+                    i += 1;
+                    poseidon_hash_zk(&[node.clone(), known[i].1.clone()])?
+                } else {
+                    let sibling = supplied.next().ok_or(SynthesisError::Unsatisfiable)?.clone();
+                    if index & 1 == 0 {
+                        poseidon_hash_zk(&[node.clone(), sibling])?
+                    } else {
+                        poseidon_hash_zk(&[sibling, node.clone()])?
+                    }
+                };
+                parents.push((index >> 1, parent));
+                i += 1;
+            }
+            known = parents;
+        }
+        
+        if supplied.next().is_some() || known.len() != 1 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(known.remove(0).1)
+    }
+    
+    /// Enforce that every leaf is in the tree with `root`
+    pub fn enforce_valid(&self, root: &FpVar<F>) -> Result<(), SynthesisError> {
+        self.compute_root()?.enforce_equal(root)
+    }
+}
+
 /// Generic Merkle tree gadget utilities
 pub struct MerkleTreeGadget;
 
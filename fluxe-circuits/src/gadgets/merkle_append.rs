@@ -1,8 +1,9 @@
 use ark_bls12_381::Fr as F;
 use ark_r1cs_std::{fields::fp::FpVar, boolean::Boolean, prelude::*};
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use super::merkle::MerklePathVar;
 use super::poseidon::poseidon_hash_zk;
-use fluxe_core::merkle::AppendWitness;
+use fluxe_core::merkle::{AppendWitness, MerklePath, TreeParams};
 
 /// Append proof for an Incremental Merkle Tree.
 /// 
@@ -20,6 +21,8 @@ pub struct ImtAppendProofVar {
     pub appended_leaf: FpVar<F>,
     pub pre_siblings: Vec<FpVar<F>>,
     pub height: usize,
+    /// Path to the last leaf of the old tree (dummy for the first append)
+    pub last_leaf_path: MerklePathVar,
 }
 
 impl ImtAppendProofVar {
@@ -40,6 +43,7 @@ impl ImtAppendProofVar {
                 .map(|s| FpVar::new_witness(cs.clone(), || Ok(s)))
                 .collect::<Result<Vec<_>, _>>()?,
             height: witness.height,
+            last_leaf_path: Self::last_leaf_path_var(cs, witness.last_leaf_path, witness.height)?,
         })
     }
     
@@ -60,9 +64,69 @@ impl ImtAppendProofVar {
                 .map(|s| FpVar::new_witness(cs.clone(), || Ok(s)))
                 .collect::<Result<Vec<_>, _>>()?,
             height: witness.height,
+            last_leaf_path: Self::last_leaf_path_var(cs, witness.last_leaf_path, witness.height)?,
         })
     }
 
+    /// Append onto an already-constrained root, e.g. the previous append in a chain
+    /// `appended_leaf` is the in-circuit leaf; the new root is witnessed from the witness's
+    /// pre-insertion siblings, which `enforce` ties to both roots so no existing leaf can change
+    pub fn chained(
+        cs: ConstraintSystemRef<F>,
+        witness: &AppendWitness,
+        old_root: &FpVar<F>,
+        appended_leaf: &FpVar<F>,
+    ) -> Result<Self, SynthesisError> {
+        let leaf = appended_leaf.value().unwrap_or(witness.leaf);
+        let new_root = AppendWitness { leaf, ..witness.clone() }.compute_new_root(&TreeParams::new(witness.height));
+        Ok(Self {
+            old_root: old_root.clone(),
+            new_root: FpVar::new_witness(cs.clone(), || Ok(new_root))?,
+            leaf_index: FpVar::new_witness(cs.clone(), || Ok(F::from(witness.leaf_index as u64)))?,
+            appended_leaf: appended_leaf.clone(),
+            pre_siblings: witness
+                .pre_siblings
+                .iter()
+                .map(|s| FpVar::new_witness(cs.clone(), || Ok(*s)))
+                .collect::<Result<Vec<_>, _>>()?,
+            height: witness.height,
+            last_leaf_path: Self::last_leaf_path_var(cs, witness.last_leaf_path.clone(), witness.height)?,
+        })
+    }
+
+    /// Allocate the last-leaf path, padding with a zero path so the shape never depends
+    /// on whether the tree was empty
+    fn last_leaf_path_var(
+        cs: ConstraintSystemRef<F>,
+        path: Option<MerklePath>,
+        height: usize,
+    ) -> Result<MerklePathVar, SynthesisError> {
+        let path = path.unwrap_or_else(|| MerklePath {
+            leaf_index: 0,
+            siblings: vec![F::from(0u64); height],
+            leaf: F::from(0u64),
+        });
+        MerklePathVar::new_witness(cs, || Ok(path))
+    }
+
+    /// Verify the append targets the old tree's next free index
+    /// Leaves fill left to right, so with the slot at leaf_index empty (checked against
+    /// the old root) the tree has exactly leaf_index leaves iff leaf_index is 0 or
+    /// leaf leaf_index - 1 is a non-empty member of the old tree
+    pub fn verify_next_index(&self) -> Result<Boolean<F>, SynthesisError> {
+        let is_first = self.leaf_index.is_eq(&FpVar::zero())?;
+        
+        let last_index = &self.last_leaf_path.leaf_index + &FpVar::one();
+        let last_index_valid = last_index.is_eq(&self.leaf_index)?;
+        let last_leaf_nonzero = self.last_leaf_path.leaf.is_neq(&FpVar::zero())?;
+        let last_leaf_member = self.last_leaf_path.verify(&self.old_root)?;
+        let last_leaf_valid = last_index_valid
+            .and(&last_leaf_nonzero)?
+            .and(&last_leaf_member)?;
+        
+        is_first.or(&last_leaf_valid)
+    }
+
     /// Verify the append operation is valid
     pub fn verify(&self) -> Result<Boolean<F>, SynthesisError> {
         // SANITY CHECK 1: Verify the siblings array length matches tree height
@@ -93,13 +157,17 @@ impl ImtAppendProofVar {
         // (appending should change the root)
         let roots_different = self.old_root.is_neq(&self.new_root)?;
         
-        // 3. All checks must pass
+        // 3. The append is at the tree's current size, so no slot is skipped
+        let next_index_valid = self.verify_next_index()?;
+        
+        // 4. All checks must pass
         siblings_len_valid
             .and(&index_in_range)?
             .and(&leaf_nonzero)?
             .and(&old_root_valid)?
             .and(&new_root_valid)?
-            .and(&roots_different)
+            .and(&roots_different)?
+            .and(&next_index_valid)
     }
     
     /// Enforce that this is a valid append proof
@@ -108,6 +176,16 @@ impl ImtAppendProofVar {
         is_valid.enforce_equal(&Boolean::TRUE)
     }
     
+    /// Enforce the append grows the tree: the next-index check pins the old root to
+    /// exactly leaf_index leaves and the new root to leaf_index + 1, so no transition
+    /// keeping or shrinking the leaf count satisfies it
+    pub fn enforce_grows(&self) -> Result<(), SynthesisError> {
+        self.enforce()?;
+        let old_leaf_count = &self.leaf_index;
+        let new_leaf_count = &self.leaf_index + FpVar::one();
+        old_leaf_count.enforce_cmp(&new_leaf_count, std::cmp::Ordering::Less, false)
+    }
+    
     /// Compute root with empty leaf at the append position
     pub fn compute_root_with_empty(&self) -> Result<FpVar<F>, SynthesisError> {
         let mut current = FpVar::zero(); // Empty leaf
@@ -197,8 +275,7 @@ mod tests {
         assert_eq!(witness.compute_old_root(&params), old_root);
         
         // Actually append and verify new root
-        tree.append(new_leaf);
-        let new_root = tree.root();
+        let new_root = tree.append_returning_root(new_leaf);
         assert_eq!(witness.compute_new_root(&params), new_root);
     }
     
@@ -218,8 +295,7 @@ mod tests {
         // Create witness
         let witness = tree.generate_append_witness(new_leaf);
         
-        tree.append(new_leaf);
-        let new_root = tree.root();
+        let new_root = tree.append_returning_root(new_leaf);
         
         // Create and test gadget
         let gadget = ImtAppendProofVar::new_witness(
@@ -232,4 +308,37 @@ mod tests {
         gadget.enforce().unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
+    
+    #[test]
+    fn test_append_rejects_stale_or_skipped_index() {
+        let mut rng = thread_rng();
+        let params = TreeParams::new(4);
+        let mut tree = IncrementalTree::new(4);
+        for _ in 0..3 {
+            tree.append(F::rand(&mut rng));
+        }
+        let old_root = tree.root();
+        let new_leaf = F::rand(&mut rng);
+        
+        let append_at = |leaf_index: usize, last_leaf_path: Option<MerklePath>| {
+            let mut witness = AppendWitness::new(new_leaf, leaf_index, tree.get_siblings_for_index(leaf_index), 4);
+            witness.last_leaf_path = last_leaf_path;
+            let new_root = witness.compute_new_root(&params);
+            
+            let cs = ConstraintSystem::<F>::new_ref();
+            let gadget = ImtAppendProofVar::new_witness(cs.clone(), witness, old_root, new_root).unwrap();
+            gadget.enforce().unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // The real next index is accepted
+        assert!(append_at(3, tree.get_path(2)));
+        
+        // Index 0 against a non-empty tree would overwrite the first leaf
+        assert!(!append_at(0, None));
+        
+        // Index 5 is an empty slot, but skipping slots 3 and 4 is rejected
+        assert!(!append_at(5, tree.get_path(2)));
+        assert!(!append_at(5, tree.get_path(4)));
+    }
 }
\ No newline at end of file
@@ -17,6 +17,8 @@ pub mod pool_policy;
 pub mod zk_object;
 pub mod callbacks;
 pub mod memo;
+pub mod lineage;
+pub mod value_commitment;
 
 pub use auth::*;
 pub use comparison::*;
@@ -38,4 +40,6 @@ pub use pool_policy::*;
 pub use zk_object::*;
 pub use callbacks::*;
 pub use memo::*;
+pub use lineage::*;
+pub use value_commitment::*;
 
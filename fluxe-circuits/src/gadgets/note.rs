@@ -4,7 +4,7 @@ use ark_r1cs_std::uint8::UInt8;
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 use fluxe_core::{
-    crypto::{domain_sep_to_field, DOM_NOTE, DOM_NF},
+    crypto::{domain_sep_to_field, DOM_NOTE, DOM_NF, DOM_VALUE_RANDOMNESS},
     data_structures::Note,
 };
 
@@ -14,9 +14,9 @@ use crate::gadgets::poseidon::poseidon_hash_zk;
 #[derive(Clone)]
 pub struct NoteVar {
     pub asset_type: FpVar<F>,
-    /// NOTE: v_comm is treated as an opaque field (x-coordinate of native commitment).
-    /// No in-circuit opening is enforced here until core & gadget schemes are aligned.
-    pub v_comm_x: FpVar<F>,
+    /// NOTE: v_comm is treated as opaque fields ([x, y] from `PedersenCommitment::to_field_repr`).
+    /// Circuits that need the opening enforce it with `ValueCommitmentGadget`.
+    pub v_comm: [FpVar<F>; 2],
     pub value: FpVar<F>, // Actual value (private)
     pub owner_addr: FpVar<F>,
     pub psi: Vec<UInt8<F>>,
@@ -45,12 +45,12 @@ impl NoteVar {
         // Currently not implemented due to missing EC operations in circuit
         let _ = value_randomness; // Suppress unused warning
         
-        // Use the commitment from the note directly
-        use ark_ff::{BigInteger, PrimeField};
-        let v_comm_bytes = note.v_comm.commitment.x.into_bigint().to_bytes_le();
-        let v_comm_x_fr = fluxe_core::utils::bytes_to_field(&v_comm_bytes);
-        let commitment_x = FpVar::new_witness(cs.clone(), || Ok(v_comm_x_fr))?;
-        let v_comm_x = commitment_x;
+        // Use the commitment from the note directly, in the same encoding as the native hash
+        let [v_comm_x, v_comm_y] = note.v_comm.to_field_repr();
+        let v_comm = [
+            FpVar::new_witness(cs.clone(), || Ok(v_comm_x))?,
+            FpVar::new_witness(cs.clone(), || Ok(v_comm_y))?,
+        ];
         
         let owner_addr = FpVar::new_witness(cs.clone(), || Ok(note.owner_addr))?;
         
@@ -68,7 +68,7 @@ impl NoteVar {
         
         Ok(Self {
             asset_type,
-            v_comm_x,
+            v_comm,
             value: value_var,
             owner_addr,
             psi,
@@ -114,7 +114,8 @@ impl NoteVar {
         let input = vec![
             dom_note,
             self.asset_type.clone(),
-            self.v_comm_x.clone(), // Use x-coordinate of commitment point
+            self.v_comm[0].clone(), // Commitment point, x then y
+            self.v_comm[1].clone(),
             self.owner_addr.clone(),
             psi_field,
             self.chain_hint.clone(),
@@ -151,7 +152,40 @@ impl NoteVar {
             multiplier *= &two_five_six;
         }
         
-        poseidon_hash_zk(&[dom_nf, nk.clone(), psi_field, cm])
+        let nf = poseidon_hash_zk(&[dom_nf, nk.clone(), psi_field, cm])?;
+        
+        // Key 0 is the nullifier tree's sentinel; a note hashing to it is unspendable
+        nf.enforce_not_equal(&FpVar::zero())?;
+        Ok(nf)
+    }
+    
+    /// Value randomness derived from the creator's secret key and this note's psi,
+    /// matching `Note::derive_value_randomness`
+    pub fn derived_value_randomness(&self, owner_sk: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let dom = FpVar::constant(domain_sep_to_field(DOM_VALUE_RANDOMNESS));
+        poseidon_hash_zk(&[dom, owner_sk.clone(), self.psi_field()?])
+    }
+    
+    /// psi as a field element, truncated to 31 bytes like `utils::bytes_to_field`
+    fn psi_field(&self) -> Result<FpVar<F>, SynthesisError> {
+        let truncated_psi = if self.psi.len() > 31 {
+            &self.psi[..31]
+        } else {
+            &self.psi[..]
+        };
+        
+        let mut psi_field = FpVar::zero();
+        let mut multiplier = FpVar::one();
+        let two_five_six = FpVar::constant(F::from(256u64));
+        
+        for byte in truncated_psi {
+            let byte_bits = byte.to_bits_le()?;
+            let byte_value = Boolean::le_bits_to_fp_var(&byte_bits)?;
+            psi_field += &byte_value * &multiplier;
+            multiplier *= &two_five_six;
+        }
+        
+        Ok(psi_field)
     }
     
     /// Verify value is in valid range (simplified - would use bulletproofs)
@@ -244,4 +278,37 @@ mod tests {
         nullifier_var.enforce_equal(&expected).unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
+
+    #[test]
+    fn test_note_commitment_differential() {
+        use rand::Rng;
+
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        
+        for _ in 0..16 {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let value: u64 = rng.gen();
+            let randomness = F::rand(&mut rng);
+            let v_comm = PedersenCommitment::commit(
+                &params,
+                value,
+                &PedersenRandomness { r: randomness },
+            );
+            
+            let mut note = Note::new(rng.gen(), v_comm, F::rand(&mut rng), rng.gen(), rng.gen());
+            note.chain_hint = rng.gen();
+            note.compliance_hash = F::rand(&mut rng);
+            note.lineage_hash = F::rand(&mut rng);
+            note.callbacks_hash = F::rand(&mut rng);
+            note.memo_hash = F::rand(&mut rng);
+            
+            let note_var = NoteVar::new_witness(cs.clone(), || Ok(note.clone()), value, &randomness).unwrap();
+            let commitment_var = note_var.commitment().unwrap();
+            
+            assert_eq!(commitment_var.value().unwrap(), note.commitment());
+            commitment_var.enforce_equal(&FpVar::constant(note.commitment())).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
 }
\ No newline at end of file
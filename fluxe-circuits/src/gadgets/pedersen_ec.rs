@@ -346,7 +346,7 @@ mod tests {
         // Check constraints are satisfied
         assert!(cs.is_satisfied().unwrap());
     }
-    
+
     #[test]
     fn test_commitment_homomorphism() {
         let mut rng = test_rng();
@@ -2,8 +2,12 @@ use ark_bls12_381::Fr as F;
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use fluxe_core::crypto::{domain_sep_to_field, poseidon_hash, DOM_SPEND_WINDOW};
 
-use crate::gadgets::{merkle::MerklePathVar, poseidon::poseidon_hash_zk, range_proof::RangeProofGadget};
+use crate::gadgets::{
+    auth::AuthGadget, comparison::ComparisonGadget, merkle::MerklePathVar,
+    poseidon::poseidon_hash_zk, range_proof::RangeProofGadget, zk_object::ComplianceStateVar,
+};
 
 /// Pool policy enforcement gadget
 /// Handles inbound/outbound allowlists, denylists, and transfer limits
@@ -35,6 +39,10 @@ impl PoolPolicyGadget {
         dest_policy_path.leaf.enforce_equal(&dest_hash)?;
         dest_policy_path.enforce_valid(pool_rules_root)?;
         
+        // Bind each policy to the pool it governs; a valid policy for another pool is not enough
+        source_policy.pool_id.enforce_equal(source_pool_id)?;
+        dest_policy.pool_id.enforce_equal(dest_pool_id)?;
+        
         // Check source pool allows outbound to destination
         Self::check_outbound_allowed(cs.clone(), dest_pool_id, source_policy)?;
         
@@ -96,6 +104,168 @@ impl PoolPolicyGadget {
         Ok(())
     }
     
+    /// Enforce the pool's fee floor
+    /// A consolidation (every input and output owned by the same address) may pay
+    /// the lower consolidation floor; any other transfer pays the standard floor.
+    /// Either floor is raised by the pool's dust surcharge for each dust output.
+    /// Owners and output values come paired with their note's dummy flag: dummies only
+    /// pad the shape, so they count toward neither
+    pub fn enforce_fee_floor(
+        cs: ConstraintSystemRef<F>,
+        fee: &FpVar<F>,
+        consolidation: &Boolean<F>,
+        owner_addrs: &[(FpVar<F>, Boolean<F>)],
+        output_values: &[(FpVar<F>, Boolean<F>)],
+        policy: &PoolPolicyVar,
+    ) -> Result<(), SynthesisError> {
+        // Consolidation flag requires all real notes to share one owner
+        let same_owner = AuthGadget::same_real_owner(owner_addrs)?;
+        consolidation.and(&same_owner.not())?.enforce_equal(&Boolean::FALSE)?;
+        
+        // A dummy stands in at the dust threshold, which is not dust
+        let counted = output_values
+            .iter()
+            .map(|(value, is_dummy)| is_dummy.select(&policy.dust_threshold, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        // Pick the floor and require fee >= floor + dust surcharge
+        let floor = consolidation.select(&policy.consolidation_min_fee, &policy.min_fee)?;
+        RangeProofGadget::prove_range_bits(cs.clone(), &floor, 64)?;
+        Self::enforce_dust_surcharge(cs, fee, &floor, &counted, policy)
+    }
+    
+    /// Count outputs whose value is below the pool's dust threshold
+    pub fn dust_count(
+        cs: ConstraintSystemRef<F>,
+        output_values: &[FpVar<F>],
+        policy: &PoolPolicyVar,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let mut count = FpVar::zero();
+        for value in output_values {
+            let is_dust = ComparisonGadget::is_less_than(cs.clone(), value, &policy.dust_threshold)?;
+            count += FpVar::from(is_dust);
+        }
+        Ok(count)
+    }
+    
+    /// Enforce fee >= base_fee + dust_count * dust_surcharge
+    /// Discourages transfers that split value into many small outputs
+    pub fn enforce_dust_surcharge(
+        cs: ConstraintSystemRef<F>,
+        fee: &FpVar<F>,
+        base_fee: &FpVar<F>,
+        output_values: &[FpVar<F>],
+        policy: &PoolPolicyVar,
+    ) -> Result<(), SynthesisError> {
+        let dust_count = Self::dust_count(cs.clone(), output_values, policy)?;
+        let required = base_fee + &dust_count * &policy.dust_surcharge;
+        
+        RangeProofGadget::prove_range_bits(cs.clone(), fee, 64)?;
+        RangeProofGadget::prove_range_bits(cs.clone(), &policy.dust_surcharge, 64)?;
+        let meets_required = ComparisonGadget::is_less_than_or_equal(cs, &required, fee)?;
+        meets_required.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Enforce sum(output_values) <= max_per_tx when the policy sets AGGREGATE_CAP_FLAG
+    /// Caps the total value moved by one transfer, however it is split across outputs
+    pub fn enforce_aggregate_cap(
+        cs: ConstraintSystemRef<F>,
+        output_values: &[FpVar<F>],
+        policy: &PoolPolicyVar,
+    ) -> Result<(), SynthesisError> {
+        let mut total = FpVar::zero();
+        for value in output_values {
+            RangeProofGadget::prove_range_bits(cs.clone(), value, 64)?;
+            total += value;
+        }
+        
+        let has_aggregate_cap = policy.flags.has_aggregate_cap()?;
+        let within_cap = ComparisonGadget::is_less_than_or_equal(cs, &total, &policy.max_per_tx)?;
+        has_aggregate_cap.select(&within_cap, &Boolean::TRUE)?.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Enforce that dest_pool follows from source_pool under a deterministic
+    /// transition function keyed on the holder's compliance level.
+    /// Staying in the source pool is always allowed.
+    pub fn enforce_pool_transition_fn(
+        cs: ConstraintSystemRef<F>,
+        source_pool: &FpVar<F>,
+        dest_pool: &FpVar<F>,
+        state: &ComplianceStateVar,
+        transition_fn: &PoolTransitionFn,
+    ) -> Result<(), SynthesisError> {
+        let mut allowed = dest_pool.is_eq(source_pool)?;
+        
+        for rule in &transition_fn.transitions {
+            let from_matches = source_pool.is_eq(&FpVar::constant(F::from(rule.source_pool as u64)))?;
+            let to_matches = dest_pool.is_eq(&FpVar::constant(F::from(rule.dest_pool as u64)))?;
+            let level_ok = ComparisonGadget::is_greater_than_or_equal(
+                cs.clone(),
+                &state.level,
+                &FpVar::constant(F::from(rule.min_level as u64)),
+            )?;
+            allowed = allowed.or(&from_matches.and(&to_matches)?.and(&level_ok)?)?;
+        }
+        
+        allowed.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Enforce a fee-exempt migration: the fee is zero and dest_pool is a different
+    /// pool listed as a transition from source_pool. Migrations move a holder's funds
+    /// on the operator's behalf, so the rules' compliance levels are not checked.
+    pub fn enforce_migration(
+        source_pool: &FpVar<F>,
+        dest_pool: &FpVar<F>,
+        fee: &FpVar<F>,
+        transition_fn: &PoolTransitionFn,
+    ) -> Result<(), SynthesisError> {
+        fee.enforce_equal(&FpVar::zero())?;
+        
+        let mut listed = Boolean::FALSE;
+        for rule in &transition_fn.transitions {
+            let from_matches = source_pool.is_eq(&FpVar::constant(F::from(rule.source_pool as u64)))?;
+            let to_matches = dest_pool.is_eq(&FpVar::constant(F::from(rule.dest_pool as u64)))?;
+            listed = listed.or(&from_matches.and(&to_matches)?)?;
+        }
+        
+        listed.and(&dest_pool.is_neq(source_pool)?)?.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Enforce the daily limit over a rolling window: `timestamp` must fall in
+    /// [window_start, window_start + window_length) and, when DAILY_LIMIT_FLAG is set,
+    /// spent_in_window + amount <= max_per_day
+    /// The accumulator must open `window_cm`; returns the commitment to the accumulator
+    /// after this spend under `blinding_new`, to be carried into the next transaction
+    pub fn check_rolling_window(
+        cs: ConstraintSystemRef<F>,
+        spent_in_window: &FpVar<F>,
+        amount: &FpVar<F>,
+        timestamp: &FpVar<F>,
+        window_start: &FpVar<F>,
+        window_length: &FpVar<F>,
+        blinding: &FpVar<F>,
+        window_cm: &FpVar<F>,
+        blinding_new: &FpVar<F>,
+        policy: &PoolPolicyVar,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let dom = FpVar::constant(domain_sep_to_field(DOM_SPEND_WINDOW));
+        poseidon_hash_zk(&[dom.clone(), window_start.clone(), spent_in_window.clone(), blinding.clone()])?
+            .enforce_equal(window_cm)?;
+        
+        // window_start <= timestamp < window_start + window_length
+        ComparisonGadget::enforce_le(cs.clone(), window_start, timestamp, 64)?;
+        let window_end = window_start + window_length;
+        ComparisonGadget::enforce_le(cs.clone(), &(timestamp + FpVar::one()), &window_end, 64)?;
+        
+        let spent_after = spent_in_window + amount;
+        RangeProofGadget::prove_range_bits(cs.clone(), amount, 64)?;
+        let has_daily_limit = policy.flags.has_daily_limit()?;
+        let within_limit = ComparisonGadget::is_le(cs, &spent_after, &policy.max_per_day, 64)?;
+        has_daily_limit.select(&within_limit, &Boolean::TRUE)?.enforce_equal(&Boolean::TRUE)?;
+        
+        poseidon_hash_zk(&[dom, window_start.clone(), spent_after, blinding_new.clone()])
+    }
+    
     /// Check if outbound transfer to specific pool is allowed
     fn check_outbound_allowed(
         cs: ConstraintSystemRef<F>,
@@ -109,7 +279,7 @@ impl PoolPolicyGadget {
         // Conditionally check: if has_allowlist then must be in list
         // For simplicity, we always check but only enforce if flag is set
         // This maintains constant circuit size
-        Self::check_pool_in_list(cs.clone(), dest_pool_id, &policy.outbound_allow)?;
+        Self::check_pool_allowlisted(cs.clone(), dest_pool_id, &policy.outbound_allow, &policy.outbound_allow_root, policy)?;
         
         let _has_outbound_denylist = policy.flags.has_outbound_denylist()?;
         // Must NOT be in denylist (always check for constant circuit)
@@ -126,7 +296,7 @@ impl PoolPolicyGadget {
     ) -> Result<(), SynthesisError> {
         let _has_inbound_allowlist = policy.flags.has_inbound_allowlist()?;
         // Always check for constant circuit size
-        Self::check_pool_in_list(cs.clone(), source_pool_id, &policy.inbound_allow)?;
+        Self::check_pool_allowlisted(cs.clone(), source_pool_id, &policy.inbound_allow, &policy.inbound_allow_root, policy)?;
         
         let _has_inbound_denylist = policy.flags.has_inbound_denylist()?;
         Self::check_pool_not_in_list(cs, source_pool_id, &policy.inbound_deny)?;
@@ -140,18 +310,13 @@ impl PoolPolicyGadget {
         amount: &FpVar<F>,
         policy: &PoolPolicyVar,
     ) -> Result<(), SynthesisError> {
-        // Check per-transaction limit
-        let has_per_tx_limit = policy.flags.has_per_tx_limit()?;
-        // Since FpVar doesn't have is_le, we check that amount != max_per_tx + 1
-        // In practice, we'd need a proper comparison gadget
-        // For now, we just ensure amount is not equal to zero (simplified)
-        let amount_nonzero = amount.is_neq(&FpVar::zero())?;
-        // Only enforce if has_per_tx_limit is true
-        let should_check = has_per_tx_limit.select(&amount_nonzero, &Boolean::TRUE)?;
-        should_check.enforce_equal(&Boolean::TRUE)?;
-        
         // Range check the amount
-        RangeProofGadget::prove_range_bits(cs, amount, 64)?;
+        RangeProofGadget::prove_range_bits(cs.clone(), amount, 64)?;
+        
+        // Enforce amount <= max_per_tx only if has_per_tx_limit is true
+        let has_per_tx_limit = policy.flags.has_per_tx_limit()?;
+        let within_limit = ComparisonGadget::is_le(cs, amount, &policy.max_per_tx, 64)?;
+        has_per_tx_limit.and(&within_limit.not())?.enforce_equal(&Boolean::FALSE)?;
         
         Ok(())
     }
@@ -163,7 +328,7 @@ impl PoolPolicyGadget {
         timestamp: &FpVar<F>,
         policy: &PoolPolicyVar,
     ) -> Result<(), SynthesisError> {
-        // Simplified time checking - real implementation would track rolling windows
+        // Cumulative daily spend is enforced by `check_rolling_window`, which carries it between transactions
         
         let has_daily_limit = policy.flags.has_daily_limit()?;
         // In practice, would check cumulative daily amount
@@ -180,12 +345,56 @@ impl PoolPolicyGadget {
         Ok(())
     }
     
-    /// Check if pool ID is in an allowed list using proper bitmap bit extraction
-    fn check_pool_in_list(
-        _cs: ConstraintSystemRef<F>,
+    /// Check a pool against one of the policy's allowlists, by Merkle membership under
+    /// `allowlist_root` when the policy sets MERKLE_ALLOWLIST_FLAG, else by the bitmap
+    fn check_pool_allowlisted(
+        cs: ConstraintSystemRef<F>,
         pool_id: &FpVar<F>,
         allowed_bitmap: &FpVar<F>,
+        allowlist_root: &FpVar<F>,
+        policy: &PoolPolicyVar,
+    ) -> Result<(), SynthesisError> {
+        let uses_set = policy.flags.has_merkle_allowlist()?;
+        let in_bitmap = Self::pool_in_list(pool_id, allowed_bitmap)?;
+        
+        // Without a membership proof only the bitmap can admit the pool
+        let in_set = match &policy.allowlist_membership {
+            Some(membership) => Self::pool_in_set(cs, pool_id, allowlist_root, membership)?,
+            None => Boolean::FALSE,
+        };
+        
+        uses_set.select(&in_set, &in_bitmap)?.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Enforce that a pool is in an allowlist committed to by `allowlist_root`
+    /// Leaves are `PoolPolicyUtils::allowlist_leaf` of each allowed pool, so any 32-bit pool ID fits
+    pub fn check_pool_in_set(
+        cs: ConstraintSystemRef<F>,
+        pool_id: &FpVar<F>,
+        allowlist_root: &FpVar<F>,
+        membership: &MerklePathVar,
     ) -> Result<(), SynthesisError> {
+        Self::pool_in_set(cs, pool_id, allowlist_root, membership)?.enforce_equal(&Boolean::TRUE)
+    }
+    
+    /// Whether `membership` proves the pool's leaf under `allowlist_root`
+    fn pool_in_set(
+        cs: ConstraintSystemRef<F>,
+        pool_id: &FpVar<F>,
+        allowlist_root: &FpVar<F>,
+        membership: &MerklePathVar,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        RangeProofGadget::prove_range_bits(cs, pool_id, 32)?;
+        let leaf = poseidon_hash_zk(&[pool_id.clone()])?;
+        membership.leaf.is_eq(&leaf)?.and(&membership.verify(allowlist_root)?)
+    }
+    
+    /// Check if pool ID is in an allowed list using proper bitmap bit extraction
+    /// Only pools 0-255 are representable
+    fn pool_in_list(
+        pool_id: &FpVar<F>,
+        allowed_bitmap: &FpVar<F>,
+    ) -> Result<Boolean<F>, SynthesisError> {
         // Convert pool_id to bits to use as index (take lower 8 bits for 256 pools)
         let pool_id_bits = pool_id.to_bits_le()?;
         let index_bits = &pool_id_bits[..8.min(pool_id_bits.len())];
@@ -210,10 +419,7 @@ impl PoolPolicyGadget {
             is_allowed = index_matches.select(bit, &is_allowed)?;
         }
         
-        // Enforce that pool is in the allowed list
-        is_allowed.enforce_equal(&Boolean::TRUE)?;
-        
-        Ok(())
+        Ok(is_allowed)
     }
     
     /// Check if pool ID is NOT in a denied list using proper bitmap bit extraction
@@ -252,6 +458,48 @@ impl PoolPolicyGadget {
     }
 }
 
+/// One allowed pool transition: holders at min_level or above may move
+/// funds from source_pool to dest_pool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolTransition {
+    pub source_pool: u32,
+    pub dest_pool: u32,
+    pub min_level: u8,
+}
+
+/// Configurable pool transition function (circuit constant)
+#[derive(Clone, Debug, Default)]
+pub struct PoolTransitionFn {
+    pub transitions: Vec<PoolTransition>,
+}
+
+impl PoolTransitionFn {
+    pub fn new(transitions: Vec<PoolTransition>) -> Self {
+        Self { transitions }
+    }
+    
+    /// Tiered promotion: pool i+1 is reachable from pool i at compliance level i+1
+    pub fn tiered(num_pools: u32) -> Self {
+        Self::new(
+            (1..num_pools)
+                .map(|i| PoolTransition {
+                    source_pool: i,
+                    dest_pool: i + 1,
+                    min_level: i as u8 + 1,
+                })
+                .collect(),
+        )
+    }
+    
+    /// Native check of `PoolPolicyGadget::enforce_migration`'s pool move
+    pub fn allows_migration(&self, source_pool: u32, dest_pool: u32) -> bool {
+        source_pool != dest_pool
+            && self.transitions
+                .iter()
+                .any(|rule| rule.source_pool == source_pool && rule.dest_pool == dest_pool)
+    }
+}
+
 /// Pool policy variable for circuits
 #[derive(Clone)]
 pub struct PoolPolicyVar {
@@ -262,7 +510,17 @@ pub struct PoolPolicyVar {
     pub outbound_deny: FpVar<F>,
     pub max_per_tx: FpVar<F>,
     pub max_per_day: FpVar<F>,
+    pub min_fee: FpVar<F>,
+    pub consolidation_min_fee: FpVar<F>,
+    pub dust_threshold: FpVar<F>,  // Outputs below this value are dust
+    pub dust_surcharge: FpVar<F>,  // Extra fee per dust output
     pub flags: PoolFlagsVar,
+    /// Merkle allowlist roots, used instead of the bitmaps under MERKLE_ALLOWLIST_FLAG
+    pub inbound_allow_root: FpVar<F>,
+    pub outbound_allow_root: FpVar<F>,
+    /// Proof that the counterparty pool is in this policy's Merkle allowlist: outbound
+    /// when this is the source pool's policy, inbound when it is the destination's (not hashed)
+    pub allowlist_membership: Option<MerklePathVar>,
 }
 
 impl PoolPolicyVar {
@@ -275,6 +533,10 @@ impl PoolPolicyVar {
         outbound_deny: u64,
         max_per_tx: u64,
         max_per_day: u64,
+        min_fee: u64,
+        consolidation_min_fee: u64,
+        dust_threshold: u64,
+        dust_surcharge: u64,
         flags: u32,
     ) -> Result<Self, SynthesisError> {
         Ok(Self {
@@ -285,10 +547,35 @@ impl PoolPolicyVar {
             outbound_deny: FpVar::new_witness(cs.clone(), || Ok(F::from(outbound_deny)))?,
             max_per_tx: FpVar::new_witness(cs.clone(), || Ok(F::from(max_per_tx)))?,
             max_per_day: FpVar::new_witness(cs.clone(), || Ok(F::from(max_per_day)))?,
-            flags: PoolFlagsVar::new_witness(cs, flags)?,
+            min_fee: FpVar::new_witness(cs.clone(), || Ok(F::from(min_fee)))?,
+            consolidation_min_fee: FpVar::new_witness(cs.clone(), || Ok(F::from(consolidation_min_fee)))?,
+            dust_threshold: FpVar::new_witness(cs.clone(), || Ok(F::from(dust_threshold)))?,
+            dust_surcharge: FpVar::new_witness(cs.clone(), || Ok(F::from(dust_surcharge)))?,
+            flags: PoolFlagsVar::new_witness(cs.clone(), flags)?,
+            inbound_allow_root: FpVar::new_witness(cs.clone(), || Ok(F::from(0u64)))?,
+            outbound_allow_root: FpVar::new_witness(cs, || Ok(F::from(0u64)))?,
+            allowlist_membership: None,
         })
     }
     
+    /// Commit to Merkle allowlists of pool IDs (see `PoolPolicyUtils::allowlist_leaf`)
+    pub fn with_allowlist_roots(
+        mut self,
+        cs: ConstraintSystemRef<F>,
+        inbound_root: F,
+        outbound_root: F,
+    ) -> Result<Self, SynthesisError> {
+        self.inbound_allow_root = FpVar::new_witness(cs.clone(), || Ok(inbound_root))?;
+        self.outbound_allow_root = FpVar::new_witness(cs, || Ok(outbound_root))?;
+        Ok(self)
+    }
+    
+    /// Attach the counterparty pool's membership proof for a Merkle allowlist check
+    pub fn with_allowlist_membership(mut self, membership: MerklePathVar) -> Self {
+        self.allowlist_membership = Some(membership);
+        self
+    }
+    
     /// Compute hash of the policy for Merkle tree operations
     pub fn hash(&self) -> Result<FpVar<F>, SynthesisError> {
         let inputs = vec![
@@ -299,13 +586,50 @@ impl PoolPolicyVar {
             self.outbound_deny.clone(),
             self.max_per_tx.clone(),
             self.max_per_day.clone(),
+            self.min_fee.clone(),
+            self.consolidation_min_fee.clone(),
+            self.dust_threshold.clone(),
+            self.dust_surcharge.clone(),
             self.flags.bits.clone(),
+            self.inbound_allow_root.clone(),
+            self.outbound_allow_root.clone(),
         ];
         
         poseidon_hash_zk(&inputs)
     }
 }
 
+/// Spend accumulated in a pool's current rolling window, carried between transactions
+/// as H(DOM_SPEND_WINDOW, window_start, spent, blinding)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpendWindow {
+    pub window_start: u64,
+    pub spent: u64,
+    pub blinding: F,
+}
+
+impl SpendWindow {
+    /// A fresh window starting at `window_start` with nothing spent
+    pub fn new(window_start: u64, blinding: F) -> Self {
+        Self { window_start, spent: 0, blinding }
+    }
+    
+    /// Commitment opened by `check_rolling_window`
+    pub fn commitment(&self) -> F {
+        poseidon_hash(&[
+            domain_sep_to_field(DOM_SPEND_WINDOW),
+            F::from(self.window_start),
+            F::from(self.spent),
+            self.blinding,
+        ])
+    }
+    
+    /// The window after spending `amount`, re-blinded with `blinding`
+    pub fn after_spend(&self, amount: u64, blinding: F) -> Self {
+        Self { window_start: self.window_start, spent: self.spent + amount, blinding }
+    }
+}
+
 /// Pool policy flags variable
 #[derive(Clone)]
 pub struct PoolFlagsVar {
@@ -319,6 +643,8 @@ impl PoolFlagsVar {
     const INBOUND_DENYLIST_FLAG: u32 = 1 << 3;
     const PER_TX_LIMIT_FLAG: u32 = 1 << 4;
     const DAILY_LIMIT_FLAG: u32 = 1 << 5;
+    const AGGREGATE_CAP_FLAG: u32 = 1 << 6;
+    const MERKLE_ALLOWLIST_FLAG: u32 = 1 << 7;
     
     pub fn new_witness(
         cs: ConstraintSystemRef<F>,
@@ -359,6 +685,16 @@ impl PoolFlagsVar {
         self.check_flag(Self::DAILY_LIMIT_FLAG)
     }
     
+    /// Check if max_per_tx caps the sum of a transfer's outputs
+    pub fn has_aggregate_cap(&self) -> Result<Boolean<F>, SynthesisError> {
+        self.check_flag(Self::AGGREGATE_CAP_FLAG)
+    }
+    
+    /// Check if allowlists are Merkle roots of pool IDs rather than bitmaps
+    pub fn has_merkle_allowlist(&self) -> Result<Boolean<F>, SynthesisError> {
+        self.check_flag(Self::MERKLE_ALLOWLIST_FLAG)
+    }
+    
     /// Check if a specific flag bit is set
     fn check_flag(&self, flag_mask: u32) -> Result<Boolean<F>, SynthesisError> {
         // Convert to bits and check the specific bit position
@@ -380,6 +716,11 @@ impl PoolFlagsVar {
 pub struct PoolPolicyUtils;
 
 impl PoolPolicyUtils {
+    /// Leaf for a pool in a Merkle allowlist
+    pub fn allowlist_leaf(pool_id: u32) -> F {
+        fluxe_core::crypto::poseidon_hash(&[F::from(pool_id as u64)])
+    }
+    
     /// Create a permissive policy (allows all transfers)
     pub fn create_permissive_policy(
         cs: ConstraintSystemRef<F>,
@@ -394,6 +735,10 @@ impl PoolPolicyUtils {
             0,        // Deny none outbound
             u64::MAX, // No per-tx limit
             u64::MAX, // No daily limit
+            0,        // No fee floor
+            0,        // No consolidation fee floor
+            0,        // No dust threshold
+            0,        // No dust surcharge
             0,        // No flags set
         )
     }
@@ -416,6 +761,10 @@ impl PoolPolicyUtils {
             0,            // No outbound denylist
             1_000_000,    // 1M unit per-tx limit
             10_000_000,   // 10M unit daily limit
+            10,           // Standard fee floor
+            1,            // Reduced fee floor for consolidations
+            100,          // Outputs under 100 units are dust
+            5,            // 5 unit surcharge per dust output
             PoolFlagsVar::INBOUND_ALLOWLIST_FLAG 
                 | PoolFlagsVar::OUTBOUND_ALLOWLIST_FLAG 
                 | PoolFlagsVar::PER_TX_LIMIT_FLAG
@@ -444,6 +793,10 @@ mod tests {
             0,        // outbound_deny
             1000,     // max_per_tx
             10000,    // max_per_day
+            10,       // min_fee
+            1,        // consolidation_min_fee
+            100,      // dust_threshold
+            5,        // dust_surcharge
             0x3F,     // flags
         ).unwrap();
         
@@ -483,6 +836,10 @@ mod tests {
             0,     // outbound_deny
             1000,  // max_per_tx (higher than amount)
             10000, // max_per_day
+            0,     // min_fee
+            0,     // consolidation_min_fee
+            0,     // dust_threshold
+            0,     // dust_surcharge
             PoolFlagsVar::PER_TX_LIMIT_FLAG,
         ).unwrap();
         
@@ -490,6 +847,29 @@ mod tests {
         assert!(cs.is_satisfied().unwrap());
     }
 
+    #[test]
+    fn test_amount_over_per_tx_limit() {
+        let check = |amount: u64, flags: u32| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let amount = FpVar::new_witness(cs.clone(), || Ok(F::from(amount))).unwrap();
+            let policy = PoolPolicyVar::new_witness(
+                cs.clone(), 1, 0, 0, 0, 0,
+                1000,  // max_per_tx
+                10000, // max_per_day
+                0, 0, 0, 0,
+                flags,
+            ).unwrap();
+            PoolPolicyGadget::check_amount_limits(cs.clone(), &amount, &policy).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        assert!(check(1000, PoolFlagsVar::PER_TX_LIMIT_FLAG));
+        assert!(!check(1001, PoolFlagsVar::PER_TX_LIMIT_FLAG));
+        
+        // Without the flag max_per_tx is not enforced
+        assert!(check(1001, 0));
+    }
+
     #[test]
     fn test_permissive_policy() {
         let cs = ConstraintSystem::<F>::new_ref();
@@ -502,4 +882,307 @@ mod tests {
         PoolPolicyGadget::check_amount_limits(cs.clone(), &large_amount, &policy).unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_consolidation_fee_floor() {
+        // Standard floor 10, consolidation floor 1; fee of 2 only clears the reduced floor
+        // `padding` dummy notes at another address follow the real owners
+        let check = |owners: &[u64], padding: usize, consolidation: bool| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let policy = PoolPolicyUtils::create_restrictive_policy(cs.clone(), 1, &[1]).unwrap();
+            let fee = FpVar::new_witness(cs.clone(), || Ok(F::from(2u64))).unwrap();
+            let flag = Boolean::new_witness(cs.clone(), || Ok(consolidation)).unwrap();
+            let owner_vars: Vec<_> = owners
+                .iter()
+                .map(|o| (*o, false))
+                .chain(std::iter::repeat((99, true)).take(padding))
+                .map(|(o, dummy)| {
+                    (
+                        FpVar::new_witness(cs.clone(), || Ok(F::from(o))).unwrap(),
+                        Boolean::new_witness(cs.clone(), || Ok(dummy)).unwrap(),
+                    )
+                })
+                .collect();
+            PoolPolicyGadget::enforce_fee_floor(cs.clone(), &fee, &flag, &owner_vars, &[], &policy).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // Same-owner consolidation qualifies for the reduced floor, padded or not
+        assert!(check(&[7, 7, 7], 0, true));
+        assert!(check(&[7, 7], 2, true));
+        
+        // Cross-owner transfer cannot claim consolidation
+        assert!(!check(&[7, 7, 8], 0, true));
+        assert!(!check(&[7, 8], 2, true));
+        
+        // Without the flag the standard floor applies
+        assert!(!check(&[7, 7, 8], 0, false));
+        assert!(!check(&[7, 7, 7], 0, false));
+    }
+
+    #[test]
+    fn test_aggregate_cap() {
+        // Cap of 1000 across outputs; each output alone is well under it
+        let check = |values: &[u64], flags: u32| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let policy = PoolPolicyVar::new_witness(
+                cs.clone(), 1, 0, 0, 0, 0,
+                1000,  // max_per_tx
+                10000, // max_per_day
+                0, 0, 0, 0,
+                flags,
+            ).unwrap();
+            let value_vars: Vec<_> = values
+                .iter()
+                .map(|v| FpVar::new_witness(cs.clone(), || Ok(F::from(*v))).unwrap())
+                .collect();
+            PoolPolicyGadget::enforce_aggregate_cap(cs.clone(), &value_vars, &policy).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        assert!(check(&[400, 400, 200], PoolFlagsVar::AGGREGATE_CAP_FLAG));
+        assert!(!check(&[400, 400, 201], PoolFlagsVar::AGGREGATE_CAP_FLAG));
+        
+        // Without the flag max_per_tx is not an aggregate cap
+        assert!(check(&[400, 400, 201], PoolFlagsVar::PER_TX_LIMIT_FLAG));
+    }
+
+    #[test]
+    fn test_pool_transition_fn() {
+        use fluxe_core::data_structures::ComplianceState;
+        
+        // Pools 1 -> 2 -> 3, each step gated on the next compliance level
+        let transition_fn = PoolTransitionFn::tiered(3);
+        let check = |from: u64, to: u64, level: u8| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let source = FpVar::new_witness(cs.clone(), || Ok(F::from(from))).unwrap();
+            let dest = FpVar::new_witness(cs.clone(), || Ok(F::from(to))).unwrap();
+            let state = ComplianceStateVar::new_witness(cs.clone(), || {
+                Ok(ComplianceState::new_verified(level))
+            }).unwrap();
+            PoolPolicyGadget::enforce_pool_transition_fn(cs.clone(), &source, &dest, &state, &transition_fn)
+                .unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // Valid promotion and staying put pass
+        assert!(check(1, 2, 2));
+        assert!(check(2, 3, 3));
+        assert!(check(2, 2, 0));
+        
+        // Skipping a tier or promoting without the required level fails
+        assert!(!check(1, 3, 3));
+        assert!(!check(1, 2, 1));
+        assert!(!check(2, 1, 3));
+    }
+
+    #[test]
+    fn test_migration() {
+        let transition_fn = PoolTransitionFn::tiered(3);
+        let check = |from: u64, to: u64, fee: u64| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let source = FpVar::new_witness(cs.clone(), || Ok(F::from(from))).unwrap();
+            let dest = FpVar::new_witness(cs.clone(), || Ok(F::from(to))).unwrap();
+            let fee = FpVar::new_input(cs.clone(), || Ok(F::from(fee))).unwrap();
+            PoolPolicyGadget::enforce_migration(&source, &dest, &fee, &transition_fn).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // Listed moves pass fee-free, whatever the holder's level
+        assert!(check(1, 2, 0));
+        assert!(check(2, 3, 0));
+        
+        // A fee, an unlisted move, or staying put is not a migration
+        assert!(!check(1, 2, 1));
+        assert!(!check(1, 3, 0));
+        assert!(!check(2, 2, 0));
+        
+        assert!(transition_fn.allows_migration(1, 2));
+        assert!(!transition_fn.allows_migration(2, 1));
+    }
+
+    #[test]
+    fn test_merkle_pool_allowlist() {
+        use fluxe_core::merkle::IncrementalTree;
+        
+        // Pool 1 may send to pools 7 and 70_000, neither representable in a bitmap
+        let mut allowlist = IncrementalTree::new(4);
+        allowlist.append(PoolPolicyUtils::allowlist_leaf(7));
+        allowlist.append(PoolPolicyUtils::allowlist_leaf(70_000));
+        
+        let check = |dest: u64, proven_index: usize, flags: u32| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let membership = MerklePathVar::new_witness(cs.clone(), || Ok(allowlist.get_path(proven_index).unwrap()))
+                .unwrap();
+            let policy = PoolPolicyVar::new_witness(
+                cs.clone(), 1, 0, 0,
+                0,  // outbound_allow bitmap admits nothing
+                0,
+                1000, 10000, 0, 0, 0, 0,
+                flags,
+            ).unwrap()
+                .with_allowlist_roots(cs.clone(), F::from(0u64), allowlist.root())
+                .unwrap()
+                .with_allowlist_membership(membership);
+            let dest = FpVar::new_witness(cs.clone(), || Ok(F::from(dest))).unwrap();
+            PoolPolicyGadget::check_outbound_allowed(cs.clone(), &dest, &policy).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        let merkle = PoolFlagsVar::OUTBOUND_ALLOWLIST_FLAG | PoolFlagsVar::MERKLE_ALLOWLIST_FLAG;
+        
+        // A pool ID above 255 is admitted by its membership proof
+        assert!(check(70_000, 1, merkle));
+        assert!(check(7, 0, merkle));
+        
+        // Another pool cannot borrow a member's proof, even one aliasing it in the low bits
+        assert!(!check(70_001, 1, merkle));
+        assert!(!check(70_000 + 256, 1, merkle));
+        
+        // Without the flag the empty bitmap decides
+        assert!(!check(70_000, 1, PoolFlagsVar::OUTBOUND_ALLOWLIST_FLAG));
+        
+        // The allowlist roots are part of the policy hash
+        let cs = ConstraintSystem::<F>::new_ref();
+        let plain = PoolPolicyUtils::create_permissive_policy(cs.clone(), 1).unwrap();
+        let rooted = PoolPolicyUtils::create_permissive_policy(cs.clone(), 1).unwrap()
+            .with_allowlist_roots(cs.clone(), F::from(0u64), allowlist.root())
+            .unwrap();
+        assert_ne!(plain.hash().unwrap().value().unwrap(), rooted.hash().unwrap().value().unwrap());
+    }
+
+    #[test]
+    fn test_transfer_policy_bound_to_pool_ids() {
+        use fluxe_core::merkle::IncrementalTree;
+        
+        // Policies for pools 1 -> 2 in the rules tree; the notes claim the given pools
+        let check = |source: u64, dest: u64| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let source_policy = PoolPolicyUtils::create_permissive_policy(cs.clone(), 1).unwrap();
+            let dest_policy = PoolPolicyUtils::create_permissive_policy(cs.clone(), 2).unwrap();
+            
+            let mut rules = IncrementalTree::new(4);
+            rules.append(source_policy.hash().unwrap().value().unwrap());
+            rules.append(dest_policy.hash().unwrap().value().unwrap());
+            let source_path = MerklePathVar::new_witness(cs.clone(), || Ok(rules.get_path(0).unwrap())).unwrap();
+            let dest_path = MerklePathVar::new_witness(cs.clone(), || Ok(rules.get_path(1).unwrap())).unwrap();
+            let root = FpVar::new_input(cs.clone(), || Ok(rules.root())).unwrap();
+            
+            let source_id = FpVar::new_witness(cs.clone(), || Ok(F::from(source))).unwrap();
+            let dest_id = FpVar::new_witness(cs.clone(), || Ok(F::from(dest))).unwrap();
+            let amount = FpVar::new_witness(cs.clone(), || Ok(F::from(100u64))).unwrap();
+            let timestamp = FpVar::new_witness(cs.clone(), || Ok(F::from(0u64))).unwrap();
+            PoolPolicyGadget::enforce_pool_transfer_policy(
+                cs.clone(),
+                &source_id,
+                &dest_id,
+                &amount,
+                &timestamp,
+                &root,
+                &source_policy,
+                &dest_policy,
+                &source_path,
+                &dest_path,
+            ).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        assert!(check(1, 2));
+        
+        // A genuine policy for the wrong pool is rejected on either side
+        assert!(!check(3, 2));
+        assert!(!check(1, 3));
+        assert!(!check(2, 1));
+    }
+
+    #[test]
+    fn test_dust_surcharge() {
+        // Standard floor 10, dust below 100 units, 5 unit surcharge per dust output;
+        // `padding` zero-value dummy outputs follow the real ones
+        let check = |fee: u64, outputs: &[u64], padding: usize| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let policy = PoolPolicyUtils::create_restrictive_policy(cs.clone(), 1, &[1]).unwrap();
+            let fee = FpVar::new_witness(cs.clone(), || Ok(F::from(fee))).unwrap();
+            let flag = Boolean::new_witness(cs.clone(), || Ok(false)).unwrap();
+            let value_vars: Vec<_> = outputs
+                .iter()
+                .map(|v| FpVar::new_witness(cs.clone(), || Ok(F::from(*v))).unwrap())
+                .collect();
+            let count = PoolPolicyGadget::dust_count(cs.clone(), &value_vars, &policy).unwrap();
+            let expected = outputs.iter().filter(|&&v| v < 100).count() as u64;
+            assert_eq!(count.value().unwrap(), F::from(expected));
+            let outputs: Vec<_> = value_vars
+                .into_iter()
+                .map(|v| (v, Boolean::FALSE))
+                .chain((0..padding).map(|_| (FpVar::zero(), Boolean::TRUE)))
+                .collect();
+            PoolPolicyGadget::enforce_fee_floor(cs.clone(), &fee, &flag, &[], &outputs, &policy).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // No dust: the plain floor suffices, and padding is not surcharged
+        assert!(check(10, &[100, 500], 0));
+        assert!(check(10, &[100, 500], 2));
+        
+        // Four dust outputs need 10 + 4 * 5 = 30
+        let dusty = [1, 20, 50, 99, 1000];
+        assert!(!check(10, &dusty, 0));
+        assert!(!check(29, &dusty, 0));
+        assert!(check(30, &dusty, 0));
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        // Daily limit of 10000 over a window [1000, 1000 + 86400)
+        let spend = |window: &SpendWindow, opened: F, amount: u64, timestamp: u64, flags: u32| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let policy = PoolPolicyVar::new_witness(
+                cs.clone(), 1, 0, 0, 0, 0,
+                1000,  // max_per_tx
+                10000, // max_per_day
+                0, 0, 0, 0,
+                flags,
+            ).unwrap();
+            let witness = |value: F| FpVar::new_witness(cs.clone(), || Ok(value)).unwrap();
+            let next = window.after_spend(amount, F::from(amount + 1));
+            let cm_new = PoolPolicyGadget::check_rolling_window(
+                cs.clone(),
+                &witness(F::from(window.spent)),
+                &witness(F::from(amount)),
+                &witness(F::from(timestamp)),
+                &witness(F::from(window.window_start)),
+                &witness(F::from(86400u64)),
+                &witness(window.blinding),
+                &witness(opened),
+                &witness(next.blinding),
+                &policy,
+            ).unwrap();
+            assert_eq!(cm_new.value().unwrap(), next.commitment());
+            (cs.is_satisfied().unwrap(), next)
+        };
+        let daily = PoolFlagsVar::DAILY_LIMIT_FLAG;
+        
+        // Spends accumulate under the limit, each opening the previous commitment
+        let mut window = SpendWindow::new(1000, F::from(7u64));
+        for amount in [4000, 4000, 2000] {
+            let (ok, next) = spend(&window, window.commitment(), amount, 5000, daily);
+            assert!(ok);
+            window = next;
+        }
+        assert_eq!(window.spent, 10000);
+        
+        // The cumulative amount may not exceed max_per_day, unless the flag is unset
+        assert!(!spend(&window, window.commitment(), 1, 5000, daily).0);
+        assert!(spend(&window, window.commitment(), 1, 5000, 0).0);
+        
+        // Understating the spend so far doesn't open the carried commitment
+        let understated = SpendWindow { spent: 0, ..window };
+        assert!(!spend(&understated, window.commitment(), 1, 5000, daily).0);
+        
+        // The timestamp must fall inside the window
+        let fresh = SpendWindow::new(1000, F::from(7u64));
+        assert!(spend(&fresh, fresh.commitment(), 1, 1000, daily).0);
+        assert!(spend(&fresh, fresh.commitment(), 1, 1000 + 86399, daily).0);
+        assert!(!spend(&fresh, fresh.commitment(), 1, 999, daily).0);
+        assert!(!spend(&fresh, fresh.commitment(), 1, 1000 + 86400, daily).0);
+    }
+}
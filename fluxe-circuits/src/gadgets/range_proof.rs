@@ -36,7 +36,7 @@ impl RangeProofGadget {
         let reconstructed = Self::le_bits_to_fp_var(&value_bits[..bits.min(value_bits.len())])?;
         
         // Create a mask for the valid bits
-        let mask = if bits >= 254 {
+        let mask = if bits >= value_bits.len() {
             // For full field element range
             value.clone()
         } else {
@@ -45,7 +45,7 @@ impl RangeProofGadget {
         };
         
         // For values that should fit in 'bits' bits, verify reconstruction matches
-        if bits < 254 {
+        if bits < value_bits.len() {
             value.enforce_equal(&mask)?;
         }
         
@@ -6,6 +6,9 @@ use fluxe_core::data_structures::{ExitReceipt, IngressReceipt};
 
 use crate::gadgets::poseidon::poseidon_hash_zk;
 
+/// Bit width of receipt amounts (matches note values)
+pub const RECEIPT_AMOUNT_BITS: usize = 64;
+
 /// Ingress receipt variable for circuits
 #[derive(Clone)]
 pub struct IngressReceiptVar {
@@ -53,6 +56,7 @@ pub struct ExitReceiptVar {
     pub burned_nf: FpVar<F>,
     pub nonce: FpVar<F>,
     pub aux: FpVar<F>,
+    pub exit_destination: FpVar<F>,
 }
 
 impl ExitReceiptVar {
@@ -68,7 +72,8 @@ impl ExitReceiptVar {
             amount: FpVar::new_witness(cs.clone(), || Ok(receipt.amount.to_field()))?,
             burned_nf: FpVar::new_witness(cs.clone(), || Ok(receipt.burned_nf))?,
             nonce: FpVar::new_witness(cs.clone(), || Ok(F::from(receipt.nonce)))?,
-            aux: FpVar::new_witness(cs, || Ok(receipt.aux))?,
+            aux: FpVar::new_witness(cs.clone(), || Ok(receipt.aux))?,
+            exit_destination: FpVar::new_witness(cs, || Ok(receipt.exit_destination))?,
         })
     }
     
@@ -80,6 +85,7 @@ impl ExitReceiptVar {
             self.burned_nf.clone(),
             self.nonce.clone(),
             self.aux.clone(),
+            self.exit_destination.clone(),
         ])
     }
 }
\ No newline at end of file
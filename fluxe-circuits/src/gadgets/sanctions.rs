@@ -20,22 +20,69 @@ impl SanctionsChecker {
         merkle_path: &[FpVar<F>],
     ) -> Result<(), SynthesisError> {
         // Verify the low leaf is in the tree by computing root from path
-        let low_leaf_hash = low_leaf.hash()?;
+        Self::path_root(low_leaf, merkle_path)?.enforce_equal(sanctions_root)?;
         
+        // Verify the gap: low_leaf.key < identifier < low_leaf.next_key
+        Self::enforce_gap_constraint(identifier, low_leaf)?;
+        
+        Ok(())
+    }
+    
+    /// Whether the non-membership proof holds; the checks of `prove_not_sanctioned`,
+    /// returned instead of enforced
+    pub fn is_not_sanctioned(
+        identifier: &FpVar<F>,
+        sanctions_root: &FpVar<F>,
+        low_leaf: &SanctionsLeafVar,
+        merkle_path: &[FpVar<F>],
+    ) -> Result<Boolean<F>, SynthesisError> {
+        let in_tree = Self::path_root(low_leaf, merkle_path)?.is_eq(sanctions_root)?;
+        
+        // low_leaf.key < identifier < low_leaf.next_key (or next_key = 0)
+        let above_low = Self::enforce_positive_conditional(&(identifier - &low_leaf.key))?;
+        let below_next = low_leaf.next_key.is_eq(&FpVar::zero())?
+            .or(&Self::enforce_positive_conditional(&(&low_leaf.next_key - identifier))?)?;
+        
+        in_tree.and(&above_low)?.and(&below_next)
+    }
+    
+    /// Prove an identifier is absent from the sanctions list of every jurisdiction set in
+    /// `jurisdiction_bits` (bit j = jurisdiction j, as in the compliance state)
+    /// `lists[i]` is a (jurisdiction, sanctions_root) pair proven by `proofs[i]`; lists of
+    /// jurisdictions the holder is not in may carry any proof and are not enforced
+    pub fn prove_not_sanctioned_in_jurisdictions(
+        identifier: &FpVar<F>,
+        jurisdiction_bits: &FpVar<F>,
+        lists: &[(u8, FpVar<F>)],
+        proofs: &[(SanctionsLeafVar, Vec<FpVar<F>>)],
+    ) -> Result<(), SynthesisError> {
+        if lists.len() != proofs.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        let bits = jurisdiction_bits.to_bits_le()?;
+        for ((jurisdiction, sanctions_root), (low_leaf, merkle_path)) in lists.iter().zip(proofs.iter()) {
+            // Jurisdictions past the field's bits can never be set
+            let applies = bits.get(*jurisdiction as usize).cloned().unwrap_or(Boolean::FALSE);
+            let cleared = Self::is_not_sanctioned(identifier, sanctions_root, low_leaf, merkle_path)?;
+            applies.and(&cleared.not())?.enforce_equal(&Boolean::FALSE)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Root reached by hashing the low leaf up its path
+    fn path_root(
+        low_leaf: &SanctionsLeafVar,
+        merkle_path: &[FpVar<F>],
+    ) -> Result<FpVar<F>, SynthesisError> {
         // Manually compute root from path (simple binary merkle tree)
-        let mut current = low_leaf_hash;
+        let mut current = low_leaf.hash()?;
         for sibling in merkle_path {
             // Hash current with sibling (order doesn't matter for simplified version)
             current = poseidon_hash_zk(&[current, sibling.clone()])?;
         }
-        
-        // Verify computed root matches expected
-        current.enforce_equal(sanctions_root)?;
-        
-        // Verify the gap: low_leaf.key < identifier < low_leaf.next_key
-        Self::enforce_gap_constraint(identifier, low_leaf)?;
-        
-        Ok(())
+        Ok(current)
     }
     
     /// Check multiple identifiers are not sanctioned
@@ -312,6 +359,48 @@ mod tests {
         assert!(cs.is_satisfied().unwrap());
     }
 
+    #[test]
+    fn test_jurisdiction_sanctions_lists() {
+        // Each list is (jurisdiction, low key, next key) for the identifier 150
+        let check = |jurisdictions: &[u8], lists: &[(u8, u64, u64)]| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let identifier = FpVar::new_witness(cs.clone(), || Ok(F::from(150u64))).unwrap();
+            let bits: u64 = jurisdictions.iter().map(|j| 1u64 << j).sum();
+            let jurisdiction_bits = FpVar::new_witness(cs.clone(), || Ok(F::from(bits))).unwrap();
+            
+            let mut roots = Vec::new();
+            let mut proofs = Vec::new();
+            for (jurisdiction, low_key, next_key) in lists {
+                let leaf = SanctionsLeafVar::new_witness(
+                    cs.clone(),
+                    F::from(*low_key),
+                    F::from(*next_key),
+                    None,
+                ).unwrap();
+                let path: Vec<_> = (0..4)
+                    .map(|i| FpVar::new_witness(cs.clone(), || Ok(F::from(i + *jurisdiction as u64))).unwrap())
+                    .collect();
+                let root = SanctionsChecker::path_root(&leaf, &path).unwrap().value().unwrap();
+                roots.push((*jurisdiction, FpVar::new_input(cs.clone(), || Ok(root)).unwrap()));
+                proofs.push((leaf, path));
+            }
+            
+            SanctionsChecker::prove_not_sanctioned_in_jurisdictions(&identifier, &jurisdiction_bits, &roots, &proofs)
+                .unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // Cleared on both lists of a holder in jurisdictions 1 and 2
+        assert!(check(&[1, 2], &[(1, 100, 200), (2, 140, 0)]));
+        
+        // Listed in either jurisdiction: the best low leaf is the identifier itself
+        assert!(!check(&[1, 2], &[(1, 150, 200), (2, 140, 0)]));
+        assert!(!check(&[1, 2], &[(1, 100, 200), (2, 150, 300)]));
+        
+        // A list of a jurisdiction the holder is not in does not apply
+        assert!(check(&[1], &[(1, 100, 200), (2, 150, 300)]));
+    }
+
     #[test]
     fn test_positive_enforcement() {
         let cs = ConstraintSystem::<F>::new_ref();
@@ -3,7 +3,7 @@ use ark_r1cs_std::{fields::fp::FpVar, boolean::Boolean, prelude::*};
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 use super::sorted_tree::{RangePathVar, SortedLeafVar};
 use super::merkle::MerklePathVar;
-use fluxe_core::merkle::{RangePath, SortedLeaf, MerklePath, TreeParams};
+use fluxe_core::merkle::{RangePath, SortedLeaf, MerklePath, TreeConfig, TreeError, TreeParams};
 
 /// Witness data for sorted insert operation
 #[derive(Clone, Debug)]
@@ -45,6 +45,14 @@ impl SortedInsertWitness {
         }
     }
     
+    /// Check the range proof and update paths were built for a `tree` of the configured `height`
+    pub fn check_height(&self, tree: &'static str, height: usize) -> Result<(), TreeError> {
+        for path in [&self.range_proof.low_path, &self.new_leaf_path, &self.pred_update_path] {
+            TreeConfig::check_siblings(tree, height, path.siblings.len())?;
+        }
+        Ok(())
+    }
+    
     /// Compute the root before insertion
     pub fn compute_old_root(&self, params: &TreeParams) -> F {
         // The old root is what the range proof verifies against
@@ -210,6 +218,35 @@ impl SimtInsertVar {
         is_valid.enforce_equal(&Boolean::TRUE)
     }
     
+    /// Enforce that inserting exactly `targets`, in order, takes `old_root` to `new_root`
+    /// Each witness is chained onto the previous root and must insert its target; a
+    /// witness count differing from the target count is rejected, so the root delta
+    /// cannot hide extra insertions. No targets means the root must not move
+    pub fn enforce_chain(
+        cs: ConstraintSystemRef<F>,
+        old_root: &FpVar<F>,
+        new_root: &FpVar<F>,
+        targets: &[FpVar<F>],
+        witnesses: &[SortedInsertWitness],
+    ) -> Result<(), SynthesisError> {
+        if witnesses.len() != targets.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        let mut current = old_root.clone();
+        for (target, witness) in targets.iter().zip(witnesses.iter()) {
+            // Root after this insertion, at the witness's tree height
+            let new_root_value = witness.compute_new_root(&TreeParams::new(witness.height));
+            let insert = Self::new_witness(cs.clone(), witness.clone(), current.value()?, new_root_value)?;
+            
+            insert.target.enforce_equal(target)?;
+            insert.old_root.enforce_equal(&current)?;
+            insert.enforce()?;
+            current = insert.new_root;
+        }
+        current.enforce_equal(new_root)
+    }
+    
     /// Verify the linking structure is maintained correctly
     fn verify_linking_structure(&self) -> Result<Boolean<F>, SynthesisError> {
         // The updated predecessor should point to the new leaf
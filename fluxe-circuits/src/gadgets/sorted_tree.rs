@@ -5,11 +5,12 @@ use ark_r1cs_std::{
     prelude::*,
 };
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use fluxe_core::merkle::{SortedLeaf, RangePath};
+use fluxe_core::merkle::{SortedLeaf, RangePath, SortedMembershipProof};
 
 use super::poseidon::poseidon_hash_zk;
 use super::merkle::MerklePathVar;
 use super::comparison::ComparisonGadget;
+use super::range_proof::RangeProofGadget;
 
 /// Variable for sorted tree leaf
 #[derive(Clone)]
@@ -77,6 +78,7 @@ impl RangePathVar {
     }
     
     /// Verify non-membership proof
+    /// Callers must also range-check the low path index, as enforce_valid does
     pub fn verify(&self, root: &FpVar<F>) -> Result<Boolean<F>, SynthesisError> {
         // 1. Verify low leaf is in tree
         let leaf_hash = self.low_leaf.hash()?;
@@ -93,16 +95,75 @@ impl RangePathVar {
     
     /// Enforce that this is a valid non-membership proof
     pub fn enforce_valid(&self, root: &FpVar<F>) -> Result<(), SynthesisError> {
+        // The root only depends on the low index bits the siblings walk; higher bits
+        // would let one leaf open under several indices
+        RangeProofGadget::prove_range_bits(
+            self.low_path.leaf_index.cs(),
+            &self.low_path.leaf_index,
+            self.low_path.siblings.len(),
+        )?;
+        
         let is_valid = self.verify(root)?;
         is_valid.enforce_equal(&Boolean::TRUE)
     }
+    
+    /// Enforce a valid non-membership proof whose low leaf is stored at `pred_index`
+    pub fn enforce_valid_at_index(
+        &self,
+        root: &FpVar<F>,
+        pred_index: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        self.low_path.leaf_index.enforce_equal(pred_index)?;
+        self.enforce_valid(root)
+    }
+}
+
+/// Variable for sorted tree membership proof
+#[derive(Clone)]
+pub struct SortedMembershipVar {
+    pub leaf: SortedLeafVar,
+    pub path: MerklePathVar,
+}
+
+impl SortedMembershipVar {
+    pub fn new_witness(
+        cs: ConstraintSystemRef<F>,
+        f: impl FnOnce() -> Result<SortedMembershipProof, SynthesisError>,
+    ) -> Result<Self, SynthesisError> {
+        let proof = f()?;
+        Ok(Self {
+            leaf: SortedLeafVar::new_witness(cs.clone(), || Ok(proof.leaf))?,
+            path: MerklePathVar::new_witness(cs, || Ok(proof.path))?,
+        })
+    }
+    
+    /// Verify that `key` is present in the tree
+    pub fn verify(&self, key: &FpVar<F>, root: &FpVar<F>) -> Result<Boolean<F>, SynthesisError> {
+        // 1. Leaf holds the key, and the key is not the zero sentinel
+        let key_matches = self.leaf.key.is_eq(key)?;
+        let not_sentinel = key.is_neq(&FpVar::zero())?;
+        
+        // 2. Leaf is in tree
+        let leaf_hash = self.leaf.hash()?;
+        let leaf_matches = self.path.leaf.is_eq(&leaf_hash)?;
+        let computed_root = self.path.compute_root()?;
+        let path_valid = computed_root.is_eq(root)?;
+        
+        Boolean::kary_and(&[key_matches, not_sentinel, leaf_matches, path_valid])
+    }
+    
+    /// Enforce that `key` is present in the tree
+    pub fn enforce_member(&self, key: &FpVar<F>, root: &FpVar<F>) -> Result<(), SynthesisError> {
+        let is_member = self.verify(key, root)?;
+        is_member.enforce_equal(&Boolean::TRUE)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use ark_relations::r1cs::ConstraintSystem;
-    use fluxe_core::merkle::{SortedTree, MerkleTree};
+    use fluxe_core::merkle::{SortedTree, MerkleTree, TreeParams};
     
     #[test]
     fn test_sorted_leaf_gadget() {
@@ -151,4 +212,85 @@ mod tests {
         
         assert!(cs.is_satisfied().unwrap());
     }
+    
+    #[test]
+    fn test_range_path_rejects_wrong_index() {
+        let mut tree = SortedTree::new(4);
+        tree.insert(F::from(100)).unwrap();
+        tree.insert(F::from(200)).unwrap();
+        tree.insert(F::from(300)).unwrap();
+        
+        let target = F::from(150);
+        let range_path = tree.prove_non_membership(target).unwrap();
+        let pred_index = tree.index_of(&range_path.low_leaf.key).unwrap();
+        let params = TreeParams::new(4);
+        
+        // Off-circuit: the honest proof passes only at the predecessor's index
+        assert!(range_path.verify_at_index(&tree.root(), &params, pred_index));
+        assert!(!range_path.verify_at_index(&tree.root(), &params, pred_index + 1));
+        
+        let mut wrong = range_path.clone();
+        wrong.low_path.leaf_index = pred_index + 1;
+        assert!(!wrong.verify_at_index(&tree.root(), &params, pred_index));
+        assert!(!wrong.verify(&tree.root(), &params));
+        
+        let check = |proof: RangePath, claimed_index: usize| {
+            let cs = ConstraintSystem::<F>::new_ref();
+            let proof_var = RangePathVar::new_witness(cs.clone(), || Ok(proof)).unwrap();
+            let root_var = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+            let index_var = FpVar::new_witness(cs.clone(), || Ok(F::from(claimed_index as u64))).unwrap();
+            proof_var.enforce_valid_at_index(&root_var, &index_var).unwrap();
+            cs.is_satisfied().unwrap()
+        };
+        
+        // In-circuit: honest proof at the right index passes
+        assert!(check(range_path.clone(), pred_index));
+        
+        // Claiming another predecessor index fails
+        assert!(!check(range_path.clone(), pred_index + 1));
+        
+        // An index aliasing the same position above the tree height fails
+        let mut aliased = range_path.clone();
+        aliased.low_path.leaf_index = pred_index + (1 << 4);
+        assert!(!check(aliased, pred_index + (1 << 4)));
+    }
+    
+    #[test]
+    fn test_membership_approved_key() {
+        let cs = ConstraintSystem::<F>::new_ref();
+        
+        let mut tree = SortedTree::new(4);
+        tree.insert(F::from(100)).unwrap();
+        tree.insert(F::from(200)).unwrap();
+        
+        let proof = tree.prove_key_membership(F::from(200)).unwrap();
+        assert!(proof.verify(&F::from(200), &tree.root(), &TreeParams::new(4)));
+        
+        let proof_var = SortedMembershipVar::new_witness(cs.clone(), || Ok(proof)).unwrap();
+        let key_var = FpVar::new_witness(cs.clone(), || Ok(F::from(200))).unwrap();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+        proof_var.enforce_member(&key_var, &root_var).unwrap();
+        
+        assert!(cs.is_satisfied().unwrap());
+    }
+    
+    #[test]
+    fn test_membership_rejects_unapproved_key() {
+        let cs = ConstraintSystem::<F>::new_ref();
+        
+        let mut tree = SortedTree::new(4);
+        tree.insert(F::from(100)).unwrap();
+        tree.insert(F::from(200)).unwrap();
+        
+        // 150 is not in the tree, so the best a prover can do is reuse a proof for another key
+        assert!(tree.prove_key_membership(F::from(150)).is_none());
+        let proof = tree.prove_key_membership(F::from(100)).unwrap();
+        
+        let proof_var = SortedMembershipVar::new_witness(cs.clone(), || Ok(proof)).unwrap();
+        let key_var = FpVar::new_witness(cs.clone(), || Ok(F::from(150))).unwrap();
+        let root_var = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+        proof_var.enforce_member(&key_var, &root_var).unwrap();
+        
+        assert!(!cs.is_satisfied().unwrap());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,200 @@
+use ark_bls12_381::{Fq, Fr as F, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    boolean::Boolean,
+    fields::{fp::FpVar, nonnative::NonNativeFieldVar, FieldVar},
+    prelude::*,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::SynthesisError;
+use fluxe_core::crypto::pedersen::PedersenParams;
+
+use crate::gadgets::comparison::ComparisonGadget;
+
+/// G1 base field element, emulated in the circuit's field
+type G1FqVar = NonNativeFieldVar<Fq, F>;
+
+/// Bits of each `to_field_repr` coordinate (31 bytes, see `utils::bytes_to_field`)
+const REPR_BITS: usize = 31 * 8;
+
+/// Affine G1 point with emulated coordinates
+#[derive(Clone)]
+struct G1PointVar {
+    x: G1FqVar,
+    y: G1FqVar,
+}
+
+impl G1PointVar {
+    fn constant(point: G1Affine) -> Self {
+        Self {
+            x: G1FqVar::constant(point.x),
+            y: G1FqVar::constant(point.y),
+        }
+    }
+
+    /// Incomplete affine addition; the inverse enforces distinct x, so a satisfied
+    /// sum is never a doubling or the identity
+    fn add_distinct(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let lambda = (&other.y - &self.y) * (&other.x - &self.x).inverse()?;
+        let x = lambda.square()? - &self.x - &other.x;
+        let y = lambda * (&self.x - &x) - &self.y;
+        Ok(Self { x, y })
+    }
+
+    /// self + bit * base
+    fn conditional_add(&self, bit: &Boolean<F>, base: &Self) -> Result<Self, SynthesisError> {
+        let sum = self.add_distinct(base)?;
+        Ok(Self {
+            x: G1FqVar::conditionally_select(bit, &sum.x, &self.x)?,
+            y: G1FqVar::conditionally_select(bit, &sum.y, &self.y)?,
+        })
+    }
+
+    /// self + sum of bits[i] * 2^i * base, for a constant base
+    fn add_scalar_mul(&self, bits: &[Boolean<F>], base: G1Affine) -> Result<Self, SynthesisError> {
+        let mut acc = self.clone();
+        let mut power = G1Projective::from(base);
+        for bit in bits {
+            acc = acc.conditional_add(bit, &Self::constant(power.into_affine()))?;
+            power.double_in_place();
+        }
+        Ok(acc)
+    }
+}
+
+/// Opening of a note's value commitment g^v * h^r (`PedersenParams::setup_value_commitment`)
+/// G1's base field is not the circuit's, so the curve arithmetic is emulated
+pub struct ValueCommitmentGadget;
+
+impl ValueCommitmentGadget {
+    /// Starting point of the accumulator, so no honest partial sum is the identity
+    fn offset() -> G1Affine {
+        let scalar = F::from_le_bytes_mod_order(b"FLUXE_VALUE_COMMITMENT_OFFSET");
+        (G1Projective::generator() * scalar).into_affine()
+    }
+
+    /// `to_field_repr` of g^value * h^randomness; value must fit in 64 bits
+    pub fn commit(value: &FpVar<F>, randomness: &FpVar<F>) -> Result<[FpVar<F>; 2], SynthesisError> {
+        let params = PedersenParams::setup_value_commitment();
+
+        let value_bits = value.to_bits_le()?;
+        for bit in &value_bits[64..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        // Canonical bits of r, a G1 scalar
+        let randomness_bits = randomness.to_bits_le()?;
+
+        let offset = Self::offset();
+        let point = G1PointVar::constant(offset)
+            .add_scalar_mul(&value_bits[..64], params.g)?
+            .add_scalar_mul(&randomness_bits, params.h)?
+            .add_distinct(&G1PointVar::constant(-offset))?;
+
+        // Canonical bits, truncated like `to_field_repr`
+        let x_bits = point.x.to_bits_le()?;
+        let y_bits = point.y.to_bits_le()?;
+        Ok([
+            Boolean::le_bits_to_fp_var(&x_bits[..REPR_BITS])?,
+            Boolean::le_bits_to_fp_var(&y_bits[..REPR_BITS])?,
+        ])
+    }
+
+    /// Enforce, when `condition` holds, that `v_comm` (a note's `to_field_repr`) opens to
+    /// `value` under `randomness`
+    pub fn conditional_enforce_opening(
+        v_comm: &[FpVar<F>; 2],
+        value: &FpVar<F>,
+        randomness: &FpVar<F>,
+        condition: &Boolean<F>,
+    ) -> Result<(), SynthesisError> {
+        let expected = Self::commit(value, randomness)?;
+        for (coord, expected) in v_comm.iter().zip(expected.iter()) {
+            coord.conditional_enforce_equal(expected, condition)?;
+        }
+        Ok(())
+    }
+
+    /// Enforce, when `condition` holds, that `v_comm` opens to `value` under `randomness`
+    /// and that value is at least `min_denom` (both 64-bit)
+    pub fn conditional_enforce_min_denomination(
+        v_comm: &[FpVar<F>; 2],
+        value: &FpVar<F>,
+        randomness: &FpVar<F>,
+        min_denom: &FpVar<F>,
+        condition: &Boolean<F>,
+    ) -> Result<(), SynthesisError> {
+        Self::conditional_enforce_opening(v_comm, value, randomness, condition)?;
+        let at_least_min = ComparisonGadget::is_le(value.cs(), min_denom, value, 64)?;
+        at_least_min.conditional_enforce_equal(&Boolean::TRUE, condition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::crypto::pedersen::{PedersenCommitment, PedersenRandomness};
+
+    fn opens(committed: u64, claimed: u64, r: F, claimed_r: F) -> bool {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, committed, &PedersenRandomness { r }).to_field_repr();
+
+        let v_comm_var = [
+            FpVar::new_witness(cs.clone(), || Ok(v_comm[0])).unwrap(),
+            FpVar::new_witness(cs.clone(), || Ok(v_comm[1])).unwrap(),
+        ];
+        let value_var = FpVar::new_witness(cs.clone(), || Ok(F::from(claimed))).unwrap();
+        let r_var = FpVar::new_witness(cs.clone(), || Ok(claimed_r)).unwrap();
+        ValueCommitmentGadget::conditional_enforce_opening(&v_comm_var, &value_var, &r_var, &Boolean::TRUE)
+            .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    fn meets_min_denomination(committed: u64, claimed: u64, min_denom: u64) -> bool {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let params = PedersenParams::setup_value_commitment();
+        let r = F::from(987654321u64);
+        let v_comm = PedersenCommitment::commit(&params, committed, &PedersenRandomness { r }).to_field_repr();
+
+        let v_comm_var = [
+            FpVar::new_witness(cs.clone(), || Ok(v_comm[0])).unwrap(),
+            FpVar::new_witness(cs.clone(), || Ok(v_comm[1])).unwrap(),
+        ];
+        let value_var = FpVar::new_witness(cs.clone(), || Ok(F::from(claimed))).unwrap();
+        let r_var = FpVar::new_witness(cs.clone(), || Ok(r)).unwrap();
+        let min_var = FpVar::new_input(cs.clone(), || Ok(F::from(min_denom))).unwrap();
+        ValueCommitmentGadget::conditional_enforce_min_denomination(
+            &v_comm_var,
+            &value_var,
+            &r_var,
+            &min_var,
+            &Boolean::TRUE,
+        )
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_value_commitment_min_denomination() {
+        // Below the minimum fails, at and above pass
+        assert!(!meets_min_denomination(99, 99, 100));
+        assert!(meets_min_denomination(100, 100, 100));
+        assert!(meets_min_denomination(5000, 5000, 100));
+
+        // Claiming a larger value than the commitment opens to fails
+        assert!(!meets_min_denomination(50, 150, 100));
+    }
+
+    #[test]
+    fn test_value_commitment_opening() {
+        let r = F::from(123456789u64);
+        assert!(opens(1000, 1000, r, r));
+        assert!(opens(0, 0, r, r));
+
+        // Wrong value or randomness
+        assert!(!opens(1000, 1001, r, r));
+        assert!(!opens(1000, 1000, r, r + F::from(1u64)));
+    }
+}
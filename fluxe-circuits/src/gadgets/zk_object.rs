@@ -14,6 +14,7 @@ pub struct ZkObjectVar {
     pub state_hash: FpVar<F>,
     pub serial: FpVar<F>,
     pub cb_head_hash: FpVar<F>,
+    pub owner_addr: FpVar<F>,
 }
 
 impl ZkObjectVar {
@@ -25,7 +26,8 @@ impl ZkObjectVar {
         Ok(Self {
             state_hash: FpVar::new_witness(cs.clone(), || Ok(obj.state_hash))?,
             serial: FpVar::new_witness(cs.clone(), || Ok(F::from(obj.serial)))?,
-            cb_head_hash: FpVar::new_witness(cs, || Ok(obj.cb_head_hash))?,
+            cb_head_hash: FpVar::new_witness(cs.clone(), || Ok(obj.cb_head_hash))?,
+            owner_addr: FpVar::new_witness(cs, || Ok(obj.owner_addr))?,
         })
     }
     
@@ -35,6 +37,7 @@ impl ZkObjectVar {
             self.state_hash.clone(),
             self.serial.clone(),
             self.cb_head_hash.clone(),
+            self.owner_addr.clone(),
         ])
     }
 }
@@ -47,6 +50,7 @@ pub struct ComplianceStateVar {
     pub frozen: Boolean<F>,
     pub last_review_time: FpVar<F>,
     pub jurisdiction_bits: FpVar<F>,
+    pub permitted_pools: FpVar<F>,
     pub daily_limit: FpVar<F>,
     pub monthly_limit: FpVar<F>,
     pub yearly_limit: FpVar<F>,
@@ -68,6 +72,7 @@ impl ComplianceStateVar {
                 // Match the native bytes_to_field implementation
                 Ok(fluxe_core::utils::bytes_to_field(&state.jurisdiction_bits))
             })?,
+            permitted_pools: FpVar::new_witness(cs.clone(), || Ok(F::from(state.permitted_pools)))?,
             daily_limit: FpVar::new_witness(cs.clone(), || Ok(state.daily_limit.to_field()))?,
             monthly_limit: FpVar::new_witness(cs.clone(), || Ok(state.monthly_limit.to_field()))?,
             yearly_limit: FpVar::new_witness(cs.clone(), || Ok(state.yearly_limit.to_field()))?,
@@ -90,10 +95,72 @@ impl ComplianceStateVar {
             frozen_field,
             self.last_review_time.clone(),
             self.jurisdiction_bits.clone(),
+            self.permitted_pools.clone(),
             self.daily_limit.clone(),
             self.monthly_limit.clone(),
             self.yearly_limit.clone(),
             self.rep_hash.clone(),
         ])
     }
+    
+    /// Enforce that this state hashes to `state_hash` (as ComplianceState::hash does natively)
+    pub fn enforce_hash(&self, state_hash: &FpVar<F>) -> Result<(), SynthesisError> {
+        self.hash()?.enforce_equal(state_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use fluxe_core::types::Amount;
+    use rand::{thread_rng, Rng};
+
+    fn random_state<R: Rng>(rng: &mut R) -> ComplianceState {
+        ComplianceState {
+            level: rng.gen_range(0..4),
+            risk_score: rng.gen(),
+            frozen: rng.gen(),
+            last_review_time: rng.gen(),
+            jurisdiction_bits: rng.gen(),
+            permitted_pools: rng.gen(),
+            daily_limit: Amount::from(rng.gen::<u64>()),
+            monthly_limit: Amount::from(rng.gen::<u128>()),
+            yearly_limit: Amount::from(rng.gen::<u128>()),
+            rep_hash: F::rand(rng),
+        }
+    }
+
+    #[test]
+    fn test_compliance_state_hash_matches_native() {
+        let mut rng = thread_rng();
+        let cs = ConstraintSystem::<F>::new_ref();
+
+        for _ in 0..8 {
+            let state = random_state(&mut rng);
+            let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state.clone())).unwrap();
+            assert_eq!(state_var.hash().unwrap().value().unwrap(), state.hash());
+
+            let native_var = FpVar::new_witness(cs.clone(), || Ok(state.hash())).unwrap();
+            state_var.enforce_hash(&native_var).unwrap();
+        }
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_compliance_state_hash_rejects_other_state() {
+        let mut rng = thread_rng();
+        let cs = ConstraintSystem::<F>::new_ref();
+
+        let state = random_state(&mut rng);
+        let mut other = state.clone();
+        other.frozen = !other.frozen;
+        let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(other)).unwrap();
+        let native_var = FpVar::new_witness(cs.clone(), || Ok(state.hash())).unwrap();
+        state_var.enforce_hash(&native_var).unwrap();
+
+        assert!(!cs.is_satisfied().unwrap());
+    }
 }
\ No newline at end of file
@@ -4,10 +4,13 @@ pub mod mint;
 pub mod burn;
 pub mod transfer;
 pub mod object_update;
+pub mod disclosure;
 pub mod setup;
+pub mod prover;
 
 pub use circuits::*;
 pub use mint::*;
 pub use burn::*;
 pub use transfer::*;
-pub use object_update::*;
\ No newline at end of file
+pub use object_update::*;
+pub use disclosure::*;
\ No newline at end of file
@@ -4,14 +4,16 @@ use ark_r1cs_std::boolean::Boolean;
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use fluxe_core::{
-    crypto::poseidon_hash,
+    crypto::{compute_ec_public_key, domain_sep_to_field, owner_address_from_pk, poseidon_hash, DOM_OWNER_ADDR},
     data_structures::{IngressReceipt, Note},
-    merkle::{IncrementalTree, AppendWitness},
+    merkle::{IncrementalTree, AppendWitness, SortedMembershipProof, SortedTree, TreeConfig, TreeError},
     types::*,
 };
 
-use crate::circuits::FluxeCircuit;
+use crate::circuits::{ConstraintSections, FluxeCircuit};
 use crate::gadgets::*;
+use crate::gadgets::auth::AuthGadget;
+use crate::gadgets::sorted_tree::SortedMembershipVar;
 
 /// Mint circuit for deposits (boundary-in transactions)
 #[derive(Clone)]
@@ -35,6 +37,12 @@ pub struct MintCircuit {
     /// Append witness for ingress receipt (pre-insertion siblings)
     pub ingress_append_witness: AppendWitness,
     
+    /// Minter secret key, authenticated against minter_pk
+    pub minter_sk: F,
+    
+    /// Membership of the minter's owner address in the authorized minters S-IMT
+    pub minter_proof: Option<SortedMembershipProof>,
+    
     // Public inputs
     /// Old commitment tree root
     pub cmt_root_old: MerkleRoot,
@@ -56,6 +64,16 @@ pub struct MintCircuit {
     
     /// Commitment to output notes list
     pub cm_out_list_commit: F,
+    
+    /// Minter public key (sk·G on Jubjub)
+    pub minter_pk: (F, F),
+    
+    /// Root of the S-IMT of owner addresses allowed to mint
+    pub authorized_minters_root: MerkleRoot,
+    
+    /// Configured tree heights the append witnesses must match (None skips the check);
+    /// `new` takes them from the trees it witnesses against
+    pub tree_config: Option<TreeConfig>,
 }
 
 impl MintCircuit {
@@ -89,32 +107,17 @@ impl MintCircuit {
         
         // For each note, get the append witness before appending
         for cm in commitments.iter() {
-            let leaf_index = cmt_tree.num_leaves();
-            let pre_siblings = cmt_tree.get_siblings_for_index(leaf_index);
-            cmt_append_witnesses.push(AppendWitness::new(
-                *cm,
-                leaf_index,
-                pre_siblings,
-                cmt_tree.height(),
-            ));
+            cmt_append_witnesses.push(cmt_tree.generate_append_witness(*cm));
             cmt_tree.append(*cm);
         }
         
-        // Get ingress append witness
+        // Get ingress append witness, including the path to the current last leaf
         let ingress_hash = ingress_receipt.hash();
-        let ingress_leaf_index = ingress_tree.num_leaves();
-        let ingress_pre_siblings = ingress_tree.get_siblings_for_index(ingress_leaf_index);
-        let ingress_append_witness = AppendWitness::new(
-            ingress_hash,
-            ingress_leaf_index,
-            ingress_pre_siblings,
-            ingress_tree.height(),
-        );
-        ingress_tree.append(ingress_hash);
+        let ingress_append_witness = ingress_tree.generate_append_witness(ingress_hash);
+        let ingress_root_new = ingress_tree.append_returning_root(ingress_hash);
         
-        // Get new roots after appending
+        // Get new CMT root after appending
         let cmt_root_new = cmt_tree.root();
-        let ingress_root_new = ingress_tree.root();
         
         Self {
             notes_out,
@@ -123,6 +126,8 @@ impl MintCircuit {
             ingress_receipt,
             cmt_append_witnesses,
             ingress_append_witness,
+            minter_sk: F::from(0u64),
+            minter_proof: None,
             cmt_root_old,
             cmt_root_new,
             ingress_root_old,
@@ -130,12 +135,44 @@ impl MintCircuit {
             asset_type,
             amount,
             cm_out_list_commit: cm_list,
+            minter_pk: (F::from(0u64), F::from(0u64)),
+            authorized_minters_root: F::from(0u64),
+            tree_config: Some(TreeConfig {
+                cmt: cmt_tree.height(),
+                ingress: ingress_tree.height(),
+                ..TreeConfig::default()
+            }),
         }
     }
+    
+    /// Mint with `minter_sk`, whose owner address must be a key in `authorized_minters`
+    pub fn with_minter(mut self, minter_sk: F, authorized_minters: &SortedTree) -> Result<Self, FluxeError> {
+        let minter_pk = compute_ec_public_key(minter_sk);
+        let minter_addr = owner_address_from_pk(minter_pk.0, minter_pk.1);
+        let proof = authorized_minters
+            .prove_key_membership(minter_addr)
+            .ok_or_else(|| FluxeError::Other("Minter not authorized".to_string()))?;
+        self.minter_sk = minter_sk;
+        self.minter_proof = Some(proof);
+        self.minter_pk = minter_pk;
+        self.authorized_minters_root = authorized_minters.root();
+        Ok(self)
+    }
+    
+    /// Require the witnesses to be built for the trees in `config`
+    pub fn with_tree_config(mut self, config: TreeConfig) -> Self {
+        self.tree_config = Some(config);
+        self
+    }
 }
 
 impl ConstraintSynthesizer<F> for MintCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Shape check: witnesses built for another tree height cannot be hashed to the roots
+        if self.check_tree_heights().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         // Witness private inputs
         let notes_out_vars = self.notes_out
             .iter()
@@ -165,10 +202,17 @@ impl ConstraintSynthesizer<F> for MintCircuit {
         let asset_type_var = FpVar::new_input(cs.clone(), || Ok(F::from(self.asset_type as u64)))?;
         let amount_var = FpVar::new_input(cs.clone(), || Ok(self.amount.to_field()))?;
         let cm_out_list_var = FpVar::new_input(cs.clone(), || Ok(self.cm_out_list_commit))?;
+        let minter_pk_x_var = FpVar::new_input(cs.clone(), || Ok(self.minter_pk.0))?;
+        let minter_pk_y_var = FpVar::new_input(cs.clone(), || Ok(self.minter_pk.1))?;
+        let authorized_minters_root_var = FpVar::new_input(cs.clone(), || Ok(self.authorized_minters_root))?;
         
         // Constraint 1: Verify ingress receipt is valid
+        ConstraintSections::begin(&cs, "ingress receipt");
         ingress_var.asset_type.enforce_equal(&asset_type_var)?;
         ingress_var.amount.enforce_equal(&amount_var)?;
+        ConstraintSections::begin(&cs, "receipt range");
+        RangeProofGadget::prove_range_bits(cs.clone(), &ingress_var.amount, RECEIPT_AMOUNT_BITS)?;
+        ConstraintSections::begin(&cs, "outputs");
         
         // Constraint 1b: Verify beneficiary_cm matches output commitments
         // The ingress receipt should specify which notes are being minted
@@ -216,26 +260,15 @@ impl ConstraintSynthesizer<F> for MintCircuit {
         for (i, note_var) in notes_out_vars.iter().enumerate() {
             let witness = &self.cmt_append_witnesses[i];
             
-            // Create append proof for this note
-            let append_proof = ImtAppendProofVar {
-                old_root: current_cmt_root.clone(),
-                new_root: FpVar::new_witness(cs.clone(), || Ok(F::from(0)))?, // Will be computed
-                leaf_index: FpVar::new_witness(cs.clone(), || Ok(F::from(witness.leaf_index as u64)))?,
-                appended_leaf: note_var.commitment()?,
-                pre_siblings: witness.pre_siblings
-                    .iter()
-                    .map(|s| FpVar::new_witness(cs.clone(), || Ok(*s)))
-                    .collect::<Result<Vec<_>, _>>()?,
-                height: witness.height,
-            };
-            
-            // Verify the old root matches
-            let computed_old = append_proof.compute_root_with_empty()?;
-            computed_old.enforce_equal(&current_cmt_root)?;
-            
-            // Compute and set new root
-            let computed_new_root = append_proof.compute_root_with_leaf()?;
-            current_cmt_root = computed_new_root;
+            // Append this note at the tree's next free slot
+            let append_proof = ImtAppendProofVar::chained(
+                cs.clone(),
+                witness,
+                &current_cmt_root,
+                &note_var.commitment()?,
+            )?;
+            append_proof.enforce()?;
+            current_cmt_root = append_proof.new_root;
         }
         
         // Verify final CMT root matches
@@ -243,20 +276,16 @@ impl ConstraintSynthesizer<F> for MintCircuit {
         
         // Constraint 7: Verify INGRESS_ROOT update using proper append proof
         let ingress_hash = ingress_var.hash()?;
-        let ingress_append_proof = ImtAppendProofVar {
-            old_root: ingress_root_old_var.clone(),
-            new_root: ingress_root_new_var.clone(),
-            leaf_index: FpVar::new_witness(cs.clone(), || Ok(F::from(self.ingress_append_witness.leaf_index as u64)))?,
-            appended_leaf: ingress_hash,
-            pre_siblings: self.ingress_append_witness.pre_siblings
-                .iter()
-                .map(|s| FpVar::new_witness(cs.clone(), || Ok(*s)))
-                .collect::<Result<Vec<_>, _>>()?,
-            height: self.ingress_append_witness.height,
-        };
+        let mut ingress_append_proof = ImtAppendProofVar::chained(
+            cs.clone(),
+            &self.ingress_append_witness,
+            &ingress_root_old_var,
+            &ingress_hash,
+        )?;
+        ingress_append_proof.new_root = ingress_root_new_var.clone();
         
-        // Verify the append is valid
-        ingress_append_proof.enforce()?;
+        // Verify the append is valid and INGRESS_ROOT only grows
+        ingress_append_proof.enforce_grows()?;
         
         // Constraint 8: Verify all notes have valid compliance fields
         for note_var in &notes_out_vars {
@@ -265,6 +294,22 @@ impl ConstraintSynthesizer<F> for MintCircuit {
             pool_id_nonzero.enforce_equal(&Boolean::TRUE)?;
         }
         
+        // Constraint 9: Minter holds the key for minter_pk, and its address is authorized
+        if let Some(ref minter_proof) = self.minter_proof {
+            let minter_sk_var = FpVar::new_witness(cs.clone(), || Ok(self.minter_sk))?;
+            let (pk_x, pk_y) = AuthGadget::scalar_mult_generator_fr(cs.clone(), &minter_sk_var)?;
+            pk_x.enforce_equal(&minter_pk_x_var)?;
+            pk_y.enforce_equal(&minter_pk_y_var)?;
+            
+            let dom_owner = FpVar::constant(domain_sep_to_field(DOM_OWNER_ADDR));
+            let minter_addr = poseidon_hash_zk(&[dom_owner, pk_x, pk_y])?;
+            let minter_proof_var = SortedMembershipVar::new_witness(cs.clone(), || Ok(minter_proof.clone()))?;
+            minter_proof_var.enforce_member(&minter_addr, &authorized_minters_root_var)?;
+        } else {
+            // SECURITY: Without it, anyone could mint
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         Ok(())
     }
 }
@@ -279,6 +324,9 @@ impl FluxeCircuit for MintCircuit {
             F::from(self.asset_type as u64),
             self.amount.to_field(),
             self.cm_out_list_commit,
+            self.minter_pk.0,
+            self.minter_pk.1,
+            self.authorized_minters_root,
         ]
     }
     
@@ -296,6 +344,26 @@ impl FluxeCircuit for MintCircuit {
             }
         }
         
+        // Verify the authorization proof is for the minter's address
+        let minter_addr = owner_address_from_pk(self.minter_pk.0, self.minter_pk.1);
+        match &self.minter_proof {
+            Some(proof) if proof.leaf.key == minter_addr => {}
+            _ => return Err(FluxeError::Other("Minter not authorized".to_string())),
+        }
+        
+        Ok(())
+    }
+    
+    /// Check every append witness has one sibling per level of its configured tree
+    fn check_tree_heights(&self) -> Result<(), TreeError> {
+        let config = match &self.tree_config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        for witness in &self.cmt_append_witnesses {
+            witness.check_height("CMT", config.cmt)?;
+        }
+        self.ingress_append_witness.check_height("ingress", config.ingress)?;
         Ok(())
     }
 }
@@ -308,46 +376,53 @@ mod tests {
     use fluxe_core::crypto::pedersen::{PedersenParams, PedersenCommitment, PedersenRandomness};
     use rand::thread_rng;
 
-    #[test]
-    fn test_mint_circuit_with_merkle_trees() {
-        use fluxe_core::merkle::IncrementalTree;
-        use fluxe_core::crypto::poseidon_hash;
-        
-        // Test with a single output note
+    const TEST_MINTER_SK: u64 = 7;
+    const TEST_VALUE: u64 = 1000;
+
+    /// Tree of authorized minter addresses holding the address of `minter_sk`
+    fn minters_with(minter_sk: F) -> SortedTree {
+        let (pk_x, pk_y) = compute_ec_public_key(minter_sk);
+        let mut minters = SortedTree::new(16);
+        minters.insert(owner_address_from_pk(pk_x, pk_y)).unwrap();
+        minters
+    }
+
+    /// Authorize `circuit` with the test minter key
+    fn with_test_minter(circuit: MintCircuit) -> MintCircuit {
+        let minter_sk = F::from(TEST_MINTER_SK);
+        circuit.with_minter(minter_sk, &minters_with(minter_sk)).unwrap()
+    }
+    
+    /// Mint of a single TEST_VALUE note into a fresh CMT, appending its receipt to
+    /// `ingress_tree`; no minter is set yet
+    fn single_note_mint(ingress_tree: &mut IncrementalTree) -> MintCircuit {
         let mut rng = thread_rng();
         let params = PedersenParams::setup_value_commitment();
         
-        let value = 1000u64;
         let randomness = F::rand(&mut rng);
-        
         let v_comm = PedersenCommitment::commit(
             &params,
-            value,
+            TEST_VALUE,
             &PedersenRandomness { r: randomness },
         );
+        let note = Note::new(1, v_comm, F::rand(&mut rng), [1u8; 32], 1);
+        let beneficiary_cm = IngressReceipt::beneficiary_commitment(std::slice::from_ref(&note));
+        let ingress = IngressReceipt::new(1, Amount::from(TEST_VALUE as u128), beneficiary_cm, 1);
         
-        let owner = F::rand(&mut rng);
-        let note = Note::new(1, v_comm, owner, [1u8; 32], 1);
-        
-        // Create ingress receipt
-        // The beneficiary_cm should be a hash chain of all output commitments
-        let cm = note.commitment();
-        let beneficiary_cm = poseidon_hash(&[F::from(0u64), cm]);  // Hash chain starting from 0
-        let ingress = IngressReceipt::new(1, Amount::from(value as u128), beneficiary_cm, 1);
-        
-        // Create proper Merkle trees
-        let mut cmt_tree = IncrementalTree::new(16); // 16 levels
-        let mut ingress_tree = IncrementalTree::new(16);
-        
-        // Use the constructor which properly handles witness generation and tree updates
-        let circuit = MintCircuit::new(
+        MintCircuit::new(
             vec![note],
-            vec![value],
+            vec![TEST_VALUE],
             vec![randomness],
             ingress,
-            &mut cmt_tree,
-            &mut ingress_tree,
-        );
+            &mut IncrementalTree::new(16),
+            ingress_tree,
+        )
+    }
+
+    #[test]
+    fn test_mint_circuit_with_merkle_trees() {
+        // Test with a single output note
+        let circuit = with_test_minter(single_note_mint(&mut IncrementalTree::new(16)));
         
         // Test constraint generation
         let cs = ConstraintSystem::<F>::new_ref();
@@ -366,4 +441,138 @@ mod tests {
         }
         assert!(cs.is_satisfied().unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mint_rejects_out_of_range_ingress_amount() {
+        let mut circuit = with_test_minter(single_note_mint(&mut IncrementalTree::new(16)));
+        
+        // Receipt and public amount wider than 64 bits, otherwise consistent
+        let wide = Amount::new((1u128 << 64) + TEST_VALUE as u128);
+        circuit.ingress_receipt.amount = wide;
+        circuit.amount = wide;
+        
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        let diagnosis = MintCircuit::diagnose(&cs).expect("Should diagnose the failure");
+        assert!(diagnosis.contains("'receipt range'"), "Unexpected failure: {}", diagnosis);
+    }
+
+    #[test]
+    fn test_beneficiary_commitment_matches_circuit() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        
+        let values = vec![600u64, 400u64];
+        let mut notes = Vec::new();
+        let mut randomness = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let r = F::rand(&mut rng);
+            let v_comm = PedersenCommitment::commit(&params, *value, &PedersenRandomness { r });
+            notes.push(Note::new(1, v_comm, F::rand(&mut rng), [i as u8; 32], 1));
+            randomness.push(r);
+        }
+        
+        // Same value the API puts in the ingress receipt
+        let beneficiary_cm = IngressReceipt::beneficiary_commitment(&notes);
+        let chained = poseidon_hash(&[
+            poseidon_hash(&[F::from(0u64), notes[0].commitment()]),
+            notes[1].commitment(),
+        ]);
+        assert_eq!(beneficiary_cm, chained);
+        
+        let ingress = IngressReceipt::new(1, Amount::from(1000u64), beneficiary_cm, 1);
+        let mut cmt_tree = IncrementalTree::new(16);
+        let mut ingress_tree = IncrementalTree::new(16);
+        let circuit = with_test_minter(MintCircuit::new(
+            notes.clone(),
+            values.clone(),
+            randomness.clone(),
+            ingress,
+            &mut cmt_tree,
+            &mut ingress_tree,
+        ));
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        
+        // A receipt committing to the notes in another order is rejected
+        notes.swap(0, 1);
+        let ingress = IngressReceipt::new(1, Amount::from(1000u64), IngressReceipt::beneficiary_commitment(&notes), 1);
+        notes.swap(0, 1);
+        let mut cmt_tree = IncrementalTree::new(16);
+        let mut ingress_tree = IncrementalTree::new(16);
+        let circuit = with_test_minter(MintCircuit::new(notes, values, randomness, ingress, &mut cmt_tree, &mut ingress_tree));
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_ingress_root_only_grows() {
+        // Ingress tree already holding two earlier receipts
+        let mut ingress_tree = IncrementalTree::new(16);
+        let one_leaf_root = ingress_tree.append_returning_root(F::from(11u64));
+        ingress_tree.append(F::from(12u64));
+        
+        let circuit = with_test_minter(single_note_mint(&mut ingress_tree));
+        let cs = ConstraintSystem::<F>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        
+        // Claiming the same number of leaves is rejected
+        let mut same = circuit.clone();
+        same.ingress_root_new = same.ingress_root_old;
+        let cs = ConstraintSystem::<F>::new_ref();
+        same.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        
+        // Claiming fewer leaves is rejected
+        let mut fewer = circuit;
+        fewer.ingress_root_new = one_leaf_root;
+        let cs = ConstraintSystem::<F>::new_ref();
+        fewer.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        
+        println!("✓ Ingress root growth test passed");
+    }
+
+    #[test]
+    fn test_unauthorized_minter_rejected() {
+        let circuit = single_note_mint(&mut IncrementalTree::new(16));
+        
+        // An authorized minter is satisfied
+        let authorized = with_test_minter(circuit.clone());
+        assert!(authorized.verify_public_inputs().is_ok());
+        let cs = ConstraintSystem::<F>::new_ref();
+        authorized.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        
+        // Without a minter there is nothing to prove
+        assert!(circuit.verify_public_inputs().is_err());
+        assert!(circuit.clone().generate_constraints(ConstraintSystem::<F>::new_ref()).is_err());
+        
+        // A key outside the authorized set gets no membership proof
+        let outsider_sk = F::from(8u64);
+        let minters = minters_with(F::from(TEST_MINTER_SK));
+        assert!(circuit.clone().with_minter(outsider_sk, &minters).is_err());
+        
+        // Nor can it borrow the authorized minter's proof and public key
+        let mut impostor = authorized.clone();
+        impostor.minter_sk = outsider_sk;
+        let cs = ConstraintSystem::<F>::new_ref();
+        impostor.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        
+        // Or pair its own key with the authorized minter's proof
+        let mut own_key = authorized;
+        own_key.minter_sk = outsider_sk;
+        own_key.minter_pk = compute_ec_public_key(outsider_sk);
+        assert!(own_key.verify_public_inputs().is_err());
+        let cs = ConstraintSystem::<F>::new_ref();
+        own_key.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+        
+        println!("✓ Unauthorized minter rejected");
+    }
+}
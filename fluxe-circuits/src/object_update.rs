@@ -5,13 +5,46 @@ use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use fluxe_core::{
     data_structures::{CallbackEntry, CallbackInvocation, ComplianceState, ZkObject},
-    merkle::{MerklePath, RangePath},
+    merkle::{MerklePath, RangePath, TreeConfig, TreeError},
     types::*,
 };
 
 use crate::circuits::FluxeCircuit;
 use crate::gadgets::*;
 
+/// One slot of the object-update public-input vector
+/// `ObjectUpdateCircuit::public_input_layout` lists the slots in order; clients and
+/// verifiers should index public inputs through it rather than by position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectUpdateInput {
+    /// OBJ_ROOT before the update
+    ObjRootOld,
+    /// OBJ_ROOT after appending the new object
+    ObjRootNew,
+    /// CB_ROOT the callback membership/non-membership is checked against
+    CbRoot,
+    /// Time the update is evaluated at
+    CurrentTime,
+    /// Owner committed in the object (only with `with_owner` or `with_freeze`)
+    Owner,
+    /// i-th padded callback operation hash (only with `with_callback_ops`)
+    CallbackOpHash(usize),
+}
+
+impl ObjectUpdateInput {
+    /// Stable name of the slot
+    pub fn name(&self) -> String {
+        match self {
+            ObjectUpdateInput::ObjRootOld => "obj_root_old".to_string(),
+            ObjectUpdateInput::ObjRootNew => "obj_root_new".to_string(),
+            ObjectUpdateInput::CbRoot => "cb_root".to_string(),
+            ObjectUpdateInput::CurrentTime => "current_time".to_string(),
+            ObjectUpdateInput::Owner => "owner".to_string(),
+            ObjectUpdateInput::CallbackOpHash(i) => format!("callback_op_hash[{}]", i),
+        }
+    }
+}
+
 /// Object update circuit for compliance state transitions
 #[derive(Clone)]
 pub struct ObjectUpdateCircuit {
@@ -62,6 +95,19 @@ pub struct ObjectUpdateCircuit {
     
     /// Current time
     pub current_time: Time,
+    
+    /// Owner of the object, constrained to the one it commits to; public input when set
+    pub owner: Option<AuthAddr>,
+    
+    /// Apply a Freeze callback escalation to the owner's object
+    pub freeze: bool,
+    
+    /// Hashes of the update's callback operations; when set they are public inputs
+    /// padded with no-ops to MAX_CALLBACK_OPS slots (fixed circuit shape)
+    pub callback_op_hashes: Option<Vec<F>>,
+    
+    /// Configured tree heights the witness paths must match (None skips the check)
+    pub tree_config: Option<TreeConfig>,
 }
 
 impl ObjectUpdateCircuit {
@@ -98,6 +144,10 @@ impl ObjectUpdateCircuit {
             obj_root_new,
             cb_root,
             current_time,
+            owner: None,
+            freeze: false,
+            callback_op_hashes: None,
+            tree_config: None,
         }
     }
     
@@ -135,12 +185,75 @@ impl ObjectUpdateCircuit {
             obj_root_new,
             cb_root,
             current_time,
+            owner: None,
+            freeze: false,
+            callback_op_hashes: None,
+            tree_config: None,
+        }
+    }
+    
+    /// Expose the object's owner so the verifier can index the new object under it;
+    /// `owner_addr` must be the owner both objects commit to
+    pub fn with_owner(mut self, owner_addr: AuthAddr) -> Self {
+        self.owner = Some(owner_addr);
+        self
+    }
+    
+    /// Apply a Freeze escalation against `owner_addr`, who must own the object: state_new
+    /// must be state_old frozen, with nothing else changed
+    pub fn with_freeze(mut self, owner_addr: AuthAddr) -> Self {
+        self.freeze = true;
+        self.with_owner(owner_addr)
+    }
+    
+    /// Bind the update's callback operations as a padded list of MAX_CALLBACK_OPS public inputs
+    pub fn with_callback_ops(mut self, ops: &[CallbackOperation]) -> Self {
+        self.callback_op_hashes = Some(ops.iter().map(CallbackOperation::hash).collect());
+        self
+    }
+    
+    /// Require the witnesses to be built for the trees in `config`
+    pub fn with_tree_config(mut self, config: TreeConfig) -> Self {
+        self.tree_config = Some(config);
+        self
+    }
+    
+    /// Callback op hashes padded with no-ops, or None if the list is over the cap
+    /// Public-input slots in order; the shape depends on `with_owner` and `with_callback_ops`
+    pub fn public_input_layout(&self) -> Vec<ObjectUpdateInput> {
+        let mut layout = vec![
+            ObjectUpdateInput::ObjRootOld,
+            ObjectUpdateInput::ObjRootNew,
+            ObjectUpdateInput::CbRoot,
+            ObjectUpdateInput::CurrentTime,
+        ];
+        if self.owner.is_some() {
+            layout.push(ObjectUpdateInput::Owner);
         }
+        if self.padded_callback_op_hashes().is_some() {
+            layout.extend((0..MAX_CALLBACK_OPS).map(ObjectUpdateInput::CallbackOpHash));
+        }
+        layout
+    }
+    
+    fn padded_callback_op_hashes(&self) -> Option<Vec<F>> {
+        self.callback_op_hashes.as_ref().and_then(|hashes| {
+            (hashes.len() <= MAX_CALLBACK_OPS).then(|| {
+                let mut padded = hashes.clone();
+                padded.resize(MAX_CALLBACK_OPS, F::from(0u64));
+                padded
+            })
+        })
     }
 }
 
 impl ConstraintSynthesizer<F> for ObjectUpdateCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Shape check: witnesses built for another tree height cannot be hashed to the roots
+        if self.check_tree_heights().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         // Witness old object
         let obj_old_var = ZkObjectVar::new_witness(
             cs.clone(),
@@ -170,11 +283,21 @@ impl ConstraintSynthesizer<F> for ObjectUpdateCircuit {
             || Ok(self.obj_path_old.clone()),
         )?;
         
-        // Input public values
+        // Input public values, in `public_input_layout` order
         let obj_root_old_var = FpVar::new_input(cs.clone(), || Ok(self.obj_root_old))?;
         let obj_root_new_var = FpVar::new_input(cs.clone(), || Ok(self.obj_root_new))?;
         let cb_root_var = FpVar::new_input(cs.clone(), || Ok(self.cb_root))?;
         let current_time_var = FpVar::new_input(cs.clone(), || Ok(F::from(self.current_time)))?;
+        // Public so the verifier can match it against the owner it indexes
+        let owner_var = self.owner
+            .map(|owner_addr| FpVar::new_input(cs.clone(), || Ok(owner_addr)))
+            .transpose()?;
+        if self.callback_op_hashes.is_some() {
+            let padded = self.padded_callback_op_hashes().ok_or(SynthesisError::Unsatisfiable)?;
+            for op_hash in padded {
+                FpVar::new_input(cs.clone(), || Ok(op_hash))?;
+            }
+        }
         
         // Constraint 1: Verify old object membership in OBJ_ROOT_old
         let cm_obj_old = obj_old_var.commitment()?;
@@ -182,16 +305,19 @@ impl ConstraintSynthesizer<F> for ObjectUpdateCircuit {
         obj_path_var.enforce_valid(&obj_root_old_var)?;
         
         // Constraint 2: Verify state hashes match
-        let computed_state_hash_old = state_old_var.hash()?;
-        computed_state_hash_old.enforce_equal(&obj_old_var.state_hash)?;
-        
-        let computed_state_hash_new = state_new_var.hash()?;
-        computed_state_hash_new.enforce_equal(&obj_new_var.state_hash)?;
+        state_old_var.enforce_hash(&obj_old_var.state_hash)?;
+        state_new_var.enforce_hash(&obj_new_var.state_hash)?;
         
         // Constraint 3: Verify serial increment
         let serial_old_plus_one = &obj_old_var.serial + &FpVar::one();
         serial_old_plus_one.enforce_equal(&obj_new_var.serial)?;
         
+        // Constraint 3b: The object stays with its owner, the one any exposed owner must be
+        obj_new_var.owner_addr.enforce_equal(&obj_old_var.owner_addr)?;
+        if let Some(ref owner_var) = owner_var {
+            owner_var.enforce_equal(&obj_old_var.owner_addr)?;
+        }
+        
         // Constraint 4: Process callback if present
         if let Some(ref callback_entry) = self.callback_entry {
             let callback_var = CallbackEntryVar::new_witness(
@@ -209,6 +335,9 @@ impl ConstraintSynthesizer<F> for ObjectUpdateCircuit {
                 // Verify callback ticket matches
                 callback_var.ticket.enforce_equal(&invocation_var.ticket)?;
                 
+                // Bind the payload commitment to the full payload so it cannot be truncated
+                invocation_var.enforce_payload_commitment()?;
+                
                 // Verify invocation is in CB_ROOT using S-IMT membership proof
                 if let Some(ref cb_path) = self.cb_path {
                     let cb_path_var = MerklePathVar::new_witness(
@@ -305,6 +434,18 @@ impl ConstraintSynthesizer<F> for ObjectUpdateCircuit {
         // Constraint 5: Verify state transition is valid
         ObjectUpdateCircuit::verify_state_transition_static(&state_old_var, &state_new_var)?;
         
+        // Constraint 5b: A Freeze escalation sets the frozen flag (limits are zeroed by
+        // Constraint 5) and leaves the rest of the state alone
+        if self.freeze {
+            state_new_var.frozen.enforce_equal(&Boolean::TRUE)?;
+            state_new_var.level.enforce_equal(&state_old_var.level)?;
+            state_new_var.risk_score.enforce_equal(&state_old_var.risk_score)?;
+            state_new_var.last_review_time.enforce_equal(&state_old_var.last_review_time)?;
+            state_new_var.jurisdiction_bits.enforce_equal(&state_old_var.jurisdiction_bits)?;
+            state_new_var.permitted_pools.enforce_equal(&state_old_var.permitted_pools)?;
+            state_new_var.rep_hash.enforce_equal(&state_old_var.rep_hash)?;
+        }
+        
         // Constraint 6: Compute new object commitment
         let cm_obj_new = obj_new_var.commitment()?;
         
@@ -373,15 +514,31 @@ impl ObjectUpdateCircuit {
 
 impl FluxeCircuit for ObjectUpdateCircuit {
     fn public_inputs(&self) -> Vec<F> {
-        vec![
-            self.obj_root_old,
-            self.obj_root_new,
-            self.cb_root,
-            F::from(self.current_time),
-        ]
+        let padded = self.padded_callback_op_hashes().unwrap_or_default();
+        self.public_input_layout()
+            .into_iter()
+            .map(|slot| match slot {
+                ObjectUpdateInput::ObjRootOld => self.obj_root_old,
+                ObjectUpdateInput::ObjRootNew => self.obj_root_new,
+                ObjectUpdateInput::CbRoot => self.cb_root,
+                ObjectUpdateInput::CurrentTime => F::from(self.current_time),
+                ObjectUpdateInput::Owner => self.owner.unwrap_or_default(),
+                ObjectUpdateInput::CallbackOpHash(i) => padded[i],
+            })
+            .collect()
     }
     
     fn verify_public_inputs(&self) -> Result<(), FluxeError> {
+        if let Some(hashes) = &self.callback_op_hashes {
+            if hashes.len() > MAX_CALLBACK_OPS {
+                return Err(FluxeError::Other(format!(
+                    "{} callback operations exceed the cap of {}",
+                    hashes.len(),
+                    MAX_CALLBACK_OPS
+                )));
+            }
+        }
+        
         // Verify serial increment
         if self.obj_new.serial != self.obj_old.serial + 1 {
             return Err(FluxeError::Other("Invalid serial update".to_string()));
@@ -396,6 +553,37 @@ impl FluxeCircuit for ObjectUpdateCircuit {
             return Err(FluxeError::Other("New state hash mismatch".to_string()));
         }
         
+        if self.obj_new.owner_addr != self.obj_old.owner_addr {
+            return Err(FluxeError::Other("Object update changes the object's owner".to_string()));
+        }
+        if self.owner.map_or(false, |owner_addr| owner_addr != self.obj_old.owner_addr) {
+            return Err(FluxeError::Other("Exposed owner is not the object's owner".to_string()));
+        }
+        
+        if self.freeze {
+            let mut frozen = self.state_old.clone();
+            frozen.freeze();
+            if self.state_new != frozen {
+                return Err(FluxeError::Other("Freeze escalation must only freeze the state".to_string()));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check every witness path has one sibling per level of its configured tree
+    fn check_tree_heights(&self) -> Result<(), TreeError> {
+        let config = match &self.tree_config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        TreeConfig::check_siblings("object", config.object, self.obj_path_old.siblings.len())?;
+        if let Some(path) = &self.cb_path {
+            TreeConfig::check_siblings("CB", config.cb, path.siblings.len())?;
+        }
+        if let Some(proof) = &self.cb_nonmembership {
+            TreeConfig::check_siblings("CB", config.cb, proof.low_path.siblings.len())?;
+        }
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,171 @@
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_groth16::Groth16;
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use fluxe_core::types::FluxeError;
+
+use crate::{
+    circuits::FluxeCircuit,
+    mint::MintCircuit,
+    burn::BurnCircuit,
+    transfer::TransferCircuit,
+    object_update::ObjectUpdateCircuit,
+    setup::{CircuitType, SetupManager},
+};
+
+/// Builds proofs from populated circuits with the proving keys of a `SetupManager`
+/// Output is in the API's submission format: compressed proof bytes and 0x-prefixed
+/// hex public inputs
+pub struct Prover<'a> {
+    setup: &'a SetupManager,
+}
+
+impl<'a> Prover<'a> {
+    /// Create a prover over the setups already generated or loaded in `setup`
+    pub fn new(setup: &'a SetupManager) -> Self {
+        Self { setup }
+    }
+
+    /// Prove a mint
+    pub fn prove_mint(&self, circuit: MintCircuit) -> Result<(Vec<u8>, Vec<String>), FluxeError> {
+        self.prove(CircuitType::Mint, circuit.with_tree_config(self.setup.tree_config()))
+    }
+
+    /// Prove a burn
+    pub fn prove_burn(&self, circuit: BurnCircuit) -> Result<(Vec<u8>, Vec<String>), FluxeError> {
+        self.prove(CircuitType::Burn, circuit.with_tree_config(self.setup.tree_config()))
+    }
+
+    /// Prove a transfer
+    /// The setup's transfer key publishes the sanctions fallback count, so the circuit does too
+    pub fn prove_transfer(&self, circuit: TransferCircuit) -> Result<(Vec<u8>, Vec<String>), FluxeError> {
+        let circuit = circuit.with_sanctions_fallbacks_exposed().with_tree_config(self.setup.tree_config());
+        self.prove(CircuitType::Transfer, circuit)
+    }
+
+    /// Prove an object update
+    pub fn prove_object_update(&self, circuit: ObjectUpdateCircuit) -> Result<(Vec<u8>, Vec<String>), FluxeError> {
+        self.prove(CircuitType::ObjectUpdate, circuit.with_tree_config(self.setup.tree_config()))
+    }
+
+    /// Check the witness against the tree heights the setup's keys were generated for
+    /// (`FluxeError::Tree` names a mismatched tree) and its public inputs, then prove
+    /// under `circuit_type`'s key
+    fn prove<C: FluxeCircuit>(&self, circuit_type: CircuitType, circuit: C) -> Result<(Vec<u8>, Vec<String>), FluxeError> {
+        let setup = self.setup.get_setup(circuit_type)
+            .ok_or_else(|| FluxeError::Other(format!("No trusted setup for {:?}", circuit_type)))?;
+
+        circuit.check_tree_heights()?;
+        circuit.verify_public_inputs().map_err(|e| {
+            FluxeError::InvalidProof(format!("{:?} witness doesn't match its public inputs: {}", circuit_type, e))
+        })?;
+        let public_inputs = circuit.public_inputs();
+
+        let proof = Groth16::<Bls12_381>::prove(&setup.proving_key, circuit, &mut rand::thread_rng())
+            .map_err(|e| FluxeError::InvalidProof(format!("Proof generation failed: {}", e)))?;
+
+        let mut proof_bytes = Vec::new();
+        proof.serialize_compressed(&mut proof_bytes)
+            .map_err(|e| FluxeError::SerializationError(e.to_string()))?;
+        let public_inputs = public_inputs
+            .iter()
+            .map(field_to_hex)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((proof_bytes, public_inputs))
+    }
+}
+
+/// 0x-prefixed hex of a field element's compressed encoding
+fn field_to_hex(field: &F) -> Result<String, FluxeError> {
+    let mut bytes = Vec::new();
+    field.serialize_compressed(&mut bytes)
+        .map_err(|e| FluxeError::SerializationError(e.to_string()))?;
+    Ok(format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::test_rng;
+    use ark_ff::UniformRand;
+    use ark_groth16::Proof;
+    use ark_serialize::CanonicalDeserialize;
+    use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+    use fluxe_core::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+    use fluxe_core::data_structures::{IngressReceipt, Note};
+    use fluxe_core::merkle::{IncrementalTree, SortedTree, TreeConfig, TreeError};
+    use fluxe_core::types::Amount;
+
+    fn mint_circuit(rng: &mut impl ark_std::rand::RngCore, height: usize) -> MintCircuit {
+        let value = 1000u64;
+        let randomness = F::rand(rng);
+        let v_comm = PedersenCommitment::commit(
+            &PedersenParams::setup_value_commitment(),
+            value,
+            &PedersenRandomness { r: randomness },
+        );
+        let note = Note::new(1, v_comm, F::rand(rng), [1u8; 32], 1);
+        let ingress = IngressReceipt::new(
+            1,
+            Amount::from(value as u128),
+            IngressReceipt::beneficiary_commitment(&[note.clone()]),
+            1,
+        );
+        MintCircuit::new(
+            vec![note],
+            vec![value],
+            vec![randomness],
+            ingress,
+            &mut IncrementalTree::new(height),
+            &mut IncrementalTree::new(height),
+        )
+    }
+
+    #[test]
+    fn test_prover_output_verifies() {
+        let mut rng = test_rng();
+        let mut manager = SetupManager::new().with_tree_config(TreeConfig::uniform(16));
+        manager.generate_setup(CircuitType::Mint, &mut rng).unwrap();
+        let prover = Prover::new(&manager);
+
+        let minter_sk = F::rand(&mut rng);
+        let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+        let mut authorized_minters = SortedTree::new(16);
+        authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+        let circuit = mint_circuit(&mut rng, 16).with_minter(minter_sk, &authorized_minters).unwrap();
+        let expected_inputs = circuit.public_inputs();
+
+        let (proof_bytes, hex_inputs) = prover.prove_mint(circuit).unwrap();
+        assert_eq!(hex_inputs, expected_inputs.iter().map(|f| field_to_hex(f).unwrap()).collect::<Vec<_>>());
+        assert!(hex_inputs.iter().all(|input| input.starts_with("0x")));
+
+        let proof = Proof::<Bls12_381>::deserialize_compressed(&proof_bytes[..]).unwrap();
+        let vk = &manager.get_setup(CircuitType::Mint).unwrap().verifying_key;
+        assert!(Groth16::<Bls12_381>::verify(vk, &expected_inputs, &proof).unwrap());
+
+        // A witness that fails its own public-input checks is rejected before proving
+        match prover.prove_mint(mint_circuit(&mut rng, 16)) {
+            Err(FluxeError::InvalidProof(msg)) => assert!(msg.contains("Minter not authorized"), "{}", msg),
+            other => panic!("expected witness rejection, got {:?}", other.map(|_| ())),
+        }
+
+        // A witness built for other tree heights than the setup's is rejected naming the tree
+        let taller = mint_circuit(&mut rng, 20).with_minter(minter_sk, &authorized_minters).unwrap();
+        match prover.prove_mint(taller) {
+            Err(FluxeError::Tree(TreeError::HeightMismatch { tree, expected, actual })) => {
+                assert_eq!((tree, expected, actual), ("CMT", 16, 20));
+            }
+            other => panic!("expected a height mismatch, got {:?}", other.map(|_| ())),
+        }
+
+        // Circuits without a loaded setup can't be proven
+        let empty = SetupManager::new();
+        assert!(matches!(
+            Prover::new(&empty).prove_mint(mint_circuit(&mut rng, 16)),
+            Err(FluxeError::Other(_))
+        ));
+
+        println!("✓ Prover output test passed");
+    }
+}
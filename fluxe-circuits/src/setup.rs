@@ -1,5 +1,5 @@
 use ark_bls12_381::{Bls12_381, Fr as F};
-use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
 use ark_std::rand::{CryptoRng, RngCore, SeedableRng};
@@ -7,10 +7,14 @@ use rand_chacha::ChaCha20Rng;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
+use fluxe_core::server_verifier::VerifyingKeySet;
+use fluxe_core::merkle::TreeConfig;
+use fluxe_core::types::FluxeError;
 
 use crate::{
+    circuits::FluxeCircuit,
     mint::MintCircuit,
-    burn::BurnCircuit,
+    burn::BurnBuilder,
     transfer::TransferCircuit,
     object_update::ObjectUpdateCircuit,
 };
@@ -78,11 +82,24 @@ impl TrustedSetup {
             verifying_key,
         })
     }
+    
+    /// Prove with a ChaCha20 RNG seeded from `seed`: the same witness and seed always give
+    /// byte-identical proofs. Tests and snapshots only, since the seed reveals the blinding
+    pub fn prove_deterministic<C: FluxeCircuit>(&self, circuit: C, seed: u64) -> Result<Proof<Bls12_381>, FluxeError> {
+        circuit.verify_public_inputs()?;
+        
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        Groth16::<Bls12_381>::prove(&self.proving_key, circuit, &mut rng)
+            .map_err(|e| FluxeError::InvalidProof(format!("Proof generation failed: {}", e)))
+    }
 }
 
 /// Setup manager for all circuits
 pub struct SetupManager {
     setups: std::collections::HashMap<CircuitType, TrustedSetup>,
+    
+    /// Tree heights the circuits are set up for; must match the verified state's
+    tree_config: TreeConfig,
 }
 
 impl Default for SetupManager {
@@ -96,9 +113,21 @@ impl SetupManager {
     pub fn new() -> Self {
         Self {
             setups: std::collections::HashMap::new(),
+            tree_config: TreeConfig::default(),
         }
     }
     
+    /// Set the circuits up for the tree heights in `config`
+    pub fn with_tree_config(mut self, config: TreeConfig) -> Self {
+        self.tree_config = config;
+        self
+    }
+    
+    /// Tree heights the circuits are set up for
+    pub fn tree_config(&self) -> TreeConfig {
+        self.tree_config
+    }
+    
     /// Generate trusted setup for all circuits
     pub fn generate_all_setups<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Result<(), Box<dyn std::error::Error>> {
         // Generate setup for MintCircuit
@@ -149,8 +178,8 @@ impl SetupManager {
         };
         
         use fluxe_core::merkle::IncrementalTree;
-        let mut cmt_tree = IncrementalTree::new(16);
-        let mut ingress_tree = IncrementalTree::new(16);
+        let mut cmt_tree = IncrementalTree::new(self.tree_config.cmt);
+        let mut ingress_tree = IncrementalTree::new(self.tree_config.ingress);
         
         let dummy_circuit = MintCircuit::new(
             vec![dummy_note],
@@ -165,7 +194,16 @@ impl SetupManager {
             },
             &mut cmt_tree,
             &mut ingress_tree,
-        );
+        ).with_tree_config(self.tree_config);
+        
+        // Minter authorization has a fixed shape, so any authorized key will do
+        use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+        use fluxe_core::merkle::SortedTree;
+        let minter_sk = F::rand(rng);
+        let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+        let mut authorized_minters = SortedTree::new(16);
+        authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y))?;
+        let dummy_circuit = dummy_circuit.with_minter(minter_sk, &authorized_minters)?;
         
         let (proving_key, verifying_key) = Groth16::<Bls12_381>::circuit_specific_setup(dummy_circuit, rng)?;
         
@@ -178,113 +216,49 @@ impl SetupManager {
     /// Generate setup for BurnCircuit
     fn generate_burn_setup<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<TrustedSetup, Box<dyn std::error::Error>> {
         use ark_ff::UniformRand;
-        use fluxe_core::data_structures::{Note, ExitReceipt};
-        use fluxe_core::crypto::pedersen::PedersenCommitment;
-        use fluxe_core::merkle::{MerklePath, AppendWitness};
-        use ark_ec::CurveGroup;
-        use ark_bls12_381::G1Projective;
+        use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+        use fluxe_core::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+        use fluxe_core::data_structures::Note;
+        use fluxe_core::merkle::{IncrementalTree, SortedTree};
         
-        // Generate consistent owner key and address
+        // Build the dummy burn the way real burns are built so the shapes match:
+        // a spendable note in an otherwise empty CMT, exiting to an approved destination
         let owner_sk = F::rand(rng);
-        let owner_addr = {
-            use fluxe_core::crypto::poseidon_hash;
-            poseidon_hash(&[owner_sk])
-        };
+        let (owner_pk_x, owner_pk_y) = compute_ec_public_key(owner_sk);
+        let value = 1000u64;
+        let randomness = F::rand(rng);
+        let v_comm = PedersenCommitment::commit(
+            &PedersenParams::setup_value_commitment(),
+            value,
+            &PedersenRandomness { r: randomness },
+        );
+        let mut dummy_note = Note::new(1, v_comm, owner_address_from_pk(owner_pk_x, owner_pk_y), [0u8; 32], 1);
+        dummy_note.compliance_hash = F::from(1u64);
+        dummy_note.callbacks_hash = F::from(1u64);
         
-        let dummy_note = Note {
-            asset_type: 1,
-            v_comm: PedersenCommitment {
-                commitment: G1Projective::rand(rng).into_affine(),
-            },
-            owner_addr,
-            psi: [0u8; 32],
-            chain_hint: 0,
-            compliance_hash: F::rand(rng),
-            lineage_hash: F::rand(rng),
-            pool_id: 1,
-            callbacks_hash: F::rand(rng),
-            memo_hash: F::from(0u64),
-        };
+        let mut cmt_tree = IncrementalTree::new(self.tree_config.cmt);
+        cmt_tree.append(dummy_note.commitment());
+        let mut nft_tree = SortedTree::new(self.tree_config.nft);
+        let mut exit_tree = IncrementalTree::new(self.tree_config.exit);
         
-        // Generate nullifier for consistency
-        let nk = F::rand(rng);
-        let nf_in = dummy_note.nullifier(&nk);
+        // The exit allowlist is outside the tree config; burns prove against a 32-high one
+        let mut exit_allow_tree = SortedTree::new(32);
+        let destination = F::from(1u64);
+        exit_allow_tree.insert(destination)?;
         
-        let dummy_circuit = BurnCircuit {
-            note_in: dummy_note.clone(),
-            value_in: 1000,
-            value_randomness_in: F::rand(rng),
+        let dummy_circuit = BurnBuilder::new(
+            &mut cmt_tree,
+            &mut nft_tree,
+            &mut exit_tree,
+            dummy_note,
+            value,
+            randomness,
             owner_sk,
-            owner_pk_x: F::rand(rng),
-            owner_pk_y: F::rand(rng),
-            nk,
-            cm_path: MerklePath {
-                leaf_index: 0,
-                siblings: vec![F::from(0u64); 32],
-                leaf: F::rand(rng),
-            },
-            nf_nonmembership: Some({
-                use fluxe_core::merkle::{RangePath, SortedLeaf};
-                RangePath {
-                    target: nf_in,
-                    low_leaf: SortedLeaf {
-                        key: F::from(0u64),
-                        next_key: F::from(u64::MAX),
-                        next_index: 1,
-                    },
-                    low_path: MerklePath {
-                        leaf_index: 0,
-                        siblings: vec![F::from(0u64); 32],
-                        leaf: F::from(0u64),
-                    },
-                }
-            }),
-            nf_insert_witness: None, // Dummy circuit doesn't need real witness
-            exit_receipt: ExitReceipt {
-                asset_type: 1,
-                amount: 500u64.into(),
-                burned_nf: nf_in,
-                nonce: 1,
-                aux: F::from(0u64),
-            },
-            exit_append_witness: AppendWitness {
-                leaf_index: 0,
-                leaf: ExitReceipt {
-                    asset_type: 1,
-                    amount: 500u64.into(),
-                    burned_nf: nf_in,
-                    nonce: 1,
-                    aux: F::from(0u64),
-                }.hash(),
-                pre_siblings: vec![F::from(0u64); 32],
-                height: 32,
-            },
-            cmt_root: F::rand(rng),
-            nft_root_old: F::from(0u64),
-            nft_root_new: {
-                // Compute using simplified method
-                use fluxe_core::crypto::poseidon_hash;
-                let binding = poseidon_hash(&[F::from(0u64), nf_in, F::from(9999u64)]);
-                poseidon_hash(&[binding, nf_in])
-            },
-            exit_root_old: F::from(0u64),
-            exit_root_new: {
-                // Compute using simplified method
-                use fluxe_core::crypto::poseidon_hash;
-                let exit_hash = ExitReceipt {
-                    asset_type: 1,
-                    amount: 500u64.into(),
-                    burned_nf: nf_in,
-                    nonce: 1,
-                    aux: F::from(0u64),
-                }.hash();
-                let binding = poseidon_hash(&[F::from(0u64), exit_hash, F::from(0u64)]);
-                poseidon_hash(&[binding, exit_hash])
-            },
-            asset_type: 1,
-            amount: 500u64.into(),
-            nf_in,
-        };
+            F::rand(rng),
+        )
+        .with_exit_destination(&exit_allow_tree, destination)?
+        .build()?
+        .with_tree_config(self.tree_config);
         
         let (proving_key, verifying_key) = Groth16::<Bls12_381>::circuit_specific_setup(dummy_circuit, rng)?;
         
@@ -331,7 +305,7 @@ impl SetupManager {
             nks.push(F::rand(rng));
             cm_paths.push(MerklePath {
                 leaf_index: 0,
-                siblings: vec![F::from(0u64); 32],
+                siblings: vec![F::from(0u64); self.tree_config.cmt],
                 leaf: F::rand(rng),
             });
             nf_list.push(F::rand(rng));
@@ -374,7 +348,7 @@ impl SetupManager {
             };
             let low_path = MerklePath {
                 leaf_index: 0,
-                siblings: vec![F::rand(rng); 16],
+                siblings: vec![F::rand(rng); self.tree_config.nft],
                 leaf: F::rand(rng),
             };
             nm_proofs.push(Some(RangePath {
@@ -412,8 +386,30 @@ impl SetupManager {
             sanctions_root: F::rand(rng),
             pool_rules_root: F::rand(rng),
             nf_list,
+            n_out: cm_list.len() as u64,
             cm_list,
             fee: 10u64.into(),
+            consolidation: false,
+            checked_compliance: false,
+            compliance_states_in: Vec::new(),
+            require_fresh_review: false,
+            current_time: 0,
+            max_review_age: 0,
+            fee_note: None,
+            recent_cmt_roots: Vec::new(),
+            cm_path_roots: Vec::new(),
+            memos_out: Vec::new(),
+            derived_randomness: false,
+            min_denomination: None,
+            asset_fees: Vec::new(),
+            check_permitted_pools: false,
+            migration: None,
+            tree_config: Some(self.tree_config),
+            block_frozen_recipients: false,
+            recipient_compliance: Vec::new(),
+            owner_index_root: F::from(0u64),
+            // Published so the verifier can flag transfers that skipped a sanctions proof
+            expose_sanctions_fallbacks: true,
         };
         
         let (proving_key, verifying_key) = Groth16::<Bls12_381>::circuit_specific_setup(dummy_circuit, rng)?;
@@ -440,12 +436,14 @@ impl SetupManager {
             state_hash: state_old.hash(),
             serial: 100,
             cb_head_hash: F::rand(rng),
+            owner_addr: F::rand(rng),
         };
         
         let obj_new = ZkObject {
             state_hash: state_new.hash(),
             serial: 101,
             cb_head_hash: obj_old.cb_head_hash,
+            owner_addr: obj_old.owner_addr,
         };
         
         let dummy_circuit = ObjectUpdateCircuit {
@@ -460,7 +458,7 @@ impl SetupManager {
             cb_nonmembership: None,
             obj_path_old: MerklePath {
                 leaf_index: 0,
-                siblings: vec![F::from(0u64); 32],
+                siblings: vec![F::from(0u64); self.tree_config.object],
                 leaf: F::rand(rng),
             },
             decrypt_key: None,
@@ -468,6 +466,10 @@ impl SetupManager {
             obj_root_new: F::rand(rng),
             cb_root: F::rand(rng),
             current_time: 2000,
+            owner: None,
+            freeze: false,
+            callback_op_hashes: None,
+            tree_config: Some(self.tree_config),
         };
         
         let (proving_key, verifying_key) = Groth16::<Bls12_381>::circuit_specific_setup(dummy_circuit, rng)?;
@@ -478,11 +480,34 @@ impl SetupManager {
         })
     }
     
+    /// Generate and store the trusted setup for one circuit
+    pub fn generate_setup<R: RngCore + CryptoRng>(&mut self, circuit_type: CircuitType, rng: &mut R) -> Result<(), Box<dyn std::error::Error>> {
+        let setup = match circuit_type {
+            CircuitType::Mint => self.generate_mint_setup(rng)?,
+            CircuitType::Burn => self.generate_burn_setup(rng)?,
+            CircuitType::Transfer => self.generate_transfer_setup(rng)?,
+            CircuitType::ObjectUpdate => self.generate_object_update_setup(rng)?,
+        };
+        self.setups.insert(circuit_type, setup);
+        Ok(())
+    }
+    
     /// Get setup for a specific circuit type
     pub fn get_setup(&self, circuit_type: CircuitType) -> Option<&TrustedSetup> {
         self.setups.get(&circuit_type)
     }
     
+    /// Verifying keys of the generated setups, for a `ServerVerifier`
+    pub fn verifying_keys(&self) -> VerifyingKeySet {
+        let vk = |circuit_type| self.get_setup(circuit_type).map(|setup| setup.verifying_key.clone());
+        VerifyingKeySet {
+            mint: vk(CircuitType::Mint),
+            burn: vk(CircuitType::Burn),
+            transfer: vk(CircuitType::Transfer),
+            object_update: vk(CircuitType::ObjectUpdate),
+        }
+    }
+    
     /// Save all setups to directory
     pub fn save_all(&self, dir: &Path) -> Result<(), std::io::Error> {
         for (circuit_type, setup) in &self.setups {
@@ -561,4 +586,228 @@ mod tests {
         
         println!("✓ Setup serialization test passed");
     }
+    
+    #[test]
+    fn test_prove_deterministic() {
+        use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+        use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+        
+        // x * y = z with z public
+        #[derive(Clone)]
+        struct ProductCircuit {
+            x: F,
+            y: F,
+        }
+        impl ConstraintSynthesizer<F> for ProductCircuit {
+            fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+                let z = FpVar::new_input(cs.clone(), || Ok(self.x * self.y))?;
+                let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+                let y = FpVar::new_witness(cs, || Ok(self.y))?;
+                (x * y).enforce_equal(&z)
+            }
+        }
+        impl FluxeCircuit for ProductCircuit {
+            fn public_inputs(&self) -> Vec<F> {
+                vec![self.x * self.y]
+            }
+            fn verify_public_inputs(&self) -> Result<(), FluxeError> {
+                Ok(())
+            }
+        }
+        
+        let circuit = ProductCircuit { x: F::from(3u64), y: F::from(5u64) };
+        let (proving_key, verifying_key) =
+            Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut test_rng()).unwrap();
+        let setup = TrustedSetup { proving_key, verifying_key };
+        
+        let to_bytes = |proof: &Proof<Bls12_381>| {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        
+        // Same witness and seed: byte-identical proofs that both verify
+        let proof_a = setup.prove_deterministic(circuit.clone(), 7).unwrap();
+        let proof_b = setup.prove_deterministic(circuit.clone(), 7).unwrap();
+        assert_eq!(to_bytes(&proof_a), to_bytes(&proof_b));
+        for proof in [&proof_a, &proof_b] {
+            assert!(Groth16::<Bls12_381>::verify(&setup.verifying_key, &circuit.public_inputs(), proof).unwrap());
+        }
+        
+        // Another seed re-randomizes the proof
+        let proof_c = setup.prove_deterministic(circuit, 8).unwrap();
+        assert_ne!(to_bytes(&proof_a), to_bytes(&proof_c));
+        
+        println!("✓ Deterministic proving test passed");
+    }
+    
+    #[test]
+    fn test_server_verifies_real_mint_proof() {
+        use ark_ff::UniformRand;
+        use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+        use fluxe_core::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+        use fluxe_core::data_structures::{IngressReceipt, Note};
+        use fluxe_core::merkle::{IncrementalTree, SortedTree};
+        use fluxe_core::server_verifier::{ServerVerifier, TransactionBuilder, TransactionData};
+        use fluxe_core::state_manager::StateManager;
+        use fluxe_core::types::Amount;
+        
+        let mut rng = test_rng();
+        let mut manager = SetupManager::new();
+        manager.generate_setup(CircuitType::Mint, &mut rng).unwrap();
+        
+        // Mint 1000 of asset 1 with an authorized minter
+        let value = 1000u64;
+        let randomness = F::rand(&mut rng);
+        let v_comm = PedersenCommitment::commit(
+            &PedersenParams::setup_value_commitment(),
+            value,
+            &PedersenRandomness { r: randomness },
+        );
+        let note = Note::new(1, v_comm, F::rand(&mut rng), [1u8; 32], 1);
+        let ingress = IngressReceipt::new(
+            1,
+            Amount::from(value as u128),
+            IngressReceipt::beneficiary_commitment(&[note.clone()]),
+            1,
+        );
+        let minter_sk = F::rand(&mut rng);
+        let (minter_pk_x, minter_pk_y) = compute_ec_public_key(minter_sk);
+        let mut authorized_minters = SortedTree::new(16);
+        authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+        
+        let mut state = StateManager::new(16);
+        state.update_authorized_minters_root(authorized_minters.root());
+        let circuit = MintCircuit::new(
+            vec![note.clone()],
+            vec![value],
+            vec![randomness],
+            ingress.clone(),
+            &mut IncrementalTree::new(16),
+            &mut IncrementalTree::new(16),
+        )
+        .with_minter(minter_sk, &authorized_minters)
+        .unwrap();
+        let public_inputs = circuit.public_inputs();
+        let proof = manager.get_setup(CircuitType::Mint).unwrap().prove_deterministic(circuit, 1).unwrap();
+        
+        let roots = state.get_roots();
+        let tx = TransactionBuilder::new_mint(roots.clone(), roots).build(
+            proof.clone(),
+            public_inputs,
+            TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(value as u128),
+                notes_out: vec![note],
+                ingress_receipt: ingress,
+            },
+        );
+        
+        let mut verifier = ServerVerifier::with_keys(state, None, None, None, None);
+        verifier.rotate_keys(manager.verifying_keys());
+        assert!(verifier.verify_transaction(&tx).unwrap());
+        verifier.add_transaction(tx.clone()).unwrap();
+        
+        // A proof for other public inputs is rejected
+        let mut tampered = tx.clone();
+        tampered.public_inputs[5] += F::from(1u64);
+        assert!(!verifier.verify_transaction(&tampered).unwrap());
+        assert!(verifier.add_transaction(tampered).is_err());
+        
+        // So is a tampered proof
+        let mut tampered = tx;
+        tampered.proof.a = proof.c;
+        assert!(!verifier.verify_transaction(&tampered).unwrap());
+        assert!(verifier.add_transaction(tampered).is_err());
+        
+        println!("✓ Real mint proof verified by the server");
+    }
+
+    #[test]
+    fn test_server_applies_real_burn_with_confirmation_delay() {
+        use ark_ff::UniformRand;
+        use fluxe_core::crypto::{compute_ec_public_key, owner_address_from_pk};
+        use fluxe_core::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+        use fluxe_core::data_structures::Note;
+        use fluxe_core::merkle::SortedTree;
+        use fluxe_core::server_verifier::{claimed_new_roots, ServerVerifier, TransactionBuilder, TransactionData};
+        use fluxe_core::state_manager::StateManager;
+        use fluxe_core::types::Amount;
+        
+        let mut rng = test_rng();
+        let tree_config = TreeConfig::uniform(16);
+        let mut manager = SetupManager::new().with_tree_config(tree_config);
+        manager.generate_setup(CircuitType::Burn, &mut rng).unwrap();
+        
+        // A minted note of 1000 sits in CMT; spends stay reversible for one batch
+        let owner_sk = F::rand(&mut rng);
+        let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
+        let value = 1000u64;
+        let randomness = F::rand(&mut rng);
+        let v_comm = PedersenCommitment::commit(
+            &PedersenParams::setup_value_commitment(),
+            value,
+            &PedersenRandomness { r: randomness },
+        );
+        let mut note = Note::new(1, v_comm, owner_address_from_pk(pk_x, pk_y), [2u8; 32], 1);
+        note.compliance_hash = F::from(1u64);
+        note.callbacks_hash = F::from(1u64);
+        let mut exit_allow_tree = SortedTree::new(32);
+        let destination = F::from(77u64);
+        exit_allow_tree.insert(destination).unwrap();
+        
+        let mut state = StateManager::new(16).with_confirmation_delay(1);
+        state.cmt_tree.append(note.commitment());
+        state.checkpoint_cmt_root();
+        state.supply.insert(1, Amount::from(value as u128));
+        state.exit_allow_root = exit_allow_tree.root();
+        let empty_nft_root = state.nft_tree.root();
+        
+        // Prove the burn against copies of the live trees
+        let (mut cmt_tree, mut nft_tree, mut exit_tree) =
+            (state.cmt_tree.clone(), state.nft_tree.clone(), state.exit_tree.clone());
+        let nk = F::rand(&mut rng);
+        let circuit = BurnBuilder::new(&mut cmt_tree, &mut nft_tree, &mut exit_tree, note.clone(), value, randomness, owner_sk, nk)
+            .with_amount(Amount::from(400u64))
+            .with_exit_destination(&exit_allow_tree, destination)
+            .unwrap()
+            .build()
+            .unwrap()
+            .with_tree_config(tree_config);
+        let public_inputs = circuit.public_inputs();
+        let proof = manager.get_setup(CircuitType::Burn).unwrap().prove_deterministic(circuit.clone(), 1).unwrap();
+        
+        let data = TransactionData::Burn {
+            asset_type: 1,
+            amount: Amount::from(400u64),
+            nullifier: circuit.nf_in,
+            consumed_cm: note.commitment(),
+            exit_receipt: circuit.exit_receipt.clone(),
+        };
+        let old_roots = state.get_roots();
+        let new_roots = claimed_new_roots(&old_roots, &public_inputs, &data).unwrap();
+        let tx = TransactionBuilder::new_burn(old_roots, new_roots).build(proof, public_inputs, data);
+        
+        let mut verifier = ServerVerifier::with_keys(state, None, None, None, None);
+        verifier.rotate_keys(manager.verifying_keys());
+        verifier.add_transaction(tx).unwrap();
+        
+        // The spend lands at the NFT root the proof claims, though it is not yet final
+        verifier.process_batch().unwrap();
+        assert_eq!(verifier.state.nft_tree.root(), circuit.nft_root_new);
+        assert_eq!(verifier.state.staged_nullifiers.len(), 1);
+        assert!(verifier.state.nullifier_exists(circuit.nf_in));
+        
+        // A reorg of that batch takes the nullifier back out of the NFT
+        let mut reorged = verifier.state.clone();
+        assert_eq!(reorged.rollback_nullifiers(0).unwrap(), 1);
+        assert_eq!(reorged.nft_tree.root(), empty_nft_root);
+        assert!(!reorged.nullifier_exists(circuit.nf_in));
+        
+        // Confirmed, it is final and the root is unchanged
+        assert_eq!(verifier.state.finalize_nullifiers(0), 1);
+        assert_eq!(verifier.state.nft_tree.root(), circuit.nft_root_new);
+        
+        println!("✓ Real burn applied under a confirmation delay");
+    }
 }
\ No newline at end of file
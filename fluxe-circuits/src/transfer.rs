@@ -4,15 +4,30 @@ use ark_r1cs_std::boolean::Boolean;
 use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use fluxe_core::{
-    data_structures::Note,
-    merkle::{MerklePath, RangePath, AppendWitness},
+    crypto::{poseidon_hash, MemoEncryption},
+    crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness},
+    data_structures::{ComplianceState, Note, ZkObject},
+    merkle::{MerklePath, RangePath, AppendWitness, SortedMembershipProof, TreeConfig, TreeError},
     types::*,
 };
 
-use crate::circuits::FluxeCircuit;
+use crate::circuits::{ConstraintSections, FluxeCircuit};
 use crate::gadgets::*;
 use crate::gadgets::sorted_insert::SortedInsertWitness;
 
+/// Maximum number of outputs per transfer (fixed circuit shape)
+pub const MAX_OUTPUTS: usize = 8;
+
+/// Maximum distinct asset types in a multi-asset transfer (fixed circuit shape)
+pub const MAX_ASSETS: usize = 4;
+
+/// Bits bounding outputs plus fee: MAX_OUTPUTS + 1 values of 64 bits each,
+/// far below the field size so the conservation sum cannot wrap
+pub const CONSERVATION_SUM_BITS: usize = 68;
+
+/// Minimum compliance level for inputs when checked_compliance is set
+pub const MIN_CHECKED_COMPLIANCE_LEVEL: u8 = 1;
+
 /// Transfer circuit for private value transfers
 #[derive(Clone)]
 pub struct TransferCircuit {
@@ -74,7 +89,8 @@ pub struct TransferCircuit {
     /// CMT append witnesses for output notes (pre-insertion siblings)
     pub cmt_appends_out: Vec<AppendWitness>,
     
-    /// NFT insert witnesses for nullifier insertions
+    /// NFT insert witnesses for nullifier insertions, chained in nf_list order:
+    /// nf_insert_witnesses[i].target must equal nf_list[i] (see `Note::cmp_nullifiers`)
     pub nf_insert_witnesses: Vec<SortedInsertWitness>,
     
     // Public inputs
@@ -96,28 +112,109 @@ pub struct TransferCircuit {
     /// Pool rules root
     pub pool_rules_root: MerkleRoot,
     
-    /// Input nullifiers
+    /// Input nullifiers, in the order their insert witnesses were generated;
+    /// `new_with_nft_witnesses` puts them in canonical `Note::cmp_nullifiers` order
     pub nf_list: Vec<Nullifier>,
     
     /// Output commitments
     pub cm_list: Vec<Commitment>,
     
+    /// Declared number of appended commitments (non-dummy outputs plus fee note), bound as a public input
+    pub n_out: u64,
+    
     /// Transaction fee
     pub fee: Amount,
+    
+    /// Self-transfer consolidation; proven same-owner, pays the pool's reduced fee floor
+    pub consolidation: bool,
+    
+    /// Bind each input's compliance_hash to a witnessed state (circuit shape flag)
+    pub checked_compliance: bool,
+    
+    /// Compliance states behind the input notes, used when checked_compliance is set
+    pub compliance_states_in: Vec<ComplianceState>,
+    
+    /// Block inputs whose compliance review is stale (circuit shape flag, requires checked_compliance)
+    pub require_fresh_review: bool,
+    
+    /// Current time, public input when require_fresh_review is set
+    pub current_time: Time,
+    
+    /// Maximum age of an input's last compliance review, public input when require_fresh_review is set
+    pub max_review_age: Time,
+    
+    /// Sequencer fee note and its value randomness; when set, the fee is collected
+    /// into this note, whose commitment is the last entry of cm_list and is appended to CMT
+    pub fee_note: Option<(Note, F)>,
+    
+    /// Accepted window of recent CMT roots, public inputs when non-empty (circuit shape);
+    /// inputs then prove membership against any root in the window instead of cmt_root_old
+    pub recent_cmt_roots: Vec<MerkleRoot>,
+    
+    /// Root each input's cm_path was taken against, used when recent_cmt_roots is set
+    pub cm_path_roots: Vec<MerkleRoot>,
+    
+    /// Memo ciphertext attached to each output (None, or an empty list, means no memo);
+    /// an output without a memo must carry memo_hash 0
+    pub memos_out: Vec<Option<Vec<u8>>>,
+    
+    /// Require each input's pool to be permitted by its compliance state
+    /// (circuit shape flag, requires checked_compliance)
+    pub check_permitted_pools: bool,
+    
+    /// Open each real output's v_comm in-circuit under `Note::derive_value_randomness` of the
+    /// first input's owner key and the output's psi (circuit shape flag)
+    pub derived_randomness: bool,
+    
+    /// Smallest value a real output may hold, checked against its opened v_comm; fixed by
+    /// the circuit, so the verifying key commits to the policy (dummies and the fee note are exempt)
+    pub min_denomination: Option<u64>,
+    
+    /// Per-asset fees of a multi-asset transfer; when non-empty, value is conserved per asset
+    /// over MAX_ASSETS public (asset, fee) slots instead of across one shared asset type
+    pub asset_fees: Vec<(AssetType, Amount)>,
+    /// Fee-exempt pool migration: when set, the fee must be zero so value is preserved
+    /// exactly, and the outputs' pool must be a transition of the inputs' pool listed here
+    pub migration: Option<PoolTransitionFn>,
+    
+    /// Configured tree heights the witness paths must match (None skips the check)
+    pub tree_config: Option<TreeConfig>,
+    
+    /// Reject outputs to frozen recipients (circuit shape flag); owner_index_root becomes a public input
+    pub block_frozen_recipients: bool,
+    
+    /// Each output's recipient compliance state, with the zk-object holding it and the owner
+    /// index proof binding that object to the output's owner_addr; used when block_frozen_recipients is set
+    pub recipient_compliance: Vec<(ComplianceState, ZkObject, SortedMembershipProof)>,
+    
+    /// Owner index root the recipients' objects are proven against
+    pub owner_index_root: MerkleRoot,
+    
+    /// Publish how many sender and recipient addresses lack a sanctions non-membership
+    /// proof (circuit shape flag); the count becomes the last public input
+    pub expose_sanctions_fallbacks: bool,
 }
 
 impl TransferCircuit {
     /// Helper to generate NFT insert witnesses from a SortedTree
     /// Call this before creating the circuit to get proper witnesses
+    /// Witnesses are chained in canonical nullifier order (`Note::cmp_nullifiers`),
+    /// the same order `new_with_nft_witnesses` gives nf_list, whatever the order of `nullifiers`
     pub fn generate_nft_insert_witnesses(
         nft_tree: &fluxe_core::merkle::SortedTree,
         nullifiers: &[F],
     ) -> Result<Vec<SortedInsertWitness>, String> {
+        let mut sorted = nullifiers.to_vec();
+        sorted.sort_by(Note::cmp_nullifiers);
+        
+        // Insert into a scratch copy so each witness starts from the previous root
+        let mut scratch = nft_tree.clone();
         let mut witnesses = Vec::new();
         
-        for nf in nullifiers {
-            // Export witness for inserting this nullifier
-            let witness = nft_tree.export_insert_witness(*nf)?;
+        for nf in sorted {
+            // Export witness for inserting this nullifier, then apply it
+            let witness = scratch.export_insert_witness(nf)?;
+            scratch.insert(nf)?;
             
             // Convert to circuit witness format (gadgets version)
             let insert_witness = SortedInsertWitness {
@@ -138,6 +235,11 @@ impl TransferCircuit {
     
     /// Create a TransferCircuit with proper NFT insert witnesses
     /// This is the recommended way to build the circuit with full witness data
+    /// Inputs (and their per-input witnesses) are reordered by nullifier into canonical
+    /// `Note::cmp_nullifiers` order, and nf_insert_witnesses sorted by target to match.
+    /// Callers must therefore build output lineages from the parent lineages in that
+    /// canonical order, not the order the inputs are passed in.
+    /// Every per-input vector needs exactly one entry per input; otherwise returns an error
     pub fn new_with_nft_witnesses(
         notes_in: Vec<Note>,
         values_in: Vec<u64>,
@@ -164,19 +266,47 @@ impl TransferCircuit {
         sanctions_root: MerkleRoot,
         pool_rules_root: MerkleRoot,
         fee: Amount,
-    ) -> Self {
+    ) -> Result<Self, FluxeError> {
+        if nks.len() != notes_in.len() {
+            return Err(FluxeError::Other(format!(
+                "nks has {} entries for {} inputs",
+                nks.len(),
+                notes_in.len()
+            )));
+        }
+        
         // Compute nullifier list
         let nf_list: Vec<F> = notes_in.iter()
             .zip(nks.iter())
             .map(|(note, nk)| note.nullifier(nk))
             .collect();
         
+        // Put inputs in canonical nullifier order, the order the NFT inserts chain in
+        let mut order: Vec<usize> = (0..nf_list.len()).collect();
+        order.sort_by(|&a, &b| Note::cmp_nullifiers(&nf_list[a], &nf_list[b]));
+        let nf_list = permute_inputs("nf_list", nf_list, &order)?;
+        let notes_in = permute_inputs("notes_in", notes_in, &order)?;
+        let values_in = permute_inputs("values_in", values_in, &order)?;
+        let value_randomness_in = permute_inputs("value_randomness_in", value_randomness_in, &order)?;
+        let nks = permute_inputs("nks", nks, &order)?;
+        let owner_sks = permute_inputs("owner_sks", owner_sks, &order)?;
+        let owner_pks = permute_inputs("owner_pks", owner_pks, &order)?;
+        let cm_paths = permute_inputs("cm_paths", cm_paths, &order)?;
+        let nf_nonmembership_proofs = permute_inputs("nf_nonmembership_proofs", nf_nonmembership_proofs, &order)?;
+        let sanctions_nm_proofs_in = permute_inputs("sanctions_nm_proofs_in", sanctions_nm_proofs_in, &order)?;
+        
+        let mut nf_insert_witnesses = nf_insert_witnesses;
+        nf_insert_witnesses.sort_by(|a, b| Note::cmp_nullifiers(&a.target, &b.target));
+        
         // Compute output commitment list  
         let cm_list: Vec<F> = notes_out.iter()
             .map(|note| note.commitment())
             .collect();
         
-        Self {
+        // Dummy outputs are public commitments but never appended
+        let n_out = notes_out.iter().filter(|note| !note.is_dummy()).count() as u64;
+        
+        Ok(Self {
             notes_in,
             values_in,
             value_randomness_in,
@@ -204,9 +334,30 @@ impl TransferCircuit {
             sanctions_root,
             pool_rules_root,
             nf_list,
+            n_out,
             cm_list,
             fee,
-        }
+            consolidation: false,
+            checked_compliance: false,
+            compliance_states_in: Vec::new(),
+            require_fresh_review: false,
+            current_time: 0,
+            max_review_age: 0,
+            fee_note: None,
+            recent_cmt_roots: Vec::new(),
+            cm_path_roots: Vec::new(),
+            memos_out: Vec::new(),
+            derived_randomness: false,
+            min_denomination: None,
+            asset_fees: Vec::new(),
+            check_permitted_pools: false,
+            migration: None,
+            tree_config: None,
+            block_frozen_recipients: false,
+            recipient_compliance: Vec::new(),
+            owner_index_root: F::from(0u64),
+            expose_sanctions_fallbacks: false,
+        })
     }
     
     /// Legacy constructor for backward compatibility
@@ -249,6 +400,9 @@ impl TransferCircuit {
             .map(|note| note.commitment())
             .collect();
         
+        // Dummy outputs are public commitments but never appended
+        let n_out = notes_out.iter().filter(|note| !note.is_dummy()).count() as u64;
+        
         Self {
             notes_in,
             values_in,
@@ -277,20 +431,249 @@ impl TransferCircuit {
             sanctions_root,
             pool_rules_root,
             nf_list,
+            n_out,
             cm_list,
             fee,
+            consolidation: false,
+            checked_compliance: false,
+            compliance_states_in: Vec::new(),
+            require_fresh_review: false,
+            current_time: 0,
+            max_review_age: 0,
+            fee_note: None,
+            recent_cmt_roots: Vec::new(),
+            cm_path_roots: Vec::new(),
+            memos_out: Vec::new(),
+            derived_randomness: false,
+            min_denomination: None,
+            asset_fees: Vec::new(),
+            check_permitted_pools: false,
+            migration: None,
+            tree_config: None,
+            block_frozen_recipients: false,
+            recipient_compliance: Vec::new(),
+            owner_index_root: F::from(0u64),
+            expose_sanctions_fallbacks: false,
+        }
+    }
+    
+    /// Check the nullifier ordering contract: nf_list[i] is the target of the
+    /// i-th insert witness, so the in-circuit insert chain sees them in tree order
+    pub fn check_nullifier_order(&self) -> Result<(), FluxeError> {
+        if self.nf_insert_witnesses.is_empty() {
+            return Ok(());
+        }
+        if self.nf_insert_witnesses.len() != self.nf_list.len() {
+            return Err(FluxeError::Other("Nullifier/insert witness count mismatch".to_string()));
         }
+        if let Some(i) = self.nf_list
+            .iter()
+            .zip(self.nf_insert_witnesses.iter())
+            .position(|(nf, witness)| *nf != witness.target)
+        {
+            return Err(FluxeError::Other(format!(
+                "Nullifier {} does not match its insert witness; nf_list must follow insert witness order",
+                i
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Collect the fee into a note appended to CMT after the outputs
+    /// The caller must also supply its append witness and the resulting cmt_root_new
+    pub fn with_fee_note(mut self, note: Note, value_randomness: F) -> Self {
+        self.cm_list.push(note.commitment());
+        self.n_out += 1;
+        self.fee_note = Some((note, value_randomness));
+        self
+    }
+    
+    /// Require every input's last compliance review to be at most max_review_age old at current_time
+    /// Only meaningful together with checked_compliance, which binds the states to the notes
+    pub fn with_fresh_review(mut self, current_time: Time, max_review_age: Time) -> Self {
+        self.require_fresh_review = true;
+        self.current_time = current_time;
+        self.max_review_age = max_review_age;
+        self
+    }
+    
+    /// Let inputs be spent from any root in the accepted window of recent CMT roots;
+    /// cm_path_roots[i] is the root the i-th input's path was taken against
+    pub fn with_recent_cmt_roots(mut self, recent_cmt_roots: Vec<MerkleRoot>, cm_path_roots: Vec<MerkleRoot>) -> Self {
+        self.recent_cmt_roots = recent_cmt_roots;
+        self.cm_path_roots = cm_path_roots;
+        self
+    }
+    
+    /// Attach memo ciphertexts to outputs; each output's memo_hash must be
+    /// `MemoEncryption::compute_circuit_memo_hash` of its memo
+    pub fn with_output_memos(mut self, memos_out: Vec<Option<Vec<u8>>>) -> Self {
+        self.memos_out = memos_out;
+        self
+    }
+    
+    /// Require every input note's pool_id to be set in its compliance state's permitted pools
+    /// Only meaningful together with checked_compliance, which binds the states to the notes
+    pub fn with_permitted_pools_check(mut self) -> Self {
+        self.check_permitted_pools = true;
+        self
+    }
+    
+    /// Require fresh output value randomness, derived from owner_sks[0] and each output's psi
+    pub fn with_derived_randomness(mut self) -> Self {
+        self.derived_randomness = true;
+        self
+    }
+    
+    /// Require every real output to hold at least `min_denom`
+    pub fn with_min_denomination(mut self, min_denom: u64) -> Self {
+        self.min_denomination = Some(min_denom);
+        self
+    }
+    
+    /// Conserve value per asset, each paying its own fee; `fee` must then be zero
+    /// and no fee note is collected
+    pub fn with_asset_fees(mut self, asset_fees: Vec<(AssetType, Amount)>) -> Self {
+        self.asset_fees = asset_fees;
+        self
+    }
+    
+    /// Check the per-asset fee list fits the fixed slot shape and replaces the single fee
+    pub fn check_asset_fees(&self) -> Result<(), FluxeError> {
+        if self.asset_fees.is_empty() {
+            return Ok(());
+        }
+        if self.asset_fees.len() > MAX_ASSETS {
+            return Err(FluxeError::Other(format!(
+                "Transfer has {} assets, maximum is {}",
+                self.asset_fees.len(),
+                MAX_ASSETS
+            )));
+        }
+        for (i, (asset_type, fee)) in self.asset_fees.iter().enumerate() {
+            if self.asset_fees[..i].iter().any(|(other, _)| other == asset_type) {
+                return Err(FluxeError::Other(format!("Asset {} listed twice", asset_type)));
+            }
+            if fee.value() > u64::MAX as u128 {
+                return Err(FluxeError::Other(format!("Asset {} fee {} exceeds 64 bits", asset_type, fee.value())));
+            }
+        }
+        if self.fee != Amount::zero() || self.fee_note.is_some() {
+            return Err(FluxeError::Other("Multi-asset transfer pays per-asset fees only".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// Asset fee slots padded to MAX_ASSETS by repeating the first entry, which
+    /// restates its conservation equation; empty for a single-asset transfer
+    fn asset_fee_slots(&self) -> Vec<(AssetType, Amount)> {
+        let mut slots = self.asset_fees.clone();
+        if let Some(&first) = self.asset_fees.first() {
+            slots.resize(MAX_ASSETS, first);
+        }
+        slots
+    }
+    
+    /// Sender and recipient addresses without a sanctions non-membership proof; the circuit
+    /// checks these at most to be non-zero, so they are not cleared against the list
+    pub fn sanctions_fallbacks(&self) -> u64 {
+        let missing = |count: usize, proofs: &[Option<RangePath>]| {
+            (0..count).filter(|&i| proofs.get(i).map_or(true, Option::is_none)).count()
+        };
+        (missing(self.notes_in.len(), &self.sanctions_nm_proofs_in)
+            + missing(self.notes_out.len(), &self.sanctions_nm_proofs_out)) as u64
+    }
+    
+    /// Run as a fee-exempt migration from the inputs' pool to the outputs' pool
+    /// The fee must be zero and no fee note collected
+    pub fn with_migration(mut self, transition_fn: PoolTransitionFn) -> Self {
+        self.migration = Some(transition_fn);
+        self
+    }
+    
+    /// Block frozen recipients: each real output's compliance_hash must be its recipient's
+    /// unfrozen state, held by the current zk-object of the output's owner_addr in the owner
+    /// index at `owner_index_root` (see `StateManager::prove_owner_object`) and committing
+    /// to that owner_addr
+    /// Dummy outputs still take an entry, which is not checked
+    pub fn with_frozen_recipients_blocked(
+        mut self,
+        owner_index_root: MerkleRoot,
+        recipient_compliance: Vec<(ComplianceState, ZkObject, SortedMembershipProof)>,
+    ) -> Self {
+        self.block_frozen_recipients = true;
+        self.owner_index_root = owner_index_root;
+        self.recipient_compliance = recipient_compliance;
+        self
+    }
+    
+    /// Publish the sanctions fallback count (see `sanctions_fallbacks`), so a verifier can
+    /// tell which transfers cleared every address against the sanctions list
+    pub fn with_sanctions_fallbacks_exposed(mut self) -> Self {
+        self.expose_sanctions_fallbacks = true;
+        self
+    }
+    
+    /// Require the witnesses to be built for the trees in `config`
+    pub fn with_tree_config(mut self, config: TreeConfig) -> Self {
+        self.tree_config = Some(config);
+        self
     }
 }
 
+/// Reorder a per-input vector by `order`; `name` identifies a vector not sized per input
+fn permute_inputs<T>(name: &str, items: Vec<T>, order: &[usize]) -> Result<Vec<T>, FluxeError> {
+    if items.len() != order.len() {
+        return Err(FluxeError::Other(format!(
+            "{} has {} entries for {} inputs",
+            name,
+            items.len(),
+            order.len()
+        )));
+    }
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    Ok(order.iter().map(|&i| slots[i].take().expect("order is a permutation")).collect())
+}
+
 impl ConstraintSynthesizer<F> for TransferCircuit {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Shape check: output count is capped so proof size stays bounded
+        if self.notes_out.len() > MAX_OUTPUTS {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        // A migration moves funds between two pools, so it needs both sides
+        if self.migration.is_some() && (self.notes_in.is_empty() || self.notes_out.is_empty()) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        // Insert witnesses are chained in nf_list order
+        if self.check_nullifier_order().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        // Asset slots are capped so the multi-asset shape stays fixed
+        if self.check_asset_fees().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        // Witnesses built for another tree height cannot be hashed to the roots
+        if self.check_tree_heights().is_err() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
         // Create ALL public inputs in the correct order first
         // Order must match public_inputs() method exactly:
         // 1. roots: cmt_root_old, cmt_root_new, nft_root_old, nft_root_new, sanctions_root, pool_rules_root
         // 2. nullifiers from nf_list
         // 3. output commitments from cm_list
-        // 4. fee
+        // 4. n_out
+        // 5. fee
+        // 6. current_time, max_review_age (only when require_fresh_review is set)
+        // 7. recent_cmt_roots (only when non-empty)
+        // 8. (asset_type, fee) for each of MAX_ASSETS slots (only when asset_fees is non-empty)
+        // 9. owner_index_root (only when block_frozen_recipients is set)
+        // 10. sanctions fallback count (only when expose_sanctions_fallbacks is set)
         
         // Step 1: Create root public inputs
         let cmt_root_old_var = FpVar::new_input(cs.clone(), || Ok(self.cmt_root_old))?;
@@ -312,9 +695,56 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             .map(|cm| FpVar::new_input(cs.clone(), || Ok(*cm)))
             .collect::<Result<Vec<_>, _>>()?;
         
-        // Step 4: Create fee public input (last)
+        // Step 4: Create declared output count public input
+        let n_out_var = FpVar::new_input(cs.clone(), || Ok(F::from(self.n_out)))?;
+        
+        // Step 5: Create fee public input
         let fee_var = FpVar::new_input(cs.clone(), || Ok(self.fee.to_field()))?;
+        
+        // Step 6: Create review freshness public inputs (shape-dependent, last)
+        let review_window_vars = if self.require_fresh_review {
+            Some((
+                FpVar::new_input(cs.clone(), || Ok(F::from(self.current_time)))?,
+                FpVar::new_input(cs.clone(), || Ok(F::from(self.max_review_age)))?,
+            ))
+        } else {
+            None
+        };
+        
+        // Step 7: Create accepted recent CMT root inputs (shape-dependent, last)
+        let recent_cmt_root_vars: Vec<FpVar<F>> = self.recent_cmt_roots
+            .iter()
+            .map(|root| FpVar::new_input(cs.clone(), || Ok(*root)))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        // Step 8: Create per-asset fee slot inputs (shape-dependent)
+        let asset_fee_vars: Vec<(FpVar<F>, FpVar<F>)> = self.asset_fee_slots()
+            .iter()
+            .map(|(asset_type, fee)| {
+                Ok((
+                    FpVar::new_input(cs.clone(), || Ok(F::from(*asset_type as u64)))?,
+                    FpVar::new_input(cs.clone(), || Ok(fee.to_field()))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        
+        // Step 9: Create recipient owner index root input (shape-dependent)
+        let owner_index_root_var = if self.block_frozen_recipients {
+            Some(FpVar::new_input(cs.clone(), || Ok(self.owner_index_root))?)
+        } else {
+            None
+        };
+        
+        // Step 10: Create sanctions fallback count input (shape-dependent, last)
+        let sanctions_fallbacks = F::from(self.sanctions_fallbacks());
+        let sanctions_fallbacks_var = if self.expose_sanctions_fallbacks {
+            Some(FpVar::new_input(cs.clone(), || Ok(sanctions_fallbacks))?)
+        } else {
+            None
+        };
+        
         // Witness input notes
+        ConstraintSections::begin(&cs, "witnesses");
         let notes_in_var: Vec<NoteVar> = self.notes_in
             .iter()
             .enumerate()
@@ -342,6 +772,42 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             })
             .collect::<Result<Vec<_>, _>>()?;
         
+        // Outputs built by `Note::dummy` pad the shape: zero value at the sentinel owner and commitment,
+        // after every real output
+        let dummy_owner_var = FpVar::constant(Note::dummy_owner_addr());
+        let dummy_v_comm = Note::dummy_value_commitment().to_field_repr();
+        let out_is_dummy: Vec<Boolean<F>> = self.notes_out
+            .iter()
+            .zip(notes_out_var.iter())
+            .map(|(note, note_var)| {
+                let is_dummy = Boolean::new_witness(cs.clone(), || Ok(note.is_dummy()))?;
+                note_var.owner_addr.conditional_enforce_equal(&dummy_owner_var, &is_dummy)?;
+                note_var.value.conditional_enforce_equal(&FpVar::zero(), &is_dummy)?;
+                for (coord, expected) in note_var.v_comm.iter().zip(dummy_v_comm.iter()) {
+                    coord.conditional_enforce_equal(&FpVar::constant(*expected), &is_dummy)?;
+                }
+                Ok(is_dummy)
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        let mut real_out_count = FpVar::zero();
+        for (i, is_dummy) in out_is_dummy.iter().enumerate() {
+            if i > 0 {
+                is_dummy.conditional_enforce_equal(&Boolean::TRUE, &out_is_dummy[i - 1])?;
+            }
+            real_out_count += FpVar::from(is_dummy.not());
+        }
+        
+        // Witness the fee note, valued at the public fee
+        let fee_note_var = match &self.fee_note {
+            Some((note, r)) => Some(NoteVar::new_witness(
+                cs.clone(),
+                || Ok(note.clone()),
+                self.fee.value() as u64,
+                r,
+            )?),
+            None => None,
+        };
+        
         // Witness nullifier keys and paths
         let nks_var: Vec<FpVar<F>> = self.nks
             .iter()
@@ -356,13 +822,24 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
         // Note: All public inputs were already created at the beginning of the method
         
         // Constraint 1: Membership - all inputs are in CMT tree
-        for (note_var, path_var) in notes_in_var.iter().zip(paths_var.iter()) {
+        // (at cmt_root_old, or at their own root from the accepted recent window)
+        ConstraintSections::begin(&cs, "membership");
+        if !recent_cmt_root_vars.is_empty() && self.cm_path_roots.len() != notes_in_var.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        for (i, (note_var, path_var)) in notes_in_var.iter().zip(paths_var.iter()).enumerate() {
             let cm = note_var.commitment()?;
             cm.enforce_equal(&path_var.leaf)?;
-            path_var.enforce_valid(&cmt_root_old_var)?;
+            if recent_cmt_root_vars.is_empty() {
+                path_var.enforce_valid(&cmt_root_old_var)?;
+            } else {
+                let path_root_var = FpVar::new_witness(cs.clone(), || Ok(self.cm_path_roots[i]))?;
+                path_var.enforce_valid_in_window(&path_root_var, &recent_cmt_root_vars)?;
+            }
         }
         
         // Constraint 2: Nullifier correctness
+        ConstraintSections::begin(&cs, "nullifier");
         for ((note_var, nk_var), expected_nf_var) in notes_in_var.iter()
             .zip(nks_var.iter())
             .zip(nf_vars.iter())
@@ -373,7 +850,9 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
         }
         
         // Constraint 2b: EC-based owner authentication for input notes
+        ConstraintSections::begin(&cs, "authentication");
         // SECURITY CRITICAL: Verify each input note can only be spent by its owner
+        let mut owner_sk_vars = Vec::new();
         for (i, note_var) in notes_in_var.iter().enumerate() {
             if i < self.owner_sks.len() {
                 let owner_sk_var = FpVar::new_witness(cs.clone(), || Ok(self.owner_sks[i]))?;
@@ -382,54 +861,165 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
                 use crate::gadgets::auth::AuthGadget;
                 let (derived_pk_x_fq, derived_pk_y_fq) = AuthGadget::scalar_mult_generator(cs.clone(), &owner_sk_var)?;
                 
-                // Compute owner address from the derived public key: addr = H(pk_x, pk_y)
+                // Compute owner address from the derived public key: addr = H(DOM_OWNER_ADDR, pk_x, pk_y)
                 let computed_owner_addr = AuthGadget::compute_owner_address_from_fq(cs.clone(), &derived_pk_x_fq, &derived_pk_y_fq)?;
                 
                 // Enforce that computed address matches note's owner
                 computed_owner_addr.enforce_equal(&note_var.owner_addr)?;
+                owner_sk_vars.push(owner_sk_var);
+            }
+        }
+        
+        // Constraint 2c: Open each real output's v_comm when its randomness or value is policed:
+        // fresh randomness bound to the authenticated spender key, or a minimum denomination
+        if self.derived_randomness || self.min_denomination.is_some() {
+            ConstraintSections::begin(&cs, "value commitment");
+            let owner_sk_var = if self.derived_randomness {
+                Some(owner_sk_vars.first().ok_or(SynthesisError::Unsatisfiable)?)
+            } else {
+                None
+            };
+            for (i, (note_var, is_dummy)) in notes_out_var.iter().zip(out_is_dummy.iter()).enumerate() {
+                let r_var = match owner_sk_var {
+                    Some(owner_sk_var) => note_var.derived_value_randomness(owner_sk_var)?,
+                    None => FpVar::new_witness(cs.clone(), || Ok(self.value_randomness_out[i]))?,
+                };
+                match self.min_denomination {
+                    Some(min_denom) => ValueCommitmentGadget::conditional_enforce_min_denomination(
+                        &note_var.v_comm,
+                        &note_var.value,
+                        &r_var,
+                        &FpVar::constant(F::from(min_denom)),
+                        &is_dummy.not(),
+                    )?,
+                    None => ValueCommitmentGadget::conditional_enforce_opening(
+                        &note_var.v_comm,
+                        &note_var.value,
+                        &r_var,
+                        &is_dummy.not(),
+                    )?,
+                }
             }
         }
         
         // Constraint 3: Value conservation
-        let mut sum_in = FpVar::zero();
-        for note_var in &notes_in_var {
-            sum_in += &note_var.value;
+        // The fee is a public input, so bound it like the output values
+        ConstraintSections::begin(&cs, "fee range");
+        RangeProofGadget::prove_range_bits(cs.clone(), &fee_var, 64)?;
+        
+        ConstraintSections::begin(&cs, "conservation");
+        if asset_fee_vars.is_empty() {
+            let mut sum_in = FpVar::zero();
+            for note_var in &notes_in_var {
+                sum_in += &note_var.value;
+            }
+            
+            // Dummy outputs are skipped
+            let mut sum_out = FpVar::zero();
+            for (note_var, is_dummy) in notes_out_var.iter().zip(out_is_dummy.iter()) {
+                sum_out += is_dummy.select(&FpVar::zero(), &note_var.value)?;
+            }
+            // Use the fee_var that was already created as public input
+            sum_out += &fee_var;
+            RangeProofGadget::prove_range_bits(cs.clone(), &sum_out, CONSERVATION_SUM_BITS)?;
+            
+            // Sum of inputs >= sum of outputs + fee
+            sum_in.enforce_equal(&sum_out)?;
+        } else {
+            // Per-asset fees replace the single fee
+            fee_var.enforce_equal(&FpVar::zero())?;
+            
+            // Every slot sums only the notes of its asset: sum_in[a] == sum_out[a] + fee[a]
+            for (asset_var, asset_fee_var) in &asset_fee_vars {
+                let mut sum_in = FpVar::zero();
+                for note_var in &notes_in_var {
+                    let in_asset = note_var.asset_type.is_eq(asset_var)?;
+                    sum_in += in_asset.select(&note_var.value, &FpVar::zero())?;
+                }
+                
+                let mut sum_out = asset_fee_var.clone();
+                for (note_var, is_dummy) in notes_out_var.iter().zip(out_is_dummy.iter()) {
+                    let in_asset = note_var.asset_type.is_eq(asset_var)?.and(&is_dummy.not())?;
+                    sum_out += in_asset.select(&note_var.value, &FpVar::zero())?;
+                }
+                RangeProofGadget::prove_range_bits(cs.clone(), asset_fee_var, 64)?;
+                RangeProofGadget::prove_range_bits(cs.clone(), &sum_out, CONSERVATION_SUM_BITS)?;
+                sum_in.enforce_equal(&sum_out)?;
+            }
         }
         
-        let mut sum_out = FpVar::zero();
-        for note_var in &notes_out_var {
-            sum_out += &note_var.value;
+        // A collected fee lands in the fee note in full
+        if let Some(fee_note_var) = &fee_note_var {
+            fee_note_var.value.enforce_equal(&fee_var)?;
         }
-        // Use the fee_var that was already created as public input
-        sum_out += &fee_var;
         
-        // Sum of inputs >= sum of outputs + fee
-        sum_in.enforce_equal(&sum_out)?;
+        // Constraint 3a: A zero-input transfer only appends zero-value notes and pays no fee
+        // (stated directly so it doesn't rest on the field sum not wrapping)
+        if notes_in_var.is_empty() {
+            fee_var.enforce_equal(&FpVar::zero())?;
+            for note_var in &notes_out_var {
+                note_var.value.enforce_equal(&FpVar::zero())?;
+            }
+        }
         
         // Constraint 3b: Asset type consistency
-        // All inputs and outputs must have the same asset type
-        if !notes_in_var.is_empty() {
-            let asset_type = &notes_in_var[0].asset_type;
-            
-            // Check all inputs have same asset type
-            for note_var in &notes_in_var[1..] {
-                note_var.asset_type.enforce_equal(asset_type)?;
+        ConstraintSections::begin(&cs, "asset type");
+        if asset_fee_vars.is_empty() {
+            // All inputs, outputs and the fee note share one asset type; without inputs
+            // the first output fixes the type the remaining outputs must match
+            let all_notes: Vec<&NoteVar> = notes_in_var
+                .iter()
+                .chain(notes_out_var.iter())
+                .chain(fee_note_var.iter())
+                .collect();
+            if let Some((first, rest)) = all_notes.split_first() {
+                for note_var in rest {
+                    note_var.asset_type.enforce_equal(&first.asset_type)?;
+                }
+            }
+        } else {
+            // Every input is counted by some slot, so no input value goes unconserved
+            for note_var in &notes_in_var {
+                let mut in_slot = Boolean::FALSE;
+                for (asset_var, _) in &asset_fee_vars {
+                    in_slot = in_slot.or(&note_var.asset_type.is_eq(asset_var)?)?;
+                }
+                in_slot.enforce_equal(&Boolean::TRUE)?;
             }
             
-            // Check all outputs have same asset type as inputs
-            for note_var in &notes_out_var {
-                note_var.asset_type.enforce_equal(asset_type)?;
+            // A real output's asset must be spent by some input; this also rules out
+            // zero-value outputs of an asset the transfer never held
+            for (note_var, is_dummy) in notes_out_var.iter().zip(out_is_dummy.iter()) {
+                let mut funded = Boolean::FALSE;
+                for input_var in &notes_in_var {
+                    funded = funded.or(&note_var.asset_type.is_eq(&input_var.asset_type)?)?;
+                }
+                funded.conditional_enforce_equal(&Boolean::TRUE, &is_dummy.not())?;
             }
         }
         
         // Constraint 4: Range proofs for output values
+        ConstraintSections::begin(&cs, "range");
         use crate::gadgets::range_proof::RangeProofGadget;
         for note_var in &notes_out_var {
             // Use the secure bit decomposition method
             RangeProofGadget::prove_range_bits(cs.clone(), &note_var.value, 64)?;
         }
         
+        // Constraint 4b: Memo binding; an output claiming no memo must carry memo_hash 0
+        ConstraintSections::begin(&cs, "memo");
+        for (i, note_var) in notes_out_var.iter().enumerate() {
+            let memo = self.memos_out.get(i).and_then(|m| m.as_deref());
+            let has_memo = Boolean::new_witness(cs.clone(), || Ok(memo.is_some()))?;
+            let memo_var = PaddedMemoVar::new_witness(cs.clone(), memo)?;
+            memo_var.enforce_memo_hash(&has_memo, &note_var.memo_hash)?;
+        }
+        if let Some(fee_note_var) = &fee_note_var {
+            fee_note_var.memo_hash.enforce_equal(&FpVar::zero())?;
+        }
+        
         // Constraint 5: Non-membership of nullifiers in NFT_ROOT_old
+        ConstraintSections::begin(&cs, "nullifier non-membership");
         // Each nullifier must not already exist (prevent double spend)
         // Note: nft_root_old_var was already created as public input
         
@@ -461,6 +1051,7 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
         }
         
         // Constraint 6: Sanctions non-membership checks
+        ConstraintSections::begin(&cs, "sanctions");
         // Note: sanctions_root_var was already created as public input
         
         // Check sender addresses (input note owners) are not sanctioned
@@ -501,7 +1092,14 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             }
         }
         
+        // The fallback count follows from which slots carry a proof, which the circuit's
+        // shape fixes, so the verifying key commits to it
+        if let Some(ref fallbacks_var) = sanctions_fallbacks_var {
+            fallbacks_var.enforce_equal(&FpVar::constant(sanctions_fallbacks))?;
+        }
+        
         // Constraint 7: Pool policy compliance with proper Merkle membership proofs
+        ConstraintSections::begin(&cs, "pool policy");
         // Note: pool_rules_root_var was already created as public input
         
         // Verify pool IDs are valid (non-zero and within range)
@@ -544,10 +1142,13 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             // Create timestamp variable (current time)
             let timestamp = FpVar::new_witness(cs.clone(), || Ok(F::from(0u64)))?; // In practice, use actual timestamp
             
+            // A migration pays no fee, so conservation holds exactly, and follows a listed transition
+            if let Some(transition_fn) = &self.migration {
+                PoolPolicyGadget::enforce_migration(in_pool, out_pool, &fee_var, transition_fn)?;
+            }
+            
             // If we have pool policy witnesses, use full enforcement
             if !self.source_pool_policies.is_empty() && !self.dest_pool_policies.is_empty() && !self.pool_policy_paths.is_empty() {
-                use crate::gadgets::pool_policy::PoolPolicyGadget;
-                
                 // Get source and destination policies (assuming single source/dest pool)
                 let source_policy = &self.source_pool_policies[0];
                 let dest_policy = &self.dest_pool_policies[0];
@@ -567,7 +1168,38 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
                     source_path,
                     dest_path,
                 )?;
-            } else {
+                
+                // Fee floor of the source pool, waived for migrations; consolidations must prove a single owner
+                // Dummy outputs sit at the sentinel owner with zero value, so they are masked out
+                let consolidation_var = Boolean::new_witness(cs.clone(), || Ok(self.consolidation))?;
+                let owner_addrs: Vec<(FpVar<F>, Boolean<F>)> = notes_in_var
+                    .iter()
+                    .map(|n| (n.owner_addr.clone(), Boolean::FALSE))
+                    .chain(notes_out_var.iter().zip(out_is_dummy.iter()).map(|(n, is_dummy)| (n.owner_addr.clone(), is_dummy.clone())))
+                    .collect();
+                let output_values: Vec<FpVar<F>> = notes_out_var
+                    .iter()
+                    .map(|n| n.value.clone())
+                    .collect();
+                if self.migration.is_none() {
+                    let flagged_values: Vec<(FpVar<F>, Boolean<F>)> = output_values
+                        .iter()
+                        .cloned()
+                        .zip(out_is_dummy.iter().cloned())
+                        .collect();
+                    PoolPolicyGadget::enforce_fee_floor(
+                        cs.clone(),
+                        &fee_var,
+                        &consolidation_var,
+                        &owner_addrs,
+                        &flagged_values,
+                        source_policy,
+                    )?;
+                }
+                
+                // Aggregate cap of the source pool on the total moved
+                PoolPolicyGadget::enforce_aggregate_cap(cs.clone(), &output_values, source_policy)?;
+            } else if self.migration.is_none() {
                 // Fallback: Basic pool transfer rules when no policy witnesses provided
                 // This is for backward compatibility and testing
                 let same_pool = in_pool.is_eq(out_pool)?;
@@ -601,6 +1233,7 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
         }
         
         // Constraint 8: Compliance gates
+        ConstraintSections::begin(&cs, "compliance");
         for note_var in &notes_in_var {
             // Check note is not frozen (compliance_hash != 0 means active)
             let not_frozen = note_var.compliance_hash.is_neq(&FpVar::zero())?;
@@ -611,33 +1244,104 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             callbacks_ok.enforce_equal(&Boolean::TRUE)?;
         }
         
-        // Constraint 9: Lineage update for output notes
-        // Update lineage hash to track note history
-        for (i, note_out_var) in notes_out_var.iter().enumerate() {
-            // Collect parent lineages from input notes
-            let parent_lineages: Vec<FpVar<F>> = notes_in_var
+        // Constraint 8b: compliance_hash commits to the witnessed state, which must pass the gates
+        if self.checked_compliance {
+            if self.compliance_states_in.len() != notes_in_var.len() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            for (note_var, state) in notes_in_var.iter().zip(self.compliance_states_in.iter()) {
+                let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state.clone()))?;
+                compliance::enforce_checked_compliance(
+                    cs.clone(),
+                    &note_var.compliance_hash,
+                    &state_var,
+                    MIN_CHECKED_COMPLIANCE_LEVEL,
+                )?;
+                
+                // Constraint 8c: the note sits in a pool the owner's state permits
+                if self.check_permitted_pools {
+                    compliance::enforce_pool_permitted(&state_var, &note_var.pool_id)?;
+                }
+                
+                // Constraint 8d: the state's last review is recent enough
+                if let Some((ref current_time_var, ref max_review_age_var)) = review_window_vars {
+                    compliance::enforce_fresh_review(
+                        cs.clone(),
+                        &state_var,
+                        current_time_var,
+                        max_review_age_var,
+                    )?;
+                }
+            }
+        } else if self.require_fresh_review || self.check_permitted_pools {
+            // Checks on an unbound state prove nothing
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        
+        // Constraint 8e: freezing propagates to recipients, whose states must be their current and unfrozen
+        ConstraintSections::begin(&cs, "frozen recipients");
+        if let Some(ref owner_index_root_var) = owner_index_root_var {
+            if self.recipient_compliance.len() != notes_out_var.len() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            for ((note_var, is_dummy), (state, object, proof)) in notes_out_var
                 .iter()
-                .map(|n| n.lineage_hash.clone())
-                .collect();
-            
-            // Context could be transaction hash or block number
-            let context = FpVar::new_witness(cs.clone(), || Ok(F::from(i as u64)))?;
-            
-            // Compute expected lineage
-            let mut lineage_input = parent_lineages;
-            lineage_input.push(context);
-            let expected_lineage = poseidon_hash_zk(&lineage_input)?;
-            
-            // Verify lineage matches
-            note_out_var.lineage_hash.enforce_equal(&expected_lineage)?;
+                .zip(out_is_dummy.iter())
+                .zip(self.recipient_compliance.iter())
+            {
+                let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state.clone()))?;
+                let object_var = ZkObjectVar::new_witness(cs.clone(), || Ok(object.clone()))?;
+                let proof_var = SortedMembershipVar::new_witness(cs.clone(), || Ok(proof.clone()))?;
+                let recipient_ok = compliance::verify_recipient_not_frozen(
+                    &note_var.compliance_hash,
+                    &note_var.owner_addr,
+                    &state_var,
+                    &object_var,
+                    &proof_var,
+                    owner_index_root_var,
+                )?;
+                recipient_ok.or(is_dummy)?.enforce_equal(&Boolean::TRUE)?;
+            }
+        }
+        
+        // Constraint 9: Lineage update for output notes
+        ConstraintSections::begin(&cs, "lineage");
+        // Each output's lineage encodes its split position and the number of real outputs;
+        // dummies trail the real outputs and carry no lineage, so padding leaves it unchanged
+        let parent_lineages: Vec<FpVar<F>> = notes_in_var
+            .iter()
+            .map(|n| n.lineage_hash.clone())
+            .collect();
+        for (i, (note_out_var, is_dummy)) in notes_out_var.iter().zip(out_is_dummy.iter()).enumerate() {
+            let expected = LineageGadget::split_lineage_hash_with_count(&parent_lineages, i, &real_out_count)?;
+            note_out_var.lineage_hash.conditional_enforce_equal(&expected, &is_dummy.not())?;
         }
         
         // Constraint 10: Tree root transitions
+        ConstraintSections::begin(&cs, "root transition");
         // Note: cmt_root_new_var and nft_root_new_var were already created as public inputs
         
         // Verify CMT_ROOT update for output notes
         // First verify that the output note commitments match the public inputs
-        for (note_var, cm_var) in notes_out_var.iter().zip(cm_vars.iter()) {
+        // The fee note, if collected, is the last public commitment
+        let appended_notes: Vec<&NoteVar> = notes_out_var.iter().chain(fee_note_var.iter()).collect();
+        if appended_notes.len() != cm_vars.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        // The fee note is never a dummy
+        let appended_is_dummy: Vec<Boolean<F>> = out_is_dummy
+            .iter()
+            .cloned()
+            .chain(fee_note_var.iter().map(|_| Boolean::FALSE))
+            .collect();
+        
+        // The declared count covers every commitment appended below, so none can be hidden
+        let mut appended_count = FpVar::zero();
+        for is_dummy in &appended_is_dummy {
+            appended_count += FpVar::from(is_dummy.not());
+        }
+        n_out_var.enforce_equal(&appended_count)?;
+        for (note_var, cm_var) in appended_notes.iter().zip(cm_vars.iter()) {
             let computed_cm = note_var.commitment()?;
             computed_cm.enforce_equal(cm_var)?;
         }
@@ -651,78 +1355,35 @@ impl ConstraintSynthesizer<F> for TransferCircuit {
             // SECURITY: Proper append witnesses are REQUIRED for outputs
             return Err(SynthesisError::Unsatisfiable);
         } else if !self.cmt_appends_out.is_empty() {
-            // Use proper append witnesses with pre-insertion siblings
+            // Every public commitment, fee note included, needs its own append
+            if self.cmt_appends_out.len() != cm_vars.len() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            
+            // Append-only: the chain starts at cmt_root_old and each proof fills the tree's
+            // next free slot (its last leaf is a member, its own slot empty) with siblings
+            // shared by the old and new root, so existing leaves, inputs included, are untouched
             let mut current_cmt = cmt_root_old_var.clone();
-            for (i, cm_var) in cm_vars.iter().enumerate() {
-                if i < self.cmt_appends_out.len() {
-                    let witness = &self.cmt_appends_out[i];
-                    
-                    // Compute what the new root should be using the witness
-                    let new_root_value = witness.compute_new_root(&fluxe_core::merkle::TreeParams::new(witness.height));
-                    
-                    let append_proof = ImtAppendProofVar {
-                        old_root: current_cmt.clone(),
-                        new_root: FpVar::new_witness(cs.clone(), || Ok(new_root_value))?,
-                        leaf_index: FpVar::new_witness(cs.clone(), || Ok(F::from(witness.leaf_index as u64)))?,
-                        height: witness.height,
-                        pre_siblings: witness.pre_siblings.iter()
-                            .map(|s| FpVar::new_witness(cs.clone(), || Ok(*s)))
-                            .collect::<Result<Vec<_>, _>>()?,
-                        appended_leaf: cm_var.clone(),
-                    };
-                    
-                    // Verify the append proof is valid
-                    append_proof.enforce()?;
-                    
-                    // Update current root for next iteration
-                    current_cmt = append_proof.new_root.clone();
-                }
+            for (i, (cm_var, witness)) in cm_vars.iter().zip(self.cmt_appends_out.iter()).enumerate() {
+                let append_proof = ImtAppendProofVar::chained(cs.clone(), witness, &current_cmt, cm_var)?;
+                append_proof.enforce()?;
+                
+                // Update current root for next iteration; a dummy's append is checked but discarded
+                current_cmt = appended_is_dummy[i].select(&current_cmt, &append_proof.new_root)?;
             }
             current_cmt.enforce_equal(&cmt_root_new_var)?;
         }
         
-        // For NFT_ROOT (sorted S-IMT): Chain insertion proofs for each nullifier
-        // Each input should provide its non-membership proof
-        let mut current_nft = nft_root_old_var.clone();
-        
-        // Use proper SortedInsertWitness for each nullifier
-        for (i, nf_var) in nf_vars.iter().enumerate() {
-            if i < self.nf_insert_witnesses.len() {
-                // Use the properly generated insert witness
-                let insert_witness = &self.nf_insert_witnesses[i];
-                
-                // Create the insert gadget with the witness
-                use crate::gadgets::sorted_insert::SimtInsertVar;
-                
-                // Calculate the new root after this insertion
-                // Use the correct tree height from the witness
-                let tree_params = fluxe_core::merkle::TreeParams::new(insert_witness.height);
-                let new_root_value = insert_witness.compute_new_root(&tree_params);
-                
-                let insert_gadget = SimtInsertVar::new_witness(
-                    cs.clone(),
-                    insert_witness.clone(),
-                    current_nft.value()?,
-                    new_root_value,
-                )?;
-                
-                // Verify the insertion matches our nullifier
-                insert_gadget.target.enforce_equal(nf_var)?;
-                
-                // Verify old root matches current state
-                insert_gadget.old_root.enforce_equal(&current_nft)?;
-                
-                // Verify the insertion is valid
-                insert_gadget.enforce()?;
-                
-                // Update current root for next iteration
-                current_nft = insert_gadget.new_root.clone();
-            } else {
-                // SECURITY: Proper insertion witness is REQUIRED
-                return Err(SynthesisError::Unsatisfiable);
-            }
-        }
-        current_nft.enforce_equal(&nft_root_new_var)?;
+        // For NFT_ROOT (sorted S-IMT): Chain one insertion proof per nullifier and nothing else
+        // A zero-input transfer nullifies nothing, so the root must not move
+        use crate::gadgets::sorted_insert::SimtInsertVar;
+        SimtInsertVar::enforce_chain(
+            cs.clone(),
+            &nft_root_old_var,
+            &nft_root_new_var,
+            &nf_vars,
+            &self.nf_insert_witnesses,
+        )?;
         
         Ok(())
     }
@@ -745,13 +1406,54 @@ impl FluxeCircuit for TransferCircuit {
         // Add output commitments
         inputs.extend(&self.cm_list);
         
+        // Add declared output count
+        inputs.push(F::from(self.n_out));
+        
         // Add fee
         inputs.push(self.fee.to_field());
         
+        // Add review window
+        if self.require_fresh_review {
+            inputs.push(F::from(self.current_time));
+            inputs.push(F::from(self.max_review_age));
+        }
+        
+        // Add accepted recent CMT roots
+        inputs.extend(&self.recent_cmt_roots);
+        
+        // Add per-asset fee slots
+        for (asset_type, fee) in self.asset_fee_slots() {
+            inputs.push(F::from(asset_type as u64));
+            inputs.push(fee.to_field());
+        }
+        
+        // Add recipient owner index root
+        if self.block_frozen_recipients {
+            inputs.push(self.owner_index_root);
+        }
+        
+        // Add sanctions fallback count
+        if self.expose_sanctions_fallbacks {
+            inputs.push(F::from(self.sanctions_fallbacks()));
+        }
+        
         inputs
     }
     
     fn verify_public_inputs(&self) -> Result<(), FluxeError> {
+        // Reject oversized transfers before any other work
+        if self.notes_out.len() > MAX_OUTPUTS {
+            return Err(FluxeError::Other(format!(
+                "Transfer has {} outputs, maximum is {}",
+                self.notes_out.len(),
+                MAX_OUTPUTS
+            )));
+        }
+        
+        if self.fee.value() > u64::MAX as u128 {
+            return Err(FluxeError::Other(format!("Fee {} exceeds 64 bits", self.fee.value())));
+        }
+        
         // Verify value conservation
         let sum_in: u128 = self.values_in.iter().map(|&v| v as u128).sum();
         let sum_out: u128 = self.values_out.iter().map(|&v| v as u128).sum();
@@ -765,10 +1467,227 @@ impl FluxeCircuit for TransferCircuit {
             return Err(FluxeError::Other("Input/nullifier count mismatch".to_string()));
         }
         
-        if self.notes_out.len() != self.cm_list.len() {
+        if self.notes_out.len() + self.fee_note.is_some() as usize != self.cm_list.len() {
             return Err(FluxeError::Other("Output/commitment count mismatch".to_string()));
         }
         
+        // Dummy outputs pad the shape after the real ones and are not appended
+        let dummies = self.notes_out.iter().filter(|note| note.is_dummy()).count();
+        if self.notes_out[..self.notes_out.len() - dummies].iter().any(Note::is_dummy) {
+            return Err(FluxeError::Other("Dummy outputs must follow every real output".to_string()));
+        }
+        let appended = self.cm_list.len() - dummies;
+        if self.n_out != appended as u64 {
+            return Err(FluxeError::Other(format!(
+                "Declared {} outputs but {} commitments are appended",
+                self.n_out,
+                appended
+            )));
+        }
+        
+        if self.memos_out.len() > self.notes_out.len() {
+            return Err(FluxeError::Other("More memos than outputs".to_string()));
+        }
+        for (i, note) in self.notes_out.iter().enumerate() {
+            let expected = match self.memos_out.get(i).and_then(|m| m.as_deref()) {
+                Some(memo) => MemoEncryption::compute_circuit_memo_hash(memo),
+                None => Some(F::from(0u64)),
+            };
+            if expected != Some(note.memo_hash) {
+                return Err(FluxeError::Other(format!("Output {} memo_hash does not match its memo", i)));
+            }
+        }
+        if self.fee_note.as_ref().map_or(false, |(note, _)| note.memo_hash != F::from(0u64)) {
+            return Err(FluxeError::Other("Fee note must not carry a memo".to_string()));
+        }
+        
+        if self.asset_fees.is_empty() {
+            // One asset type across inputs, outputs and the fee note, even without inputs
+            let mut asset_types = self.notes_in
+                .iter()
+                .chain(self.notes_out.iter())
+                .chain(self.fee_note.iter().map(|(note, _)| note))
+                .map(|note| note.asset_type);
+            if let Some(first) = asset_types.next() {
+                if asset_types.any(|asset_type| asset_type != first) {
+                    return Err(FluxeError::Other("Transfer mixes asset types".to_string()));
+                }
+            }
+        } else {
+            self.check_asset_fees()?;
+            
+            // Per-asset conservation over the listed assets, which must cover every input
+            let real_out = self.notes_out.iter().zip(self.values_out.iter()).filter(|(note, _)| !note.is_dummy());
+            for (note, _) in real_out.clone() {
+                if !self.notes_in.iter().any(|input| input.asset_type == note.asset_type) {
+                    return Err(FluxeError::Other(format!(
+                        "Output asset {} has no matching input",
+                        note.asset_type
+                    )));
+                }
+            }
+            for note in &self.notes_in {
+                if !self.asset_fees.iter().any(|(asset_type, _)| *asset_type == note.asset_type) {
+                    return Err(FluxeError::Other(format!("Input asset {} has no fee slot", note.asset_type)));
+                }
+            }
+            for (asset_type, fee) in &self.asset_fees {
+                let sum_in: u128 = self.notes_in
+                    .iter()
+                    .zip(self.values_in.iter())
+                    .filter(|(note, _)| note.asset_type == *asset_type)
+                    .map(|(_, &v)| v as u128)
+                    .sum();
+                let sum_out: u128 = real_out
+                    .clone()
+                    .filter(|(note, _)| note.asset_type == *asset_type)
+                    .map(|(_, &v)| v as u128)
+                    .sum();
+                if Amount::from(sum_in) != Amount::from(sum_out) + *fee {
+                    return Err(FluxeError::InsufficientBalance);
+                }
+            }
+        }
+        
+        if self.notes_in.is_empty() && self.nft_root_new != self.nft_root_old {
+            return Err(FluxeError::Other("Zero-input transfer must leave the NFT root unchanged".to_string()));
+        }
+        
+        self.check_nullifier_order()?;
+        
+        // Mirrors the in-circuit opening, so a mismatch is reported before proving
+        if self.derived_randomness {
+            let owner_sk = self.owner_sks.first()
+                .ok_or_else(|| FluxeError::Other("Derived randomness needs an input owner key".to_string()))?;
+            let params = PedersenParams::setup_value_commitment();
+            for (i, note) in self.notes_out.iter().enumerate().filter(|(_, note)| !note.is_dummy()) {
+                let r = Note::derive_value_randomness(owner_sk, &note.psi);
+                let opens = self.value_randomness_out.get(i) == Some(&r)
+                    && self.values_out.get(i).map_or(false, |&value| {
+                        PedersenCommitment::commit(&params, value, &PedersenRandomness { r }) == note.v_comm
+                    });
+                if !opens {
+                    return Err(FluxeError::Other(format!(
+                        "Output {} value commitment does not use derived randomness",
+                        i
+                    )));
+                }
+            }
+        }
+        
+        if let Some(min_denom) = self.min_denomination {
+            for (i, _) in self.notes_out.iter().enumerate().filter(|(_, note)| !note.is_dummy()) {
+                if self.values_out.get(i).map_or(true, |&value| value < min_denom) {
+                    return Err(FluxeError::Other(format!(
+                        "Output {} is below the minimum denomination {}",
+                        i, min_denom
+                    )));
+                }
+            }
+        }
+        
+        if !self.recent_cmt_roots.is_empty() {
+            if self.cm_path_roots.len() != self.notes_in.len() {
+                return Err(FluxeError::Other("Input/path root count mismatch".to_string()));
+            }
+            if self.cm_path_roots.iter().any(|root| !self.recent_cmt_roots.contains(root)) {
+                return Err(FluxeError::InvalidMerklePath);
+            }
+        }
+        
+        if self.require_fresh_review {
+            if !self.checked_compliance {
+                return Err(FluxeError::Other("Fresh review check requires checked compliance".to_string()));
+            }
+            for state in &self.compliance_states_in {
+                if !state.review_is_fresh(self.current_time, self.max_review_age) {
+                    return Err(FluxeError::ComplianceViolation(format!(
+                        "Compliance review at {} is older than {} at {}",
+                        state.last_review_time, self.max_review_age, self.current_time
+                    )));
+                }
+            }
+        }
+        
+        if let Some(transition_fn) = &self.migration {
+            if self.fee.value() != 0 || self.fee_note.is_some() {
+                return Err(FluxeError::Other("Migration must not pay a fee".to_string()));
+            }
+            if sum_in != sum_out {
+                return Err(FluxeError::Other("Migration must preserve value exactly".to_string()));
+            }
+            let pools = self.notes_in.first().zip(self.notes_out.first());
+            if !pools.map_or(false, |(from, to)| transition_fn.allows_migration(from.pool_id, to.pool_id)) {
+                return Err(FluxeError::Other("Migration does not follow a listed pool transition".to_string()));
+            }
+        }
+        
+        if self.block_frozen_recipients {
+            if self.recipient_compliance.len() != self.notes_out.len() {
+                return Err(FluxeError::Other("Output/recipient compliance count mismatch".to_string()));
+            }
+            for (i, (note, (state, object, proof))) in self.notes_out.iter().zip(self.recipient_compliance.iter()).enumerate() {
+                if note.is_dummy() {
+                    continue;
+                }
+                if note.compliance_hash != state.hash() || object.state_hash != state.hash() {
+                    return Err(FluxeError::Other(format!("Output {} is not bound to its recipient's compliance state", i)));
+                }
+                let object_cm = poseidon_hash(&[object.state_hash, F::from(object.serial), object.cb_head_hash, object.owner_addr]);
+                if proof.leaf.key != ZkObject::owner_index_key(note.owner_addr, object_cm) {
+                    return Err(FluxeError::Other(format!("Output {}'s recipient object is not indexed under its owner", i)));
+                }
+                if object.owner_addr != note.owner_addr {
+                    return Err(FluxeError::Other(format!("Output {}'s recipient object belongs to another owner", i)));
+                }
+                if state.frozen {
+                    return Err(FluxeError::ComplianceViolation(format!("Recipient of output {} is frozen", i)));
+                }
+            }
+        }
+        
+        if self.check_permitted_pools {
+            if !self.checked_compliance {
+                return Err(FluxeError::Other("Permitted pools check requires checked compliance".to_string()));
+            }
+            for (note, state) in self.notes_in.iter().zip(self.compliance_states_in.iter()) {
+                if !state.is_pool_permitted(note.pool_id) {
+                    return Err(FluxeError::ComplianceViolation(format!(
+                        "Pool {} is not permitted by the owner's compliance state",
+                        note.pool_id
+                    )));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check every witness path has one sibling per level of its configured tree
+    /// Pool policy paths are in-circuit variables sized by the pool rules tree, not the config
+    fn check_tree_heights(&self) -> Result<(), TreeError> {
+        let config = match &self.tree_config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        for path in self.cm_paths.iter().chain(self.cmt_paths_out.iter()) {
+            TreeConfig::check_siblings("CMT", config.cmt, path.siblings.len())?;
+        }
+        for witness in &self.cmt_appends_out {
+            witness.check_height("CMT", config.cmt)?;
+        }
+        for proof in self.nf_nonmembership_proofs.iter().chain(self.nf_nonmembership.iter()).flatten() {
+            TreeConfig::check_siblings("NFT", config.nft, proof.low_path.siblings.len())?;
+        }
+        for witness in &self.nf_insert_witnesses {
+            witness.check_height("NFT", config.nft)?;
+        }
+        for proof in self.sanctions_nm_proofs_in.iter().chain(self.sanctions_nm_proofs_out.iter()).flatten() {
+            TreeConfig::check_siblings("sanctions", config.sanctions, proof.low_path.siblings.len())?;
+        }
+        for (_, _, proof) in &self.recipient_compliance {
+            TreeConfig::check_siblings("owner index", config.object, proof.path.siblings.len())?;
+        }
         Ok(())
     }
 }
\ No newline at end of file
@@ -10,8 +10,9 @@ use fluxe_circuits::{
 };
 use fluxe_core::{
     crypto::{
-        poseidon_hash, 
+        poseidon_hash, owner_address_from_pk,
         blake2b_hash,
+        compute_split_lineage_hash, SplitProvenance,
         pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness},
     },
     data_structures::{Note, ComplianceState, ZkObject, IngressReceipt, ExitReceipt},
@@ -118,6 +119,12 @@ fn test_mint_circuit_basic() {
     let mut cmt_tree = IncrementalTree::new(16);
     let mut ingress_tree = IncrementalTree::new(16);
     
+    // Authorize a minter key
+    let minter_sk = F::rand(&mut rng);
+    let (minter_pk_x, minter_pk_y) = fluxe_core::crypto::compute_ec_public_key(minter_sk);
+    let mut authorized_minters = fluxe_core::merkle::SortedTree::new(16);
+    authorized_minters.insert(owner_address_from_pk(minter_pk_x, minter_pk_y)).unwrap();
+    
     let circuit = MintCircuit::new(
         vec![note_out],
         vec![value],
@@ -125,7 +132,9 @@ fn test_mint_circuit_basic() {
         ingress_receipt,
         &mut cmt_tree,
         &mut ingress_tree,
-    );
+    )
+    .with_minter(minter_sk, &authorized_minters)
+    .unwrap();
     
     // Test constraint generation
     let cs = ConstraintSystem::<F>::new_ref();
@@ -148,25 +157,28 @@ fn test_mint_circuit_basic() {
         println!("  asset_type: {:?}", public_inputs[4]);
         println!("  amount: {:?}", public_inputs[5]);
         println!("  cm_out_list_commit: {:?}", public_inputs[6]);
+        println!("  minter_pk: ({:?}, {:?})", public_inputs[7], public_inputs[8]);
+        println!("  authorized_minters_root: {:?}", public_inputs[9]);
     }
     assert!(satisfied, "Mint circuit constraints not satisfied");
     
     // Test public inputs
     let public_inputs = circuit.public_inputs();
-    assert_eq!(public_inputs.len(), 7);
+    assert_eq!(public_inputs.len(), 10);
     assert_eq!(public_inputs[4], F::from(asset_type as u64)); // asset_type
     assert_eq!(public_inputs[5], amount.to_field()); // amount
+    assert_eq!((public_inputs[7], public_inputs[8]), (minter_pk_x, minter_pk_y)); // minter_pk
+    assert_eq!(public_inputs[9], authorized_minters.root()); // authorized_minters_root
 }
 
-#[test]
-fn test_burn_circuit_basic() {
+/// Build a burn circuit over dummy roots with the given exit receipt amount
+fn build_test_burn_circuit(exit_amount: Amount) -> BurnCircuit {
     let mut rng = thread_rng();
     let params = PedersenParams::setup_value_commitment();
     
     // Create test data
     let asset_type = 1u32;
     let value = 500u64;
-    let amount = Amount::from(value);
     
     // Create input note
     let randomness = F::rand(&mut rng);
@@ -202,7 +214,7 @@ fn test_burn_circuit_basic() {
     let (pk_x, pk_y) = fluxe_core::crypto::compute_ec_public_key(owner_sk);
     
     // Update note to use consistent owner address
-    note_in.owner_addr = poseidon_hash(&[pk_x, pk_y]);
+    note_in.owner_addr = owner_address_from_pk(pk_x, pk_y);
     
     // Create merkle path
     let cm_path = MerklePath {
@@ -216,12 +228,18 @@ fn test_burn_circuit_basic() {
     let nf = poseidon_hash(&[nk, psi_field, note_in.commitment()]);
     let exit_receipt = ExitReceipt {
         asset_type,
-        amount,
+        amount: exit_amount,
         burned_nf: nf,
         nonce: 1,
         aux: F::from(0u64),
+        exit_destination: F::from(7u64),
     };
     
+    // Approve the exit destination
+    let mut exit_allow_tree = fluxe_core::merkle::SortedTree::new(16);
+    exit_allow_tree.insert(exit_receipt.exit_destination).unwrap();
+    let exit_allow_proof = exit_allow_tree.prove_key_membership(exit_receipt.exit_destination);
+    
     // Create non-membership proof for the nullifier
     use fluxe_core::merkle::{SortedLeaf, RangePath};
     let nm_proof = RangePath {
@@ -247,10 +265,10 @@ fn test_burn_circuit_basic() {
         leaf: exit_receipt.hash(),
         pre_siblings: vec![F::from(0u64); 32],
         height: 32,
+        last_leaf_path: None,
     };
     
-    // Create circuit
-    let circuit = BurnCircuit::new(
+    BurnCircuit::new(
         note_in,
         value,
         randomness,
@@ -263,12 +281,19 @@ fn test_burn_circuit_basic() {
         Some(insert_witness), // nf_insert_witness
         exit_receipt,
         exit_append_witness,
+        exit_allow_proof,
         F::rand(&mut rng), // cmt_root
         F::rand(&mut rng), // nft_root_old
         F::rand(&mut rng), // nft_root_new
         F::rand(&mut rng), // exit_root_old
         F::rand(&mut rng), // exit_root_new
-    );
+        exit_allow_tree.root(),
+    )
+}
+
+#[test]
+fn test_burn_circuit_basic() {
+    let circuit = build_test_burn_circuit(Amount::from(500u64));
     
     // Test constraint generation
     let cs = ConstraintSystem::<F>::new_ref();
@@ -280,7 +305,86 @@ fn test_burn_circuit_basic() {
     
     // Test public inputs
     let public_inputs = circuit.public_inputs();
-    assert_eq!(public_inputs.len(), 8);
+    assert_eq!(public_inputs.len(), 10);
+    assert_eq!(public_inputs[8], circuit.exit_allow_root);
+    assert_eq!(public_inputs[9], circuit.note_in.commitment());
+}
+
+#[test]
+fn test_burn_exit_destination_allowlist() {
+    use fluxe_core::merkle::{SortedTree, TreeParams};
+    
+    // Approved: the proof for the receipt's destination checks out against EXIT_ALLOW_ROOT
+    let circuit = build_test_burn_circuit(Amount::from(500u64));
+    let dest = circuit.exit_receipt.exit_destination;
+    let proof = circuit.exit_allow_proof.clone().unwrap();
+    assert!(proof.verify(&dest, &circuit.exit_allow_root, &TreeParams::new(16)));
+    assert!(circuit.verify_public_inputs().is_ok());
+    
+    // Not approved: no membership proof exists, and the circuit refuses to synthesize without one
+    let mut other_tree = SortedTree::new(16);
+    other_tree.insert(F::from(8u64)).unwrap();
+    assert!(other_tree.prove_key_membership(dest).is_none());
+    
+    let mut unapproved = circuit.clone();
+    unapproved.exit_allow_root = other_tree.root();
+    unapproved.exit_allow_proof = None;
+    assert!(unapproved.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    assert!(unapproved.generate_constraints(cs).is_err());
+}
+
+#[test]
+fn test_burn_rejects_out_of_range_exit_amount() {
+    // Exit receipt amount wider than 64 bits
+    let circuit = build_test_burn_circuit(Amount::new((1u128 << 64) + 500));
+    
+    let cs = ConstraintSystem::<F>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    let diagnosis = BurnCircuit::diagnose(&cs).expect("Should diagnose the failure");
+    assert!(diagnosis.contains("'amount range'"), "Unexpected failure: {}", diagnosis);
+    
+    // In range, the same circuit gets past the range check
+    let circuit = build_test_burn_circuit(Amount::from(500u64));
+    let cs = ConstraintSystem::<F>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    let diagnosis = BurnCircuit::diagnose(&cs).unwrap_or_default();
+    assert!(!diagnosis.contains("'amount range'"), "Unexpected failure: {}", diagnosis);
+}
+
+#[test]
+fn test_transfer_rejects_too_many_outputs() {
+    use fluxe_circuits::transfer::MAX_OUTPUTS;
+    
+    let params = PedersenParams::setup_value_commitment();
+    let r = F::from(1u64);
+    let v_comm = PedersenCommitment::commit(&params, 1, &PedersenRandomness { r });
+    let n = MAX_OUTPUTS + 1;
+    let notes_out: Vec<Note> = (0..n)
+        .map(|i| Note::new(1, v_comm.clone(), F::from(i as u64 + 1), [i as u8; 32], 1))
+        .collect();
+    
+    let circuit = TransferCircuit::new(
+        vec![], vec![], vec![],
+        notes_out, vec![1; n], vec![r; n],
+        vec![], vec![], vec![], vec![], vec![], vec![], vec![],
+        vec![], vec![], vec![],
+        F::from(0u64), F::from(0u64), F::from(0u64), F::from(0u64), F::from(0u64), F::from(0u64),
+        Amount::zero(),
+    );
+    
+    // Off-circuit: clear error before any proving work
+    match circuit.verify_public_inputs() {
+        Err(FluxeError::Other(msg)) => {
+            assert_eq!(msg, format!("Transfer has {} outputs, maximum is {}", n, MAX_OUTPUTS));
+        }
+        other => panic!("expected output cap error, got {:?}", other),
+    }
+    
+    // In-circuit: synthesis refuses the oversized shape
+    let cs = ConstraintSystem::<F>::new_ref();
+    assert!(circuit.generate_constraints(cs).is_err());
 }
 
 #[test]
@@ -310,7 +414,7 @@ fn test_transfer_circuit_basic() {
         // Create consistent EC authentication using real Jubjub curve
         let owner_sk = F::from((i + 1) as u64);
         let (pk_x, pk_y) = fluxe_core::crypto::compute_ec_public_key(owner_sk);
-        let owner_addr = poseidon_hash(&[pk_x, pk_y]);
+        let owner_addr = owner_address_from_pk(pk_x, pk_y);
         
         let psi_hash = blake2b_hash(&[i as u8, i as u8 + 1, i as u8 + 2]);
         let mut psi = [0u8; 32];
@@ -387,10 +491,11 @@ fn test_transfer_circuit_basic() {
         let mut psi = [0u8; 32];
         psi.copy_from_slice(&psi_hash[..32]);
         
-        // Compute lineage hash from parent lineages and context
-        let mut lineage_input = parent_lineages.clone();
-        lineage_input.push(F::from(i as u64)); // context
-        let lineage_hash = poseidon_hash(&lineage_input);
+        // Compute lineage hash from parent lineages and split position
+        let lineage_hash = compute_split_lineage_hash(
+            &parent_lineages,
+            SplitProvenance::new(i as u32, 2).unwrap(),
+        );
         
         let note = Note {
             asset_type: 1,
@@ -465,8 +570,29 @@ fn test_transfer_circuit_basic() {
         sanctions_root: F::rand(&mut rng),
         pool_rules_root: F::rand(&mut rng),
         nf_list,
+        n_out: cm_list.len() as u64,
         cm_list,
         fee: Amount::from(10u64),
+        consolidation: false,
+        checked_compliance: false,
+        compliance_states_in: Vec::new(),
+        require_fresh_review: false,
+        current_time: 0,
+        max_review_age: 0,
+        fee_note: None,
+        recent_cmt_roots: Vec::new(),
+        cm_path_roots: Vec::new(),
+        memos_out: Vec::new(),
+        derived_randomness: false,
+        min_denomination: None,
+        asset_fees: Vec::new(),
+        check_permitted_pools: false,
+        migration: None,
+        tree_config: None,
+        block_frozen_recipients: false,
+        recipient_compliance: Vec::new(),
+        owner_index_root: F::from(0u64),
+        expose_sanctions_fallbacks: false,
     };
     
     // Test constraint generation
@@ -495,12 +621,14 @@ fn test_object_update_circuit_basic() {
         state_hash: state_old.hash(),
         serial: 100,
         cb_head_hash: F::rand(&mut rng),
+        owner_addr: F::rand(&mut rng),
     };
     
     let obj_new = ZkObject {
         state_hash: state_new.hash(),
         serial: 101,
         cb_head_hash: obj_old.cb_head_hash,
+        owner_addr: obj_old.owner_addr,
     };
     
     // Create merkle path for old object
@@ -535,4 +663,195 @@ fn test_object_update_circuit_basic() {
     // Test public inputs
     let public_inputs = circuit.public_inputs();
     assert_eq!(public_inputs.len(), 4);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_object_update_freeze_escalation() {
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+    use fluxe_circuits::gadgets::{compliance, ComplianceStateVar};
+    use fluxe_circuits::transfer::MIN_CHECKED_COMPLIANCE_LEVEL;
+    use fluxe_core::merkle::IncrementalTree;
+    
+    let owner_addr = F::from(4242u64);
+    let state_old = ComplianceState::new_verified(2);
+    let obj_old = ZkObject::new(&state_old).with_owner(owner_addr);
+    
+    // Native escalation: frozen state, bumped serial
+    let mut state_new = state_old.clone();
+    let mut obj_new = obj_old.clone();
+    obj_new.apply_freeze(&mut state_new);
+    assert!(state_new.frozen);
+    assert_eq!(obj_new.serial, obj_old.serial + 1);
+    assert_eq!(obj_new.state_hash, state_new.hash());
+    
+    // Old object sits in OBJ_ROOT under the circuit's object commitment
+    let obj_cm = |obj: &ZkObject| poseidon_hash(&[obj.state_hash, F::from(obj.serial), obj.cb_head_hash, obj.owner_addr]);
+    let mut obj_tree = IncrementalTree::new(16);
+    let obj_root_old = obj_tree.append_returning_root(obj_cm(&obj_old));
+    
+    let circuit = ObjectUpdateCircuit::new(
+        obj_old.clone(),
+        state_old.clone(),
+        obj_new.clone(),
+        state_new.clone(),
+        None, None, None, None,
+        obj_tree.get_path(0).unwrap(),
+        None,
+        obj_root_old,
+        poseidon_hash(&[obj_root_old, obj_cm(&obj_new)]),
+        F::from(0u64),
+        2000,
+    ).with_freeze(owner_addr);
+    
+    assert!(circuit.verify_public_inputs().is_ok());
+    assert_eq!(circuit.public_inputs().len(), 5);
+    assert_eq!(circuit.public_inputs()[4], owner_addr);
+    let cs = ConstraintSystem::<F>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap(), "Freeze escalation should be satisfied");
+    
+    // Freezing under another owner than the one the object commits to is rejected
+    let mut misattributed = circuit.clone();
+    misattributed.owner = Some(F::from(4243u64));
+    assert!(misattributed.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    misattributed.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    
+    // So is handing the object to another owner along the way
+    let mut reassigned = circuit.clone();
+    reassigned.obj_new.owner_addr = F::from(4243u64);
+    reassigned.obj_root_new = poseidon_hash(&[obj_root_old, obj_cm(&reassigned.obj_new)]);
+    assert!(reassigned.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    reassigned.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    
+    // An escalation that leaves the owner unfrozen is rejected
+    let mut unfrozen = circuit;
+    unfrozen.state_new = state_old.clone();
+    unfrozen.obj_new.state_hash = state_old.hash();
+    unfrozen.obj_root_new = poseidon_hash(&[obj_root_old, obj_cm(&unfrozen.obj_new)]);
+    assert!(unfrozen.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    unfrozen.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    
+    // A later transfer bound to the frozen state fails the compliance gate
+    let cs = ConstraintSystem::<F>::new_ref();
+    let hash_var = FpVar::new_witness(cs.clone(), || Ok(state_new.hash())).unwrap();
+    let state_var = ComplianceStateVar::new_witness(cs.clone(), || Ok(state_new.clone())).unwrap();
+    compliance::enforce_checked_compliance(cs.clone(), &hash_var, &state_var, MIN_CHECKED_COMPLIANCE_LEVEL).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_object_update_callback_ops_cap() {
+    use fluxe_core::merkle::IncrementalTree;
+    
+    let state_old = ComplianceState::new_verified(2);
+    let obj_old = ZkObject::new(&state_old);
+    let state_new = state_old.clone();
+    let mut obj_new = obj_old.clone();
+    obj_new.serial += 1;
+    
+    let obj_cm = |obj: &ZkObject| poseidon_hash(&[obj.state_hash, F::from(obj.serial), obj.cb_head_hash, obj.owner_addr]);
+    let mut obj_tree = IncrementalTree::new(16);
+    let obj_root_old = obj_tree.append_returning_root(obj_cm(&obj_old));
+    
+    let circuit = ObjectUpdateCircuit::new(
+        obj_old.clone(),
+        state_old,
+        obj_new.clone(),
+        state_new,
+        None, None, None, None,
+        obj_tree.get_path(0).unwrap(),
+        None,
+        obj_root_old,
+        poseidon_hash(&[obj_root_old, obj_cm(&obj_new)]),
+        F::from(0u64),
+        2000,
+    );
+    
+    // A smaller set is padded with no-ops to the fixed shape
+    let ops: Vec<CallbackOperation> = (0..2).map(|i| CallbackOperation::Process(F::from(i as u64))).collect();
+    let padded = circuit.clone().with_callback_ops(&ops);
+    assert!(padded.verify_public_inputs().is_ok());
+    let inputs = padded.public_inputs();
+    assert_eq!(inputs.len(), 4 + MAX_CALLBACK_OPS);
+    assert_eq!(inputs[4..], CallbackOperation::padded_hashes(&ops, MAX_CALLBACK_OPS).unwrap()[..]);
+    let cs = ConstraintSystem::<F>::new_ref();
+    padded.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_instance_variables(), 1 + 4 + MAX_CALLBACK_OPS);
+    
+    // Exceeding the cap is rejected before and during synthesis
+    let ops: Vec<CallbackOperation> = (0..=MAX_CALLBACK_OPS).map(|i| CallbackOperation::Process(F::from(i as u64))).collect();
+    let oversized = circuit.with_callback_ops(&ops);
+    assert!(oversized.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    assert!(oversized.generate_constraints(cs).is_err());
+    
+    println!("✓ Object update callback ops cap test passed");
+}
+
+#[test]
+fn test_object_update_public_input_layout() {
+    use fluxe_circuits::object_update::ObjectUpdateInput;
+    use fluxe_core::merkle::IncrementalTree;
+    use std::collections::HashSet;
+    
+    let state_old = ComplianceState::new_verified(2);
+    let obj_old = ZkObject::new(&state_old);
+    let state_new = state_old.clone();
+    let mut obj_new = obj_old.clone();
+    obj_new.serial += 1;
+    
+    let obj_cm = |obj: &ZkObject| poseidon_hash(&[obj.state_hash, F::from(obj.serial), obj.cb_head_hash, obj.owner_addr]);
+    let mut obj_tree = IncrementalTree::new(16);
+    let obj_root_old = obj_tree.append_returning_root(obj_cm(&obj_old));
+    
+    let base = ObjectUpdateCircuit::new(
+        obj_old.clone(),
+        state_old,
+        obj_new.clone(),
+        state_new,
+        None, None, None, None,
+        obj_tree.get_path(0).unwrap(),
+        None,
+        obj_root_old,
+        poseidon_hash(&[obj_root_old, obj_cm(&obj_new)]),
+        F::from(0u64),
+        2000,
+    );
+    let ops: Vec<CallbackOperation> = (0..2).map(|i| CallbackOperation::Process(F::from(i as u64))).collect();
+    
+    let shapes = vec![
+        base.clone(),
+        base.clone().with_freeze(F::from(7u64)),
+        base.clone().with_callback_ops(&ops),
+        base.clone().with_freeze(F::from(7u64)).with_callback_ops(&ops),
+    ];
+    for circuit in shapes {
+        let layout = circuit.public_input_layout();
+        assert_eq!(layout.len(), circuit.public_inputs().len());
+        let names: HashSet<String> = layout.iter().map(|slot| slot.name()).collect();
+        assert_eq!(names.len(), layout.len(), "public input names must be unique");
+        assert_eq!(layout[..4], [
+            ObjectUpdateInput::ObjRootOld,
+            ObjectUpdateInput::ObjRootNew,
+            ObjectUpdateInput::CbRoot,
+            ObjectUpdateInput::CurrentTime,
+        ]);
+    }
+    
+    // Both optional groups present: the owner precedes the callback hashes
+    let full = base.with_freeze(F::from(7u64)).with_callback_ops(&ops);
+    let layout = full.public_input_layout();
+    assert_eq!(layout.len(), 5 + MAX_CALLBACK_OPS);
+    assert_eq!(layout[4], ObjectUpdateInput::Owner);
+    assert_eq!(layout[5], ObjectUpdateInput::CallbackOpHash(0));
+    assert_eq!(full.public_inputs()[4], F::from(7u64));
+    
+    println!("✓ Object update public input layout test passed");
+}
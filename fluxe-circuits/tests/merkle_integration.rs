@@ -5,11 +5,12 @@ use ark_std::rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 
 use fluxe_core::{
-    merkle::{IncrementalTree, SortedTree, MerklePath, AppendWitness},
+    merkle::{IncrementalTree, SortedTree, MerklePath, MerkleTree, AppendWitness, TreeConfig},
     data_structures::{Note, ExitReceipt},
     crypto::{
         pedersen::{PedersenParams, PedersenCommitment, PedersenRandomness},
-        compute_ec_public_key, poseidon_hash,
+        compute_ec_public_key, owner_address_from_pk, poseidon_hash,
+        compute_split_lineage_hash, SplitProvenance,
     },
     types::*,
 };
@@ -55,7 +56,7 @@ fn generate_sorted_insert_witness(
     let new_leaf_path = MerklePath {
         leaf_index: new_leaf_index,
         leaf: new_leaf.hash(),
-        siblings: vec![F::from(0); 16], // Height 16, siblings would be computed properly
+        siblings: vec![F::from(0); tree.height()], // Siblings would be computed properly
     };
     
     Ok(SortedInsertWitness::new(
@@ -65,7 +66,7 @@ fn generate_sorted_insert_witness(
         updated_pred_leaf,
         new_leaf_path,
         pred_update_path,
-        16, // Fixed height
+        tree.height(),
     ))
 }
 
@@ -93,7 +94,7 @@ fn test_burn_with_nonmembership_proof() {
     // Create proper EC authentication
     let owner_sk = F::from(42u64);  // Deterministic for testing
     let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
-    let owner_addr = poseidon_hash(&[pk_x, pk_y]);
+    let owner_addr = owner_address_from_pk(pk_x, pk_y);
     
     let mut note = Note::new(1, v_comm, owner_addr, [0u8; 32], 1);
     // Set non-zero compliance and callbacks hashes (required by circuit)
@@ -150,7 +151,12 @@ fn test_burn_with_nonmembership_proof() {
     );
     
     // Create exit receipt
-    let exit_receipt = ExitReceipt::new(1, Amount::from(value as u128), new_nf, 1);
+    let exit_destination = F::from(7u64);
+    let mut exit_allow_tree = SortedTree::new(16);
+    exit_allow_tree.insert(exit_destination).unwrap();
+    let exit_allow_proof = exit_allow_tree.prove_key_membership(exit_destination);
+    let exit_receipt = ExitReceipt::new(1, Amount::from(value as u128), new_nf, 1)
+        .with_destination(exit_destination);
     
     // Both roots are already set from above
     
@@ -162,6 +168,7 @@ fn test_burn_with_nonmembership_proof() {
         new_nf,
         F::from(1u64), // nonce
         F::from(0u64), // aux field (default)
+        exit_destination,
     ]);
     let append_index = F::from(0u64);
     let binding = poseidon_hash(&[exit_root_old, exit_hash, append_index]);
@@ -173,6 +180,7 @@ fn test_burn_with_nonmembership_proof() {
         leaf: exit_hash,
         pre_siblings: vec![F::from(0u64); 32],
         height: 32,
+        last_leaf_path: None,
     };
     
     let circuit = BurnCircuit {
@@ -188,14 +196,18 @@ fn test_burn_with_nonmembership_proof() {
         nf_insert_witness: Some(insert_witness),
         exit_receipt,
         exit_append_witness,
+        exit_allow_proof,
         cmt_root: cmt_tree.root(),
         nft_root_old,
         nft_root_new,
         exit_root_old,
         exit_root_new,
+        exit_allow_root: exit_allow_tree.root(),
         asset_type: 1,
         amount: Amount::from(value as u128),
         nf_in: new_nf,
+        // The exit append is simulated at deployment height against height-16 trees
+        tree_config: Some(TreeConfig { exit: 32, ..TreeConfig::uniform(16) }),
     };
     
     // Verify circuit constraints
@@ -216,6 +228,7 @@ fn test_burn_with_nonmembership_proof() {
     println!("  asset_type: {:?}", F::from(circuit.asset_type as u64));
     println!("  amount: {:?}", circuit.amount.to_field());
     println!("  nf_in: {:?}", circuit.nf_in);
+    println!("  exit_allow_root: {:?}", circuit.exit_allow_root);
     
     circuit.generate_constraints(cs.clone()).expect("Constraint generation should succeed");
     
@@ -225,7 +238,7 @@ fn test_burn_with_nonmembership_proof() {
     
     // Verify the circuit has the expected structure
     assert!(cs.num_constraints() > 0, "Circuit should generate constraints");
-    assert_eq!(cs.num_instance_variables(), 9, "Should have 9 instance vars (8 public inputs + 1 for 'one')");
+    assert_eq!(cs.num_instance_variables(), 10, "Should have 10 instance vars (9 public inputs + 1 for 'one')");
     
     println!("✓ Burn circuit with non-membership proof verified successfully");
     println!("  Constraints: {}", cs.num_constraints());
@@ -255,7 +268,7 @@ fn test_minimal_burn() {
     let nk = F::rand(&mut rng);
     let owner_sk = F::from(42u64);
     let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
-    let owner_addr = poseidon_hash(&[pk_x, pk_y]);
+    let owner_addr = owner_address_from_pk(pk_x, pk_y);
     
     let value = 100u64;
     let randomness = F::rand(&mut rng);
@@ -295,13 +308,19 @@ fn test_minimal_burn() {
     
     // Exit roots
     let exit_root_old = F::rand(&mut rng);
-    let exit_receipt = ExitReceipt::new(1, Amount::from(value as u128), new_nf, 1);
+    let exit_destination = F::from(7u64);
+    let mut exit_allow_tree = SortedTree::new(16);
+    exit_allow_tree.insert(exit_destination).unwrap();
+    let exit_allow_proof = exit_allow_tree.prove_key_membership(exit_destination);
+    let exit_receipt = ExitReceipt::new(1, Amount::from(value as u128), new_nf, 1)
+        .with_destination(exit_destination);
     let exit_hash = poseidon_hash(&[
         F::from(1u64),
         Amount::from(value as u128).to_field(),
         new_nf,
         F::from(1u64),
         F::from(0u64),
+        exit_destination,
     ]);
     let append_index = F::from(0u64);
     let binding = poseidon_hash(&[exit_root_old, exit_hash, append_index]);
@@ -313,6 +332,7 @@ fn test_minimal_burn() {
         leaf: exit_hash,
         pre_siblings: vec![F::from(0u64); 32],
         height: 32,
+        last_leaf_path: None,
     };
     
     // Create burn circuit
@@ -329,14 +349,18 @@ fn test_minimal_burn() {
         nf_insert_witness: Some(insert_witness),
         exit_receipt,
         exit_append_witness,
+        exit_allow_proof,
         cmt_root: cmt_tree.root(),
         nft_root_old,
         nft_root_new,
         exit_root_old,
         exit_root_new,
+        exit_allow_root: exit_allow_tree.root(),
         asset_type: 1,
         amount: Amount::from(value as u128),
         nf_in: new_nf,
+        // The exit append is simulated at deployment height against height-16 trees
+        tree_config: Some(TreeConfig { exit: 32, ..TreeConfig::uniform(16) }),
     };
     
     let cs = ConstraintSystem::<F>::new_ref();
@@ -352,7 +376,7 @@ fn test_minimal_burn() {
     
     // Verify the circuit has the expected structure
     assert!(cs.num_constraints() > 0, "Circuit should generate constraints");
-    assert_eq!(cs.num_instance_variables(), 9, "Should have 9 instance vars (8 public inputs + 1 for 'one')");
+    assert_eq!(cs.num_instance_variables(), 10, "Should have 10 instance vars (9 public inputs + 1 for 'one')");
     println!("  Constraints: {}", cs.num_constraints());
     println!("  Instance vars: {}", cs.num_instance_variables());
     println!("  Witness vars: {}", cs.num_witness_variables());
@@ -397,7 +421,7 @@ fn test_transfer_with_multiple_nonmembership_proofs() {
         // Create proper EC authentication for each input
         let owner_sk = F::from((42 + i) as u64);  // Different keys for each input
         let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
-        let owner_addr = poseidon_hash(&[pk_x, pk_y]);
+        let owner_addr = owner_address_from_pk(pk_x, pk_y);
         
         let mut note = Note::new(1, v_comm, owner_addr, [0u8; 32], 1);
         // Set non-zero compliance and callbacks hashes (required by circuit)
@@ -477,8 +501,11 @@ fn test_transfer_with_multiple_nonmembership_proofs() {
         // Set proper fields for output notes
         note.compliance_hash = F::from(1u64);
         note.callbacks_hash = F::from(1u64);
-        // Lineage: hash of parent lineages (simplified for test)
-        note.lineage_hash = poseidon_hash(&[notes_in[0].lineage_hash, F::from(0u64)]);
+        // Lineage: parent lineage plus split position (single output)
+        note.lineage_hash = compute_split_lineage_hash(
+            &[notes_in[0].lineage_hash],
+            SplitProvenance::new(0, 1).unwrap(),
+        );
         
         let cm = note.commitment();
         
@@ -527,8 +554,29 @@ fn test_transfer_with_multiple_nonmembership_proofs() {
         sanctions_root: F::rand(&mut rng),
         pool_rules_root: F::rand(&mut rng),
         nf_list,
+        n_out: cm_list.len() as u64,
         cm_list,
         fee: Amount::from(20u128),
+        consolidation: false,
+        checked_compliance: false,
+        compliance_states_in: Vec::new(),
+        require_fresh_review: false,
+        current_time: 0,
+        max_review_age: 0,
+        fee_note: None,
+        recent_cmt_roots: Vec::new(),
+        cm_path_roots: Vec::new(),
+        memos_out: Vec::new(),
+        derived_randomness: false,
+        min_denomination: None,
+        asset_fees: Vec::new(),
+        check_permitted_pools: false,
+        migration: None,
+        tree_config: None,
+        block_frozen_recipients: false,
+        recipient_compliance: Vec::new(),
+        owner_index_root: F::from(0u64),
+        expose_sanctions_fallbacks: false,
     };
     
     // Verify circuit constraints
@@ -601,6 +649,316 @@ fn test_sorted_tree_gap_proofs() {
     println!("✓ Sorted tree gap proofs work correctly");
 }
 
+#[test]
+fn test_cmt_multiproof_in_circuit() {
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+    use fluxe_circuits::gadgets::merkle::MultiProofVar;
+    
+    let mut rng = ChaCha20Rng::seed_from_u64(13);
+    let mut tree = IncrementalTree::new(16);
+    let leaves: Vec<F> = (0..12).map(|_| F::rand(&mut rng)).collect();
+    tree.append_batch(&leaves);
+    
+    let proof = tree.multiproof(&[4, 5, 10]).unwrap();
+    let proven = [leaves[4], leaves[5], leaves[10]];
+    assert!(proof.verify(&tree.root(), &proven, tree.params()));
+    assert!(proof.nodes.len() < 3 * tree.height());
+    
+    let check = |proven: &[F]| {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let leaf_vars: Vec<_> = proven
+            .iter()
+            .map(|l| FpVar::new_witness(cs.clone(), || Ok(*l)).unwrap())
+            .collect();
+        let root = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+        let proof_var = MultiProofVar::new_witness(cs.clone(), &proof, leaf_vars).unwrap();
+        proof_var.enforce_valid(&root).unwrap();
+        cs.is_satisfied().unwrap()
+    };
+    
+    assert!(check(&proven));
+    assert!(!check(&[leaves[4], leaves[5], leaves[11]]));
+    
+    println!("✓ CMT multiproof verifies in-circuit with shared nodes");
+}
+
+#[test]
+fn test_burn_builder_satisfied() {
+    use fluxe_circuits::burn::BurnBuilder;
+    
+    let mut rng = ChaCha20Rng::seed_from_u64(7);
+    let params = PedersenParams::setup_value_commitment();
+    
+    let owner_sk = F::from(42u64);
+    let (pk_x, pk_y) = compute_ec_public_key(owner_sk);
+    let nk = F::rand(&mut rng);
+    
+    let value = 300u64;
+    let randomness = F::rand(&mut rng);
+    let v_comm = PedersenCommitment::commit(&params, value, &PedersenRandomness { r: randomness });
+    let mut note = Note::new(1, v_comm, owner_address_from_pk(pk_x, pk_y), [3u8; 32], 1);
+    note.compliance_hash = F::from(1u64);
+    note.callbacks_hash = F::from(1u64);
+    
+    // Live trees with some unrelated history
+    let mut cmt_tree = IncrementalTree::new(16);
+    cmt_tree.append(F::rand(&mut rng));
+    cmt_tree.append(note.commitment());
+    let mut nft_tree = SortedTree::new(16);
+    nft_tree.insert(F::rand(&mut rng)).unwrap();
+    let mut exit_tree = IncrementalTree::new(16);
+    exit_tree.append(F::rand(&mut rng));
+    let mut exit_allow_tree = SortedTree::new(16);
+    let destination = F::from(1234u64);
+    exit_allow_tree.insert(destination).unwrap();
+    
+    let circuit = BurnBuilder::new(
+        &mut cmt_tree,
+        &mut nft_tree,
+        &mut exit_tree,
+        note.clone(),
+        value,
+        randomness,
+        owner_sk,
+        nk,
+    )
+    .with_amount(Amount::from(200u64))
+    .with_exit_destination(&exit_allow_tree, destination)
+    .unwrap()
+    .build()
+    .expect("Builder should produce a burn circuit");
+    
+    // The trees now sit at the circuit's new roots
+    assert_eq!(nft_tree.root(), circuit.nft_root_new);
+    assert_eq!(exit_tree.root(), circuit.exit_root_new);
+    assert!(nft_tree.contains(&note.nullifier(&nk)));
+    assert!(circuit.verify_public_inputs().is_ok());
+    
+    let cs = ConstraintSystem::<F>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap(), "Built burn circuit should be satisfied: {:?}", BurnCircuit::diagnose(&cs));
+    
+    // Burning the same note again is a double spend
+    let again = BurnBuilder::new(
+        &mut cmt_tree,
+        &mut nft_tree,
+        &mut exit_tree,
+        note,
+        value,
+        randomness,
+        owner_sk,
+        nk,
+    )
+    .with_exit_destination(&exit_allow_tree, destination)
+    .unwrap()
+    .build();
+    assert!(matches!(again, Err(FluxeError::DoubleSpend(_))));
+    
+    println!("✓ Burn builder produces a satisfied circuit");
+}
+
+#[test]
+fn test_zero_input_transfer() {
+    let mut rng = ChaCha20Rng::seed_from_u64(11);
+    let params = PedersenParams::setup_value_commitment();
+    
+    let mut nft = SortedTree::new(16);
+    nft.insert(F::from(99u64)).unwrap();
+    let cmt_tree = IncrementalTree::new(16);
+    
+    // Zero inputs, one output of the given value
+    let build = |value: u64, nft_root_new: F, rng: &mut ChaCha20Rng| {
+        let randomness = F::rand(rng);
+        let v_comm = PedersenCommitment::commit(&params, value, &PedersenRandomness { r: randomness });
+        let mut note = Note::new(1, v_comm, F::rand(rng), [5u8; 32], 1);
+        note.compliance_hash = F::from(1u64);
+        note.callbacks_hash = F::from(1u64);
+        note.lineage_hash = compute_split_lineage_hash(&[], SplitProvenance::new(0, 1).unwrap());
+        
+        let cm = note.commitment();
+        let append_witness = cmt_tree.generate_append_witness(cm);
+        let mut cmt_new = cmt_tree.clone();
+        cmt_new.append(cm);
+        
+        TransferCircuit {
+            notes_in: vec![],
+            values_in: vec![],
+            value_randomness_in: vec![],
+            notes_out: vec![note],
+            values_out: vec![value],
+            value_randomness_out: vec![randomness],
+            nks: vec![],
+            owner_sks: vec![],
+            owner_pks: vec![],
+            cm_paths: vec![],
+            nf_nonmembership_proofs: vec![],
+            sanctions_nm_proofs_in: vec![],
+            sanctions_nm_proofs_out: vec![None],
+            cmt_paths_out: vec![],
+            nf_nonmembership: vec![],
+            source_pool_policies: vec![],
+            dest_pool_policies: vec![],
+            pool_policy_paths: vec![],
+            cmt_appends_out: vec![append_witness],
+            nf_insert_witnesses: vec![],
+            cmt_root_old: cmt_tree.root(),
+            cmt_root_new: cmt_new.root(),
+            nft_root_old: nft.root(),
+            nft_root_new,
+            sanctions_root: F::from(0u64),
+            pool_rules_root: F::from(0u64),
+            nf_list: vec![],
+            n_out: 1,
+            cm_list: vec![cm],
+            fee: Amount::zero(),
+            consolidation: false,
+            checked_compliance: false,
+            compliance_states_in: Vec::new(),
+            require_fresh_review: false,
+            current_time: 0,
+            max_review_age: 0,
+            fee_note: None,
+            recent_cmt_roots: Vec::new(),
+            cm_path_roots: Vec::new(),
+            memos_out: Vec::new(),
+            derived_randomness: false,
+            min_denomination: None,
+            asset_fees: Vec::new(),
+            check_permitted_pools: false,
+            migration: None,
+            tree_config: None,
+            block_frozen_recipients: false,
+            recipient_compliance: Vec::new(),
+            owner_index_root: F::from(0u64),
+            expose_sanctions_fallbacks: false,
+        }
+    };
+    
+    // Defined valid case: zero-value output, NFT root unchanged
+    let valid = build(0, nft.root(), &mut rng);
+    assert!(valid.verify_public_inputs().is_ok());
+    let cs = ConstraintSystem::<F>::new_ref();
+    valid.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap(), "Zero-input transfer should be satisfied: {:?}", TransferCircuit::diagnose(&cs));
+    
+    // Value out of nothing is rejected
+    let minted = build(5, nft.root(), &mut rng);
+    assert!(matches!(minted.verify_public_inputs(), Err(FluxeError::InsufficientBalance)));
+    let cs = ConstraintSystem::<F>::new_ref();
+    minted.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    
+    // Moving the NFT root without inserting anything is rejected
+    let moved = build(0, F::from(1u64), &mut rng);
+    assert!(moved.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    moved.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+    
+    println!("✓ Zero-input transfer accepted only with zero-value outputs");
+}
+
+#[test]
+fn test_zero_input_output_asset_types_must_match() {
+    let mut rng = ChaCha20Rng::seed_from_u64(12);
+    let params = PedersenParams::setup_value_commitment();
+    
+    let nft = SortedTree::new(16);
+    let cmt_tree = IncrementalTree::new(16);
+    
+    // Zero inputs, two zero-value outputs with the given asset types
+    let build = |asset_types: [u32; 2], rng: &mut ChaCha20Rng| {
+        let mut notes_out = Vec::new();
+        let mut randomness_out = Vec::new();
+        let mut cm_list = Vec::new();
+        let mut appends = Vec::new();
+        let mut cmt_new = cmt_tree.clone();
+        for (i, asset_type) in asset_types.iter().enumerate() {
+            let randomness = F::rand(rng);
+            let v_comm = PedersenCommitment::commit(&params, 0, &PedersenRandomness { r: randomness });
+            let mut note = Note::new(*asset_type, v_comm, F::rand(rng), [5u8; 32], 1);
+            note.compliance_hash = F::from(1u64);
+            note.callbacks_hash = F::from(1u64);
+            note.lineage_hash = compute_split_lineage_hash(&[], SplitProvenance::new(i as u32, 2).unwrap());
+            
+            let cm = note.commitment();
+            appends.push(cmt_new.generate_append_witness(cm));
+            cmt_new.append(cm);
+            notes_out.push(note);
+            randomness_out.push(randomness);
+            cm_list.push(cm);
+        }
+        
+        TransferCircuit {
+            notes_in: vec![],
+            values_in: vec![],
+            value_randomness_in: vec![],
+            notes_out,
+            values_out: vec![0, 0],
+            value_randomness_out: randomness_out,
+            nks: vec![],
+            owner_sks: vec![],
+            owner_pks: vec![],
+            cm_paths: vec![],
+            nf_nonmembership_proofs: vec![],
+            sanctions_nm_proofs_in: vec![],
+            sanctions_nm_proofs_out: vec![None, None],
+            cmt_paths_out: vec![],
+            nf_nonmembership: vec![],
+            source_pool_policies: vec![],
+            dest_pool_policies: vec![],
+            pool_policy_paths: vec![],
+            cmt_appends_out: appends,
+            nf_insert_witnesses: vec![],
+            cmt_root_old: cmt_tree.root(),
+            cmt_root_new: cmt_new.root(),
+            nft_root_old: nft.root(),
+            nft_root_new: nft.root(),
+            sanctions_root: F::from(0u64),
+            pool_rules_root: F::from(0u64),
+            nf_list: vec![],
+            n_out: 2,
+            cm_list,
+            fee: Amount::zero(),
+            consolidation: false,
+            checked_compliance: false,
+            compliance_states_in: Vec::new(),
+            require_fresh_review: false,
+            current_time: 0,
+            max_review_age: 0,
+            fee_note: None,
+            recent_cmt_roots: Vec::new(),
+            cm_path_roots: Vec::new(),
+            memos_out: Vec::new(),
+            derived_randomness: false,
+            min_denomination: None,
+            asset_fees: Vec::new(),
+            check_permitted_pools: false,
+            migration: None,
+            tree_config: None,
+            block_frozen_recipients: false,
+            recipient_compliance: Vec::new(),
+            owner_index_root: F::from(0u64),
+            expose_sanctions_fallbacks: false,
+        }
+    };
+    
+    let matched = build([1, 1], &mut rng);
+    assert!(matched.verify_public_inputs().is_ok());
+    let cs = ConstraintSystem::<F>::new_ref();
+    matched.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap(), "Matching outputs should be satisfied: {:?}", TransferCircuit::diagnose(&cs));
+    
+    // Without inputs there is no reference asset type, yet outputs must still agree
+    let mixed = build([1, 2], &mut rng);
+    assert!(mixed.verify_public_inputs().is_err());
+    let cs = ConstraintSystem::<F>::new_ref();
+    mixed.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap(), "Mixed output asset types must be rejected");
+    
+    println!("✓ Zero-input transfer outputs share one asset type");
+}
+
 fn main() {
     test_burn_with_nonmembership_proof();
     test_transfer_with_multiple_nonmembership_proofs();
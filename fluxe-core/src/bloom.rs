@@ -0,0 +1,112 @@
+use ark_bls12_381::Fr as F;
+use ark_ff::PrimeField;
+
+/// Bloom filter over field elements, e.g. spent nullifiers
+/// A negative answer is definite; a positive one must be confirmed against the tree.
+/// Bits cannot be cleared, so removals leave stale positives until the filter is rebuilt
+#[derive(Clone, Debug)]
+pub struct FieldBloom {
+    /// Bit array, 64 bits per word
+    bits: Vec<u64>,
+    
+    /// Number of usable bits
+    num_bits: usize,
+    
+    /// Bit positions set per element
+    num_hashes: u32,
+    
+    /// Elements inserted since the filter was built
+    len: usize,
+}
+
+impl FieldBloom {
+    /// Filter sized so that `capacity` elements give at most `fp_rate` false positives
+    pub fn with_rate(capacity: usize, fp_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let fp_rate = fp_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        
+        let num_bits = ((-capacity * fp_rate.ln()) / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+        
+        Self {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes,
+            len: 0,
+        }
+    }
+    
+    /// Bit positions of an element (double hashing over its low limbs; field elements
+    /// stored here are hash outputs, so the limbs are already uniform)
+    fn positions(&self, element: &F) -> impl Iterator<Item = usize> + '_ {
+        let limbs = element.into_bigint().0;
+        let h1 = limbs[0];
+        let h2 = limbs[1] | 1;
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits as u64) as usize)
+    }
+    
+    /// Add an element
+    pub fn insert(&mut self, element: &F) {
+        let positions: Vec<usize> = self.positions(element).collect();
+        for bit in positions {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+    
+    /// False means the element was never inserted
+    pub fn may_contain(&self, element: &F) -> bool {
+        self.positions(element).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+    
+    /// Elements inserted since the filter was built
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    /// True if nothing was inserted
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    /// Heap size of the bit array
+    pub fn memory_bytes(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+    
+    /// Expected false-positive rate at the current fill
+    pub fn estimated_fp_rate(&self) -> f64 {
+        let k = self.num_hashes as f64;
+        let fill = 1.0 - (-k * self.len as f64 / self.num_bits as f64).exp();
+        fill.powf(k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_bloom_no_false_negatives() {
+        let mut rng = thread_rng();
+        let mut bloom = FieldBloom::with_rate(500, 0.01);
+        let members: Vec<F> = (0..500).map(|_| F::rand(&mut rng)).collect();
+        for member in &members {
+            bloom.insert(member);
+        }
+        
+        assert!(members.iter().all(|member| bloom.may_contain(member)));
+        assert_eq!(bloom.len(), 500);
+        
+        // Non-members are mostly rejected at the sized rate
+        let false_positives = (0..2000)
+            .filter(|_| bloom.may_contain(&F::rand(&mut rng)))
+            .count();
+        assert!(false_positives < 100, "Too many false positives: {}", false_positives);
+        assert!(bloom.estimated_fp_rate() < 0.02);
+    }
+}
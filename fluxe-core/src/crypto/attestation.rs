@@ -2,9 +2,13 @@ use crate::crypto::{poseidon_hash, SchnorrSecretKey, SchnorrPublicKey, SchnorrSi
 use crate::types::*;
 use ark_bls12_381::Fr as F;
 use ark_ff::UniformRand;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use std::collections::HashMap;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use ark_serialize::{Compress, Read, SerializationError, Validate, Write};
+use std::collections::{HashMap, HashSet};
 
+/// Highest provider trust level
+pub const MAX_TRUST_LEVEL: u8 = 100;
+
 /// Attestation provider identity
 #[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct AttestationProvider {
@@ -25,7 +29,7 @@ pub struct AttestationProvider {
 }
 
 /// Attestation data with provider signature
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SignedAttestation {
     /// User identifier being attested
     pub user_cm: F,
@@ -131,6 +135,100 @@ impl AttestationType {
     }
 }
 
+impl CanonicalSerialize for AttestationType {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            AttestationType::SanctionsClear { duration, jurisdiction } => {
+                0u8.serialize_with_mode(&mut writer, compress)?;
+                duration.serialize_with_mode(&mut writer, compress)?;
+                jurisdiction.serialize_with_mode(&mut writer, compress)
+            }
+            AttestationType::AMLPass { score, methodology } => {
+                1u8.serialize_with_mode(&mut writer, compress)?;
+                score.serialize_with_mode(&mut writer, compress)?;
+                methodology.serialize_with_mode(&mut writer, compress)
+            }
+            AttestationType::KYCComplete { level, document_hash } => {
+                2u8.serialize_with_mode(&mut writer, compress)?;
+                level.serialize_with_mode(&mut writer, compress)?;
+                document_hash.serialize_with_mode(&mut writer, compress)
+            }
+            AttestationType::AlertCleared { alert_id, resolution } => {
+                3u8.serialize_with_mode(&mut writer, compress)?;
+                alert_id.serialize_with_mode(&mut writer, compress)?;
+                resolution.serialize_with_mode(&mut writer, compress)
+            }
+            AttestationType::Custom { type_id, data_hash } => {
+                4u8.serialize_with_mode(&mut writer, compress)?;
+                type_id.serialize_with_mode(&mut writer, compress)?;
+                data_hash.serialize_with_mode(&mut writer, compress)
+            }
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        1 + match self {
+            AttestationType::SanctionsClear { duration, jurisdiction } => {
+                duration.serialized_size(compress) + jurisdiction.serialized_size(compress)
+            }
+            AttestationType::AMLPass { score, methodology } => {
+                score.serialized_size(compress) + methodology.serialized_size(compress)
+            }
+            AttestationType::KYCComplete { level, document_hash } => {
+                level.serialized_size(compress) + document_hash.serialized_size(compress)
+            }
+            AttestationType::AlertCleared { alert_id, resolution } => {
+                alert_id.serialized_size(compress) + resolution.serialized_size(compress)
+            }
+            AttestationType::Custom { type_id, data_hash } => {
+                type_id.serialized_size(compress) + data_hash.serialized_size(compress)
+            }
+        }
+    }
+}
+
+impl Valid for AttestationType {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for AttestationType {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            0 => Ok(AttestationType::SanctionsClear {
+                duration: Time::deserialize_with_mode(&mut reader, compress, validate)?,
+                jurisdiction: u32::deserialize_with_mode(&mut reader, compress, validate)?,
+            }),
+            1 => Ok(AttestationType::AMLPass {
+                score: RiskScore::deserialize_with_mode(&mut reader, compress, validate)?,
+                methodology: String::deserialize_with_mode(&mut reader, compress, validate)?,
+            }),
+            2 => Ok(AttestationType::KYCComplete {
+                level: u8::deserialize_with_mode(&mut reader, compress, validate)?,
+                document_hash: F::deserialize_with_mode(&mut reader, compress, validate)?,
+            }),
+            3 => Ok(AttestationType::AlertCleared {
+                alert_id: u64::deserialize_with_mode(&mut reader, compress, validate)?,
+                resolution: String::deserialize_with_mode(&mut reader, compress, validate)?,
+            }),
+            4 => Ok(AttestationType::Custom {
+                type_id: u32::deserialize_with_mode(&mut reader, compress, validate)?,
+                data_hash: F::deserialize_with_mode(&mut reader, compress, validate)?,
+            }),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
 impl SignedAttestation {
     /// Create new attestation
     pub fn new(
@@ -204,6 +302,9 @@ pub struct AttestationRegistry {
     
     /// Revoked attestations
     revoked: Vec<F>,
+    
+    /// Jurisdictions providers may register under (None = any non-zero jurisdiction)
+    jurisdictions: Option<HashSet<u32>>,
 }
 
 impl Default for AttestationRegistry {
@@ -218,12 +319,42 @@ impl AttestationRegistry {
             providers: HashMap::new(),
             attestations: Vec::new(),
             revoked: Vec::new(),
+            jurisdictions: None,
         }
     }
     
+    /// Only admit providers from these jurisdictions
+    pub fn with_jurisdictions(mut self, jurisdictions: &[u32]) -> Self {
+        self.jurisdictions = Some(jurisdictions.iter().copied().collect());
+        self
+    }
+    
+    /// Whether providers may register under a jurisdiction (0 is never known)
+    pub fn is_known_jurisdiction(&self, jurisdiction: u32) -> bool {
+        jurisdiction != 0
+            && self.jurisdictions.as_ref().map_or(true, |known| known.contains(&jurisdiction))
+    }
+    
     /// Register a new provider
-    pub fn register_provider(&mut self, provider: AttestationProvider) {
+    /// Rejects a trust level above MAX_TRUST_LEVEL, an unknown jurisdiction, or an id already in use
+    pub fn register_provider(&mut self, provider: AttestationProvider) -> Result<(), String> {
+        if provider.trust_level > MAX_TRUST_LEVEL {
+            return Err(format!(
+                "Provider {} trust level {} exceeds {}",
+                provider.id, provider.trust_level, MAX_TRUST_LEVEL
+            ));
+        }
+        if !self.is_known_jurisdiction(provider.jurisdiction) {
+            return Err(format!(
+                "Provider {} has unknown jurisdiction {}",
+                provider.id, provider.jurisdiction
+            ));
+        }
+        if self.providers.contains_key(&provider.id) {
+            return Err(format!("Provider id {} is already registered", provider.id));
+        }
         self.providers.insert(provider.id, provider);
+        Ok(())
     }
     
     /// Get provider by ID
@@ -231,8 +362,8 @@ impl AttestationRegistry {
         self.providers.get(&id)
     }
     
-    /// Add attestation
-    pub fn add_attestation(&mut self, attestation: SignedAttestation) -> Result<(), String> {
+    /// Check that an attestation comes from a registered provider with a valid signature
+    pub fn verify_attestation(&self, attestation: &SignedAttestation) -> Result<(), String> {
         // Verify provider exists
         let provider = self.providers
             .get(&attestation.provider_id)
@@ -243,6 +374,12 @@ impl AttestationRegistry {
             return Err("Invalid signature".to_string());
         }
         
+        Ok(())
+    }
+    
+    /// Add attestation
+    pub fn add_attestation(&mut self, attestation: SignedAttestation) -> Result<(), String> {
+        self.verify_attestation(&attestation)?;
         self.attestations.push(attestation);
         Ok(())
     }
@@ -267,6 +404,18 @@ impl AttestationRegistry {
             .collect()
     }
     
+    /// Highest KYC level attested for a user (0 if none)
+    pub fn compliance_level(&self, user_cm: F) -> u8 {
+        self.get_user_attestations(user_cm)
+            .iter()
+            .filter_map(|a| match a.attestation_type {
+                AttestationType::KYCComplete { level, .. } => Some(level),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    
     /// Compute aggregate trust score for user
     pub fn compute_trust_score(&self, user_cm: F) -> u32 {
         let attestations = self.get_user_attestations(user_cm);
@@ -406,7 +555,7 @@ mod tests {
             jurisdiction: 1,
             trust_level: 80,
         };
-        registry.register_provider(provider.clone());
+        registry.register_provider(provider.clone()).unwrap();
         
         // Create and sign attestation
         let user_cm = F::rand(&mut rng);
@@ -465,8 +614,8 @@ mod tests {
             trust_level: 50,
         };
         
-        registry.register_provider(provider1);
-        registry.register_provider(provider2);
+        registry.register_provider(provider1).unwrap();
+        registry.register_provider(provider2).unwrap();
         
         // Add attestations for user
         let user_cm = F::rand(&mut rng);
@@ -536,4 +685,72 @@ mod tests {
         let commitment2 = aggregator.aggregate_commitment();
         assert_eq!(commitment, commitment2);
     }
+    
+    #[test]
+    fn test_attestation_serialization_and_level() {
+        let mut rng = thread_rng();
+        let mut registry = AttestationRegistry::new();
+        let provider_sk = SchnorrSecretKey::random(&mut rng);
+        registry.register_provider(AttestationProvider {
+            id: 1,
+            name: "KYC Provider".to_string(),
+            public_key: provider_sk.public_key(),
+            jurisdiction: 1,
+            trust_level: 90,
+        }).unwrap();
+        
+        let user_cm = F::rand(&mut rng);
+        let mut attestation = SignedAttestation::new(
+            user_cm,
+            AttestationType::KYCComplete {
+                level: 2,
+                document_hash: F::rand(&mut rng),
+            },
+            1000,
+            1,
+            vec![7, 8],
+        );
+        attestation.sign(&provider_sk);
+        
+        // Round-trips through its canonical encoding with the signature intact
+        let mut bytes = Vec::new();
+        attestation.serialize_compressed(&mut bytes).unwrap();
+        let decoded = SignedAttestation::deserialize_compressed(&*bytes).unwrap();
+        assert_eq!(decoded, attestation);
+        assert!(registry.verify_attestation(&decoded).is_ok());
+        
+        assert_eq!(registry.compliance_level(user_cm), 0);
+        registry.add_attestation(decoded).unwrap();
+        assert_eq!(registry.compliance_level(user_cm), 2);
+    }
+    
+    #[test]
+    fn test_register_provider_validation() {
+        let mut registry = AttestationRegistry::new().with_jurisdictions(&[1, 2]);
+        let provider = |id: u32, jurisdiction: u32, trust_level: u8| AttestationProvider {
+            id,
+            name: format!("Provider {}", id),
+            public_key: SchnorrSecretKey::random(&mut thread_rng()).public_key(),
+            jurisdiction,
+            trust_level,
+        };
+        
+        // Valid provider registers
+        assert!(registry.register_provider(provider(1, 1, 100)).is_ok());
+        assert!(registry.get_provider(1).is_some());
+        
+        // Trust level out of range
+        let err = registry.register_provider(provider(2, 1, 150)).unwrap_err();
+        assert!(err.contains("trust level 150"), "{}", err);
+        
+        // Jurisdiction outside the configured set, or unset
+        assert!(registry.register_provider(provider(3, 7, 50)).is_err());
+        assert!(AttestationRegistry::new().register_provider(provider(3, 0, 50)).is_err());
+        
+        // Duplicate id does not replace the registered provider
+        let original = registry.get_provider(1).unwrap().public_key.clone();
+        let err = registry.register_provider(provider(1, 2, 60)).unwrap_err();
+        assert!(err.contains("already registered"), "{}", err);
+        assert_eq!(registry.get_provider(1).unwrap().public_key, original);
+    }
 }
\ No newline at end of file
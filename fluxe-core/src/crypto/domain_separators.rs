@@ -21,6 +21,25 @@ pub const DOM_EXIT: &[u8; 32] = b"FLUXE_EXIT_RECEIPT______________";
 /// Domain separator for ingress receipts
 pub const DOM_INGRESS: &[u8; 32] = b"FLUXE_INGRESS_RECEIPT___________";
 
+/// Domain separator for owner addresses: owner_addr = H(DOM_OWNER_ADDR, pk_x, pk_y)
+/// Shared by `owner_address_from_pk` and the circuit's `AuthGadget::compute_owner_address_from_fq`
+pub const DOM_OWNER_ADDR: &[u8; 32] = b"FLUXE_OWNER_ADDRESS_____________";
+
+/// Domain separator for privacy-preserving transaction ids
+pub const DOM_TX_ID: &[u8; 32] = b"FLUXE_TRANSACTION_ID____________";
+
+/// Domain separator for padding (dummy) notes; also their sentinel hashes and value randomness
+pub const DOM_DUMMY_NOTE: &[u8; 32] = b"FLUXE_DUMMY_NOTE________________";
+
+/// Domain separator for derived value randomness: r = H(DOM_VALUE_RANDOMNESS, owner_sk, psi)
+pub const DOM_VALUE_RANDOMNESS: &[u8; 32] = b"FLUXE_VALUE_RANDOMNESS__________";
+
+/// Domain separator for rolling spend windows: H(DOM_SPEND_WINDOW, window_start, spent, blinding)
+pub const DOM_SPEND_WINDOW: &[u8; 32] = b"FLUXE_SPEND_WINDOW______________";
+
+/// Domain separator for owner index keys: H(DOM_OWNER_OBJECT, owner_addr, object_cm)
+pub const DOM_OWNER_OBJECT: &[u8; 32] = b"FLUXE_OWNER_OBJECT______________";
+
 /// Convert domain separator to field element
 pub fn domain_sep_to_field(sep: &[u8; 32]) -> F {
     crate::utils::bytes_to_field(sep)
@@ -3,7 +3,7 @@ use ark_ec::{CurveGroup, Group};
 use ark_ed_on_bls12_381::EdwardsProjective as Jubjub;
 use ark_ff::{BigInteger, PrimeField};
 
-use super::poseidon_hash;
+use super::{domain_sep_to_field, poseidon_hash, DOM_OWNER_ADDR};
 
 /// Compute EC public key from secret key using Jubjub curve
 /// Returns (pk_x, pk_y) as field elements in Fr
@@ -29,10 +29,16 @@ pub fn compute_ec_public_key(sk: F) -> (F, F) {
     (pk_x, pk_y)
 }
 
-/// Compute owner address from EC public key
+/// Compute owner address from EC public key coordinates
+/// Must match `AuthGadget::compute_owner_address_from_fq` in-circuit
+pub fn owner_address_from_pk(pk_x: F, pk_y: F) -> F {
+    poseidon_hash(&[domain_sep_to_field(DOM_OWNER_ADDR), pk_x, pk_y])
+}
+
+/// Compute owner address from secret key
 pub fn compute_owner_address_from_sk(sk: F) -> F {
     let (pk_x, pk_y) = compute_ec_public_key(sk);
-    poseidon_hash(&[pk_x, pk_y])
+    owner_address_from_pk(pk_x, pk_y)
 }
 
 #[cfg(test)]
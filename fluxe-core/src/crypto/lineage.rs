@@ -1,5 +1,6 @@
 use ark_bls12_381::Fr as F;
 use crate::crypto::poseidon_hash;
+use crate::types::{FluxeError, Time};
 
 /// Bounded horizon lineage accumulator
 /// Tracks transaction history with automatic reset after a certain depth
@@ -94,6 +95,51 @@ pub fn compute_lineage_hash(
     poseidon_hash(&input)
 }
 
+/// Provenance of an output note within a split: its position and how many siblings were produced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitProvenance {
+    /// Index of this output among the split's outputs
+    pub position: u32,
+    /// Total number of outputs produced by the split
+    pub split_count: u32,
+}
+
+impl SplitProvenance {
+    /// Errors unless `position` is one of the split's `split_count` outputs
+    pub fn new(position: u32, split_count: u32) -> Result<Self, FluxeError> {
+        if position >= split_count {
+            return Err(FluxeError::Other(format!(
+                "Split position {} out of range for {} outputs",
+                position,
+                split_count
+            )));
+        }
+        Ok(Self {
+            position,
+            split_count,
+        })
+    }
+
+    /// Field encoding appended to the lineage input: [position, split_count]
+    pub fn to_fields(&self) -> [F; 2] {
+        [F::from(self.position as u64), F::from(self.split_count as u64)]
+    }
+}
+
+/// Compute lineage hash for an output produced by a split
+/// H(parent_1 || ... || parent_n || position || split_count)
+pub fn compute_split_lineage_hash(parent_lineages: &[F], split: SplitProvenance) -> F {
+    let mut input = parent_lineages.to_vec();
+    input.extend_from_slice(&split.to_fields());
+    poseidon_hash(&input)
+}
+
+/// Lineage hash that also commits the note's creation time
+/// H(split_lineage || created_at), so a child can prove it was created no earlier than its parents
+pub fn compute_timed_lineage_hash(split_lineage: F, created_at: Time) -> F {
+    poseidon_hash(&[split_lineage, F::from(created_at)])
+}
+
 /// Verify lineage is valid (within horizon)
 pub fn verify_lineage(
     lineage_hash: F,
@@ -176,4 +222,31 @@ mod tests {
         // Non-zero at horizon is invalid
         assert!(!verify_lineage(F::from(123u64), 10, 10));
     }
+
+    #[test]
+    fn test_split_lineage_positions() {
+        let parents = vec![F::from(11u64)];
+        
+        // 1 -> 3 split: every output gets a distinct lineage
+        let hashes: Vec<F> = (0..3)
+            .map(|i| compute_split_lineage_hash(&parents, SplitProvenance::new(i, 3).unwrap()))
+            .collect();
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[1], hashes[2]);
+        assert_ne!(hashes[0], hashes[2]);
+        
+        // Same position in a 1 -> 2 split is distinguishable from the 1 -> 3 split
+        let two_way = compute_split_lineage_hash(&parents, SplitProvenance::new(1, 2).unwrap());
+        assert_ne!(two_way, hashes[1]);
+        
+        // Encoding is H(parents || position || split_count)
+        assert_eq!(
+            hashes[1],
+            poseidon_hash(&[F::from(11u64), F::from(1u64), F::from(3u64)])
+        );
+        
+        // A position past the split's outputs is refused, not encoded
+        assert!(SplitProvenance::new(3, 3).is_err());
+        assert!(SplitProvenance::new(0, 0).is_err());
+    }
 }
\ No newline at end of file
@@ -1,9 +1,20 @@
 use ark_bls12_381::Fr as F;
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsProjective as Jubjub};
+use ark_ff::{PrimeField, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use chacha20poly1305::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     ChaCha20Poly1305, Key, Nonce,
 };
-use crate::crypto::{blake2b_hash, derive_memo_key};
+use rand::{CryptoRng, RngCore};
+use crate::crypto::{blake2b_hash, derive_memo_key, owner_address_from_pk, poseidon_hash};
+
+/// Number of 31-byte chunks a memo ciphertext is padded to when bound in-circuit
+pub const MEMO_CHUNKS: usize = 16;
+
+/// Largest memo ciphertext the transfer circuit can bind
+pub const MAX_MEMO_BYTES: usize = MEMO_CHUNKS * 31;
 
 /// Encrypted memo with authentication
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -22,19 +33,87 @@ pub struct MemoEncryption;
 impl MemoEncryption {
     /// Encrypt a memo using a shared secret
     pub fn encrypt(plaintext: &[u8], shared_secret: &[u8; 32]) -> Result<EncryptedMemo, String> {
+        // Generate random nonce
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        Self::encrypt_with_nonce(plaintext, shared_secret, nonce.as_slice())
+    }
+    
+    /// Encrypt a memo to a note's recipient via ECDH over Jubjub: a fresh ephemeral key
+    /// agrees a secret with `recipient_pk`, which must hash to `owner_addr` (the address
+    /// alone is a hash and cannot be encrypted to). The ephemeral public key travels in
+    /// the memo, so the recipient needs only its secret key (`decrypt_with_sk`)
+    pub fn encrypt_to_address<R: RngCore + CryptoRng>(
+        owner_addr: F,
+        recipient_pk: (F, F),
+        plaintext: &[u8],
+        rng: &mut R,
+    ) -> Result<EncryptedMemo, String> {
+        if owner_address_from_pk(recipient_pk.0, recipient_pk.1) != owner_addr {
+            return Err("Recipient public key does not match the owner address".to_string());
+        }
+        let recipient = Self::jubjub_point(recipient_pk)?;
+        
+        let esk = F::rand(rng);
+        let epk = Jubjub::generator().mul_bigint(esk.into_bigint()).into_affine();
+        let shared_secret = Self::ecdh_secret(recipient, esk)?;
+        
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut nonce);
+        let mut encrypted = Self::encrypt_with_nonce(plaintext, &shared_secret, &nonce)?;
+        
+        let mut epk_bytes = Vec::new();
+        epk.serialize_compressed(&mut epk_bytes)
+            .map_err(|e| format!("Ephemeral key encoding failed: {}", e))?;
+        encrypted.ephemeral_pk = Some(epk_bytes);
+        Ok(encrypted)
+    }
+    
+    /// Decrypt a memo from `encrypt_to_address` with the recipient's secret key
+    pub fn decrypt_with_sk(encrypted: &EncryptedMemo, sk: &F) -> Result<Vec<u8>, String> {
+        let epk_bytes = encrypted.ephemeral_pk
+            .as_ref()
+            .ok_or_else(|| "Memo has no ephemeral public key".to_string())?;
+        let epk = EdwardsAffine::deserialize_compressed(&epk_bytes[..])
+            .map_err(|e| format!("Invalid ephemeral public key: {}", e))?;
+        let shared_secret = Self::ecdh_secret(epk, *sk)?;
+        Self::decrypt(encrypted, &shared_secret)
+    }
+    
+    /// Jubjub point from the (x, y) coordinates returned by `compute_ec_public_key`
+    fn jubjub_point((x, y): (F, F)) -> Result<EdwardsAffine, String> {
+        // Jubjub's base field is the BLS12-381 scalar field, so the coordinates carry over
+        let point = EdwardsAffine::new_unchecked(x, y);
+        if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() || point.is_zero() {
+            return Err("Recipient public key is not a valid Jubjub point".to_string());
+        }
+        Ok(point)
+    }
+    
+    /// Blake2b of the compressed shared point scalar * point
+    fn ecdh_secret(point: EdwardsAffine, scalar: F) -> Result<[u8; 32], String> {
+        let shared = point.mul_bigint(scalar.into_bigint()).into_affine();
+        let mut bytes = Vec::new();
+        shared.serialize_compressed(&mut bytes)
+            .map_err(|e| format!("Shared point encoding failed: {}", e))?;
+        let hash = blake2b_hash(&bytes);
+        let mut secret = [0u8; 32];
+        secret.copy_from_slice(&hash[..32]);
+        Ok(secret)
+    }
+    
+    /// ChaCha20-Poly1305 under the memo key derived from `shared_secret`
+    fn encrypt_with_nonce(plaintext: &[u8], shared_secret: &[u8; 32], nonce: &[u8]) -> Result<EncryptedMemo, String> {
         // Derive encryption key from shared secret
         let key_bytes = derive_memo_key(shared_secret);
         let key = Key::from_slice(&key_bytes);
-        
-        // Generate random nonce
-        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let nonce = Nonce::from_slice(nonce);
         
         // Create cipher
         let cipher = ChaCha20Poly1305::new(key);
         
         // Encrypt
         let ciphertext = cipher
-            .encrypt(&nonce, plaintext)
+            .encrypt(nonce, plaintext)
             .map_err(|e| format!("Encryption failed: {}", e))?;
         
         // Convert nonce to array
@@ -81,6 +160,25 @@ impl MemoEncryption {
         crate::utils::bytes_to_field(&hash)
     }
     
+    /// Memo hash bound by the transfer circuit: Poseidon over the ciphertext padded to
+    /// MEMO_CHUNKS 31-byte chunks, followed by its length
+    /// None for an empty or oversized ciphertext; a note without a memo carries memo_hash 0
+    pub fn compute_circuit_memo_hash(ciphertext: &[u8]) -> Option<F> {
+        if ciphertext.is_empty() || ciphertext.len() > MAX_MEMO_BYTES {
+            return None;
+        }
+        
+        let mut input = vec![F::from(0u64); MEMO_CHUNKS];
+        for (slot, chunk) in input.iter_mut().zip(ciphertext.chunks(31)) {
+            let mut bytes = [0u8; 32];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *slot = F::from_le_bytes_mod_order(&bytes);
+        }
+        input.push(F::from(ciphertext.len() as u64));
+        
+        Some(poseidon_hash(&input))
+    }
+    
     /// Create a shared secret from sender and receiver keys (simplified)
     /// In production, use proper ECDH
     pub fn derive_shared_secret(sender_key: &F, receiver_key: &F) -> [u8; 32] {
@@ -280,6 +378,40 @@ mod tests {
         assert_eq!(recovered.metadata, memo.metadata);
     }
 
+    #[test]
+    fn test_encrypt_to_address() {
+        use crate::crypto::{compute_ec_public_key, pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness}};
+        use crate::data_structures::Note;
+        use rand::thread_rng;
+        
+        let mut rng = thread_rng();
+        let recipient_sk = F::from(424242u64);
+        let recipient_pk = compute_ec_public_key(recipient_sk);
+        let owner_addr = owner_address_from_pk(recipient_pk.0, recipient_pk.1);
+        
+        // Sender encrypts to the output note's owner and commits the memo into the note
+        let plaintext = Memo::new(F::from(1u64), owner_addr, b"invoice 17".to_vec()).to_bytes();
+        let encrypted = MemoEncryption::encrypt_to_address(owner_addr, recipient_pk, &plaintext, &mut rng).unwrap();
+        assert!(encrypted.ephemeral_pk.is_some());
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, 10, &PedersenRandomness::new(&mut rng));
+        let mut note = Note::new(1, v_comm, owner_addr, [3u8; 32], 1);
+        note.memo_hash = MemoEncryption::compute_memo_hash(&encrypted);
+        
+        // Recipient: only its secret key, the note and the delivered memo
+        assert_eq!(MemoEncryption::compute_memo_hash(&encrypted), note.memo_hash);
+        let decrypted = MemoEncryption::decrypt_with_sk(&encrypted, &recipient_sk).unwrap();
+        assert_eq!(decrypted, plaintext);
+        assert_eq!(Memo::from_bytes(&decrypted).unwrap().message, b"invoice 17".to_vec());
+        
+        // Anyone else cannot decrypt
+        assert!(MemoEncryption::decrypt_with_sk(&encrypted, &F::from(7u64)).is_err());
+        
+        // A public key that does not hash to the address is refused
+        let other_pk = compute_ec_public_key(F::from(7u64));
+        assert!(MemoEncryption::encrypt_to_address(owner_addr, other_pk, &plaintext, &mut rng).is_err());
+    }
+
     #[test]
     fn test_decryption_fails_with_wrong_key() {
         let shared_secret = [1u8; 32];
@@ -126,6 +126,18 @@ impl PedersenCommitment {
         (commitment, randomness)
     }
 
+    /// Canonical `[x, y]` field encoding of the commitment point, as hashed into note
+    /// commitments. Each Fq coordinate is truncated to 31 bytes so it lands in Fr
+    pub fn to_field_repr(&self) -> [F; 2] {
+        use ark_ff::{BigInteger, PrimeField};
+        let x_bytes = self.commitment.x.into_bigint().to_bytes_le();
+        let y_bytes = self.commitment.y.into_bigint().to_bytes_le();
+        [
+            crate::utils::bytes_to_field(&x_bytes),
+            crate::utils::bytes_to_field(&y_bytes),
+        ]
+    }
+
     /// Verify homomorphic property: Com(v1, r1) + Com(v2, r2) = Com(v1+v2, r1+r2)
     pub fn add(&self, other: &Self) -> Self {
         let sum = self.commitment + other.commitment;
@@ -55,11 +55,33 @@ impl SchnorrSecretKey {
     pub fn sign<R: Rng>(&self, message: &[F], rng: &mut R) -> SchnorrSignature {
         // Generate random nonce
         let r = F::rand(rng);
+        let pk_affine: G1Affine = self.public_key().point.into_affine();
+        self.sign_with_nonce(message, r, &pk_affine)
+    }
+
+    /// Sign many messages at once
+    /// Draws one random seed per batch and derives each nonce as
+    /// H(seed || sk || index || H(msg)), so nonces are distinct per position
+    /// even when the same message appears more than once
+    pub fn sign_batch<R: Rng>(&self, messages: &[Vec<F>], rng: &mut R) -> Vec<SchnorrSignature> {
+        let seed = F::rand(rng);
+        let pk_affine: G1Affine = self.public_key().point.into_affine();
+        
+        messages.iter()
+            .enumerate()
+            .map(|(i, message)| {
+                let r = poseidon_hash(&[seed, self.scalar, F::from(i as u64), poseidon_hash(message)]);
+                self.sign_with_nonce(message, r, &pk_affine)
+            })
+            .collect()
+    }
+
+    /// Produce a signature with the given nonce and precomputed public key
+    fn sign_with_nonce(&self, message: &[F], r: F, pk_affine: &G1Affine) -> SchnorrSignature {
         let generator = G1::generator();
         let r_point: G1 = generator * r;
         
         // Compute challenge c = H(R || pk || msg)
-        let pk_point = self.public_key().point;
         let mut challenge_input = vec![];
         
         // Add R coordinates
@@ -68,7 +90,6 @@ impl SchnorrSecretKey {
         challenge_input.push(fq_to_fr(r_affine.y));
         
         // Add public key coordinates
-        let pk_affine: G1Affine = pk_point.into_affine();
         challenge_input.push(fq_to_fr(pk_affine.x));
         challenge_input.push(fq_to_fr(pk_affine.y));
         
@@ -180,6 +201,38 @@ mod tests {
         assert_eq!(signature, signature2);
     }
 
+    #[test]
+    fn test_sign_batch() {
+        let mut rng = test_rng();
+        
+        let sk = SchnorrSecretKey::random(&mut rng);
+        let pk = sk.public_key();
+        
+        // Include a repeated message to check nonces still differ
+        let messages = vec![
+            vec![F::from(1u64), F::from(2u64)],
+            vec![F::from(3u64)],
+            vec![F::from(1u64), F::from(2u64)],
+        ];
+        let signatures = sk.sign_batch(&messages, &mut rng);
+        assert_eq!(signatures.len(), messages.len());
+        
+        for (message, signature) in messages.iter().zip(signatures.iter()) {
+            assert!(pk.verify(message, signature));
+        }
+        
+        // No nonce reuse within the batch
+        for i in 0..signatures.len() {
+            for j in (i + 1)..signatures.len() {
+                assert_ne!(signatures[i].r_point, signatures[j].r_point);
+            }
+        }
+        
+        // A second batch over the same messages uses fresh nonces
+        let again = sk.sign_batch(&messages, &mut rng);
+        assert_ne!(signatures[0].r_point, again[0].r_point);
+    }
+
     #[test]
     fn test_public_key_to_field() {
         let mut rng = test_rng();
@@ -22,6 +22,9 @@ pub struct ComplianceState {
     /// Jurisdiction bitfield for allowed regions
     pub jurisdiction_bits: JurisdictionBits,
     
+    /// Bitmap of pools the owner may hold notes in (bit i = pool i; pools 64 and up are never permitted)
+    pub permitted_pools: u64,
+    
     /// Daily transaction limit
     pub daily_limit: Amount,
     
@@ -50,6 +53,7 @@ impl ComplianceState {
             frozen: false,
             last_review_time: 0,
             jurisdiction_bits: [0u8; 32],
+            permitted_pools: 0,
             daily_limit: Amount::zero(),
             monthly_limit: Amount::zero(),
             yearly_limit: Amount::zero(),
@@ -72,6 +76,7 @@ impl ComplianceState {
             frozen: false,
             last_review_time: 0,
             jurisdiction_bits: [0xff; 32], // All jurisdictions allowed by default
+            permitted_pools: u64::MAX, // All pools permitted by default
             daily_limit: daily,
             monthly_limit: monthly,
             yearly_limit: yearly,
@@ -88,6 +93,7 @@ impl ComplianceState {
         input.push(F::from(self.frozen as u64));
         input.push(F::from(self.last_review_time));
         input.push(crate::utils::bytes_to_field(&self.jurisdiction_bits));
+        input.push(F::from(self.permitted_pools));
         input.push(self.daily_limit.to_field());
         input.push(self.monthly_limit.to_field());
         input.push(self.yearly_limit.to_field());
@@ -117,6 +123,18 @@ impl ComplianceState {
         Ok(())
     }
 
+    /// Whether the owner may hold notes in `pool_id`
+    pub fn is_pool_permitted(&self, pool_id: PoolId) -> bool {
+        pool_id < 64 && (self.permitted_pools >> pool_id) & 1 == 1
+    }
+
+    /// Check the last review is not in the future and at most max_review_age old
+    pub fn review_is_fresh(&self, current_time: Time, max_review_age: Time) -> bool {
+        current_time
+            .checked_sub(self.last_review_time)
+            .map_or(false, |age| age <= max_review_age)
+    }
+
     /// Freeze assets
     pub fn freeze(&mut self) {
         self.frozen = true;
@@ -334,6 +352,25 @@ mod tests {
         assert!(state.can_transact(Amount::from(500u128), Amount::zero(), Amount::zero(), Amount::zero()).is_ok());
     }
 
+    #[test]
+    fn test_permitted_pools() {
+        let mut state = ComplianceState::new_verified(1);
+        assert!(state.is_pool_permitted(0));
+        assert!(state.is_pool_permitted(63));
+        assert!(!state.is_pool_permitted(64));
+        
+        state.permitted_pools = 0b101;
+        assert!(state.is_pool_permitted(0));
+        assert!(!state.is_pool_permitted(1));
+        assert!(state.is_pool_permitted(2));
+        assert!(!ComplianceState::new().is_pool_permitted(0));
+        
+        // The bitmap is part of the committed state
+        let mut other = state.clone();
+        other.permitted_pools = 0b111;
+        assert_ne!(state.hash(), other.hash());
+    }
+
     #[test]
     fn test_pool_rules() {
         let mut rule = PoolRule::new_default(1);
@@ -1,4 +1,8 @@
-use crate::crypto::{pedersen::PedersenCommitment, poseidon_hash, domain_sep_to_field, DOM_NOTE, DOM_NF};
+use crate::crypto::{
+    pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness},
+    poseidon_hash, domain_sep_to_field, DOM_DUMMY_NOTE, DOM_NOTE, DOM_NF, DOM_VALUE_RANDOMNESS,
+};
+use crate::merkle::FieldKey;
 use crate::types::*;
 use ark_bls12_381::Fr as F;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
@@ -87,17 +91,64 @@ impl Note {
         }
     }
 
+    /// Zero-value padding note for fixed-shape circuits
+    /// Its compliance and callbacks hashes are non-zero sentinels so it passes the note gates,
+    /// and its owner is `Note::dummy_owner_addr`, which no key derives, so it can never be spent
+    pub fn dummy(asset_type: AssetType, pool_id: PoolId) -> Self {
+        let sentinel = domain_sep_to_field(DOM_DUMMY_NOTE);
+        Self {
+            asset_type,
+            v_comm: Self::dummy_value_commitment(),
+            owner_addr: Self::dummy_owner_addr(),
+            psi: [0u8; 32],
+            chain_hint: 1,
+            compliance_hash: sentinel,
+            lineage_hash: F::from(0),
+            pool_id,
+            callbacks_hash: sentinel,
+            memo_hash: F::from(0),
+        }
+    }
+    
+    /// Whether this is a padding note built by `Note::dummy`
+    pub fn is_dummy(&self) -> bool {
+        self.owner_addr == Self::dummy_owner_addr() && self.v_comm == Self::dummy_value_commitment()
+    }
+    
+    /// Sentinel owner of dummy notes: H(DOM_DUMMY_NOTE), outside the H(DOM_OWNER_ADDR, pk) range
+    pub fn dummy_owner_addr() -> AuthAddr {
+        poseidon_hash(&[domain_sep_to_field(DOM_DUMMY_NOTE)])
+    }
+    
+    /// Fixed, public value randomness of dummy notes
+    pub fn dummy_value_randomness() -> F {
+        domain_sep_to_field(DOM_DUMMY_NOTE)
+    }
+    
+    /// Commitment to value 0 under the dummy randomness
+    pub fn dummy_value_commitment() -> PedersenCommitment {
+        let params = PedersenParams::setup_value_commitment();
+        let randomness = PedersenRandomness { r: Self::dummy_value_randomness() };
+        PedersenCommitment::commit(&params, 0, &randomness)
+    }
+
+    /// Value randomness derived from the creator's secret key and the note's unique psi,
+    /// so no two notes share randomness and equal values never give equal commitments
+    pub fn derive_value_randomness(owner_sk: &F, psi: &[u8; 32]) -> F {
+        poseidon_hash(&[
+            domain_sep_to_field(DOM_VALUE_RANDOMNESS),
+            *owner_sk,
+            crate::utils::bytes_to_field(psi),
+        ])
+    }
+
     /// Compute the note commitment
     pub fn commitment(&self) -> Commitment {
         let mut input = vec![domain_sep_to_field(DOM_NOTE)];
         input.push(F::from(self.asset_type));
         
-        // Serialize Pedersen commitment to field element
-        // Convert Fq coordinate to Fr by hashing
-        use ark_ff::{BigInteger, PrimeField};
-        let v_comm_bytes = self.v_comm.commitment.x.into_bigint().to_bytes_le();
-        let v_comm_fr = crate::utils::bytes_to_field(&v_comm_bytes);
-        input.push(v_comm_fr);
+        // Pedersen commitment point in its canonical [x, y] encoding
+        input.extend(self.v_comm.to_field_repr());
         
         input.push(self.owner_addr);
         input.push(crate::utils::bytes_to_field(&self.psi));
@@ -112,6 +163,8 @@ impl Note {
     }
 
     /// Compute the nullifier for this note
+    /// Field-zero (negligible odds) collides with the NFT sentinel: the tree refuses
+    /// it and circuits enforce a nonzero nullifier, so such a note cannot be spent
     pub fn nullifier(&self, nk: &F) -> Nullifier {
         let cm = self.commitment();
         let psi_field = crate::utils::bytes_to_field(&self.psi);
@@ -126,6 +179,12 @@ impl Note {
         poseidon_hash(&input)
     }
 
+    /// Canonical nullifier order: the NFT's key order (`FieldKey`)
+    /// Witness generation and the transfer circuit both take nullifiers in this order
+    pub fn cmp_nullifiers(a: &Nullifier, b: &Nullifier) -> std::cmp::Ordering {
+        FieldKey(*a).cmp(&FieldKey(*b))
+    }
+
     /// Update lineage hash for output notes
     pub fn update_lineage(&mut self, parent_lineages: &[LineageHash], context: &F) {
         let mut input = Vec::new();
@@ -193,7 +252,6 @@ impl AuthMethod {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto::pedersen::{PedersenParams, PedersenRandomness};
     use ark_ff::UniformRand;
     use rand::thread_rng;
 
@@ -238,4 +296,51 @@ mod tests {
         let nullifier2 = note.nullifier(&nk2);
         assert_ne!(nullifier, nullifier2);
     }
+
+    #[test]
+    fn test_cmp_nullifiers() {
+        use std::cmp::Ordering;
+        
+        // Orders by integer value, not by serialized bytes
+        assert_eq!(Note::cmp_nullifiers(&F::from(2u64), &F::from(256u64)), Ordering::Less);
+        assert_eq!(Note::cmp_nullifiers(&F::from(256u64), &F::from(2u64)), Ordering::Greater);
+        assert_eq!(Note::cmp_nullifiers(&F::from(7u64), &F::from(7u64)), Ordering::Equal);
+        
+        // -1 is the largest field element
+        assert_eq!(Note::cmp_nullifiers(&-F::from(1u64), &F::from(u64::MAX)), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_derived_value_randomness() {
+        let sk = F::from(42u64);
+        let r = Note::derive_value_randomness(&sk, &[1u8; 32]);
+        
+        // Deterministic, but distinct per psi and per key
+        assert_eq!(r, Note::derive_value_randomness(&sk, &[1u8; 32]));
+        assert_ne!(r, Note::derive_value_randomness(&sk, &[2u8; 32]));
+        assert_ne!(r, Note::derive_value_randomness(&F::from(43u64), &[1u8; 32]));
+    }
+
+    #[test]
+    fn test_dummy_note() {
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        
+        let dummy = Note::dummy(1, 3);
+        assert!(dummy.is_dummy());
+        assert_eq!(dummy.asset_type, 1);
+        assert_eq!(dummy.pool_id, 3);
+        assert_ne!(dummy.compliance_hash, F::from(0));
+        assert_ne!(dummy.callbacks_hash, F::from(0));
+        assert_eq!(dummy.memo_hash, F::from(0));
+        
+        // Opens to zero under the public dummy randomness
+        let randomness = PedersenRandomness { r: Note::dummy_value_randomness() };
+        assert_eq!(dummy.v_comm, PedersenCommitment::commit(&params, 0, &randomness));
+        
+        // A real zero-value note is not a dummy
+        let randomness = PedersenRandomness::new(&mut rng);
+        let v_comm = PedersenCommitment::commit(&params, 0, &randomness);
+        assert!(!Note::new(1, v_comm, F::rand(&mut rng), [1u8; 32], 3).is_dummy());
+    }
 }
\ No newline at end of file
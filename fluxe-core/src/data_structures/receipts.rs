@@ -1,4 +1,5 @@
 use crate::crypto::poseidon_hash;
+use crate::data_structures::Note;
 use crate::types::*;
 use ark_bls12_381::Fr as F;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
@@ -34,6 +35,14 @@ impl IngressReceipt {
         }
     }
 
+    /// Beneficiary commitment binding a receipt to the notes it mints:
+    /// the chain hash `h_i = H(h_{i-1}, cm_i)` from `h_0 = 0`, as enforced by the mint circuit
+    pub fn beneficiary_commitment(notes: &[Note]) -> F {
+        notes
+            .iter()
+            .fold(F::from(0u64), |acc, note| poseidon_hash(&[acc, note.commitment()]))
+    }
+
     /// Compute hash of this receipt
     pub fn hash(&self) -> F {
         poseidon_hash(&[
@@ -68,6 +77,9 @@ pub struct ExitReceipt {
     
     /// Auxiliary data binding to external withdrawal reference
     pub aux: F,
+    
+    /// Exit destination, must be a key in EXIT_ALLOW_ROOT
+    pub exit_destination: F,
 }
 
 impl ExitReceipt {
@@ -78,9 +90,16 @@ impl ExitReceipt {
             burned_nf,
             nonce,
             aux: F::from(0),
+            exit_destination: F::from(0),
         }
     }
 
+    /// Set the exit destination
+    pub fn with_destination(mut self, exit_destination: F) -> Self {
+        self.exit_destination = exit_destination;
+        self
+    }
+
     /// Compute hash of this receipt
     pub fn hash(&self) -> F {
         poseidon_hash(&[
@@ -89,6 +108,7 @@ impl ExitReceipt {
             self.burned_nf,
             F::from(self.nonce),
             self.aux,
+            self.exit_destination,
         ])
     }
 
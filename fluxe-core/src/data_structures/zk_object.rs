@@ -1,4 +1,4 @@
-use crate::crypto::{poseidon_hash, domain_sep_to_field, DOM_OBJ, SchnorrSignature, SchnorrPublicKey};
+use crate::crypto::{poseidon_hash, domain_sep_to_field, DOM_OBJ, DOM_OWNER_OBJECT, SchnorrSignature, SchnorrPublicKey};
 use crate::data_structures::ComplianceState;
 use crate::types::*;
 use ark_bls12_381::Fr as F;
@@ -17,6 +17,9 @@ pub struct ZkObject {
     
     /// Hash-chain head of pending callbacks
     pub cb_head_hash: F,
+    
+    /// Owner the object belongs to; committed, so an update can prove whose object it is
+    pub owner_addr: AuthAddr,
 }
 
 impl ZkObject {
@@ -26,8 +29,15 @@ impl ZkObject {
             state_hash: state.hash(),
             serial: 0,
             cb_head_hash: F::from(0),
+            owner_addr: F::from(0),
         }
     }
+    
+    /// Assign the object to `owner_addr`
+    pub fn with_owner(mut self, owner_addr: AuthAddr) -> Self {
+        self.owner_addr = owner_addr;
+        self
+    }
 
     /// Compute commitment to this object
     pub fn commitment<R: rand::Rng>(&self, rng: &mut R) -> F {
@@ -42,12 +52,18 @@ impl ZkObject {
             self.state_hash,
             F::from(self.serial),
             self.cb_head_hash,
+            self.owner_addr,
             *r_obj,
         ];
         
         poseidon_hash(&input)
     }
 
+    /// Owner index key binding `owner_addr` to its current object commitment
+    pub fn owner_index_key(owner_addr: AuthAddr, object_cm: Commitment) -> F {
+        poseidon_hash(&[domain_sep_to_field(DOM_OWNER_OBJECT), owner_addr, object_cm])
+    }
+
     /// Update object for new callback
     pub fn add_callback(&mut self, callback_entry: &CallbackEntry) {
         // Increment serial for anti-replay
@@ -66,6 +82,13 @@ impl ZkObject {
         self.cb_head_hash = poseidon_hash(&[self.cb_head_hash, callback_to_remove.hash()]);
     }
 
+    /// Force-freeze escalation: freeze the state and commit the object to it
+    pub fn apply_freeze(&mut self, state: &mut ComplianceState) {
+        state.freeze();
+        self.serial += 1;
+        self.state_hash = state.hash();
+    }
+
     /// Check if object is valid for spending
     pub fn can_spend(&self, state: &ComplianceState, _current_time: Time) -> Result<(), String> {
         // Verify state hash matches
@@ -144,6 +167,9 @@ pub struct CallbackInvocation {
     /// Encrypted payload
     pub payload: Vec<u8>,
     
+    /// Sponge commitment to the full payload (length-prefixed)
+    pub payload_commitment: F,
+    
     /// Timestamp of invocation
     pub timestamp: Time,
     
@@ -153,20 +179,36 @@ pub struct CallbackInvocation {
 
 impl CallbackInvocation {
     pub fn new(ticket: F, payload: Vec<u8>, timestamp: Time) -> Self {
+        let payload_commitment = Self::commit_payload(&payload);
         Self {
             ticket,
             payload,
+            payload_commitment,
             timestamp,
             signature: None,
         }
     }
 
+    /// Commit to a payload: H(len || chunk_1 || ... || chunk_n) over 31-byte chunks
+    /// The length prefix makes truncated or zero-stripped payloads commit differently
+    pub fn commit_payload(payload: &[u8]) -> F {
+        let mut input = vec![F::from(payload.len() as u64)];
+        input.extend(payload.chunks(31).map(crate::utils::bytes_to_field));
+        poseidon_hash(&input)
+    }
+
+    /// Check the payload against an expected commitment
+    pub fn verify_payload(&self, expected_commitment: F) -> bool {
+        self.payload_commitment == expected_commitment
+            && Self::commit_payload(&self.payload) == expected_commitment
+    }
+
     /// Sign the invocation with provider's secret key
     pub fn sign(&mut self, provider_sk: &crate::crypto::SchnorrSecretKey) {
         // Create message to sign: H(ticket || payload || timestamp)
         let payload_hash = poseidon_hash(&[
             self.ticket,
-            crate::utils::bytes_to_field(&self.payload),
+            Self::commit_payload(&self.payload),
             F::from(self.timestamp),
         ]);
         
@@ -191,7 +233,7 @@ impl CallbackInvocation {
                 // Create message that was signed
                 let payload_hash = poseidon_hash(&[
                     self.ticket,
-                    crate::utils::bytes_to_field(&self.payload),
+                    Self::commit_payload(&self.payload),
                     F::from(self.timestamp),
                 ]);
                 
@@ -206,7 +248,7 @@ impl CallbackInvocation {
         poseidon_hash(&[
             self.ticket,
             F::from(self.timestamp),
-            crate::utils::bytes_to_field(&self.payload[..32.min(self.payload.len())]),
+            Self::commit_payload(&self.payload),
         ])
     }
     
@@ -364,4 +406,28 @@ mod tests {
         invocation.timestamp = 9999999;
         assert!(!invocation.verify(&provider_pk));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_callback_payload_commitment() {
+        let payload: Vec<u8> = (0..100u8).collect();
+        let invocation = CallbackInvocation::new(F::from(7u64), payload.clone(), 1000);
+        let commitment = CallbackInvocation::commit_payload(&payload);
+
+        // Full payload passes
+        assert_eq!(invocation.payload_commitment, commitment);
+        assert!(invocation.verify_payload(commitment));
+
+        // Truncated payload fails, including a cut inside the first chunk
+        for cut in [99, 62, 31, 20] {
+            let mut truncated = invocation.clone();
+            truncated.payload.truncate(cut);
+            assert!(!truncated.verify_payload(commitment));
+        }
+
+        // Stripping trailing zero bytes changes the commitment too
+        assert_ne!(
+            CallbackInvocation::commit_payload(&[1, 2, 0]),
+            CallbackInvocation::commit_payload(&[1, 2]),
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bloom;
 pub mod crypto;
 pub mod data_structures;
 pub mod merkle;
@@ -6,6 +7,7 @@ pub mod server_verifier;
 pub mod types;
 pub mod utils;
 
+pub use bloom::*;
 pub use crypto::*;
 pub use data_structures::*;
 pub use merkle::*;
@@ -1,4 +1,4 @@
-use super::{MerklePath, MerkleTree, TreeParams};
+use super::{MerklePath, MerkleTree, MultiProof, TreeParams};
 use ark_bls12_381::Fr as F;
 // use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -18,6 +18,9 @@ pub struct IncrementalTree {
     
     /// Current root
     root: F,
+    
+    /// Keep only the frontier (rightmost node per level and its left neighbour)
+    frontier_only: bool,
 }
 
 impl IncrementalTree {
@@ -31,9 +34,37 @@ impl IncrementalTree {
             num_leaves: 0,
             nodes: HashMap::new(),
             root,
+            frontier_only: false,
+        }
+    }
+    
+    /// Create new empty tree that stores O(height) nodes
+    /// Appends, the root and append witnesses work as usual, but only the latest
+    /// leaf keeps a path: `get_path` returns None for older indices
+    pub fn new_frontier(height: usize) -> Self {
+        Self {
+            frontier_only: true,
+            ..Self::new(height)
         }
     }
     
+    /// Whether the tree only keeps its frontier
+    pub fn is_frontier_only(&self) -> bool {
+        self.frontier_only
+    }
+    
+    /// In frontier mode, drop every node except the rightmost one per level and
+    /// its left neighbour: together they supply the siblings of the next append
+    /// and of the latest leaf
+    fn prune_to_frontier(&mut self) {
+        if !self.frontier_only || self.num_leaves == 0 {
+            return;
+        }
+        
+        let last = self.num_leaves - 1;
+        self.nodes.retain(|&(level, index), _| index + 1 >= last >> level);
+    }
+    
     /// Get the number of leaves in the tree
     pub fn num_leaves(&self) -> usize {
         self.num_leaves
@@ -91,6 +122,7 @@ impl IncrementalTree {
         // Update root
         self.root = current_hash;
         self.num_leaves += 1;
+        self.prune_to_frontier();
         
         MerklePath {
             leaf_index,
@@ -99,59 +131,65 @@ impl IncrementalTree {
         }
     }
     
+    /// Append a new leaf and return the new root
+    pub fn append_returning_root(&mut self, leaf: F) -> F {
+        self.append(leaf);
+        self.root
+    }
+    
     /// Batch append multiple leaves
+    /// Each affected node is rehashed once per batch rather than once per leaf;
+    /// the root matches appending the leaves one at a time
     pub fn append_batch(&mut self, leaves: &[F]) -> Vec<MerklePath> {
+        if leaves.is_empty() {
+            return Vec::new();
+        }
+        
         let start_index = self.num_leaves;
+        let end_index = start_index + leaves.len();
         
-        // First, append all leaves
-        for &leaf in leaves {
-            let leaf_index = self.num_leaves;
-            
-            if leaf_index >= self.params.max_leaves() {
-                panic!("Tree is full");
-            }
-            
-            // Store leaf
-            self.nodes.insert((0, leaf_index), leaf);
-            self.num_leaves += 1;
-            
-            // Update nodes up the tree
-            let mut current_index = leaf_index;
-            let mut current_hash = leaf;
+        if end_index > self.params.max_leaves() {
+            panic!("Tree is full");
+        }
+        
+        // Store all leaves first
+        for (offset, &leaf) in leaves.iter().enumerate() {
+            self.nodes.insert((0, start_index + offset), leaf);
+        }
+        self.num_leaves = end_index;
+        
+        // Rehash the span of parents covering the new leaves, one level at a time
+        let mut first = start_index;
+        let mut last = end_index - 1;
+        
+        for level in 0..self.params.height {
+            first >>= 1;
+            last >>= 1;
             
-            for level in 0..self.params.height {
-                let sibling_index = current_index ^ 1;
-                
-                // Get sibling hash
-                let sibling = self.nodes
-                    .get(&(level, sibling_index))
+            for parent_index in first..=last {
+                let left = self.nodes
+                    .get(&(level, parent_index * 2))
+                    .copied()
+                    .unwrap_or_else(|| self.params.empty_at_level(level));
+                let right = self.nodes
+                    .get(&(level, parent_index * 2 + 1))
                     .copied()
                     .unwrap_or_else(|| self.params.empty_at_level(level));
                 
-                // Compute parent hash
-                let parent_index = current_index >> 1;
-                let parent_hash = if current_index & 1 == 0 {
-                    self.params.hash_pair(&current_hash, &sibling)
-                } else {
-                    self.params.hash_pair(&sibling, &current_hash)
-                };
-                
-                // Store parent
+                let parent_hash = self.params.hash_pair(&left, &right);
                 self.nodes.insert((level + 1, parent_index), parent_hash);
-                
-                // Move up
-                current_index = parent_index;
-                current_hash = parent_hash;
             }
-            
-            // Update root
-            self.root = current_hash;
         }
         
+        // Update root
+        self.root = self.nodes[&(self.params.height, 0)];
+        
         // Now generate paths for all the leaves we added
-        (start_index..self.num_leaves)
+        let paths = (start_index..self.num_leaves)
             .map(|i| self.get_path(i).expect("Leaf should exist"))
-            .collect()
+            .collect();
+        self.prune_to_frontier();
+        paths
     }
     
     /// Get siblings for a given index (for pre-insertion witnesses)
@@ -176,10 +214,14 @@ impl IncrementalTree {
     }
     
     /// Get membership proof for existing leaf
+    /// In frontier mode only the latest leaf has a path
     pub fn get_path(&self, leaf_index: usize) -> Option<MerklePath> {
         if leaf_index >= self.num_leaves {
             return None;
         }
+        if self.frontier_only && leaf_index + 1 != self.num_leaves {
+            return None;
+        }
         
         let leaf = self.nodes.get(&(0, leaf_index)).copied()?;
         
@@ -205,6 +247,49 @@ impl IncrementalTree {
         })
     }
     
+    /// Multiproof for several leaves, sharing the upper nodes their paths have in common
+    /// Indices are sorted and deduplicated; leaves are then supplied in that order
+    /// None if an index is past the last leaf, or in frontier mode where historical nodes are not kept
+    pub fn multiproof(&self, indices: &[usize]) -> Option<MultiProof> {
+        if self.frontier_only || indices.iter().any(|&index| index >= self.num_leaves) {
+            return None;
+        }
+        
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        
+        let mut nodes = Vec::new();
+        let mut level_indices = indices.clone();
+        
+        for level in 0..self.params.height {
+            let mut parents = Vec::with_capacity(level_indices.len());
+            let mut i = 0;
+            while i < level_indices.len() {
+                let index = level_indices[i];
+                if index & 1 == 0 && level_indices.get(i + 1) == Some(&(index + 1)) {
+                    // Sibling is itself proven, nothing to supply
+                    i += 1;
+                } else {
+                    let sibling = self.nodes
+                        .get(&(level, index ^ 1))
+                        .copied()
+                        .unwrap_or_else(|| self.params.empty_at_level(level));
+                    nodes.push(sibling);
+                }
+                parents.push(index >> 1);
+                i += 1;
+            }
+            level_indices = parents;
+        }
+        
+        Some(MultiProof {
+            indices,
+            nodes,
+            height: self.params.height,
+        })
+    }
+    
     /// Get leaf at index
     pub fn get_leaf(&self, index: usize) -> Option<F> {
         self.nodes.get(&(0, index)).copied()
@@ -224,11 +309,18 @@ impl IncrementalTree {
     }
     
     /// Recompute root from scratch (for verification)
+    /// In frontier mode the root is rebuilt from the latest leaf's path instead
     pub fn recompute_root(&mut self) -> F {
         if self.num_leaves == 0 {
             return self.params.empty_root();
         }
         
+        if self.frontier_only {
+            return self.get_path(self.num_leaves - 1)
+                .expect("Latest leaf should have a path")
+                .compute_root(&self.params);
+        }
+        
         // Build tree level by level
         let mut level_size = self.params.max_leaves();
         
@@ -297,7 +389,24 @@ impl IncrementalTree {
             current_index >>= 1;
         }
         
-        super::AppendWitness::new(leaf, leaf_index, pre_siblings, self.params.height)
+        let mut witness = super::AppendWitness::new(leaf, leaf_index, pre_siblings, self.params.height);
+        witness.last_leaf_path = leaf_index.checked_sub(1).and_then(|last| self.get_path(last));
+        witness
+    }
+    
+    /// Generate append witnesses for appending `leaves` in order, without inserting them
+    /// Each witness carries the pre-insertion siblings of the tree after the leaves before it,
+    /// ready to use as `TransferCircuit::cmt_appends_out`
+    pub fn generate_append_witnesses_batch(&self, leaves: &[F]) -> Vec<super::AppendWitness> {
+        let mut scratch = self.clone();
+        
+        leaves.iter()
+            .map(|&leaf| {
+                let witness = scratch.generate_append_witness(leaf);
+                scratch.append(leaf);
+                witness
+            })
+            .collect()
     }
 }
 
@@ -362,6 +471,29 @@ mod tests {
         assert_ne!(tree.root(), empty_root);
     }
 
+    #[test]
+    fn test_append_returning_root() {
+        let mut tree = IncrementalTree::new(4);
+        let params = TreeParams::new(4);
+        let mut rng = thread_rng();
+        let mut leaves = Vec::new();
+        
+        for _ in 0..5 {
+            let leaf = F::rand(&mut rng);
+            leaves.push(leaf);
+            let root = tree.append_returning_root(leaf);
+            assert_eq!(root, tree.root());
+            
+            // Reference: hash the padded leaf layer level by level
+            let mut level: Vec<F> = leaves.clone();
+            level.resize(params.max_leaves(), params.empty_at_level(0));
+            while level.len() > 1 {
+                level = level.chunks(2).map(|pair| params.hash_pair(&pair[0], &pair[1])).collect();
+            }
+            assert_eq!(root, level[0]);
+        }
+    }
+
     #[test]
     fn test_batch_append() {
         let mut tree = IncrementalTree::new(4);
@@ -398,6 +530,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_multiproof() {
+        let mut tree = IncrementalTree::new(8);
+        let leaves: Vec<F> = (0..20).map(|i| F::from(i as u64 + 1)).collect();
+        tree.append_batch(&leaves);
+        
+        let proof = tree.multiproof(&[9, 3, 2]).unwrap();
+        assert_eq!(proof.indices, vec![2, 3, 9]);
+        let proven = [leaves[2], leaves[3], leaves[9]];
+        assert!(proof.verify(&tree.root(), &proven, &tree.params));
+        
+        // Shared upper nodes are supplied once rather than per path
+        assert!(proof.nodes.len() < 3 * tree.height());
+        
+        // Wrong leaf, wrong order or extra nodes fail
+        assert!(!proof.verify(&tree.root(), &[leaves[2], leaves[3], leaves[8]], &tree.params));
+        assert!(!proof.verify(&tree.root(), &[leaves[3], leaves[2], leaves[9]], &tree.params));
+        let mut padded = proof.clone();
+        padded.nodes.push(F::from(0u64));
+        assert!(!padded.verify(&tree.root(), &proven, &tree.params));
+        
+        // Unappended leaves and frontier trees have no multiproof
+        assert!(tree.multiproof(&[2, 20]).is_none());
+        let mut frontier = IncrementalTree::new_frontier(8);
+        frontier.append_batch(&leaves);
+        assert!(frontier.multiproof(&[19]).is_none());
+    }
+
     #[test]
     #[should_panic(expected = "Tree is full")]
     fn test_tree_overflow() {
@@ -15,6 +15,9 @@ pub struct AppendWitness {
     pub leaf_index: usize,
     pub pre_siblings: Vec<F>,
     pub height: usize,
+    /// Path to leaf `leaf_index - 1` in the old tree (None for the first append);
+    /// shows the append targets the tree's current size
+    pub last_leaf_path: Option<MerklePath>,
 }
 
 impl AppendWitness {
@@ -24,9 +27,19 @@ impl AppendWitness {
             leaf_index,
             pre_siblings,
             height,
+            last_leaf_path: None,
         }
     }
     
+    /// Check the witness paths were built for a `tree` of the configured `height`
+    pub fn check_height(&self, tree: &'static str, height: usize) -> Result<(), TreeError> {
+        TreeConfig::check_siblings(tree, height, self.pre_siblings.len())?;
+        if let Some(path) = &self.last_leaf_path {
+            TreeConfig::check_siblings(tree, height, path.siblings.len())?;
+        }
+        Ok(())
+    }
+    
     /// Compute the root before insertion
     pub fn compute_old_root(&self, params: &TreeParams) -> F {
         if self.leaf_index == 0 {
@@ -85,8 +98,19 @@ pub struct MerklePath {
 }
 
 impl MerklePath {
+    /// Whether this path has one sibling per level of a tree with these params
+    pub fn matches_params(&self, params: &TreeParams) -> bool {
+        self.siblings.len() == params.height
+    }
+    
     /// Verify membership proof
+    /// A path built for a different tree height is rejected rather than hashed
+    /// against the wrong number of levels
     pub fn verify(&self, root: &F, params: &TreeParams) -> bool {
+        if !self.matches_params(params) {
+            return false;
+        }
+        
         let mut current = self.leaf;
         let mut index = self.leaf_index;
         
@@ -105,7 +129,15 @@ impl MerklePath {
     }
     
     /// Compute the root from this path
+    /// Panics if the path was built for a different tree height than `params`
     pub fn compute_root(&self, params: &TreeParams) -> F {
+        assert!(
+            self.matches_params(params),
+            "Merkle path has {} siblings but tree params have height {}",
+            self.siblings.len(),
+            params.height
+        );
+        
         let mut current = self.leaf;
         let mut index = self.leaf_index;
         
@@ -122,6 +154,93 @@ impl MerklePath {
     }
 }
 
+/// Merkle multiproof for several leaves of one tree
+/// Upper nodes shared by the individual paths are computed once instead of repeated
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiProof {
+    /// Proven leaf indices, sorted and distinct
+    pub indices: Vec<usize>,
+    
+    /// Siblings not derivable from the proven leaves, level by level from the leaves up,
+    /// left to right within a level
+    pub nodes: Vec<F>,
+    
+    /// Tree height
+    pub height: usize,
+}
+
+impl MultiProof {
+    /// Verify that `leaves` (in `indices` order) are in the tree with `root`
+    pub fn verify(&self, root: &F, leaves: &[F], params: &TreeParams) -> bool {
+        if self.height != params.height
+            || leaves.len() != self.indices.len()
+            || self.indices.is_empty()
+            || !self.indices.windows(2).all(|w| w[0] < w[1])
+            || self.indices[self.indices.len() - 1] >= params.max_leaves()
+        {
+            return false;
+        }
+        
+        let mut known: Vec<(usize, F)> = self.indices.iter().copied().zip(leaves.iter().copied()).collect();
+        let mut supplied = self.nodes.iter();
+        
+        for _ in 0..self.height {
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (index, node) = known[i];
+                let parent = if index & 1 == 0 && i + 1 < known.len() && known[i + 1].0 == index + 1 {
+                    // Both children are proven leaves' ancestors
+                    i += 1;
+                    params.hash_pair(&node, &known[i].1)
+                } else {
+                    let sibling = match supplied.next() {
+                        Some(sibling) => sibling,
+                        None => return false,
+                    };
+                    if index & 1 == 0 {
+                        params.hash_pair(&node, sibling)
+                    } else {
+                        params.hash_pair(sibling, &node)
+                    }
+                };
+                parents.push((index >> 1, parent));
+                i += 1;
+            }
+            known = parents;
+        }
+        
+        supplied.next().is_none() && known.len() == 1 && &known[0].1 == root
+    }
+    
+    /// Per level, whether each proven node pairs with its proven neighbour (true) or
+    /// consumes the next supplied sibling (false); this is the shape the gadget follows
+    pub fn shape(&self) -> Vec<Vec<bool>> {
+        let mut level_indices = self.indices.clone();
+        let mut shape = Vec::with_capacity(self.height);
+        
+        for _ in 0..self.height {
+            let mut steps = Vec::new();
+            let mut parents = Vec::new();
+            let mut i = 0;
+            while i < level_indices.len() {
+                let index = level_indices[i];
+                let paired = index & 1 == 0 && level_indices.get(i + 1) == Some(&(index + 1));
+                if paired {
+                    i += 1;
+                }
+                steps.push(paired);
+                parents.push(index >> 1);
+                i += 1;
+            }
+            shape.push(steps);
+            level_indices = parents;
+        }
+        
+        shape
+    }
+}
+
 /// Range proof for non-membership in sorted tree
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RangePath {
@@ -139,13 +258,38 @@ impl RangePath {
     /// Verify non-membership proof
     pub fn verify(&self, root: &F, params: &TreeParams) -> bool {
         // Verify low leaf is in tree
-        if !self.low_path.verify(root, params) {
+        if self.low_path.leaf != self.low_leaf.hash() || !self.low_path.verify(root, params) {
             return false;
         }
         
         // Verify target is in gap
         self.low_leaf.contains_gap(&self.target)
     }
+    
+    /// Verify non-membership proof whose low leaf is stored at `pred_index`
+    /// Rejects proofs that open a valid leaf through a path at another position
+    pub fn verify_at_index(&self, root: &F, params: &TreeParams, pred_index: usize) -> bool {
+        self.low_path.leaf_index == pred_index && self.verify(root, params)
+    }
+}
+
+/// Membership proof for a key in a sorted tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedMembershipProof {
+    /// Leaf holding the key
+    pub leaf: SortedLeaf,
+    
+    /// Path to the leaf
+    pub path: MerklePath,
+}
+
+impl SortedMembershipProof {
+    /// Verify the leaf holds `key` and is in the tree
+    pub fn verify(&self, key: &F, root: &F, params: &TreeParams) -> bool {
+        self.leaf.key == *key
+            && self.path.leaf == self.leaf.hash()
+            && self.path.verify(root, params)
+    }
 }
 
 /// Leaf in sorted Merkle tree
@@ -185,8 +329,28 @@ impl SortedLeaf {
     }
 }
 
+/// Witness data for sorted remove operation
+/// Removal relinks the predecessor first, then empties the removed leaf's slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedRemoveWitness {
+    /// The key being removed
+    pub target: F,
+    /// Leaf being removed; its next_key/next_index are the successor pointers
+    pub removed_leaf: SortedLeaf,
+    /// Path to the removed leaf after the predecessor is relinked (pre-removal)
+    pub removed_leaf_path: MerklePath,
+    /// Predecessor leaf before relinking
+    pub pred_leaf: SortedLeaf,
+    /// Path to the predecessor before relinking
+    pub pred_path: MerklePath,
+    /// Predecessor pointing past the removed leaf to its successor
+    pub updated_pred_leaf: SortedLeaf,
+    /// Tree height
+    pub height: usize,
+}
+
 /// Witness data for sorted insert operation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SortedInsertWitness {
     /// The value being inserted
     pub target: F,
@@ -226,11 +390,11 @@ impl SortedInsertWitness {
     }
     
     /// Compute the root after insertion
-    pub fn compute_new_root(&self, params: &TreeParams) -> F {
-        // This is a simplified computation
-        // In practice, this would properly update the tree structure
-        
-        // Step 1: Compute intermediate root after updating predecessor
+    /// Models the two S-IMT steps: rewrite the predecessor along `pred_update_path`,
+    /// then fill the empty slot at `new_leaf_path` whose siblings reflect that rewrite
+    /// Returns None when the two paths disagree on the intermediate root
+    pub fn compute_new_root(&self, params: &TreeParams) -> Option<F> {
+        // Step 1: Intermediate root after updating the predecessor
         let updated_pred_hash = self.updated_pred_leaf.hash();
         let intermediate_root = self.compute_root_with_leaf(
             &self.pred_update_path,
@@ -238,10 +402,19 @@ impl SortedInsertWitness {
             params,
         );
         
-        // Step 2: For simplicity, just hash the intermediate root with the new leaf
-        // In a real implementation, this would properly insert the new leaf
+        // The new leaf's slot is still empty in the intermediate tree
+        let empty_slot_root = self.compute_root_with_leaf(
+            &self.new_leaf_path,
+            params.empty_at_level(0),
+            params,
+        );
+        if empty_slot_root != intermediate_root {
+            return None;
+        }
+        
+        // Step 2: Insert the new leaf along the intermediate siblings
         let new_leaf_hash = self.new_leaf.hash();
-        poseidon_hash(&[intermediate_root, new_leaf_hash])
+        Some(self.compute_root_with_leaf(&self.new_leaf_path, new_leaf_hash, params))
     }
     
     /// Helper to compute root with a specific leaf
@@ -313,6 +486,23 @@ mod tests {
         assert!(!path.verify(&wrong_root, &params));
     }
 
+    #[test]
+    fn test_merkle_path_height_mismatch() {
+        let mut tree = IncrementalTree::new(16);
+        tree.append(F::from(1u64));
+        let path = tree.get_path(0).unwrap();
+        assert!(path.verify(&tree.root(), &TreeParams::new(16)));
+        
+        // A height-16 path is rejected under height-32 params
+        let params_32 = TreeParams::new(32);
+        assert!(!path.matches_params(&params_32));
+        assert!(!path.verify(&tree.root(), &params_32));
+        assert!(!path.verify(&IncrementalTree::new(32).root(), &params_32));
+        
+        let result = std::panic::catch_unwind(|| path.compute_root(&params_32));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_sorted_leaf() {
         let leaf = SortedLeaf {
@@ -1,13 +1,15 @@
-use super::{MerklePath, MerkleTree, RangePath, SortedLeaf, SortedInsertWitness, TreeParams};
+use super::{MerklePath, MerkleTree, RangePath, SortedLeaf, SortedInsertWitness, SortedMembershipProof, SortedRemoveWitness, TreeParams};
 use ark_bls12_381::Fr as F;
 use ark_ff::{Zero, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 // use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::cmp::Ordering;
 
 /// Wrapper for field elements that orders by field arithmetic
+/// This is the key order of every SortedTree, so insert witnesses chain in it
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct FieldKey(F);
+pub struct FieldKey(pub F);
 
 impl PartialOrd for FieldKey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -72,8 +74,17 @@ impl SortedTree {
         tree
     }
     
+    /// Key 0 belongs to the sentinel leaf and is never a real entry
+    fn reject_sentinel(key: &F) -> Result<(), String> {
+        if key.is_zero() {
+            return Err("Key 0 is reserved for the sentinel leaf".to_string());
+        }
+        Ok(())
+    }
+    
     /// Insert a new key (returns error if already exists)
     pub fn insert(&mut self, key: F) -> Result<MerklePath, String> {
+        Self::reject_sentinel(&key)?;
         if self.sorted_keys.contains_key(&FieldKey(key)) {
             return Err("Key already exists".to_string());
         }
@@ -117,6 +128,7 @@ impl SortedTree {
     
     /// Insert a new key and return witness for circuit verification
     pub fn insert_with_witness(&mut self, key: F) -> Result<SortedInsertWitness, String> {
+        Self::reject_sentinel(&key)?;
         if self.sorted_keys.contains_key(&FieldKey(key)) {
             return Err("Key already exists".to_string());
         }
@@ -174,6 +186,58 @@ impl SortedTree {
     }
     
     
+    /// Remove an existing key, splicing its predecessor past it
+    /// The slot is emptied rather than reused; returns the relinked predecessor's path
+    pub fn remove(&mut self, key: F) -> Result<MerklePath, String> {
+        let witness = self.remove_with_witness(key)?;
+        self.get_path(witness.pred_path.leaf_index)
+            .ok_or_else(|| "Could not get predecessor path".to_string())
+    }
+    
+    /// Remove an existing key and return witness for circuit verification
+    pub fn remove_with_witness(&mut self, key: F) -> Result<SortedRemoveWitness, String> {
+        Self::reject_sentinel(&key)?;
+        let index = *self.sorted_keys.get(&FieldKey(key))
+            .ok_or("Key not found")?;
+        let removed_leaf = self.leaves.get(&index)
+            .ok_or("Removed leaf not found")?
+            .clone();
+        
+        // Store original predecessor leaf and its path
+        let (_pred_key, pred_idx) = self.find_predecessor(&key);
+        let pred_leaf = self.leaves.get(&pred_idx)
+            .ok_or("Predecessor leaf not found")?
+            .clone();
+        let pred_path = self.get_path(pred_idx)
+            .ok_or("Could not get predecessor path")?;
+        
+        // Relink the predecessor to the removed leaf's successor
+        let mut updated_pred_leaf = pred_leaf.clone();
+        updated_pred_leaf.next_key = removed_leaf.next_key;
+        updated_pred_leaf.next_index = removed_leaf.next_index;
+        self.leaves.insert(pred_idx, updated_pred_leaf.clone());
+        self.update_leaf_hash(pred_idx, updated_pred_leaf.hash());
+        
+        // Path to the removed leaf in the relinked tree
+        let removed_leaf_path = self.get_path(index)
+            .ok_or("Could not get removed leaf path")?;
+        
+        // Tombstone the slot: drop the key and hash it as empty
+        self.sorted_keys.remove(&FieldKey(key));
+        self.leaves.remove(&index);
+        self.update_leaf_hash(index, self.params.empty_at_level(0));
+        
+        Ok(SortedRemoveWitness {
+            target: key,
+            removed_leaf,
+            removed_leaf_path,
+            pred_leaf,
+            pred_path,
+            updated_pred_leaf,
+            height: self.params.height,
+        })
+    }
+    
     /// Insert leaf at next available index
     fn insert_leaf(&mut self, leaf: SortedLeaf) -> MerklePath {
         let index = self.next_index;
@@ -266,6 +330,7 @@ impl SortedTree {
     
     /// Get non-membership proof for a key
     pub fn prove_non_membership(&self, target: F) -> Result<RangePath, String> {
+        Self::reject_sentinel(&target)?;
         if self.sorted_keys.contains_key(&FieldKey(target)) {
             return Err("Key exists, cannot prove non-membership".to_string());
         }
@@ -291,12 +356,23 @@ impl SortedTree {
         })
     }
     
-    /// Get membership proof for existing key
+    /// Get membership proof for existing key (never for the sentinel)
     pub fn prove_membership(&self, key: F) -> Option<MerklePath> {
+        Self::reject_sentinel(&key).ok()?;
         let index = *self.sorted_keys.get(&FieldKey(key))?;
         self.get_path(index)
     }
     
+    /// Get membership proof (leaf and path) for existing key
+    pub fn prove_key_membership(&self, key: F) -> Option<SortedMembershipProof> {
+        Self::reject_sentinel(&key).ok()?;
+        let index = *self.sorted_keys.get(&FieldKey(key))?;
+        Some(SortedMembershipProof {
+            leaf: self.leaves.get(&index)?.clone(),
+            path: self.get_path(index)?,
+        })
+    }
+    
     /// Get path for leaf at index
     pub fn get_path(&self, leaf_index: usize) -> Option<MerklePath> {
         let leaf_hash = self.nodes.get(&(0, leaf_index)).copied()?;
@@ -323,6 +399,11 @@ impl SortedTree {
         })
     }
     
+    /// Index of the leaf holding an existing key
+    pub fn index_of(&self, key: &F) -> Option<usize> {
+        self.sorted_keys.get(&FieldKey(*key)).copied()
+    }
+    
     /// Check if key exists
     pub fn contains(&self, key: &F) -> bool {
         self.sorted_keys.contains_key(&FieldKey(*key))
@@ -338,6 +419,7 @@ impl SortedTree {
     pub fn export_insert_witness(&self, key: F) -> Result<SortedInsertWitness, String> {
         use crate::merkle::SortedInsertWitness;
         
+        Self::reject_sentinel(&key)?;
         if self.sorted_keys.contains_key(&FieldKey(key)) {
             return Err("Key already exists".to_string());
         }
@@ -478,6 +560,193 @@ impl SortedTree {
     pub fn root(&self) -> F {
         self.root
     }
+    
+    /// Get the height of the tree
+    pub fn height(&self) -> usize {
+        self.params.height
+    }
+    
+    /// Number of real entries (the key-0 sentinel is not counted)
+    pub fn len_excluding_sentinels(&self) -> usize {
+        let sentinels = usize::from(self.contains(&F::zero()));
+        self.sorted_keys.len() - sentinels
+    }
+    
+    /// Aggregate statistics; reveals counts only, never keys
+    /// Slots emptied by `remove` are left out of `leaf_count` but still use up capacity
+    pub fn stats(&self) -> SortedTreeStats {
+        let height = self.params.height as u32;
+        SortedTreeStats {
+            leaf_count: self.leaves.len(),
+            entries: self.len_excluding_sentinels(),
+            capacity: 1u128.checked_shl(height),
+            utilization: self.next_index as f64 / 2f64.powi(height as i32),
+        }
+    }
+    
+    /// Persist the tree: height, next index, occupied leaves in index order, and the
+    /// root as a checksum. Internal nodes are not written; `deserialize` recomputes them
+    pub fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        (self.params.height as u64).serialize_compressed(&mut writer)?;
+        (self.next_index as u64).serialize_compressed(&mut writer)?;
+        let indices: Vec<u64> = (0..self.next_index)
+            .filter(|index| self.leaves.contains_key(index))
+            .map(|index| index as u64)
+            .collect();
+        let leaves: Vec<SortedLeaf> = indices.iter()
+            .map(|&index| self.leaves[&(index as usize)].clone())
+            .collect();
+        indices.serialize_compressed(&mut writer)?;
+        leaves.serialize_compressed(&mut writer)?;
+        self.root.serialize_compressed(&mut writer)
+    }
+    
+    /// Reload a tree written by `serialize`, rebuilding nodes and the key index
+    /// Fails with `InvalidData` if the rebuilt root differs from the stored one
+    pub fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let height = u64::deserialize_compressed(&mut reader)? as usize;
+        let next_index = u64::deserialize_compressed(&mut reader)? as usize;
+        let indices = Vec::<u64>::deserialize_compressed(&mut reader)?;
+        let leaves = Vec::<SortedLeaf>::deserialize_compressed(&mut reader)?;
+        let stored_root = F::deserialize_compressed(&mut reader)?;
+        
+        let params = TreeParams::new(height);
+        if next_index == 0 || next_index > params.max_leaves() || indices.len() != leaves.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        
+        let mut tree = Self {
+            root: params.empty_root(),
+            params,
+            sorted_keys: BTreeMap::new(),
+            leaves: HashMap::new(),
+            nodes: HashMap::new(),
+            next_index,
+        };
+        
+        // Emptied slots are absent from the leaf list and hash as empty
+        for index in 0..next_index {
+            tree.nodes.insert((0, index), tree.params.empty_at_level(0));
+        }
+        for (index, leaf) in indices.into_iter().zip(leaves) {
+            let index = index as usize;
+            if index >= next_index || tree.leaves.contains_key(&index) {
+                return Err(SerializationError::InvalidData);
+            }
+            if tree.sorted_keys.insert(FieldKey(leaf.key), index).is_some() {
+                return Err(SerializationError::InvalidData);
+            }
+            tree.nodes.insert((0, index), leaf.hash());
+            tree.leaves.insert(index, leaf);
+        }
+        
+        // Rebuild each level from the occupied span below it
+        let mut width = tree.next_index;
+        for level in 0..tree.params.height {
+            width = (width + 1) / 2;
+            for index in 0..width {
+                let left = tree.nodes
+                    .get(&(level, index * 2))
+                    .copied()
+                    .unwrap_or_else(|| tree.params.empty_at_level(level));
+                let right = tree.nodes
+                    .get(&(level, index * 2 + 1))
+                    .copied()
+                    .unwrap_or_else(|| tree.params.empty_at_level(level));
+                let parent = tree.params.hash_pair(&left, &right);
+                tree.nodes.insert((level + 1, index), parent);
+            }
+        }
+        tree.root = tree.nodes[&(tree.params.height, 0)];
+        
+        if tree.root != stored_root {
+            return Err(SerializationError::InvalidData);
+        }
+        
+        Ok(tree)
+    }
+    
+    /// Compare two trees beyond their roots: height, leaf count, the sorted key list, each
+    /// key's successor, and that every link's next_index lands on the leaf holding next_key
+    /// Leaf positions may differ (they follow insertion order); the first discrepancy is returned
+    pub fn structural_eq(&self, other: &SortedTree) -> Result<(), StructuralDiff> {
+        if self.params.height != other.params.height {
+            return Err(StructuralDiff::Height { ours: self.params.height, theirs: other.params.height });
+        }
+        if self.next_index != other.next_index {
+            return Err(StructuralDiff::LeafCount { ours: self.next_index, theirs: other.next_index });
+        }
+        
+        let ours = self.keys();
+        let theirs = other.keys();
+        for position in 0..ours.len().max(theirs.len()) {
+            let (a, b) = (ours.get(position).copied(), theirs.get(position).copied());
+            if a != b {
+                return Err(StructuralDiff::Key { position, ours: a, theirs: b });
+            }
+        }
+        
+        for key in &ours {
+            let (a, b) = (self.leaf_for(key), other.leaf_for(key));
+            if a.next_key != b.next_key {
+                return Err(StructuralDiff::NextKey { key: *key, ours: a.next_key, theirs: b.next_key });
+            }
+        }
+        
+        self.check_links(true)?;
+        other.check_links(false)
+    }
+    
+    /// Leaf holding a key known to be in the tree
+    fn leaf_for(&self, key: &F) -> &SortedLeaf {
+        &self.leaves[&self.sorted_keys[&FieldKey(*key)]]
+    }
+    
+    /// Every leaf links to its sorted successor, and next_index points at that successor's leaf
+    fn check_links(&self, is_ours: bool) -> Result<(), StructuralDiff> {
+        let keys = self.keys();
+        for (position, key) in keys.iter().enumerate() {
+            let leaf = self.leaf_for(key);
+            let successor = keys.get(position + 1).copied().unwrap_or_else(F::zero);
+            let lands_on_successor = successor.is_zero()
+                || self.leaves.get(&leaf.next_index).map_or(false, |next| next.key == successor);
+            if leaf.next_key != successor || !lands_on_successor {
+                return Err(StructuralDiff::BrokenLink { key: *key, next_index: leaf.next_index, is_ours });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// First difference found by `SortedTree::structural_eq`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StructuralDiff {
+    /// Trees of different heights
+    Height { ours: usize, theirs: usize },
+    /// Different number of occupied leaf slots
+    LeafCount { ours: usize, theirs: usize },
+    /// Sorted key lists differ at `position` (None past the end of a list)
+    Key { position: usize, ours: Option<F>, theirs: Option<F> },
+    /// Same key, different successor
+    NextKey { key: F, ours: F, theirs: F },
+    /// A leaf in one tree (ours if `is_ours`) doesn't link to its sorted successor
+    BrokenLink { key: F, next_index: usize, is_ours: bool },
+}
+
+/// Aggregate statistics for a sorted tree
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SortedTreeStats {
+    /// Occupied leaf slots, including sentinels; emptied slots are not counted
+    pub leaf_count: usize,
+    
+    /// Real entries, excluding sentinels
+    pub entries: usize,
+    
+    /// Total leaf slots (2^height), None if that does not fit in a u128
+    pub capacity: Option<u128>,
+    
+    /// Slots handed out / 2^height; emptied slots count since they are never reused
+    pub utilization: f64,
 }
 
 impl MerkleTree for SortedTree {
@@ -503,6 +772,126 @@ pub type CallbackTree = SortedTree;
 /// Sanctions tree for blacklisted entities
 pub type SanctionsTree = SortedTree;
 
+/// Default number of insert witnesses kept by a `CachedSortedTree`
+pub const DEFAULT_WITNESS_CACHE_CAPACITY: usize = 256;
+
+/// Sorted tree with an LRU cache of insert witnesses keyed by (root, target)
+/// Retries against an unchanged root reuse the cached witness; any mutation clears the cache
+#[derive(Clone, Debug)]
+pub struct CachedSortedTree {
+    /// Underlying tree; only reachable mutably through this wrapper
+    tree: SortedTree,
+    
+    /// Cached witnesses by (root, target)
+    witnesses: HashMap<(F, F), SortedInsertWitness>,
+    
+    /// Keys from least to most recently used
+    lru: VecDeque<(F, F)>,
+    
+    /// Maximum number of cached witnesses
+    capacity: usize,
+    
+    /// Lookups served from the cache
+    hits: u64,
+    
+    /// Lookups that had to build a witness
+    misses: u64,
+}
+
+impl CachedSortedTree {
+    /// Wrap a tree with the default cache capacity
+    pub fn new(tree: SortedTree) -> Self {
+        Self::with_capacity(tree, DEFAULT_WITNESS_CACHE_CAPACITY)
+    }
+    
+    /// Wrap a tree keeping at most `capacity` witnesses
+    pub fn with_capacity(tree: SortedTree, capacity: usize) -> Self {
+        Self {
+            tree,
+            witnesses: HashMap::new(),
+            lru: VecDeque::new(),
+            capacity: capacity.max(1),
+            hits: 0,
+            misses: 0,
+        }
+    }
+    
+    /// Read-only access to the underlying tree
+    pub fn tree(&self) -> &SortedTree {
+        &self.tree
+    }
+    
+    /// Unwrap, dropping the cache
+    pub fn into_inner(self) -> SortedTree {
+        self.tree
+    }
+    
+    /// Export an insert witness, reusing a cached one for the same (root, target)
+    pub fn export_insert_witness(&mut self, key: F) -> Result<SortedInsertWitness, String> {
+        let cache_key = (self.tree.root(), key);
+        if let Some(witness) = self.witnesses.get(&cache_key) {
+            let witness = witness.clone();
+            self.hits += 1;
+            self.touch(cache_key);
+            return Ok(witness);
+        }
+        
+        self.misses += 1;
+        let witness = self.tree.export_insert_witness(key)?;
+        if self.witnesses.len() >= self.capacity {
+            if let Some(evicted) = self.lru.pop_front() {
+                self.witnesses.remove(&evicted);
+            }
+        }
+        self.witnesses.insert(cache_key, witness.clone());
+        self.lru.push_back(cache_key);
+        Ok(witness)
+    }
+    
+    /// Insert a key, invalidating cached witnesses
+    pub fn insert(&mut self, key: F) -> Result<MerklePath, String> {
+        let path = self.tree.insert(key)?;
+        self.invalidate();
+        Ok(path)
+    }
+    
+    /// Insert a key returning its witness, invalidating cached witnesses
+    pub fn insert_with_witness(&mut self, key: F) -> Result<SortedInsertWitness, String> {
+        let witness = self.tree.insert_with_witness(key)?;
+        self.invalidate();
+        Ok(witness)
+    }
+    
+    /// Number of lookups served from the cache
+    pub fn cache_hits(&self) -> u64 {
+        self.hits
+    }
+    
+    /// Number of lookups that built a fresh witness
+    pub fn cache_misses(&self) -> u64 {
+        self.misses
+    }
+    
+    /// Number of witnesses currently cached
+    pub fn cached_len(&self) -> usize {
+        self.witnesses.len()
+    }
+    
+    /// Drop every cached witness
+    pub fn invalidate(&mut self) {
+        self.witnesses.clear();
+        self.lru.clear();
+    }
+    
+    /// Mark a cached key as most recently used
+    fn touch(&mut self, cache_key: (F, F)) {
+        if let Some(pos) = self.lru.iter().position(|k| *k == cache_key) {
+            self.lru.remove(pos);
+        }
+        self.lru.push_back(cache_key);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -547,6 +936,49 @@ mod tests {
         assert_eq!(keys[3], key2);
     }
 
+    #[test]
+    fn test_structural_eq() {
+        let keys = [F::from(100), F::from(200), F::from(150), F::from(50)];
+        let mut primary = SortedTree::new(4);
+        let mut replica = SortedTree::new(4);
+        for key in &keys {
+            primary.insert(*key).unwrap();
+        }
+        for key in keys.iter().rev() {
+            replica.insert(*key).unwrap();
+        }
+        
+        // Leaf positions differ with insertion order, but the linked lists match
+        assert_ne!(primary.root(), replica.root());
+        assert_eq!(primary.structural_eq(&replica), Ok(()));
+        assert_eq!(replica.structural_eq(&primary), Ok(()));
+        
+        // A replica missing a key
+        let mut short = SortedTree::new(4);
+        for key in &keys[..3] {
+            short.insert(*key).unwrap();
+        }
+        assert!(matches!(primary.structural_eq(&short), Err(StructuralDiff::LeafCount { .. })));
+        
+        // A corrupted link is reported against the tree holding it
+        let mut corrupted = replica.clone();
+        let index = corrupted.sorted_keys[&FieldKey(F::from(150))];
+        corrupted.leaves.get_mut(&index).unwrap().next_index = 0;
+        assert_eq!(
+            primary.structural_eq(&corrupted),
+            Err(StructuralDiff::BrokenLink { key: F::from(150), next_index: 0, is_ours: false })
+        );
+        
+        // A successor that skips a key
+        let mut skipped = replica.clone();
+        let index = skipped.sorted_keys[&FieldKey(F::from(100))];
+        skipped.leaves.get_mut(&index).unwrap().next_key = F::from(200);
+        assert_eq!(
+            primary.structural_eq(&skipped),
+            Err(StructuralDiff::NextKey { key: F::from(100), ours: F::from(150), theirs: F::from(200) })
+        );
+    }
+
     #[test]
     fn test_non_membership_proof() {
         let mut tree = SortedTree::new(4);
@@ -579,6 +1011,156 @@ mod tests {
         assert_eq!(result.unwrap_err(), "Key already exists");
     }
 
+    #[test]
+    fn test_sentinel_key_rejected() {
+        let mut tree = SortedTree::new(4);
+        let root = tree.root();
+        let sentinel_error = "Key 0 is reserved for the sentinel leaf".to_string();
+        
+        // A field-zero nullifier can't be inserted or proven either way
+        assert_eq!(tree.insert(F::zero()).unwrap_err(), sentinel_error);
+        assert_eq!(tree.insert_with_witness(F::zero()).unwrap_err(), sentinel_error);
+        assert_eq!(tree.export_insert_witness(F::zero()).unwrap_err(), sentinel_error);
+        assert_eq!(tree.prove_non_membership(F::zero()).unwrap_err(), sentinel_error);
+        assert!(tree.prove_membership(F::zero()).is_none());
+        assert!(tree.prove_key_membership(F::zero()).is_none());
+        
+        // Tree is untouched
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.len_excluding_sentinels(), 0);
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        use ark_ff::UniformRand;
+        use rand::thread_rng;
+        
+        let mut rng = thread_rng();
+        let mut tree = SortedTree::new(16);
+        for _ in 0..1000 {
+            tree.insert(F::rand(&mut rng)).unwrap();
+        }
+        
+        let mut bytes = Vec::new();
+        tree.serialize(&mut bytes).unwrap();
+        let mut reloaded = SortedTree::deserialize(&bytes[..]).unwrap();
+        
+        assert_eq!(reloaded.root(), tree.root());
+        assert_eq!(reloaded.keys(), tree.keys());
+        assert_eq!(reloaded.next_index(), tree.next_index());
+        
+        // Fresh proofs and inserts still work against the reloaded tree
+        let absent = F::rand(&mut rng);
+        let proof = reloaded.prove_non_membership(absent).unwrap();
+        assert!(proof.verify(&tree.root(), &tree.params));
+        let witness = reloaded.insert_with_witness(absent).unwrap();
+        tree.insert(absent).unwrap();
+        assert_eq!(witness.target, absent);
+        assert_eq!(reloaded.root(), tree.root());
+        
+        // A corrupted checksum is rejected
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(SortedTree::deserialize(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn test_insert_witness_new_root() {
+        let mut tree = SortedTree::new(4);
+        for key in [100u64, 300, 200] {
+            tree.insert(F::from(key)).unwrap();
+        }
+        
+        // Exported witness predicts the root the insert produces
+        for key in [150u64, 50, 400] {
+            let witness = tree.export_insert_witness(F::from(key)).unwrap();
+            let predicted = witness.compute_new_root(&tree.params);
+            tree.insert(F::from(key)).unwrap();
+            assert_eq!(predicted, Some(tree.root()));
+        }
+        
+        // So does the witness returned alongside an insert
+        let witness = tree.insert_with_witness(F::from(250)).unwrap();
+        assert_eq!(witness.compute_new_root(&tree.params), Some(tree.root()));
+        
+        // A predecessor path that disagrees with the new leaf path is rejected
+        let mut bad = tree.export_insert_witness(F::from(175)).unwrap();
+        bad.pred_update_path.siblings[1] += F::from(1u64);
+        assert_eq!(bad.compute_new_root(&tree.params), None);
+    }
+
+    #[test]
+    fn test_remove_relinks_predecessor() {
+        let mut tree = SortedTree::new(4);
+        for key in [100u64, 200, 300] {
+            tree.insert(F::from(key)).unwrap();
+        }
+        let index_100 = tree.index_of(&F::from(100)).unwrap();
+        let index_300 = tree.index_of(&F::from(300)).unwrap();
+        
+        // Removing a middle key points its predecessor at its successor
+        let pred_path = tree.remove(F::from(200)).unwrap();
+        assert_eq!(pred_path.leaf_index, index_100);
+        assert!(pred_path.verify(&tree.root(), &tree.params));
+        let pred = &tree.leaves[&index_100];
+        assert_eq!(pred.next_key, F::from(300));
+        assert_eq!(pred.next_index, index_300);
+        assert!(!tree.contains(&F::from(200)));
+        
+        // The removed key is provably absent again
+        let proof = tree.prove_non_membership(F::from(200)).unwrap();
+        assert!(proof.verify(&tree.root(), &tree.params));
+        assert_eq!(proof.low_leaf.key, F::from(100));
+        
+        // Removing the largest key leaves its predecessor at the end of the list
+        tree.remove(F::from(300)).unwrap();
+        assert_eq!(tree.leaves[&index_100].next_key, F::zero());
+        assert!(tree.prove_non_membership(F::from(1000)).unwrap().verify(&tree.root(), &tree.params));
+        
+        // Missing keys and the sentinel can't be removed
+        assert_eq!(tree.remove(F::from(200)).unwrap_err(), "Key not found");
+        assert!(tree.remove(F::zero()).is_err());
+        
+        // Emptied slots leave the leaf count but keep taking up capacity
+        let stats = tree.stats();
+        assert_eq!(stats.leaf_count, 2);
+        assert_eq!(stats.utilization, 4.0 / 16.0);
+        
+        // Removed keys can be inserted again
+        tree.insert(F::from(200)).unwrap();
+        assert!(tree.prove_key_membership(F::from(200)).unwrap().verify(&F::from(200), &tree.root(), &tree.params));
+        
+        // Emptied slots survive a round trip
+        let mut bytes = Vec::new();
+        tree.serialize(&mut bytes).unwrap();
+        assert_eq!(SortedTree::deserialize(&bytes[..]).unwrap().root(), tree.root());
+    }
+
+    #[test]
+    fn test_remove_witness() {
+        let mut tree = SortedTree::new(4);
+        for key in [100u64, 200, 300] {
+            tree.insert(F::from(key)).unwrap();
+        }
+        let old_root = tree.root();
+        
+        let witness = tree.remove_with_witness(F::from(200)).unwrap();
+        assert_eq!(witness.target, F::from(200));
+        assert_eq!(witness.pred_leaf.key, F::from(100));
+        assert_eq!(witness.updated_pred_leaf.next_key, witness.removed_leaf.next_key);
+        assert_eq!(witness.updated_pred_leaf.next_index, witness.removed_leaf.next_index);
+        
+        // Predecessor path opens the old root; the removed leaf path opens the relinked one
+        assert_eq!(witness.pred_path.leaf, witness.pred_leaf.hash());
+        assert!(witness.pred_path.verify(&old_root, &tree.params));
+        assert_eq!(witness.removed_leaf_path.leaf, witness.removed_leaf.hash());
+        
+        // Emptying the removed slot along its path gives the final root
+        let mut emptied = witness.removed_leaf_path.clone();
+        emptied.leaf = tree.params.empty_at_level(0);
+        assert!(emptied.verify(&tree.root(), &tree.params));
+    }
+
     #[test]
     fn test_membership_proof() {
         let mut tree = SortedTree::new(4);
@@ -613,4 +1195,48 @@ mod tests {
         let proof3 = tree.prove_non_membership(F::from(2000)).unwrap();
         assert!(proof3.verify(&tree.root(), &tree.params));
     }
+
+    #[test]
+    fn test_stats_tall_tree() {
+        // 2^64 leaves overflows usize; 2^130 overflows u128
+        let stats = SortedTree::new(64).stats();
+        assert_eq!(stats.capacity, Some(1u128 << 64));
+        assert!(stats.utilization > 0.0);
+        
+        let stats = SortedTree::new(130).stats();
+        assert_eq!(stats.capacity, None);
+        assert_eq!(stats.leaf_count, 1);
+    }
+    
+    #[test]
+    fn test_insert_witness_cache() {
+        let mut tree = SortedTree::new(8);
+        tree.insert(F::from(100)).unwrap();
+        let mut cached = CachedSortedTree::with_capacity(tree, 2);
+        
+        let first = cached.export_insert_witness(F::from(150)).unwrap();
+        let second = cached.export_insert_witness(F::from(150)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(cached.cache_misses(), 1);
+        assert_eq!(cached.cache_hits(), 1);
+        
+        // Mutation invalidates, and the fresh witness reflects the new root
+        cached.insert(F::from(300)).unwrap();
+        assert_eq!(cached.cached_len(), 0);
+        let third = cached.export_insert_witness(F::from(150)).unwrap();
+        assert_eq!(cached.cache_misses(), 2);
+        assert_ne!(third, first);
+        assert_eq!(third, cached.tree().export_insert_witness(F::from(150)).unwrap());
+        
+        // Least recently used entry is evicted at capacity
+        cached.export_insert_witness(F::from(200)).unwrap();
+        cached.export_insert_witness(F::from(150)).unwrap();
+        cached.export_insert_witness(F::from(250)).unwrap();
+        assert_eq!(cached.cached_len(), 2);
+        let hits = cached.cache_hits();
+        cached.export_insert_witness(F::from(150)).unwrap();
+        assert_eq!(cached.cache_hits(), hits + 1);
+        cached.export_insert_witness(F::from(200)).unwrap();
+        assert_eq!(cached.cache_hits(), hits + 1);
+    }
 }
\ No newline at end of file
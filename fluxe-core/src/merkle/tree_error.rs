@@ -20,6 +20,13 @@ pub enum TreeError {
     
     /// Tree corruption detected
     Corrupted,
+    
+    /// Witness path built for a different height than the configured tree
+    HeightMismatch {
+        tree: &'static str,
+        expected: usize,
+        actual: usize,
+    },
 }
 
 impl fmt::Display for TreeError {
@@ -31,6 +38,11 @@ impl fmt::Display for TreeError {
             TreeError::InvalidDepth => write!(f, "Invalid depth"),
             TreeError::DuplicateEntry => write!(f, "Duplicate entry"),
             TreeError::Corrupted => write!(f, "Tree corruption detected"),
+            TreeError::HeightMismatch { tree, expected, actual } => write!(
+                f,
+                "{} witness has {} siblings but the tree height is {}",
+                tree, actual, expected
+            ),
         }
     }
 }
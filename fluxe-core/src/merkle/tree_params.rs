@@ -1,4 +1,6 @@
 use crate::crypto::poseidon_hash;
+use crate::merkle::TreeError;
+use crate::state_manager::DEFAULT_TREE_DEPTH;
 use ark_bls12_381::Fr as F;
 use ark_ff::Zero;
 
@@ -48,9 +50,9 @@ impl TreeParams {
         self.empty_hashes[self.height]
     }
     
-    /// Maximum number of leaves
+    /// Maximum number of leaves, saturating at usize::MAX for trees of height 64 and up
     pub fn max_leaves(&self) -> usize {
-        1 << self.height
+        1usize.checked_shl(self.height as u32).unwrap_or(usize::MAX)
     }
 }
 
@@ -61,6 +63,65 @@ impl Default for TreeParams {
     }
 }
 
+/// Height of each state tree, shared by the state manager, the verifier and the circuits
+/// so witness paths are always built for the height the circuit expects
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeConfig {
+    /// Note commitment tree (CMT_ROOT)
+    pub cmt: usize,
+    
+    /// Nullifier tree (NFT_ROOT)
+    pub nft: usize,
+    
+    /// Callback tree (CB_ROOT)
+    pub cb: usize,
+    
+    /// Sanctions list (SANCTIONS_ROOT)
+    pub sanctions: usize,
+    
+    /// Exit receipts (EXIT_ROOT)
+    pub exit: usize,
+    
+    /// Ingress receipts (INGRESS_ROOT)
+    pub ingress: usize,
+    
+    /// zk-Objects (OBJ_ROOT)
+    pub object: usize,
+}
+
+impl TreeConfig {
+    /// Every tree at the same height
+    pub fn uniform(height: usize) -> Self {
+        Self {
+            cmt: height,
+            nft: height,
+            cb: height,
+            sanctions: height,
+            exit: height,
+            ingress: height,
+            object: height,
+        }
+    }
+    
+    /// Check a witness path for `tree` has one sibling per level of its configured `height`
+    pub fn check_siblings(tree: &'static str, height: usize, siblings: usize) -> Result<(), TreeError> {
+        if siblings != height {
+            return Err(TreeError::HeightMismatch {
+                tree,
+                expected: height,
+                actual: siblings,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self::uniform(DEFAULT_TREE_DEPTH)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +157,17 @@ mod tests {
         let hash3 = params.hash_pair(&right, &left);
         assert_ne!(hash1, hash3);
     }
+
+    #[test]
+    fn test_tree_config() {
+        let config = TreeConfig::default();
+        assert_eq!(config, TreeConfig::uniform(DEFAULT_TREE_DEPTH));
+        
+        let config = TreeConfig { nft: 16, ..TreeConfig::uniform(32) };
+        assert!(TreeConfig::check_siblings("CMT", config.cmt, 32).is_ok());
+        
+        // A height-32 path against the height-16 NFT names the tree and both heights
+        let err = TreeConfig::check_siblings("NFT", config.nft, 32).unwrap_err();
+        assert_eq!(err.to_string(), "NFT witness has 32 siblings but the tree height is 16");
+    }
 }
\ No newline at end of file
@@ -1,12 +1,20 @@
 use crate::{
     data_structures::{ExitReceipt, IngressReceipt, Note},
-    state_manager::StateManager,
+    merkle::{IncrementalTree, MerklePath, SortedTreeStats, TreeConfig, TreeParams},
+    state_manager::{MaintenanceConfig, MaintenanceReport, StateManager, StateOperation, TransitionProof},
     types::*,
+    utils::{field_fits_u64, field_to_u64},
 };
 use ark_bls12_381::Fr as F;
-use ark_groth16::{Groth16, Proof, VerifyingKey};
-use ark_serialize::CanonicalSerialize;
+use ark_ff::PrimeField;
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use ark_serialize::{Compress, Read, SerializationError, Validate, Write};
 use ark_snark::SNARK;
+use ark_std::rand::Rng;
+use rayon::prelude::*;
 
 /// Server-side batch verifier implementing section 12.4 of the spec
 /// Verifies client proofs and deterministically reapplies Merkle operations
@@ -14,14 +22,327 @@ pub struct ServerVerifier {
     /// State manager for tracking roots and trees
     state: StateManager,
     
-    /// Verifying keys for different circuit types
-    vk_mint: VerifyingKey<ark_bls12_381::Bls12_381>,
-    vk_burn: VerifyingKey<ark_bls12_381::Bls12_381>,
-    vk_transfer: VerifyingKey<ark_bls12_381::Bls12_381>,
-    vk_object_update: VerifyingKey<ark_bls12_381::Bls12_381>,
+    /// Verifying keys for different circuit types (None if not loaded)
+    vk_mint: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    vk_burn: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    vk_transfer: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    vk_object_update: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
     
     /// Pending transaction batch
     pending_batch: TransactionBatch,
+    
+    /// State the pending batch leaves, advanced on every admission (None while nothing is pending)
+    pending_overlay: Option<StateManager>,
+    
+    /// Observer mode: rejected submissions with reasons (None if disabled)
+    rejection_log: Option<Vec<RejectedTx>>,
+    
+    /// Fingerprints of the loaded verifying keys, refreshed on every key rotation
+    key_fingerprints: Vec<(TransactionType, [u8; 32])>,
+    
+    /// What counts as risky when tagging accepted transactions
+    risk_policy: RiskPolicy,
+    
+    /// Accepted transactions carrying at least one risk tag, for downstream review
+    flagged: Vec<FlaggedTx>,
+    
+    /// Most callback operations an object update may carry
+    max_callback_ops: usize,
+    
+    /// Superseded sanctions roots a transfer may still reference (None = no freshness check)
+    sanctions_window: Option<usize>,
+    
+    /// Sanctions roots replaced through `update_sanctions_root`, most recent last
+    previous_sanctions_roots: Vec<MerkleRoot>,
+    
+    /// Most commitments one batch may append to CMT (None = unbounded)
+    max_batch_appends: Option<usize>,
+    
+    /// Optional public-input sections of the loaded transfer key's circuit
+    transfer_layout: TransferLayout,
+    
+    /// Review window transfers are held to (None = transfers with one are rejected)
+    review_policy: Option<ReviewPolicy>,
+    
+    /// Inclusion receipts of batched transactions, by private id
+    inclusion_receipts: std::collections::HashMap<F, InclusionReceipt>,
+}
+
+/// Height of the per-block tree committing to the batch's transactions
+pub const BATCH_TX_TREE_HEIGHT: usize = 16;
+
+/// Reason an accepted transaction is flagged for compliance review
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RiskTag {
+    /// Transfer whose circuit publishes that some sender or recipient address took the
+    /// address-non-zero fallback instead of a sanctions non-membership proof
+    MissingSanctionsProof,
+    /// Creates a note in one of the policy's high-risk pools
+    HighRiskPool(PoolId),
+    /// Mint or burn of at least the policy's large-amount threshold
+    LargeAmount,
+}
+
+/// Thresholds used to compute `RiskTag`s
+#[derive(Clone, Debug)]
+pub struct RiskPolicy {
+    pub high_risk_pools: Vec<PoolId>,
+    pub large_amount: Amount,
+}
+
+impl Default for RiskPolicy {
+    fn default() -> Self {
+        Self {
+            high_risk_pools: Vec::new(),
+            large_amount: Amount::from(u128::MAX),
+        }
+    }
+}
+
+impl RiskPolicy {
+    /// Risk tags for a transaction (empty if nothing stands out)
+    /// Sanctions fallbacks are only known for transfer keys whose `layout` publishes them
+    pub fn tags_for(&self, tx: &VerifiedTransaction, layout: &TransferLayout) -> Vec<RiskTag> {
+        let mut tags = Vec::new();
+        match &tx.transaction_data {
+            TransactionData::Transfer { notes_out, .. } => {
+                if layout.sanctions_fallbacks(tx).map_or(false, |count| !count.is_zero()) {
+                    tags.push(RiskTag::MissingSanctionsProof);
+                }
+                tags.extend(self.high_risk_pool_tags(notes_out));
+            }
+            TransactionData::Mint { amount, notes_out, .. } => {
+                if *amount >= self.large_amount {
+                    tags.push(RiskTag::LargeAmount);
+                }
+                tags.extend(self.high_risk_pool_tags(notes_out));
+            }
+            TransactionData::Burn { amount, .. } => {
+                if *amount >= self.large_amount {
+                    tags.push(RiskTag::LargeAmount);
+                }
+            }
+            TransactionData::ObjectUpdate { .. } => {}
+        }
+        tags
+    }
+    
+    fn high_risk_pool_tags(&self, notes_out: &[Note]) -> Vec<RiskTag> {
+        let mut pools: Vec<PoolId> = notes_out
+            .iter()
+            .map(|note| note.pool_id)
+            .filter(|pool_id| self.high_risk_pools.contains(pool_id))
+            .collect();
+        pools.sort_unstable();
+        pools.dedup();
+        pools.into_iter().map(RiskTag::HighRiskPool).collect()
+    }
+}
+
+/// Optional public-input sections of the transfer circuit behind the transfer key
+/// Transfer inputs: [6 roots, nf_list, cm_list, n_out, fee, review window, recent CMT roots,
+/// asset fee slots, owner_index_root, sanctions fallbacks], the last five present as this layout says
+/// The default matches `SetupManager`'s transfer key, which publishes only the sanctions fallbacks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferLayout {
+    /// [current_time, max_review_age] follow the fee (require_fresh_review)
+    pub review_window: bool,
+    /// Recent CMT roots the inputs' membership is proven against
+    pub recent_cmt_roots: usize,
+    /// (asset, fee) slots of a multi-asset transfer: none, or the circuit's MAX_ASSETS
+    pub asset_fee_slots: usize,
+    /// owner_index_root follows the asset fee slots (block_frozen_recipients)
+    pub owner_index_root: bool,
+    /// Count of addresses without a sanctions non-membership proof closes the inputs
+    /// (expose_sanctions_fallbacks)
+    pub sanctions_fallbacks: bool,
+}
+
+impl Default for TransferLayout {
+    fn default() -> Self {
+        Self {
+            review_window: false,
+            recent_cmt_roots: 0,
+            asset_fee_slots: 0,
+            owner_index_root: false,
+            sanctions_fallbacks: true,
+        }
+    }
+}
+
+impl TransferLayout {
+    /// Index of a transfer's fee input, given its nullifier and output commitment counts
+    pub fn fee_index(nullifiers: usize, commitments: usize) -> usize {
+        6 + nullifiers + commitments + 1
+    }
+    
+    /// Recent CMT roots a transfer's inputs prove membership against
+    pub fn recent_cmt_roots<'a>(&self, tx: &'a VerifiedTransaction) -> &'a [F] {
+        let start = match Self::sections_start(tx) {
+            Some(start) => start + if self.review_window { 2 } else { 0 },
+            None => return &[],
+        };
+        tx.public_inputs.get(start..start + self.recent_cmt_roots).unwrap_or(&[])
+    }
+    
+    /// [current_time, max_review_age] a transfer's review freshness is proven at
+    pub fn review_window(&self, tx: &VerifiedTransaction) -> Option<(F, F)> {
+        if !self.review_window {
+            return None;
+        }
+        let start = Self::sections_start(tx)?;
+        Some((*tx.public_inputs.get(start)?, *tx.public_inputs.get(start + 1)?))
+    }
+    
+    /// Owner index root a transfer's frozen-recipient checks are proven against
+    pub fn owner_index_root(&self, tx: &VerifiedTransaction) -> Option<F> {
+        if !self.owner_index_root {
+            return None;
+        }
+        let start = Self::sections_start(tx)?;
+        let index = start
+            + if self.review_window { 2 } else { 0 }
+            + self.recent_cmt_roots
+            + 2 * self.asset_fee_slots;
+        tx.public_inputs.get(index).copied()
+    }
+    
+    /// Sender and recipient addresses a transfer's circuit only checked to be non-zero,
+    /// instead of proving them absent from the sanctions list
+    pub fn sanctions_fallbacks(&self, tx: &VerifiedTransaction) -> Option<F> {
+        if !self.sanctions_fallbacks {
+            return None;
+        }
+        let start = Self::sections_start(tx)?;
+        let index = start
+            + if self.review_window { 2 } else { 0 }
+            + self.recent_cmt_roots
+            + 2 * self.asset_fee_slots
+            + if self.owner_index_root { 1 } else { 0 };
+        tx.public_inputs.get(index).copied()
+    }
+    
+    /// Index of the first input after a transfer's fee (None for other transactions)
+    fn sections_start(tx: &VerifiedTransaction) -> Option<usize> {
+        match &tx.transaction_data {
+            TransactionData::Transfer { nullifiers, notes_out } => {
+                Some(Self::fee_index(nullifiers.len(), notes_out.len()) + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Verifier-side values a transfer's review window must be proven at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewPolicy {
+    /// The max_review_age public input must equal this
+    pub max_review_age: Time,
+    /// How far the current_time public input may be from the verifier's clock, in seconds
+    pub clock_skew: Time,
+}
+
+/// An accepted transaction flagged with its risk tags
+#[derive(Clone, Debug)]
+pub struct FlaggedTx {
+    pub tx_id: F,
+    pub circuit_type: TransactionType,
+    pub risk_tags: Vec<RiskTag>,
+}
+
+/// Verifying keys for every circuit, installed together by `ServerVerifier::rotate_keys`
+#[derive(Clone, Default)]
+pub struct VerifyingKeySet {
+    pub mint: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    pub burn: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    pub transfer: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    pub object_update: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+}
+
+impl VerifyingKeySet {
+    /// Blake2b fingerprint (first 32 bytes) of each loaded key's compressed encoding
+    pub fn fingerprints(&self) -> Vec<(TransactionType, [u8; 32])> {
+        [
+            (TransactionType::Mint, &self.mint),
+            (TransactionType::Burn, &self.burn),
+            (TransactionType::Transfer, &self.transfer),
+            (TransactionType::ObjectUpdate, &self.object_update),
+        ]
+        .into_iter()
+        .filter_map(|(tx_type, vk)| {
+            let mut bytes = Vec::new();
+            vk.as_ref()?.serialize_compressed(&mut bytes).ok()?;
+            let mut fingerprint = [0u8; 32];
+            fingerprint.copy_from_slice(&crate::crypto::blake2b_hash(&bytes)[..32]);
+            Some((tx_type, fingerprint))
+        })
+        .collect()
+    }
+}
+
+/// A submission refused by `add_transaction`, kept for compliance auditing
+#[derive(Clone, Debug)]
+pub struct RejectedTx {
+    pub reason: String,
+    pub circuit_type: TransactionType,
+    pub submitted_at: Time,
+}
+
+/// Proof that a transaction was included in a processed batch
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct InclusionReceipt {
+    pub batch_id: u64,
+    pub tx_private_id: F,
+    /// Path from the private id to the block header's `tx_root`
+    pub merkle_path_to_batch_txs: MerklePath,
+}
+
+impl InclusionReceipt {
+    /// Check the receipt against the header of the block it claims inclusion in
+    pub fn verify(&self, header: &BlockHeader) -> bool {
+        self.batch_id == header.batch_id
+            && self.merkle_path_to_batch_txs.leaf == self.tx_private_id
+            && self.merkle_path_to_batch_txs.siblings.len() == BATCH_TX_TREE_HEIGHT
+            && self.merkle_path_to_batch_txs.verify(
+                &header.tx_root,
+                &TreeParams::new(BATCH_TX_TREE_HEIGHT),
+            )
+    }
+}
+
+/// Tree-update phases of a batch, in the canonical order of spec section 7.2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BatchPhase {
+    Ingress,
+    Cmt,
+    Nft,
+    Cb,
+    Obj,
+    Exit,
+}
+
+impl BatchPhase {
+    /// INGRESS appends → CMT appends → NFT inserts → CB inserts → OBJ appends → EXIT appends
+    const CANONICAL: [BatchPhase; 6] = [
+        BatchPhase::Ingress,
+        BatchPhase::Cmt,
+        BatchPhase::Nft,
+        BatchPhase::Cb,
+        BatchPhase::Obj,
+        BatchPhase::Exit,
+    ];
+    
+    /// Copy the only root this phase moves from `from` into `to`
+    fn carry_root(self, from: &StateRoots, to: &mut StateRoots) {
+        match self {
+            BatchPhase::Ingress => to.ingress_root = from.ingress_root,
+            BatchPhase::Cmt => to.cmt_root = from.cmt_root,
+            BatchPhase::Nft => to.nft_root = from.nft_root,
+            BatchPhase::Cb => to.cb_root = from.cb_root,
+            BatchPhase::Obj => to.obj_root = from.obj_root,
+            BatchPhase::Exit => to.exit_root = from.exit_root,
+        }
+    }
 }
 
 /// A batch of transactions to be processed together
@@ -33,7 +354,7 @@ pub struct TransactionBatch {
 }
 
 /// A transaction that has been client-proven and verified
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct VerifiedTransaction {
     pub tx_type: TransactionType,
     pub proof: Proof<ark_bls12_381::Bls12_381>,
@@ -43,6 +364,88 @@ pub struct VerifiedTransaction {
     pub transaction_data: TransactionData,
 }
 
+impl VerifiedTransaction {
+    /// Content-addressed id: hash of the public inputs
+    pub fn content_id(&self) -> F {
+        crate::crypto::poseidon_hash(&self.public_inputs)
+    }
+    
+    /// Stable, privacy-preserving id for receipts and status polling
+    /// Hashes only what the block publishes anyway: sorted nullifiers, output
+    /// commitments, ingress/exit receipt, roots and fee
+    pub fn private_id(&self) -> F {
+        let mut nullifiers = self.nullifiers();
+        nullifiers.sort_by_cached_key(|nf| nf.into_bigint());
+        let commitments = self.output_commitments();
+        let receipt = match &self.transaction_data {
+            TransactionData::Mint { ingress_receipt, .. } => ingress_receipt.hash(),
+            TransactionData::Burn { exit_receipt, .. } => exit_receipt.hash(),
+            _ => F::from(0u64),
+        };
+        
+        let mut input = vec![
+            crate::crypto::domain_sep_to_field(crate::crypto::DOM_TX_ID),
+            F::from(nullifiers.len() as u64),
+        ];
+        input.extend(nullifiers);
+        input.push(F::from(commitments.len() as u64));
+        input.extend(commitments);
+        input.extend([receipt, self.old_roots.digest(), self.new_roots.digest(), self.fee()]);
+        crate::crypto::poseidon_hash(&input)
+    }
+    
+    /// Fee paid by the transaction
+    /// Transfers carry it after their roots, nf_list, cm_list (mirrored by `notes_out`) and
+    /// n_out; inputs after the fee depend on the circuit's shape (see `TransferLayout`)
+    pub fn fee(&self) -> F {
+        match &self.transaction_data {
+            TransactionData::Transfer { nullifiers, notes_out } => {
+                let index = TransferLayout::fee_index(nullifiers.len(), notes_out.len());
+                self.public_inputs.get(index).copied().unwrap_or(F::from(0))
+            }
+            _ => F::from(0),
+        }
+    }
+    
+    /// Nullifiers consumed by the transaction
+    pub fn nullifiers(&self) -> Vec<Nullifier> {
+        match &self.transaction_data {
+            TransactionData::Burn { nullifier, .. } => vec![*nullifier],
+            TransactionData::Transfer { nullifiers, .. } => nullifiers.clone(),
+            _ => Vec::new(),
+        }
+    }
+    
+    /// Commitments the transaction appends (notes and objects); dummy outputs are never appended
+    pub fn output_commitments(&self) -> Vec<Commitment> {
+        match &self.transaction_data {
+            TransactionData::Mint { notes_out, .. } | TransactionData::Transfer { notes_out, .. } => {
+                notes_out.iter().filter(|note| !note.is_dummy()).map(|note| note.commitment()).collect()
+            }
+            TransactionData::ObjectUpdate { new_object_cm, .. } => vec![*new_object_cm],
+            TransactionData::Burn { .. } => Vec::new(),
+        }
+    }
+    
+    /// Asset whose supply the transaction changes (mints and burns)
+    fn supply_asset(&self) -> Option<AssetType> {
+        match &self.transaction_data {
+            TransactionData::Mint { asset_type, .. } | TransactionData::Burn { asset_type, .. } => Some(*asset_type),
+            _ => None,
+        }
+    }
+    
+    /// Two transactions conflict if they spend a common nullifier, create a common
+    /// commitment, or both change the supply of the same asset
+    pub fn conflicts_with(&self, other: &VerifiedTransaction) -> bool {
+        let nullifiers = self.nullifiers();
+        let commitments = self.output_commitments();
+        other.nullifiers().iter().any(|nf| nullifiers.contains(nf))
+            || other.output_commitments().iter().any(|cm| commitments.contains(cm))
+            || (self.supply_asset().is_some() && self.supply_asset() == other.supply_asset())
+    }
+}
+
 /// Specific transaction data for different types
 #[derive(Clone, Debug)]
 pub enum TransactionData {
@@ -56,6 +459,8 @@ pub enum TransactionData {
         asset_type: AssetType,
         amount: Amount,
         nullifier: Nullifier,
+        /// Commitment of the burned note (public input of the burn circuit)
+        consumed_cm: Commitment,
         exit_receipt: ExitReceipt,
     },
     Transfer {
@@ -66,9 +471,121 @@ pub enum TransactionData {
         old_object_cm: F,
         new_object_cm: F,
         callback_ops: Vec<CallbackOperation>,
+        /// Owner to index the new object under; needed when the owner isn't indexed yet,
+        /// e.g. on object creation (None follows the old object's indexed owner). Must be
+        /// the owner the proof exposes
+        owner: Option<AuthAddr>,
     },
 }
 
+impl CanonicalSerialize for TransactionData {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            TransactionData::Mint { asset_type, amount, notes_out, ingress_receipt } => {
+                0u8.serialize_with_mode(&mut writer, compress)?;
+                asset_type.serialize_with_mode(&mut writer, compress)?;
+                amount.serialize_with_mode(&mut writer, compress)?;
+                notes_out.serialize_with_mode(&mut writer, compress)?;
+                ingress_receipt.serialize_with_mode(&mut writer, compress)
+            }
+            TransactionData::Burn { asset_type, amount, nullifier, consumed_cm, exit_receipt } => {
+                1u8.serialize_with_mode(&mut writer, compress)?;
+                asset_type.serialize_with_mode(&mut writer, compress)?;
+                amount.serialize_with_mode(&mut writer, compress)?;
+                nullifier.serialize_with_mode(&mut writer, compress)?;
+                consumed_cm.serialize_with_mode(&mut writer, compress)?;
+                exit_receipt.serialize_with_mode(&mut writer, compress)
+            }
+            TransactionData::Transfer { nullifiers, notes_out } => {
+                2u8.serialize_with_mode(&mut writer, compress)?;
+                nullifiers.serialize_with_mode(&mut writer, compress)?;
+                notes_out.serialize_with_mode(&mut writer, compress)
+            }
+            TransactionData::ObjectUpdate { old_object_cm, new_object_cm, callback_ops, owner } => {
+                3u8.serialize_with_mode(&mut writer, compress)?;
+                old_object_cm.serialize_with_mode(&mut writer, compress)?;
+                new_object_cm.serialize_with_mode(&mut writer, compress)?;
+                callback_ops.serialize_with_mode(&mut writer, compress)?;
+                owner.serialize_with_mode(&mut writer, compress)
+            }
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        1 + match self {
+            TransactionData::Mint { asset_type, amount, notes_out, ingress_receipt } => {
+                asset_type.serialized_size(compress)
+                    + amount.serialized_size(compress)
+                    + notes_out.serialized_size(compress)
+                    + ingress_receipt.serialized_size(compress)
+            }
+            TransactionData::Burn { asset_type, amount, nullifier, consumed_cm, exit_receipt } => {
+                asset_type.serialized_size(compress)
+                    + amount.serialized_size(compress)
+                    + nullifier.serialized_size(compress)
+                    + consumed_cm.serialized_size(compress)
+                    + exit_receipt.serialized_size(compress)
+            }
+            TransactionData::Transfer { nullifiers, notes_out } => {
+                nullifiers.serialized_size(compress) + notes_out.serialized_size(compress)
+            }
+            TransactionData::ObjectUpdate { old_object_cm, new_object_cm, callback_ops, owner } => {
+                old_object_cm.serialized_size(compress)
+                    + new_object_cm.serialized_size(compress)
+                    + callback_ops.serialized_size(compress)
+                    + owner.serialized_size(compress)
+            }
+        }
+    }
+}
+
+impl Valid for TransactionData {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TransactionData {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let tag = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        let data = match tag {
+            0 => TransactionData::Mint {
+                asset_type: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                amount: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                notes_out: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                ingress_receipt: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            },
+            1 => TransactionData::Burn {
+                asset_type: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                amount: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                nullifier: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                consumed_cm: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                exit_receipt: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            },
+            2 => TransactionData::Transfer {
+                nullifiers: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                notes_out: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            },
+            3 => TransactionData::ObjectUpdate {
+                old_object_cm: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                new_object_cm: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                callback_ops: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+                owner: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            },
+            _ => return Err(SerializationError::InvalidData),
+        };
+        Ok(data)
+    }
+}
+
 impl ServerVerifier {
     pub fn new(
         state: StateManager,
@@ -77,205 +594,972 @@ impl ServerVerifier {
         vk_transfer: VerifyingKey<ark_bls12_381::Bls12_381>,
         vk_object_update: VerifyingKey<ark_bls12_381::Bls12_381>,
     ) -> Self {
-        Self {
+        Self::with_keys(
             state,
-            vk_mint,
-            vk_burn,
-            vk_transfer,
-            vk_object_update,
+            Some(vk_mint),
+            Some(vk_burn),
+            Some(vk_transfer),
+            Some(vk_object_update),
+        )
+    }
+
+    /// Create a verifier with only some circuit keys loaded
+    /// Transactions for a circuit without a key are rejected
+    pub fn with_keys(
+        state: StateManager,
+        vk_mint: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+        vk_burn: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+        vk_transfer: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+        vk_object_update: Option<VerifyingKey<ark_bls12_381::Bls12_381>>,
+    ) -> Self {
+        let mut verifier = Self {
+            state,
+            vk_mint: None,
+            vk_burn: None,
+            vk_transfer: None,
+            vk_object_update: None,
             pending_batch: TransactionBatch {
                 transactions: Vec::new(),
                 batch_id: 0,
                 timestamp: 0,
             },
+            pending_overlay: None,
+            rejection_log: None,
+            key_fingerprints: Vec::new(),
+            risk_policy: RiskPolicy::default(),
+            flagged: Vec::new(),
+            max_callback_ops: MAX_CALLBACK_OPS,
+            sanctions_window: None,
+            previous_sanctions_roots: Vec::new(),
+            max_batch_appends: None,
+            transfer_layout: TransferLayout::default(),
+            review_policy: None,
+            inclusion_receipts: std::collections::HashMap::new(),
+        };
+        verifier.rotate_keys(VerifyingKeySet {
+            mint: vk_mint,
+            burn: vk_burn,
+            transfer: vk_transfer,
+            object_update: vk_object_update,
+        });
+        verifier
+    }
+    
+    /// Replace every verifying key at once, e.g. after a circuit upgrade
+    /// Taking `&mut self` means the swap happens under the caller's write lock, so no
+    /// proof is ever checked against a mix of old and new keys. Circuits missing from
+    /// `new_keys` are unloaded. Already-queued transactions were verified on admission
+    /// and stay queued
+    pub fn rotate_keys(&mut self, new_keys: VerifyingKeySet) {
+        self.key_fingerprints = new_keys.fingerprints();
+        self.vk_mint = new_keys.mint;
+        self.vk_burn = new_keys.burn;
+        self.vk_transfer = new_keys.transfer;
+        self.vk_object_update = new_keys.object_update;
+    }
+    
+    /// Fingerprints of the verifying keys currently in use, per circuit
+    pub fn active_key_fingerprints(&self) -> &[(TransactionType, [u8; 32])] {
+        &self.key_fingerprints
+    }
+    
+    /// Enable observer mode: every `add_transaction` failure is recorded with its reason
+    pub fn with_rejection_log(mut self) -> Self {
+        self.rejection_log = Some(Vec::new());
+        self
+    }
+    
+    /// Tag accepted transactions for review according to `policy`
+    pub fn with_risk_policy(mut self, policy: RiskPolicy) -> Self {
+        self.risk_policy = policy;
+        self
+    }
+    
+    /// Describe the optional public-input sections of the transfer key's circuit
+    pub fn with_transfer_layout(mut self, layout: TransferLayout) -> Self {
+        self.transfer_layout = layout;
+        self
+    }
+    
+    /// Hold transfers' review windows to `policy` (see `TransferLayout::review_window`)
+    pub fn with_review_policy(mut self, policy: ReviewPolicy) -> Self {
+        self.review_policy = Some(policy);
+        self
+    }
+    
+    /// Lower the cap on callback operations per object update (at most MAX_CALLBACK_OPS,
+    /// the object-update circuit's shape)
+    pub fn with_max_callback_ops(mut self, max_callback_ops: usize) -> Self {
+        self.max_callback_ops = max_callback_ops.min(MAX_CALLBACK_OPS);
+        self
+    }
+    
+    /// Reject batches that would grow CMT by more than `max_batch_appends` leaves
+    pub fn with_max_batch_appends(mut self, max_batch_appends: usize) -> Self {
+        self.max_batch_appends = Some(max_batch_appends);
+        self
+    }
+    
+    /// Reject transfers whose sanctions root is stale: it must be the current root or one
+    /// of the `window` roots it replaced (0 = current root only)
+    pub fn with_sanctions_freshness(mut self, window: usize) -> Self {
+        self.sanctions_window = Some(window);
+        self
+    }
+    
+    /// Install a new sanctions root, remembering the old one for the freshness window
+    pub fn update_sanctions_root(&mut self, new_root: MerkleRoot) {
+        let old_root = self.state.sanctions_root;
+        if old_root != new_root {
+            self.previous_sanctions_roots.push(old_root);
+            let keep = self.sanctions_window.unwrap_or(0);
+            let excess = self.previous_sanctions_roots.len().saturating_sub(keep);
+            self.previous_sanctions_roots.drain(..excess);
+        }
+        self.state.update_sanctions_root(new_root);
+        if let Some(overlay) = self.pending_overlay.as_mut() {
+            overlay.update_sanctions_root(new_root);
+        }
+    }
+    
+    /// Whether a transfer proven against `root` passes the sanctions freshness policy
+    fn sanctions_root_is_fresh(&self, root: &MerkleRoot) -> bool {
+        let window = match self.sanctions_window {
+            Some(window) => window,
+            None => return true,
+        };
+        let recent = &self.previous_sanctions_roots;
+        *root == self.state.sanctions_root
+            || recent[recent.len().saturating_sub(window)..].contains(root)
+    }
+    
+    /// Accepted transactions that were tagged with at least one risk, oldest first
+    pub fn flagged_transactions(&self) -> &[FlaggedTx] {
+        &self.flagged
+    }
+    
+    /// Record the risk tags of an accepted transaction, if any
+    fn flag_risks(&mut self, tx: &VerifiedTransaction) {
+        let risk_tags = self.risk_policy.tags_for(tx, &self.transfer_layout);
+        if !risk_tags.is_empty() {
+            self.flagged.push(FlaggedTx {
+                tx_id: tx.private_id(),
+                circuit_type: tx.tx_type.clone(),
+                risk_tags,
+            });
+        }
+    }
+    
+    /// Rejections recorded at or after time `t` (empty if observer mode is off)
+    pub fn rejections_since(&self, t: Time) -> Vec<&RejectedTx> {
+        self.rejection_log
+            .iter()
+            .flatten()
+            .filter(|rejected| rejected.submitted_at >= t)
+            .collect()
+    }
+    
+    /// Periodic upkeep: state maintenance plus trimming the rejection log to the most
+    /// recent `max_rejections` entries
+    pub fn maintenance(&mut self, config: &MaintenanceConfig) -> MaintenanceReport {
+        let mut report = self.state.maintenance(config);
+        if let Some(log) = self.rejection_log.as_mut() {
+            let excess = log.len().saturating_sub(config.max_rejections);
+            log.drain(..excess);
+            report.rejections_trimmed = excess;
         }
+        report
+    }
+    
+    /// Circuit types this verifier holds a verifying key for
+    pub fn loaded_circuit_types(&self) -> Vec<TransactionType> {
+        [
+            (TransactionType::Mint, self.vk_mint.is_some()),
+            (TransactionType::Burn, self.vk_burn.is_some()),
+            (TransactionType::Transfer, self.vk_transfer.is_some()),
+            (TransactionType::ObjectUpdate, self.vk_object_update.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(tx_type, loaded)| loaded.then_some(tx_type))
+        .collect()
     }
     
     /// Add a transaction to the pending batch
+    /// Runs every check `validate_only` runs, so only transactions that can apply are queued
     pub fn add_transaction(&mut self, tx: VerifiedTransaction) -> Result<(), FluxeError> {
-        // Verify the proof first
-        self.verify_transaction_proof(&tx)?;
+        let next = match self.check_against(self.pending_state(), &tx) {
+            Ok(next) => next,
+            Err(e) => {
+                if let Some(log) = self.rejection_log.as_mut() {
+                    let submitted_at = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    log.push(RejectedTx {
+                        reason: e.to_string(),
+                        circuit_type: tx.tx_type.clone(),
+                        submitted_at,
+                    });
+                }
+                return Err(e);
+            }
+        };
         
         // Add to pending batch
-        self.pending_batch.transactions.push(tx);
+        self.enqueue(tx);
+        self.pending_overlay = Some(next);
         Ok(())
     }
-    
-    /// Process the entire batch and produce a block
-    pub fn process_batch(&mut self) -> Result<BlockHeader, FluxeError> {
-        if self.pending_batch.transactions.is_empty() {
-            return Err(FluxeError::Other("No transactions in batch".to_string()));
+
+    /// Dry-run a transaction: run every acceptance check without queuing it or mutating state
+    /// Like admission, it checks against the state the pending batch leaves
+    pub fn validate_only(&self, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        self.check_against(self.pending_state(), tx).map(|_| ())
+    }
+
+    /// Roots the next admitted transaction starts from: the current roots advanced by the pending batch
+    pub fn pending_roots(&self) -> StateRoots {
+        self.pending_state().get_roots()
+    }
+
+    /// State after the pending batch: the overlay admission keeps, or the live state
+    /// while nothing is pending
+    fn pending_state(&self) -> &StateManager {
+        self.pending_overlay.as_ref().unwrap_or(&self.state)
+    }
+
+    /// Validate an ordered batch where later transactions may depend on earlier ones:
+    /// each is checked against the state left by the pending batch and the accepted
+    /// transactions before it
+    pub fn validate_batch(&self, txs: &[VerifiedTransaction]) -> Vec<Result<(), FluxeError>> {
+        self.check_sequence(txs).0
+    }
+
+    /// Per-transaction results of `validate_batch`, plus the state the accepted
+    /// transactions leave (None if none was accepted)
+    fn check_sequence(&self, txs: &[VerifiedTransaction]) -> (Vec<Result<(), FluxeError>>, Option<StateManager>) {
+        let mut scratch: Option<StateManager> = None;
+        let results: Vec<Result<(), FluxeError>> = txs.iter()
+            .map(|tx| {
+                let next = self.check_against(scratch.as_ref().unwrap_or(self.pending_state()), tx)?;
+                scratch = Some(next);
+                Ok(())
+            })
+            .collect();
+        (results, scratch)
+    }
+
+    /// Validate and queue an ordered batch atomically: nothing is queued unless every
+    /// transaction passes. The batch is queued in the given order, since each transaction
+    /// may build on the ones before it. Returns the per-transaction validation results
+    pub fn add_transactions(&mut self, txs: Vec<VerifiedTransaction>) -> Vec<Result<(), FluxeError>> {
+        let (results, next) = self.check_sequence(&txs);
+        if results.iter().all(Result::is_ok) {
+            for tx in txs {
+                self.enqueue(tx);
+            }
+            if let Some(next) = next {
+                self.pending_overlay = Some(next);
+            }
         }
-        
-        let prev_roots = self.state.get_roots();
-        
-        // Process transactions in canonical order according to spec section 7.2:
-        // INGRESS appends → CMT appends → NFT inserts → CB inserts → OBJ appends → EXIT appends
-        
-        // 1. Process all ingress operations (mints)
-        for tx in &self.pending_batch.transactions {
-            if let TransactionData::Mint { ingress_receipt, .. } = &tx.transaction_data {
-                self.state.ingress_tree.append(ingress_receipt.hash());
+        results
+    }
+
+    /// Run every acceptance check for `tx` against `state` (which already includes the
+    /// pending batch), returning the state after it
+    fn check_against(&self, state: &StateManager, tx: &VerifiedTransaction) -> Result<StateManager, FluxeError> {
+        // 1. Proof must verify under the circuit's key
+        self.verify_transaction_proof(tx)?;
+
+        // 2. Transaction must be built against the given roots, with membership proven
+        //    against recent CMT roots
+        if tx.old_roots != state.get_roots() {
+            return Err(FluxeError::Other(
+                "Transaction references stale roots".to_string()
+            ));
+        }
+        self.check_membership_roots(state, tx)?;
+
+        // 3. Nullifiers must be unspent, including by the pending batch
+        for nf in tx.nullifiers() {
+            if state.nullifier_exists(nf) {
+                return Err(FluxeError::DoubleSpend(nf));
             }
         }
-        
-        // 2. Process all CMT appends (mints and transfers)
-        for tx in &self.pending_batch.transactions {
-            match &tx.transaction_data {
-                TransactionData::Mint { notes_out, .. } => {
-                    for note in notes_out {
-                        self.state.cmt_tree.append(note.commitment());
-                    }
-                }
-                TransactionData::Transfer { notes_out, .. } => {
-                    for note in notes_out {
-                        self.state.cmt_tree.append(note.commitment());
-                    }
-                }
-                _ => {}
+
+        // 4. Apply to a scratch copy so tree and supply checks run against real state
+        let mut scratch = state.clone();
+        Self::apply_transaction(&mut scratch, tx)?;
+        Ok(scratch)
+    }
+
+    /// Check the CMT roots `tx` only proves note membership against are recent in `state`:
+    /// a burn's note root and a transfer's recent roots. They leave CMT as it is, so unlike
+    /// a transfer's cmt_root_old, which its appends build on, they need not be current
+    fn check_membership_roots(&self, state: &StateManager, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        let roots = match &tx.transaction_data {
+            // [cmt, nft_old, ..]
+            TransactionData::Burn { .. } => tx.public_inputs.get(..1).unwrap_or(&[]),
+            TransactionData::Transfer { .. } => self.transfer_layout.recent_cmt_roots(tx),
+            _ => &[],
+        };
+        if roots.iter().any(|root| !state.is_recent_cmt_root(*root)) {
+            return Err(FluxeError::Other(
+                "Transaction proves membership against a CMT root outside the recent window".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether a transaction with this private id is queued in the pending batch
+    pub fn is_pending(&self, id: &F) -> bool {
+        self.pending_batch.transactions.iter().any(|tx| tx.private_id() == *id)
+    }
+
+    /// Number of transactions queued in the pending batch
+    pub fn pending_count(&self) -> usize {
+        self.pending_batch.transactions.len()
+    }
+
+    /// Serialize the pending batch (metadata, transactions, proofs and public inputs)
+    /// so a standby sequencer can take over
+    pub fn export_mempool(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.pending_batch.batch_id.serialize_compressed(&mut bytes)
+            .and_then(|_| self.pending_batch.timestamp.serialize_compressed(&mut bytes))
+            .and_then(|_| self.pending_batch.transactions.serialize_compressed(&mut bytes))
+            .expect("serializing into a Vec cannot fail");
+        bytes
+    }
+
+    /// Import a mempool exported by another sequencer
+    /// The import takes over the exported batch id and timestamp, so it is refused while
+    /// this verifier has its own pending batch. Every transaction is re-validated, in
+    /// exported order, against this verifier's state advanced by the transactions before
+    /// it; on any failure nothing is imported
+    pub fn import_mempool(&mut self, bytes: &[u8]) -> Result<usize, FluxeError> {
+        if !self.pending_batch.transactions.is_empty() {
+            return Err(FluxeError::Other(
+                "Cannot import a mempool over a non-empty pending batch".to_string()
+            ));
+        }
+
+        let mut reader = bytes;
+        let batch_id = u64::deserialize_compressed(&mut reader)
+            .map_err(|e| FluxeError::SerializationError(format!("Invalid mempool batch id: {}", e)))?;
+        let timestamp = Time::deserialize_compressed(&mut reader)
+            .map_err(|e| FluxeError::SerializationError(format!("Invalid mempool timestamp: {}", e)))?;
+        let transactions = Vec::<VerifiedTransaction>::deserialize_compressed(&mut reader)
+            .map_err(|e| FluxeError::SerializationError(format!("Invalid mempool transactions: {}", e)))?;
+
+        let count = transactions.len();
+        if let Some(Err(e)) = self.add_transactions(transactions).into_iter().find(Result::is_err) {
+            return Err(e);
+        }
+
+        self.pending_batch.batch_id = batch_id;
+        self.pending_batch.timestamp = timestamp;
+        Ok(count)
+    }
+
+    /// Partition transactions into independent groups: transactions that conflict,
+    /// directly or through a chain of conflicts, share a group. Groups hold indices
+    /// in ascending order and are ordered by their first index.
+    pub fn partition_independent(txs: &[VerifiedTransaction]) -> Vec<Vec<usize>> {
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while parent[root] != root {
+                root = parent[root];
             }
+            parent[i] = root;
+            root
         }
         
-        // 3. Process all NFT inserts (burns and transfers)
-        for tx in &self.pending_batch.transactions {
-            match &tx.transaction_data {
-                TransactionData::Burn { nullifier, .. } => {
-                    if self.state.nft_tree.contains(nullifier) {
-                        return Err(FluxeError::DoubleSpend(*nullifier));
-                    }
-                    self.state.nft_tree.insert(*nullifier)?;
-                }
-                TransactionData::Transfer { nullifiers, .. } => {
-                    for &nf in nullifiers {
-                        if self.state.nft_tree.contains(&nf) {
-                            return Err(FluxeError::DoubleSpend(nf));
-                        }
-                        self.state.nft_tree.insert(nf)?;
-                    }
+        let mut parent: Vec<usize> = (0..txs.len()).collect();
+        for i in 0..txs.len() {
+            for j in (i + 1)..txs.len() {
+                if txs[i].conflicts_with(&txs[j]) {
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[a.max(b)] = a.min(b);
                 }
-                _ => {}
             }
         }
         
-        // 4. Process callback operations
-        for tx in &self.pending_batch.transactions {
-            if let TransactionData::ObjectUpdate { callback_ops, .. } = &tx.transaction_data {
-                for op in callback_ops {
-                    match op {
-                        CallbackOperation::Add(invocation) => {
-                            self.state.cb_tree.insert(invocation.ticket)?;
-                        }
-                        CallbackOperation::Process(_ticket) => {
-                            // Mark as processed - implementation depends on callback design
-                            // This might involve updating the sorted tree structure
-                        }
-                    }
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of_root = std::collections::HashMap::new();
+        for i in 0..txs.len() {
+            let root = find(&mut parent, i);
+            let slot = *group_of_root.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[slot].push(i);
+        }
+        groups
+    }
+
+    /// Dry-run a batch: independent groups are applied in parallel, each to its own
+    /// copy of the state, while transactions within a group apply serially in batch order
+    /// Conflicting transactions share a group, so a double spend or overdrawn supply
+    /// surfaces here without touching the live state
+    pub fn apply_groups_parallel(&self, txs: &[VerifiedTransaction]) -> Result<(), FluxeError> {
+        Self::partition_independent(txs)
+            .par_iter()
+            .try_for_each(|group| {
+                let mut scratch = self.state.clone();
+                for &i in group {
+                    Self::apply_transaction(&mut scratch, &txs[i])?;
                 }
-            }
+                Ok(())
+            })
+    }
+
+    /// Apply a single transaction to a state in canonical order, recording the transition
+    /// in the state's event log under its current batch
+    fn apply_transaction(state: &mut StateManager, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        let old_roots = state.get_roots();
+        let mut operations = Vec::new();
+        for phase in BatchPhase::CANONICAL {
+            operations.extend(Self::apply_phase(state, tx, phase)?);
         }
         
-        // 5. Process all OBJ appends (object updates)
-        for tx in &self.pending_batch.transactions {
-            if let TransactionData::ObjectUpdate { new_object_cm, .. } = &tx.transaction_data {
-                self.state.obj_tree.append(*new_object_cm);
+        let new_roots = state.get_roots();
+        state.record(TransitionProof {
+            old_roots,
+            new_roots,
+            operations,
+        });
+        Ok(())
+    }
+
+    /// Apply a batch in canonical order (spec section 7.2): each phase runs over every
+    /// transaction before the next phase starts. A phase moves only its own tree, so after
+    /// a transaction's operations in a phase that tree's root is the one applying the batch
+    /// one transaction at a time would leave; those roots are checked against each
+    /// transaction's declared new roots and recorded as its transition
+    fn apply_canonical(state: &mut StateManager, txs: &[VerifiedTransaction]) -> Result<(), FluxeError> {
+        let prev_roots = state.get_roots();
+        let mut reconstructed = vec![prev_roots.clone(); txs.len()];
+        let mut operations = vec![Vec::new(); txs.len()];
+        for phase in BatchPhase::CANONICAL {
+            for (i, tx) in txs.iter().enumerate() {
+                operations[i].extend(Self::apply_phase(state, tx, phase)?);
+                phase.carry_root(&state.get_roots(), &mut reconstructed[i]);
             }
         }
         
-        // 6. Process all EXIT appends (burns)
-        for tx in &self.pending_batch.transactions {
-            if let TransactionData::Burn { exit_receipt, .. } = &tx.transaction_data {
-                self.state.exit_tree.append(exit_receipt.hash());
+        let mut old_roots = prev_roots;
+        for ((tx, new_roots), operations) in txs.iter().zip(reconstructed).zip(operations) {
+            Self::verify_root_consistency(tx, &new_roots)?;
+            state.record(TransitionProof {
+                old_roots,
+                new_roots: new_roots.clone(),
+                operations,
+            });
+            old_roots = new_roots;
+        }
+        Ok(())
+    }
+
+    /// Apply `tx`'s operations for one phase, returning them
+    fn apply_phase(
+        state: &mut StateManager,
+        tx: &VerifiedTransaction,
+        phase: BatchPhase,
+    ) -> Result<Vec<StateOperation>, FluxeError> {
+        let operations = match (phase, &tx.transaction_data) {
+            (BatchPhase::Ingress, TransactionData::Mint { asset_type, amount, ingress_receipt, .. }) => {
+                let ingress_hash = ingress_receipt.hash();
+                state.ingress_tree.append(ingress_hash);
+                let supply = state.supply.entry(*asset_type).or_insert(Amount::zero());
+                *supply = *supply + *amount;
+                vec![StateOperation::IngressAppend(ingress_hash)]
+            }
+            (BatchPhase::Cmt, TransactionData::Mint { .. } | TransactionData::Transfer { .. }) => {
+                // Dummy outputs pad the circuit shape; their appends are discarded in-circuit
+                let commitments = tx.output_commitments();
+                for &cm in &commitments {
+                    state.cmt_tree.append(cm);
+                }
+                state.checkpoint_cmt_root();
+                vec![StateOperation::CmtAppend(commitments)]
+            }
+            (BatchPhase::Nft, TransactionData::Burn { nullifier, .. }) => {
+                if state.nullifier_exists(*nullifier) {
+                    return Err(FluxeError::DoubleSpend(*nullifier));
+                }
+                state.spend_nullifier(*nullifier)?;
+                vec![StateOperation::NftInsert(*nullifier)]
+            }
+            (BatchPhase::Nft, TransactionData::Transfer { nullifiers, .. }) => {
+                for &nf in nullifiers {
+                    if state.nullifier_exists(nf) {
+                        return Err(FluxeError::DoubleSpend(nf));
+                    }
+                    state.spend_nullifier(nf)?;
+                }
+                vec![StateOperation::NftBatchInsert(nullifiers.clone())]
+            }
+            (BatchPhase::Cb, TransactionData::ObjectUpdate { old_object_cm, new_object_cm, callback_ops, .. }) => {
+                let mut operations = Vec::new();
+                for op in callback_ops {
+                    match op {
+                        CallbackOperation::Add(invocation) => {
+                            state.cb_tree.insert(invocation.ticket)?;
+                            operations.push(StateOperation::CbInsert(invocation.ticket));
+                        }
+                        CallbackOperation::Freeze(owner_addr) => {
+                            Self::check_owner_claim(state, *owner_addr, *old_object_cm, *new_object_cm)?;
+                            state.index_object(*owner_addr, *new_object_cm);
+                            operations.push(StateOperation::Freeze(*owner_addr));
+                        }
+                        CallbackOperation::Process(_) => {}
+                    }
+                }
+                operations
+            }
+            (BatchPhase::Obj, TransactionData::ObjectUpdate { old_object_cm, new_object_cm, owner, .. }) => {
+                if let Some(owner_addr) = owner {
+                    Self::check_owner_claim(state, *owner_addr, *old_object_cm, *new_object_cm)?;
+                }
+                state.obj_tree.append(*new_object_cm);
+                match owner {
+                    Some(owner_addr) => state.index_object(*owner_addr, *new_object_cm),
+                    None => state.reindex_object(*old_object_cm, *new_object_cm),
+                }
+                vec![StateOperation::ObjAppend(*new_object_cm)]
+            }
+            (BatchPhase::Exit, TransactionData::Burn { asset_type, amount, exit_receipt, .. }) => {
+                let exit_hash = exit_receipt.hash();
+                state.exit_tree.append(exit_hash);
+                let supply = state.supply.entry(*asset_type).or_insert(Amount::zero());
+                if *supply < *amount {
+                    return Err(FluxeError::InsufficientBalance);
+                }
+                *supply = *supply - *amount;
+                vec![StateOperation::ExitAppend(exit_hash)]
+            }
+            _ => Vec::new(),
+        };
+        Ok(operations)
+    }
+
+    /// An owner may only be (re)indexed to its own object's successor
+    fn check_owner_claim(
+        state: &StateManager,
+        owner_addr: AuthAddr,
+        old_object_cm: Commitment,
+        new_object_cm: Commitment,
+    ) -> Result<(), FluxeError> {
+        let held = state.object_for_owner(owner_addr);
+        let held_elsewhere = held.map_or(false, |cm| cm != old_object_cm && cm != new_object_cm);
+        let owned_by_other = state.object_owners.get(&old_object_cm).map_or(false, |o| *o != owner_addr);
+        if held_elsewhere || owned_by_other {
+            return Err(FluxeError::Other(
+                "Object update claims an owner indexed to another object".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reject a batch burning the same note commitment twice, whatever nullifiers the burns claim
+    fn check_burned_commitments(transactions: &[VerifiedTransaction]) -> Result<(), FluxeError> {
+        let mut burned = std::collections::HashSet::new();
+        for tx in transactions {
+            if let TransactionData::Burn { consumed_cm, .. } = &tx.transaction_data {
+                if !burned.insert(*consumed_cm) {
+                    return Err(FluxeError::Other("Note commitment burned twice in one batch".to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Commit to the transactions of a block, in block order
+    fn batch_tx_tree(txs: &[VerifiedTransaction]) -> Result<IncrementalTree, FluxeError> {
+        if txs.len() > 1 << BATCH_TX_TREE_HEIGHT {
+            return Err(FluxeError::Other(format!(
+                "Batch of {} transactions exceeds the {}-leaf transaction tree",
+                txs.len(),
+                1usize << BATCH_TX_TREE_HEIGHT
+            )));
+        }
+        let mut tree = IncrementalTree::new(BATCH_TX_TREE_HEIGHT);
+        for tx in txs {
+            tree.append(tx.private_id());
+        }
+        Ok(tree)
+    }
+    
+    /// Commitments a batch appends to CMT: every non-dummy mint and transfer output
+    fn cmt_appends(txs: &[VerifiedTransaction]) -> usize {
+        txs.iter()
+            .map(|tx| match &tx.transaction_data {
+                TransactionData::Mint { notes_out, .. }
+                | TransactionData::Transfer { notes_out, .. } => {
+                    notes_out.iter().filter(|note| !note.is_dummy()).count()
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+    
+    /// Receipt proving a batched transaction made it into its block
+    pub fn inclusion_receipt(&self, private_id: &F) -> Option<&InclusionReceipt> {
+        self.inclusion_receipts.get(private_id)
+    }
+    
+    /// Process the entire batch and produce a block
+    /// The batch is atomic: if any step fails the state is restored to before it
+    pub fn process_batch(&mut self) -> Result<BlockHeader, FluxeError> {
+        let snapshot = self.state.snapshot();
+        let result = self.apply_batch();
+        if result.is_err() {
+            self.state.restore(snapshot);
+            self.evict_failing();
+        } else {
+            self.pending_overlay = None;
+        }
+        result
+    }
+    
+    /// Drop pending transactions that cannot apply, so a failed batch is not retried as is
+    /// The batch is replayed one transaction at a time from the current state; each that
+    /// fails, leaves other roots than it declares or re-burns a note is removed, and the
+    /// state the survivors leave becomes the pending overlay
+    fn evict_failing(&mut self) {
+        let mut scratch = self.state.clone();
+        let mut burned = std::collections::HashSet::new();
+        self.pending_batch.transactions.retain(|tx| {
+            if let TransactionData::Burn { consumed_cm, .. } = &tx.transaction_data {
+                if burned.contains(consumed_cm) {
+                    return false;
+                }
             }
+            let mut next = scratch.clone();
+            let applies = Self::apply_transaction(&mut next, tx).is_ok()
+                && Self::verify_root_consistency(tx, &next.get_roots()).is_ok();
+            if applies {
+                scratch = next;
+                if let TransactionData::Burn { consumed_cm, .. } = &tx.transaction_data {
+                    burned.insert(*consumed_cm);
+                }
+            }
+            applies
+        });
+        self.pending_overlay = (!self.pending_batch.transactions.is_empty()).then_some(scratch);
+    }
+    
+    /// Apply the pending batch to the live state; `process_batch` rolls back on error
+    fn apply_batch(&mut self) -> Result<BlockHeader, FluxeError> {
+        if self.pending_batch.transactions.is_empty() {
+            return Err(FluxeError::Other("No transactions in batch".to_string()));
         }
         
-        // Update supply accounting
-        self.update_supply_accounting()?;
+        let prev_roots = self.state.get_roots();
         
-        // Roots are updated automatically by the state manager operations
-        let new_roots = self.state.get_roots();
+        // The batch keeps its admission order, so each transaction follows the ones it builds on
+        let tx_tree = Self::batch_tx_tree(&self.pending_batch.transactions)?;
         
-        // Verify reconstructed roots match declared roots
-        self.verify_root_consistency(&new_roots)?;
+        // Catch conflicts and overgrown batches before touching the live state
+        if let Some(cap) = self.max_batch_appends {
+            let appends = Self::cmt_appends(&self.pending_batch.transactions);
+            if appends > cap {
+                return Err(FluxeError::Other(format!(
+                    "Batch appends {} commitments, more than the cap of {}",
+                    appends,
+                    cap
+                )));
+            }
+        }
+        Self::check_burned_commitments(&self.pending_batch.transactions)?;
+        self.apply_groups_parallel(&self.pending_batch.transactions)?;
+        
+        Self::apply_canonical(&mut self.state, &self.pending_batch.transactions)?;
+        let new_roots = self.state.get_roots();
         
         // Create block header
         let header = BlockHeader {
             prev_roots,
+            roots_digest: new_roots.digest(),
             new_roots,
+            tx_root: tx_tree.root(),
             batch_id: self.pending_batch.batch_id,
             agg_proof: self.generate_aggregate_proof()?,
             timestamp: self.pending_batch.timestamp,
         };
         
-        // Advance to next batch
+        for (index, tx) in self.pending_batch.transactions.iter().enumerate() {
+            if let Some(path) = tx_tree.get_path(index) {
+                self.inclusion_receipts.insert(tx.private_id(), InclusionReceipt {
+                    batch_id: header.batch_id,
+                    tx_private_id: tx.private_id(),
+                    merkle_path_to_batch_txs: path,
+                });
+            }
+        }
+        
+        // Advance to next batch; the state's events and staged nullifiers are keyed by it
         self.pending_batch.batch_id += 1;
+        self.state.batch_id += 1;
         self.pending_batch.transactions.clear();
         
         Ok(header)
     }
     
-    /// Verify a single transaction's proof
-    fn verify_transaction_proof(&self, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
-        let vk = match tx.tx_type {
-            TransactionType::Mint => &self.vk_mint,
-            TransactionType::Burn => &self.vk_burn,
-            TransactionType::Transfer => &self.vk_transfer,
-            TransactionType::ObjectUpdate => &self.vk_object_update,
-        };
+    /// Independently replay a block: re-verify every proof, apply the transactions
+    /// to a copy of the current state and check the header's root transition
+    pub fn verify_block(&self, header: &BlockHeader, txs: &[VerifiedTransaction]) -> Result<(), FluxeError> {
+        if header.prev_roots != self.state.get_roots() {
+            return Err(FluxeError::Other(
+                "Block does not chain off the current roots".to_string()
+            ));
+        }
+        if header.roots_digest != header.new_roots.digest() {
+            return Err(FluxeError::Other(
+                "Block header roots digest doesn't match its roots".to_string()
+            ));
+        }
+        if txs.is_empty() {
+            return Err(FluxeError::Other("No transactions in block".to_string()));
+        }
         
-        let verified = Groth16::<ark_bls12_381::Bls12_381>::verify(vk, &tx.public_inputs, &tx.proof)
-            .map_err(|e| FluxeError::InvalidProof(format!("Groth16 verification failed: {}", e)))?;
+        // Replay in block order, which the header's transaction root commits to
+        if Self::batch_tx_tree(txs)?.root() != header.tx_root {
+            return Err(FluxeError::Other(
+                "Block header transaction root doesn't match its transactions".to_string()
+            ));
+        }
         
-        if !verified {
+        self.verify_batch(txs)?;
+        let mut scratch = self.state.clone();
+        Self::apply_canonical(&mut scratch, txs)?;
+        
+        if scratch.get_roots() != header.new_roots {
+            return Err(FluxeError::Other(
+                "Replayed roots don't match block header".to_string()
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    /// Deterministic order for independent transactions before their roots are chained:
+    /// highest fee first, equal fees by content id ascending. The pending batch itself is
+    /// never reordered (see `enqueue`), so a sequencer submits in this order
+    pub fn order_transactions(txs: &mut [VerifiedTransaction]) {
+        txs.sort_by_cached_key(Self::order_key);
+    }
+    
+    /// Sort key of `order_transactions`
+    fn order_key(tx: &VerifiedTransaction) -> impl Ord {
+        (std::cmp::Reverse(tx.fee().into_bigint()), tx.content_id().into_bigint())
+    }
+    
+    /// Queue an accepted transaction behind everything already pending
+    /// Every admission path keeps this order: each transaction declares the roots left by
+    /// the ones queued before it, so moving it ahead of them would break its roots
+    fn enqueue(&mut self, tx: VerifiedTransaction) {
+        self.flag_risks(&tx);
+        self.pending_batch.transactions.push(tx);
+    }
+    
+    /// Verify a single transaction's proof
+    fn verify_transaction_proof(&self, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        self.check_proof_context(tx)?;
+        if !self.verify_transaction(tx)? {
             return Err(FluxeError::InvalidProof("Proof verification failed".to_string()));
         }
         
         Ok(())
     }
     
-    /// Update supply accounting based on mint/burn operations
-    fn update_supply_accounting(&mut self) -> Result<(), FluxeError> {
-        for tx in &self.pending_batch.transactions {
-            match &tx.transaction_data {
-                TransactionData::Mint { asset_type, amount, .. } => {
-                    let supply = self.state.supply
-                        .entry(*asset_type)
-                        .or_insert(Amount::zero());
-                    *supply = *supply + *amount;
-                }
-                TransactionData::Burn { asset_type, amount, .. } => {
-                    let supply = self.state.supply
-                        .entry(*asset_type)
-                        .or_insert(Amount::zero());
-                    if *supply < *amount {
-                        return Err(FluxeError::InsufficientBalance);
-                    }
-                    *supply = *supply - *amount;
+    /// Checks on a transaction's public data that must pass before its proof is worth verifying
+    fn check_proof_context(&self, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        // Reject oversized callback lists before any proof work
+        if let TransactionData::ObjectUpdate { callback_ops, owner, .. } = &tx.transaction_data {
+            CallbackOperation::padded_hashes(callback_ops, self.max_callback_ops)?;
+            
+            // Only the owner the proof exposes, which the circuit holds to the owner committed
+            // in the object, may be indexed. Public inputs: [obj_old, obj_new, cb_root,
+            // current_time, owner (4), ..]
+            let freeze_owners = callback_ops.iter().filter_map(|op| match op {
+                CallbackOperation::Freeze(owner_addr) => Some(owner_addr),
+                _ => None,
+            });
+            if owner.iter().chain(freeze_owners).any(|owner_addr| tx.public_inputs.get(4) != Some(owner_addr)) {
+                return Err(FluxeError::InvalidProof(
+                    "Object update indexes an owner its proof doesn't expose".to_string(),
+                ));
+            }
+        }
+        
+        // Mints must prove their minter against the live authorized-minters tree
+        if let TransactionData::Mint { .. } = &tx.transaction_data {
+            // Public inputs: [.., authorized_minters_root (9)]. A mint carries exactly its key's
+            // inputs, so 9 is only unset under a key that doesn't expose the root
+            self.verifying_key_for(tx)?;
+            if let Some(root) = tx.public_inputs.get(9) {
+                if *root != self.state.authorized_minters_root {
+                    return Err(FluxeError::ComplianceViolation(
+                        "Mint proves its minter against a different authorized minters root".to_string(),
+                    ));
                 }
-                _ => {}
             }
         }
+        
+        // The burn circuit exposes the burned note's commitment last (9); it must be the one claimed
+        if let TransactionData::Burn { consumed_cm, .. } = &tx.transaction_data {
+            match tx.public_inputs.get(9) {
+                Some(cm) if cm == consumed_cm => {}
+                Some(_) => return Err(FluxeError::InvalidProof("Burn proves a different note commitment".to_string())),
+                None => return Err(FluxeError::InvalidProof("Burn doesn't expose the burned note commitment".to_string())),
+            }
+        }
+        
+        // Transfers must not be proven against a sanctions list that has since been replaced
+        if let TransactionData::Transfer { .. } = &tx.transaction_data {
+            // Public inputs: [.., sanctions_root (4), ..]
+            let fresh = tx.public_inputs.get(4).map_or(self.sanctions_window.is_none(), |root| {
+                self.sanctions_root_is_fresh(root)
+            });
+            if !fresh {
+                return Err(FluxeError::ComplianceViolation(
+                    "Transfer references a stale sanctions root".to_string(),
+                ));
+            }
+            
+            // A review window is only meaningful at the verifier's time and age limit
+            if self.transfer_layout.review_window {
+                self.check_review_window(tx)?;
+            }
+            
+            // Frozen recipients are only blocked if the proof used the live owner index
+            if self.transfer_layout.owner_index_root
+                && self.transfer_layout.owner_index_root(tx) != Some(self.state.owner_index_root())
+            {
+                return Err(FluxeError::ComplianceViolation(
+                    "Transfer references a stale owner index root".to_string(),
+                ));
+            }
+        }
+        
         Ok(())
     }
     
-    /// Verify that reconstructed roots match the declared roots from transactions
-    fn verify_root_consistency(&self, new_roots: &StateRoots) -> Result<(), FluxeError> {
-        // In a more sophisticated implementation, this would verify that all 
-        // transactions' declared new roots are consistent with the final state
-        
-        // For now, we just verify that at least one transaction's new roots match
-        // the final state (meaning the batch was processed correctly)
+    /// Check a transfer's [current_time, max_review_age] against the review policy and clock
+    fn check_review_window(&self, tx: &VerifiedTransaction) -> Result<(), FluxeError> {
+        let policy = self.review_policy.as_ref().ok_or_else(|| {
+            FluxeError::ComplianceViolation("No review policy for transfers with a review window".to_string())
+        })?;
+        let (current_time, max_review_age) = self.transfer_layout.review_window(tx).ok_or_else(|| {
+            FluxeError::InvalidProof("Transfer is missing its review window inputs".to_string())
+        })?;
+        if max_review_age != F::from(policy.max_review_age) {
+            return Err(FluxeError::ComplianceViolation(
+                "Transfer proves review freshness against a different max_review_age".to_string(),
+            ));
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let in_window = field_fits_u64(&current_time)
+            && field_to_u64(&current_time).abs_diff(now) <= policy.clock_skew;
+        if !in_window {
+            return Err(FluxeError::ComplianceViolation(
+                "Transfer proves review freshness at a current_time off the verifier's clock".to_string(),
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Run Groth16 verification of `tx`'s proof under the verifying key for its circuit type
+    /// Errors when no key is loaded or the public inputs don't fit the key's layout;
+    /// otherwise reports whether the proof verifies
+    pub fn verify_transaction(&self, tx: &VerifiedTransaction) -> Result<bool, FluxeError> {
+        let vk = self.verifying_key_for(tx)?;
+        Groth16::<ark_bls12_381::Bls12_381>::verify(vk, &tx.public_inputs, &tx.proof)
+            .map_err(|e| FluxeError::InvalidProof(format!("Groth16 verification failed: {}", e)))
+    }
+    
+    /// Verify the proofs of `txs` together, rejecting the whole batch if any one fails
+    /// Proofs are grouped by circuit type, each key is prepared once, and every group
+    /// is checked with a single random linear combination (see `batch_verify_groth16`)
+    pub fn verify_batch(&self, txs: &[VerifiedTransaction]) -> Result<(), FluxeError> {
+        let mut groups: std::collections::HashMap<TransactionType, Vec<&VerifiedTransaction>> =
+            std::collections::HashMap::new();
+        for tx in txs {
+            self.check_proof_context(tx)?;
+            self.verifying_key_for(tx)?;
+            groups.entry(tx.tx_type.clone()).or_default().push(tx);
+        }
         
-        if let Some(last_tx) = self.pending_batch.transactions.last() {
-            if &last_tx.new_roots != new_roots {
-                return Err(FluxeError::Other(
-                    "Reconstructed roots don't match declared roots".to_string()
-                ));
+        let mut rng = rand::thread_rng();
+        for group in groups.values() {
+            let pvk = prepare_verifying_key(self.verifying_key_for(group[0])?);
+            let proofs: Vec<_> = group
+                .iter()
+                .map(|tx| (&tx.proof, tx.public_inputs.as_slice()))
+                .collect();
+            if !batch_verify_groth16(&pvk, &proofs, &mut rng)? {
+                return Err(FluxeError::InvalidProof(format!(
+                    "Batch verification failed for {:?} proofs",
+                    group[0].tx_type
+                )));
             }
         }
         
         Ok(())
     }
     
+    /// Verifying key for `tx`'s circuit type, after checking the public inputs fit its layout
+    fn verifying_key_for(
+        &self,
+        tx: &VerifiedTransaction,
+    ) -> Result<&VerifyingKey<ark_bls12_381::Bls12_381>, FluxeError> {
+        let vk = match tx.tx_type {
+            TransactionType::Mint => self.vk_mint.as_ref(),
+            TransactionType::Burn => self.vk_burn.as_ref(),
+            TransactionType::Transfer => self.vk_transfer.as_ref(),
+            TransactionType::ObjectUpdate => self.vk_object_update.as_ref(),
+        }
+        .ok_or_else(|| FluxeError::Other(format!("no verifying key for {:?}", tx.tx_type)))?;
+        
+        let expected_inputs = vk.gamma_abc_g1.len() - 1;
+        if tx.public_inputs.len() > expected_inputs {
+            return Err(FluxeError::InvalidProof(format!(
+                "{} public inputs exceeds the circuit's {}",
+                tx.public_inputs.len(),
+                expected_inputs
+            )));
+        }
+        
+        // Object updates, mints and burns must supply exactly the slots of the circuit's public-input layout
+        let exact = matches!(tx.tx_type, TransactionType::ObjectUpdate | TransactionType::Mint | TransactionType::Burn);
+        if exact && tx.public_inputs.len() != expected_inputs {
+            return Err(FluxeError::InvalidProof(format!(
+                "{:?} supplies {} public inputs, layout expects {}",
+                tx.tx_type,
+                tx.public_inputs.len(),
+                expected_inputs
+            )));
+        }
+        
+        Ok(vk)
+    }
+    
+    /// Verify a transaction's declared new roots are the roots reconstructed right after applying it
+    fn verify_root_consistency(tx: &VerifiedTransaction, new_roots: &StateRoots) -> Result<(), FluxeError> {
+        if &tx.new_roots != new_roots {
+            return Err(FluxeError::Other(
+                "Reconstructed roots don't match declared roots".to_string()
+            ));
+        }
+        
+        Ok(())
+    }
+    
     /// Generate aggregated proof for the entire batch
     fn generate_aggregate_proof(&self) -> Result<Vec<u8>, FluxeError> {
         // Placeholder for aggregated proof generation
@@ -312,11 +1596,26 @@ impl ServerVerifier {
         self.state.get_roots()
     }
     
+    /// Tree heights of the verified state, for building circuits and witnesses against it
+    pub fn tree_config(&self) -> TreeConfig {
+        self.state.tree_config
+    }
+    
     /// Get supply for an asset
     pub fn get_supply(&self, asset_type: AssetType) -> Amount {
         self.state.get_supply(asset_type)
     }
     
+    /// Statistics over the nullifier tree (spent-nullifier count and utilization)
+    pub fn nft_stats(&self) -> SortedTreeStats {
+        self.state.nft_tree.stats()
+    }
+    
+    /// Current object commitment of an owner, followed through applied object updates
+    pub fn object_for_owner(&self, owner_addr: AuthAddr) -> Option<Commitment> {
+        self.state.object_for_owner(owner_addr)
+    }
+    
     /// Check if address is sanctioned
     pub fn is_sanctioned(&self, _address: &F) -> bool {
         // In a real implementation, this would check against the sanctions tree
@@ -374,6 +1673,118 @@ impl TransactionBuilder {
     }
 }
 
+/// Roots a transaction claims to produce, read from its circuit's public inputs
+/// The old roots those inputs reference must match `old_roots`
+pub fn claimed_new_roots(
+    old_roots: &StateRoots,
+    public_inputs: &[F],
+    transaction_data: &TransactionData,
+) -> Result<StateRoots, FluxeError> {
+    // (name, public input index, old root it must equal) per circuit layout
+    let (required, checks): (usize, Vec<(&str, usize, MerkleRoot)>) = match transaction_data {
+        // [cmt_old, cmt_new, ingress_old, ingress_new, asset, amount, cm_list, pk_x, pk_y, minters]
+        TransactionData::Mint { .. } => (10, vec![
+            ("CMT", 0, old_roots.cmt_root),
+            ("INGRESS", 2, old_roots.ingress_root),
+            ("MINTERS", 9, old_roots.authorized_minters_root),
+        ]),
+        // [cmt, nft_old, nft_new, exit_old, exit_new, asset, amount, nf, exit_allow, cm_in]
+        // The CMT root is membership-only and may be any recent one, checked on admission
+        TransactionData::Burn { .. } => (10, vec![
+            ("NFT", 1, old_roots.nft_root),
+            ("EXIT", 3, old_roots.exit_root),
+            ("EXIT_ALLOW", 8, old_roots.exit_allow_root),
+        ]),
+        // [cmt_old, cmt_new, nft_old, nft_new, sanctions, pool_rules, ...]
+        TransactionData::Transfer { .. } => (6, vec![
+            ("CMT", 0, old_roots.cmt_root),
+            ("NFT", 2, old_roots.nft_root),
+            ("SANCTIONS", 4, old_roots.sanctions_root),
+            ("POOL_RULES", 5, old_roots.pool_rules_root),
+        ]),
+        // [obj_old, obj_new, cb_root, current_time, ...]
+        TransactionData::ObjectUpdate { .. } => (3, vec![
+            ("OBJ", 0, old_roots.obj_root),
+            ("CB", 2, old_roots.cb_root),
+        ]),
+    };
+    
+    if public_inputs.len() < required {
+        return Err(FluxeError::Other(format!(
+            "Expected at least {} public inputs, got {}",
+            required,
+            public_inputs.len()
+        )));
+    }
+    for (name, index, root) in checks {
+        if public_inputs[index] != root {
+            return Err(FluxeError::Other(format!(
+                "Public inputs reference a different {} root than the current state",
+                name
+            )));
+        }
+    }
+    
+    let mut new_roots = old_roots.clone();
+    match transaction_data {
+        TransactionData::Mint { .. } => {
+            new_roots.cmt_root = public_inputs[1];
+            new_roots.ingress_root = public_inputs[3];
+        }
+        TransactionData::Burn { .. } => {
+            new_roots.nft_root = public_inputs[2];
+            new_roots.exit_root = public_inputs[4];
+        }
+        TransactionData::Transfer { .. } => {
+            new_roots.cmt_root = public_inputs[1];
+            new_roots.nft_root = public_inputs[3];
+        }
+        TransactionData::ObjectUpdate { .. } => {
+            new_roots.obj_root = public_inputs[1];
+        }
+    }
+    Ok(new_roots)
+}
+
+/// Check several Groth16 proofs under one prepared key with a random linear combination
+/// Each proof i is weighted by a fresh random r_i, reducing the n pairing equations
+/// e(A_i, B_i) = alpha_beta * e(L_i, gamma) * e(C_i, delta) to one product check:
+/// n + 2 Miller loops and a single final exponentiation instead of 3n and n
+pub fn batch_verify_groth16<R: Rng>(
+    pvk: &PreparedVerifyingKey<ark_bls12_381::Bls12_381>,
+    proofs: &[(&Proof<ark_bls12_381::Bls12_381>, &[F])],
+    rng: &mut R,
+) -> Result<bool, FluxeError> {
+    type E = ark_bls12_381::Bls12_381;
+    if proofs.is_empty() {
+        return Ok(true);
+    }
+    
+    let mut g1 = Vec::with_capacity(proofs.len() + 2);
+    let mut g2 = Vec::with_capacity(proofs.len() + 2);
+    let mut sum_l = <E as Pairing>::G1::zero();
+    let mut sum_c = <E as Pairing>::G1::zero();
+    let mut sum_r = F::zero();
+    for (proof, inputs) in proofs {
+        let r = F::rand(rng);
+        let l = Groth16::<E>::prepare_inputs(pvk, inputs)
+            .map_err(|e| FluxeError::InvalidProof(format!("Groth16 verification failed: {}", e)))?;
+        g1.push(<E as Pairing>::G1Prepared::from((proof.a * r).into_affine()));
+        g2.push(<E as Pairing>::G2Prepared::from(proof.b));
+        sum_l += l * r;
+        sum_c += proof.c * r;
+        sum_r += r;
+    }
+    g1.push(sum_l.into_affine().into());
+    g2.push(pvk.gamma_g2_neg_pc.clone());
+    g1.push(sum_c.into_affine().into());
+    g2.push(pvk.delta_g2_neg_pc.clone());
+    
+    let product = E::final_exponentiation(E::multi_miller_loop(g1, g2))
+        .ok_or_else(|| FluxeError::InvalidProof("Final exponentiation failed".to_string()))?;
+    Ok(product.0 == pvk.alpha_g1_beta_g2.pow(sum_r.into_bigint()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +1818,1610 @@ mod tests {
         (vk.clone(), vk.clone(), vk.clone(), vk)
     }
 
+    // Mock setup returning a real proof over the empty dummy circuit
+    fn create_mock_verifier_and_proof() -> (ServerVerifier, Proof<ark_bls12_381::Bls12_381>) {
+        let (vk, proof) = create_mock_key_and_proof();
+        let verifier = ServerVerifier::new(
+            StateManager::new(32),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+            vk,
+        );
+        (verifier, proof)
+    }
+
+    fn create_mock_key_and_proof() -> (
+        VerifyingKey<ark_bls12_381::Bls12_381>,
+        Proof<ark_bls12_381::Bls12_381>,
+    ) {
+        use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+        #[derive(Clone)]
+        struct DummyCircuit;
+        impl ConstraintSynthesizer<F> for DummyCircuit {
+            fn generate_constraints(self, _cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+                Ok(())
+            }
+        }
+
+        let mut rng = thread_rng();
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            DummyCircuit, &mut rng
+        ).unwrap();
+        let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, DummyCircuit, &mut rng).unwrap();
+        (vk, proof)
+    }
+
+    fn mock_mint(roots: StateRoots, proof: Proof<ark_bls12_381::Bls12_381>) -> VerifiedTransaction {
+        TransactionBuilder::new_mint(roots.clone(), roots).build(
+            proof,
+            vec![],
+            TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(1000u64),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(1000u64), F::from(7u64), 1),
+            },
+        )
+    }
+
+    /// Load a burn key over the burn circuit's ten public inputs into `verifier`. The returned
+    /// closure builds a burn of 10 of asset 1 whose proof exposes `consumed_cm` and proves
+    /// membership against the CMT root of `roots`
+    fn mock_burn_prover(verifier: &mut ServerVerifier) -> impl Fn(StateRoots, Nullifier, Commitment, u64) -> VerifiedTransaction {
+        use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+        #[derive(Clone)]
+        struct BurnInputsCircuit([F; 10]);
+        impl ConstraintSynthesizer<F> for BurnInputsCircuit {
+            fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+                for input in self.0 {
+                    cs.new_input_variable(|| Ok(input))?;
+                }
+                Ok(())
+            }
+        }
+
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            BurnInputsCircuit([F::from(0u64); 10]), &mut thread_rng()
+        ).unwrap();
+        verifier.vk_burn = Some(vk);
+        move |roots: StateRoots, nf: Nullifier, consumed_cm: Commitment, nonce: u64| {
+            let mut inputs = [F::from(0u64); 10];
+            inputs[0] = roots.cmt_root;
+            inputs[9] = consumed_cm;
+            let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, BurnInputsCircuit(inputs), &mut thread_rng()).unwrap();
+            TransactionBuilder::new_burn(roots.clone(), roots).build(
+                proof,
+                inputs.to_vec(),
+                TransactionData::Burn {
+                    asset_type: 1,
+                    amount: Amount::from(10u64),
+                    nullifier: nf,
+                    consumed_cm,
+                    exit_receipt: ExitReceipt::new(1, Amount::from(10u64), nf, nonce),
+                },
+            )
+        }
+    }
+
+    /// Put `txs` in batch order and chain them from `state`: each starts from the roots
+    /// the one before it leaves and declares the roots it leaves in turn
+    fn declare_chained_roots(mut state: StateManager, txs: &mut [VerifiedTransaction]) -> StateManager {
+        ServerVerifier::order_transactions(txs);
+        for tx in txs.iter_mut() {
+            tx.old_roots = state.get_roots();
+            ServerVerifier::apply_transaction(&mut state, tx).unwrap();
+            tx.new_roots = state.get_roots();
+        }
+        state
+    }
+
+    #[test]
+    fn test_validate_only_does_not_mutate_state() {
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let roots_before = verifier.get_current_roots();
+
+        let tx = mock_mint(roots_before.clone(), proof);
+        verifier.validate_only(&tx).unwrap();
+
+        // Nothing applied, nothing queued
+        assert_eq!(verifier.get_current_roots(), roots_before);
+        assert_eq!(verifier.get_supply(1), Amount::zero());
+        assert!(verifier.pending_batch.transactions.is_empty());
+
+        println!("✓ Validate-only dry run test passed");
+    }
+
+    #[test]
+    fn test_validate_only_reports_failure() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let mock_burn = mock_burn_prover(&mut verifier);
+        let spent = F::from(42u64);
+        verifier.state.insert_nullifier(spent).unwrap();
+        let roots = verifier.get_current_roots();
+
+        // Already spent nullifier is reported as a double spend
+        let burn = mock_burn(roots.clone(), spent, F::from(43u64), 1);
+        assert!(matches!(verifier.validate_only(&burn), Err(FluxeError::DoubleSpend(nf)) if nf == spent));
+
+        // A burn must expose the commitment of the note it burns
+        let mut unexposed = mock_burn(roots.clone(), F::from(44u64), F::from(45u64), 2);
+        unexposed.public_inputs.truncate(9);
+        assert!(matches!(
+            verifier.validate_only(&unexposed),
+            Err(FluxeError::InvalidProof(msg)) if msg.contains("burned note commitment")
+        ));
+
+        // Wrong public inputs are reported as an invalid proof
+        let mut bad_inputs = mock_mint(roots, proof);
+        bad_inputs.public_inputs = vec![F::from(1u64)];
+        assert!(matches!(verifier.validate_only(&bad_inputs), Err(FluxeError::InvalidProof(_))));
+
+        println!("✓ Validate-only failure reporting test passed");
+    }
+
+    #[test]
+    fn test_admission_matches_validate_only() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let mock_burn = mock_burn_prover(&mut verifier);
+        verifier.state.supply.insert(1, Amount::from(1000u64));
+        let burn = |roots: StateRoots, consumed_cm: u64| {
+            mock_burn(roots, F::from(42u64), F::from(consumed_cm), consumed_cm)
+        };
+        let roots = verifier.get_current_roots();
+        verifier.add_transaction(burn(roots.clone(), 43)).unwrap();
+        let pending_roots = verifier.pending_roots();
+        assert_ne!(pending_roots, roots);
+
+        // Spending the queued nullifier again is a double spend on both paths
+        let double_spend = burn(pending_roots.clone(), 44);
+        assert!(matches!(verifier.validate_only(&double_spend), Err(FluxeError::DoubleSpend(_))));
+        assert!(matches!(verifier.add_transaction(double_spend), Err(FluxeError::DoubleSpend(_))));
+
+        // Built on the live roots instead of the pending ones, a transaction is stale on both
+        let stale = mock_mint(roots, proof.clone());
+        assert!(matches!(verifier.validate_only(&stale), Err(FluxeError::Other(msg)) if msg.contains("stale roots")));
+        assert!(matches!(verifier.add_transaction(stale), Err(FluxeError::Other(msg)) if msg.contains("stale roots")));
+        assert_eq!(verifier.pending_count(), 1);
+
+        // Chained onto the pending burn, both accept it and the batch applies
+        let mint = mock_mint(pending_roots, proof.clone());
+        verifier.validate_only(&mint).unwrap();
+        verifier.add_transaction(mint).unwrap();
+        assert_eq!(verifier.pending_count(), 2);
+
+        println!("✓ Admission and dry run agreement test passed");
+    }
+
+    #[test]
+    fn test_pending_overlay_follows_batch() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let mut txs = vec![mock_mint(roots.clone(), proof.clone()), mock_mint(roots.clone(), proof.clone())];
+        declare_chained_roots(verifier.state.clone(), &mut txs);
+        let (first, second) = (txs[0].clone(), txs[1].clone());
+
+        // Each admission advances the overlay to the roots the admitted transaction declares
+        assert_eq!(verifier.pending_roots(), roots);
+        verifier.add_transaction(first.clone()).unwrap();
+        assert_eq!(verifier.pending_roots(), first.new_roots);
+        verifier.add_transaction(second.clone()).unwrap();
+        assert_eq!(verifier.pending_roots(), second.new_roots);
+
+        // Once the batch applies, the pending roots are the live roots again
+        verifier.process_batch().unwrap();
+        assert!(verifier.pending_overlay.is_none());
+        assert_eq!(verifier.pending_roots(), verifier.get_current_roots());
+
+        // A failed batch keeps the overlay of the transactions that survive eviction
+        let mut txs = vec![mock_mint(verifier.get_current_roots(), proof.clone())];
+        declare_chained_roots(verifier.state.clone(), &mut txs);
+        let survivor = txs.remove(0);
+        verifier.add_transaction(survivor.clone()).unwrap();
+        verifier.pending_batch.transactions.push(first);
+        assert!(verifier.process_batch().is_err());
+        assert_eq!(verifier.pending_count(), 1);
+        assert_eq!(verifier.pending_roots(), survivor.new_roots);
+
+        // A sanctions root update moves the pending roots along with the live ones
+        let sanctions_root = F::from(99u64);
+        verifier.update_sanctions_root(sanctions_root);
+        assert_eq!(verifier.pending_roots().sanctions_root, sanctions_root);
+        let chained = mock_mint(verifier.pending_roots(), proof);
+        verifier.add_transaction(chained).unwrap();
+
+        println!("✓ Pending overlay test passed");
+    }
+
+    #[test]
+    fn test_recent_cmt_root_accepted() {
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let mut verifier = verifier.with_transfer_layout(TransferLayout {
+            recent_cmt_roots: 1,
+            ..TransferLayout::default()
+        });
+        verifier.state = StateManager::new(32).with_root_history(2);
+        verifier.state.supply.insert(1, Amount::from(1000u64));
+        let mock_burn = mock_burn_prover(&mut verifier);
+        let stale_cmt_root = verifier.get_current_roots().cmt_root;
+        
+        let mint_block = |state: &mut StateManager, i: u64| {
+            let receipt = IngressReceipt::new(1, Amount::from(5u64), F::from(i), i);
+            state.process_mint(&receipt, &[F::from(1000 + i)]).unwrap();
+        };
+        let burn = |roots: &StateRoots| mock_burn(roots.clone(), F::from(77u64), F::from(78u64), 1);
+        // A burn proves membership against its first input; this transfer, with no
+        // nullifiers or outputs, against the one after [6 roots, n_out, fee]
+        let membership_against = |roots: &StateRoots, cmt_root: F| {
+            let mut burn = burn(roots);
+            burn.public_inputs = vec![cmt_root];
+            let mut transfer = TransactionBuilder::new_transfer(roots.clone(), roots.clone()).build(
+                proof.clone(),
+                vec![F::from(0u64); 8],
+                TransactionData::Transfer { nullifiers: vec![], notes_out: vec![] },
+            );
+            transfer.public_inputs.push(cmt_root);
+            [burn, transfer]
+        };
+        
+        // Membership proven against the CMT root one block back is accepted
+        mint_block(&mut verifier.state, 1);
+        let roots = verifier.get_current_roots();
+        for tx in membership_against(&roots, stale_cmt_root) {
+            verifier.check_membership_roots(&verifier.state, &tx).unwrap();
+        }
+        
+        // Once the root leaves the window it is rejected
+        mint_block(&mut verifier.state, 2);
+        let roots = verifier.get_current_roots();
+        for tx in membership_against(&roots, stale_cmt_root) {
+            assert!(matches!(
+                verifier.check_membership_roots(&verifier.state, &tx),
+                Err(FluxeError::Other(msg)) if msg.contains("recent window")
+            ));
+        }
+        
+        // The roots a transaction transitions from must be current, CMT included
+        verifier.validate_only(&burn(&roots)).unwrap();
+        let mut stale_burn = burn(&roots);
+        stale_burn.old_roots.cmt_root = verifier.state.cmt_root_history[0];
+        assert!(matches!(
+            verifier.validate_only(&stale_burn),
+            Err(FluxeError::Other(msg)) if msg.contains("stale roots")
+        ));
+        let mut stale_mint = mock_mint(roots.clone(), proof.clone());
+        stale_mint.old_roots.cmt_root = verifier.state.cmt_root_history[0];
+        assert!(verifier.validate_only(&stale_mint).is_err());
+        
+        // A burn against a recent root claims no CMT change, so it applies at the current roots
+        let mut public_inputs = vec![F::from(0u64); 10];
+        public_inputs[0] = verifier.state.cmt_root_history[0];
+        public_inputs[1] = roots.nft_root;
+        public_inputs[3] = roots.exit_root;
+        public_inputs[8] = roots.exit_allow_root;
+        let data = burn(&roots).transaction_data;
+        let new_roots = claimed_new_roots(&roots, &public_inputs, &data).unwrap();
+        assert_eq!(new_roots.cmt_root, roots.cmt_root);
+        
+        println!("✓ Recent CMT root window test passed");
+    }
+
+    #[test]
+    fn test_failed_batch_rolls_back() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots_before = verifier.get_current_roots();
+        
+        // Two object updates adding the same callback ticket: the second insert fails
+        // after the mint and the first update have applied
+        let ticket = F::from(99u64);
+        let object_update = |new_object_cm: u64| VerifiedTransaction {
+            tx_type: TransactionType::ObjectUpdate,
+            proof: proof.clone(),
+            public_inputs: vec![],
+            old_roots: roots_before.clone(),
+            new_roots: roots_before.clone(),
+            transaction_data: TransactionData::ObjectUpdate {
+                old_object_cm: F::from(1u64),
+                new_object_cm: F::from(new_object_cm),
+                callback_ops: vec![CallbackOperation::Add(
+                    crate::data_structures::zk_object::CallbackInvocation::new(ticket, vec![1, 2, 3], 5),
+                )],
+                owner: None,
+            },
+        };
+        let mut txs = vec![mock_mint(roots_before.clone(), proof.clone()), object_update(10)];
+        declare_chained_roots(verifier.state.clone(), &mut txs);
+        for tx in txs {
+            verifier.add_transaction(tx).unwrap();
+        }
+        
+        // Admission refuses the duplicate ticket, so queue it directly to fail the batch
+        verifier.pending_batch.transactions.push(object_update(11));
+        
+        assert!(verifier.process_batch().is_err());
+        
+        // Ingress, CMT and supply changes from earlier in the batch are undone
+        assert_eq!(verifier.get_current_roots(), roots_before);
+        assert_eq!(verifier.get_supply(1), Amount::zero());
+        assert!(!verifier.state.cb_tree.contains(&ticket));
+        
+        // Only the failing update is evicted; the rest of the batch then goes through
+        assert_eq!(verifier.pending_count(), 2);
+        verifier.process_batch().unwrap();
+        assert!(verifier.state.cb_tree.contains(&ticket));
+        
+        println!("✓ Failed batch rollback test passed");
+    }
+
+    #[test]
+    fn test_private_id() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+
+        let mint = mock_mint(roots.clone(), proof.clone());
+        let mut other = mock_mint(roots, proof);
+        if let TransactionData::Mint { ingress_receipt, .. } = &mut other.transaction_data {
+            *ingress_receipt = IngressReceipt::new(1, Amount::from(1000u64), F::from(7u64), 2);
+        }
+        assert_ne!(mint.private_id(), other.private_id());
+
+        // Stable across a serialization round trip
+        let mut bytes = Vec::new();
+        mint.serialize_compressed(&mut bytes).unwrap();
+        let restored = VerifiedTransaction::deserialize_compressed(&*bytes).unwrap();
+        assert_eq!(restored.private_id(), mint.private_id());
+
+        // Queued transactions can be polled by id
+        let id = mint.private_id();
+        assert!(!verifier.is_pending(&id));
+        verifier.add_transaction(mint).unwrap();
+        assert!(verifier.is_pending(&id));
+        assert!(!verifier.is_pending(&other.private_id()));
+
+        println!("✓ Private transaction id test passed");
+    }
+
+    #[test]
+    fn test_claimed_new_roots_for_transfer() {
+        let (_, proof) = create_mock_verifier_and_proof();
+        let old_roots = StateManager::new(32).get_roots();
+        let cmt_root_new = F::from(101u64);
+        let nft_root_new = F::from(202u64);
+        let public_inputs = vec![
+            old_roots.cmt_root,
+            cmt_root_new,
+            old_roots.nft_root,
+            nft_root_new,
+            old_roots.sanctions_root,
+            old_roots.pool_rules_root,
+            F::from(9u64), // nullifier
+            F::from(10u64), // output commitment
+            F::from(1u64), // n_out
+            F::from(0u64), // fee
+        ];
+        let data = TransactionData::Transfer { nullifiers: vec![F::from(9u64)], notes_out: vec![] };
+
+        // The transaction carries the roots from its public inputs, not the old ones
+        let new_roots = claimed_new_roots(&old_roots, &public_inputs, &data).unwrap();
+        let tx = TransactionBuilder::new_transfer(old_roots.clone(), new_roots).build(proof, public_inputs.clone(), data.clone());
+        assert_eq!(tx.new_roots.cmt_root, cmt_root_new);
+        assert_eq!(tx.new_roots.nft_root, nft_root_new);
+        assert_ne!(tx.new_roots, tx.old_roots);
+        assert_eq!(tx.new_roots.obj_root, old_roots.obj_root);
+
+        // Inputs built against another CMT root are refused
+        let mut stale = public_inputs.clone();
+        stale[0] = F::from(5u64);
+        assert!(claimed_new_roots(&old_roots, &stale, &data).is_err());
+
+        // Too few inputs to carry the roots
+        assert!(claimed_new_roots(&old_roots, &public_inputs[..3], &data).is_err());
+    }
+
+    #[test]
+    fn test_rejection_log() {
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let mut verifier = verifier.with_rejection_log();
+        let roots = verifier.get_current_roots();
+
+        // A proof for different inputs fails verification
+        let (_, other_proof) = create_mock_key_and_proof();
+        let forged = mock_mint(roots.clone(), other_proof);
+        assert!(verifier.add_transaction(forged).is_err());
+
+        // More public inputs than the circuit takes
+        let mut over_limit = mock_mint(roots, proof);
+        over_limit.public_inputs = vec![F::from(1u64)];
+        assert!(verifier.add_transaction(over_limit).is_err());
+
+        let rejected = verifier.rejections_since(0);
+        assert_eq!(rejected.len(), 2);
+        assert!(rejected.iter().all(|r| r.circuit_type == TransactionType::Mint));
+        assert_ne!(rejected[0].reason, rejected[1].reason);
+        assert!(rejected[1].reason.contains("public inputs"));
+        assert!(verifier.rejections_since(Time::MAX).is_empty());
+        assert!(verifier.pending_batch.transactions.is_empty());
+
+        println!("✓ Rejection log test passed");
+    }
+
+    #[test]
+    fn test_add_transactions_dependent_batch() {
+        use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, 1000, &PedersenRandomness::new(&mut rng));
+        let note = Note::new(1, v_comm, F::from(11u64), [3u8; 32], 0);
+        let nf = note.nullifier(&F::from(5u64));
+
+        // Mint the note, then spend it in the same batch against the post-mint roots
+        let roots = verifier.get_current_roots();
+        let mut mint = mock_mint(roots.clone(), proof.clone());
+        if let TransactionData::Mint { notes_out, .. } = &mut mint.transaction_data {
+            notes_out.push(note.clone());
+        }
+        let mut after_mint = verifier.state.clone();
+        ServerVerifier::apply_transaction(&mut after_mint, &mint).unwrap();
+        let mid_roots = after_mint.get_roots();
+        let transfer = TransactionBuilder::new_transfer(mid_roots.clone(), mid_roots).build(
+            proof,
+            vec![],
+            TransactionData::Transfer {
+                nullifiers: vec![nf],
+                notes_out: vec![],
+            },
+        );
+
+        // A failing item sinks the whole batch: replaying the spend no longer
+        // chains off the roots it left behind
+        let results = verifier.add_transactions(vec![mint.clone(), transfer.clone(), transfer.clone()]);
+        assert!(results[0].is_ok() && results[1].is_ok());
+        assert!(results[2].is_err());
+        assert!(verifier.pending_batch.transactions.is_empty());
+
+        let results = verifier.add_transactions(vec![mint, transfer]);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(verifier.pending_batch.transactions.len(), 2);
+
+        println!("✓ Atomic dependent batch test passed");
+    }
+
+    #[test]
+    fn test_add_transactions_behind_pending() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let mut txs: Vec<VerifiedTransaction> = (1..=3u64)
+            .map(|i| {
+                let mut tx = mock_mint(StateRoots::new(), proof.clone());
+                if let TransactionData::Mint { ingress_receipt, .. } = &mut tx.transaction_data {
+                    *ingress_receipt = IngressReceipt::new(1, Amount::from(1000u64), F::from(i), i);
+                }
+                tx
+            })
+            .collect();
+        let expected = declare_chained_roots(verifier.state.clone(), &mut txs).get_roots();
+
+        verifier.add_transaction(txs[0].clone()).unwrap();
+
+        // A batch built on the live roots ignores the pending mint and is refused whole
+        let mut stale = txs[1].clone();
+        stale.old_roots = verifier.get_current_roots();
+        let results = verifier.add_transactions(vec![stale, txs[2].clone()]);
+        assert!(results.iter().all(Result::is_err));
+        assert_eq!(verifier.pending_batch.transactions.len(), 1);
+
+        // Built on the pending roots, the batch queues behind the pending mint
+        let results = verifier.add_transactions(txs[1..].to_vec());
+        assert!(results.iter().all(Result::is_ok));
+        let queued: Vec<F> = verifier.pending_batch.transactions.iter().map(|tx| tx.private_id()).collect();
+        assert_eq!(queued, txs.iter().map(|tx| tx.private_id()).collect::<Vec<_>>());
+
+        verifier.process_batch().unwrap();
+        assert_eq!(verifier.get_current_roots(), expected);
+
+        println!("✓ Batch behind pending transaction test passed");
+    }
+
+    #[test]
+    fn test_max_batch_appends() {
+        use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+
+        let (vk, proof) = create_mock_key_and_proof();
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        let capped_verifier = || ServerVerifier::new(
+            StateManager::new(32),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+        ).with_max_batch_appends(2);
+        let mut mint_with_outputs = |verifier: &ServerVerifier, count: u8| {
+            let mut mint = mock_mint(verifier.get_current_roots(), proof.clone());
+            if let TransactionData::Mint { notes_out, .. } = &mut mint.transaction_data {
+                for i in 0..count {
+                    let v_comm = PedersenCommitment::commit(&params, 100, &PedersenRandomness::new(&mut rng));
+                    notes_out.push(Note::new(1, v_comm, F::from(11u64), [i; 32], 0));
+                }
+            }
+            let mut after = verifier.state.clone();
+            ServerVerifier::apply_transaction(&mut after, &mint).unwrap();
+            mint.new_roots = after.get_roots();
+            mint
+        };
+
+        // Three appends against a cap of two is rejected without touching state
+        let mut verifier = capped_verifier();
+        let roots = verifier.get_current_roots();
+        let overgrown = mint_with_outputs(&verifier, 3);
+        verifier.add_transaction(overgrown).unwrap();
+        assert!(matches!(verifier.process_batch(), Err(FluxeError::Other(_))));
+        assert_eq!(verifier.get_current_roots(), roots);
+
+        // Exactly at the cap succeeds
+        let mut verifier = capped_verifier();
+        let within = mint_with_outputs(&verifier, 2);
+        verifier.add_transaction(within).unwrap();
+        verifier.process_batch().unwrap();
+        assert_eq!(verifier.state.cmt_tree.num_leaves(), 2);
+
+        println!("✓ Batch append cap test passed");
+    }
+
+    #[test]
+    fn test_dummy_outputs_not_appended() {
+        use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+
+        let (_, proof) = create_mock_verifier_and_proof();
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, 100, &PedersenRandomness::new(&mut rng));
+        let real = Note::new(1, v_comm, F::from(11u64), [1u8; 32], 0);
+        let state = StateManager::new(32);
+        let roots = state.get_roots();
+        let transfer = TransactionBuilder::new_transfer(roots.clone(), roots).build(
+            proof,
+            vec![],
+            TransactionData::Transfer {
+                nullifiers: vec![],
+                notes_out: vec![real.clone(), Note::dummy(1, 0)],
+            },
+        );
+
+        // Only the real output is appended, counted against the cap, or committed to
+        assert_eq!(transfer.output_commitments(), vec![real.commitment()]);
+        assert_eq!(ServerVerifier::cmt_appends(std::slice::from_ref(&transfer)), 1);
+        let mut after = state.clone();
+        ServerVerifier::apply_transaction(&mut after, &transfer).unwrap();
+        assert_eq!(after.cmt_tree.num_leaves(), 1);
+    }
+
+    #[test]
+    fn test_object_for_owner() {
+        use crate::data_structures::{CallbackEntry, ComplianceState, ZkObject};
+
+        let mut rng = thread_rng();
+        let owner = F::from(42u64);
+        // [obj_old, obj_new, cb_root, current_time, owner]
+        let public_inputs = vec![F::from(0u64), F::from(0u64), F::from(0u64), F::from(0u64), owner];
+        let circuit = InputsCircuit(public_inputs.clone());
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+        let mut verifier = ServerVerifier::with_keys(StateManager::new(32), None, None, None, Some(vk));
+        let r_obj = F::from(9u64);
+        let mut object = ZkObject::new(&ComplianceState::new_verified(1)).with_owner(owner);
+        let first_object_cm = object.commitment_with_randomness(&r_obj);
+
+        // Two updates of a never-frozen owner's object, each bumping the serial; the
+        // first names the owner, the second follows the index
+        for i in 0..2 {
+            let old_object_cm = object.commitment_with_randomness(&r_obj);
+            object.add_callback(&CallbackEntry::new(1, 1000, F::from(5u64)));
+            let mut update = VerifiedTransaction {
+                tx_type: TransactionType::ObjectUpdate,
+                proof: proof.clone(),
+                public_inputs: public_inputs.clone(),
+                old_roots: verifier.get_current_roots(),
+                new_roots: verifier.get_current_roots(),
+                transaction_data: TransactionData::ObjectUpdate {
+                    old_object_cm,
+                    new_object_cm: object.commitment_with_randomness(&r_obj),
+                    callback_ops: vec![],
+                    owner: (i == 0).then_some(owner),
+                },
+            };
+            let mut after = verifier.state.clone();
+            ServerVerifier::apply_transaction(&mut after, &update).unwrap();
+            update.new_roots = after.get_roots();
+
+            // Queued updates are not reflected until their batch is processed
+            verifier.add_transaction(update).unwrap();
+            assert_ne!(verifier.object_for_owner(owner), Some(object.commitment_with_randomness(&r_obj)));
+            verifier.process_batch().unwrap();
+        }
+
+        assert_eq!(object.serial, 2);
+        assert_eq!(verifier.object_for_owner(owner), Some(object.commitment_with_randomness(&r_obj)));
+        assert_eq!(verifier.state.object_owners.len(), 1);
+        assert_eq!(verifier.object_for_owner(F::from(43u64)), None);
+
+        // Only the current object is in the owner index, bound to its owner
+        let current = ZkObject::owner_index_key(owner, object.commitment_with_randomness(&r_obj));
+        let proof = verifier.state.prove_owner_object(owner).unwrap();
+        let params = crate::merkle::TreeParams::new(verifier.state.tree_config.object);
+        assert!(proof.verify(&current, &verifier.state.owner_index_root(), &params));
+        assert!(!verifier.state.owner_index.contains(&ZkObject::owner_index_key(owner, first_object_cm)));
+        assert!(verifier.state.prove_owner_object(F::from(43u64)).is_none());
+
+        // Another object can't take over the owner's index entry
+        let hijack = VerifiedTransaction {
+            tx_type: TransactionType::ObjectUpdate,
+            proof: create_mock_key_and_proof().1,
+            public_inputs: vec![],
+            old_roots: verifier.get_current_roots(),
+            new_roots: verifier.get_current_roots(),
+            transaction_data: TransactionData::ObjectUpdate {
+                old_object_cm: F::from(500u64),
+                new_object_cm: F::from(501u64),
+                callback_ops: vec![],
+                owner: Some(owner),
+            },
+        };
+        let mut after = verifier.state.clone();
+        assert!(ServerVerifier::apply_transaction(&mut after, &hijack).is_err());
+
+        // Nor can another object freeze its way into it
+        let mut freeze_hijack = hijack;
+        if let TransactionData::ObjectUpdate { callback_ops, owner: claimed, .. } = &mut freeze_hijack.transaction_data {
+            *callback_ops = vec![CallbackOperation::Freeze(owner)];
+            *claimed = None;
+        }
+        let mut after = verifier.state.clone();
+        assert!(ServerVerifier::apply_transaction(&mut after, &freeze_hijack).is_err());
+
+        // An update may only index the owner its proof exposes, whether it names the owner
+        // or freezes them
+        let mut unproven = freeze_hijack;
+        unproven.public_inputs = public_inputs;
+        unproven.public_inputs[4] = F::from(43u64);
+        assert!(matches!(verifier.check_proof_context(&unproven), Err(FluxeError::InvalidProof(_))));
+        if let TransactionData::ObjectUpdate { callback_ops, owner: claimed, .. } = &mut unproven.transaction_data {
+            *callback_ops = vec![];
+            *claimed = Some(owner);
+        }
+        assert!(matches!(verifier.check_proof_context(&unproven), Err(FluxeError::InvalidProof(_))));
+        unproven.public_inputs[4] = owner;
+        assert!(verifier.check_proof_context(&unproven).is_ok());
+
+        println!("✓ Object-by-owner index test passed");
+    }
+
+    #[test]
+    fn test_mempool_export_import() {
+        let (vk, proof) = create_mock_key_and_proof();
+        let new_verifier = || ServerVerifier::new(
+            StateManager::new(32),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+        );
+        let mut primary = new_verifier();
+        let roots = primary.get_current_roots();
+
+        // Three mints, each chained onto the roots the one before it leaves
+        let mut txs: Vec<_> = (1..=3u64).map(|i| {
+            let mut tx = mock_mint(roots.clone(), proof.clone());
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(100 * i),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(100 * i), F::from(i), i),
+            };
+            tx
+        }).collect();
+        let expected = declare_chained_roots(StateManager::new(32), &mut txs);
+        for tx in &txs {
+            primary.add_transaction(tx.clone()).unwrap();
+        }
+
+        // Primary dies; standby resumes from the exported mempool
+        let bytes = primary.export_mempool();
+        let mut standby = new_verifier();
+        assert_eq!(standby.import_mempool(&bytes).unwrap(), 3);
+
+        let header = standby.process_batch().unwrap();
+        assert_eq!(header.new_roots, expected.get_roots());
+        assert_eq!(standby.get_supply(1), Amount::from(600u64));
+
+        // A verifier with its own pending batch keeps it and its metadata
+        let mut busy = new_verifier();
+        busy.add_transaction(txs[0].clone()).unwrap();
+        let batch_id = busy.pending_batch.batch_id;
+        let timestamp = busy.pending_batch.timestamp;
+        assert!(busy.import_mempool(&bytes).is_err());
+        assert_eq!(busy.pending_batch.transactions.len(), 1);
+        assert_eq!(busy.pending_batch.batch_id, batch_id);
+        assert_eq!(busy.pending_batch.timestamp, timestamp);
+
+        // Corrupt bytes are rejected without queuing anything
+        let mut other = new_verifier();
+        assert!(other.import_mempool(&bytes[..bytes.len() - 1]).is_err());
+        assert!(other.pending_batch.transactions.is_empty());
+
+        // So are transactions that all start from the same roots instead of chaining,
+        // which admission would refuse, so they are queued directly
+        let mut unchained = new_verifier();
+        for mut tx in txs {
+            tx.old_roots = roots.clone();
+            unchained.pending_batch.transactions.push(tx);
+        }
+        assert!(matches!(
+            other.import_mempool(&unchained.export_mempool()),
+            Err(FluxeError::Other(msg)) if msg.contains("stale roots")
+        ));
+        assert!(other.pending_batch.transactions.is_empty());
+
+        println!("✓ Mempool export/import test passed");
+    }
+
+    #[test]
+    fn test_equal_fee_tie_break() {
+        let mut rng = thread_rng();
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            InputsCircuit(vec![F::from(0u64)]), &mut rng
+        ).unwrap();
+        let roots = StateManager::new(32).get_roots();
+        
+        // Two zero-fee mints that differ only in receipt and public inputs
+        let txs: Vec<_> = (1..=2u64).map(|i| {
+            let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, InputsCircuit(vec![F::from(i)]), &mut rng).unwrap();
+            let mut tx = mock_mint(roots.clone(), proof);
+            tx.public_inputs = vec![F::from(i)];
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(10u64),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(10u64), F::from(i), i),
+            };
+            tx
+        }).collect();
+        assert_eq!(txs[0].fee(), txs[1].fee());
+        assert!(txs[0].content_id().into_bigint() < txs[1].content_id().into_bigint());
+        
+        // Independent verifiers, either arrival order, repeated runs: the candidates are put
+        // in canonical order, chained and submitted, giving the same block every time
+        let mut headers = Vec::new();
+        let mut batches = Vec::new();
+        for _ in 0..2 {
+            for arrival in [[0, 1], [1, 0]] {
+                let mut verifier = ServerVerifier::with_keys(
+                    StateManager::new(32),
+                    Some(vk.clone()),
+                    None,
+                    None,
+                    None,
+                );
+                let mut batch: Vec<_> = arrival.iter().map(|&i| txs[i].clone()).collect();
+                declare_chained_roots(verifier.state.clone(), &mut batch);
+                for tx in &batch {
+                    verifier.add_transaction(tx.clone()).unwrap();
+                }
+                batches.push(batch.iter().map(VerifiedTransaction::content_id).collect::<Vec<_>>());
+                headers.push(verifier.process_batch().unwrap());
+            }
+        }
+        assert!(headers.iter().all(|h| h == &headers[0]));
+        assert_eq!(batches[0], vec![txs[0].content_id(), txs[1].content_id()]);
+        assert!(batches.iter().all(|b| b == &batches[0]));
+        
+        println!("✓ Equal-fee tie-break test passed");
+    }
+
+    #[test]
+    fn test_dependent_batch_keeps_admission_order() {
+        use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+
+        let mut rng = thread_rng();
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, 1000, &PedersenRandomness::new(&mut rng));
+        let note = Note::new(1, v_comm, F::from(11u64), [3u8; 32], 0);
+        let nf = note.nullifier(&F::from(5u64));
+
+        // [6 roots, nf, n_out, fee, trailing shape-dependent input]
+        let mut transfer_inputs = vec![F::from(0u64); 10];
+        transfer_inputs[8] = F::from(5u64);
+        transfer_inputs[9] = F::from(1_700_000_000u64);
+        let transfer_circuit = InputsCircuit(transfer_inputs.clone());
+        let (pk, vk_transfer) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            transfer_circuit.clone(), &mut rng
+        ).unwrap();
+        let transfer_proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, transfer_circuit, &mut rng).unwrap();
+        let (vk_mint, mint_proof) = create_mock_key_and_proof();
+        let new_verifier = || ServerVerifier::with_keys(
+            StateManager::new(32),
+            Some(vk_mint.clone()),
+            None,
+            Some(vk_transfer.clone()),
+            None,
+        );
+
+        // A zero-fee mint, then a fee-paying transfer spending its note
+        let mut verifier = new_verifier();
+        let mut mint = mock_mint(verifier.get_current_roots(), mint_proof);
+        if let TransactionData::Mint { notes_out, .. } = &mut mint.transaction_data {
+            notes_out.push(note);
+        }
+        let mut transfer = TransactionBuilder::new_transfer(StateRoots::new(), StateRoots::new()).build(
+            transfer_proof,
+            transfer_inputs,
+            TransactionData::Transfer { nullifiers: vec![nf], notes_out: vec![] },
+        );
+        assert_eq!(transfer.fee(), F::from(5u64));
+        assert!(transfer.fee() > mint.fee());
+
+        let mut state = verifier.state.clone();
+        for tx in [&mut mint, &mut transfer] {
+            tx.old_roots = state.get_roots();
+            ServerVerifier::apply_transaction(&mut state, tx).unwrap();
+            tx.new_roots = state.get_roots();
+        }
+
+        // Admitted in turn, the higher fee does not pull the spend ahead of its mint
+        verifier.add_transaction(mint.clone()).unwrap();
+        verifier.add_transaction(transfer.clone()).unwrap();
+        let order = [mint.private_id(), transfer.private_id()];
+        let queued: Vec<F> = verifier.pending_batch.transactions.iter().map(VerifiedTransaction::private_id).collect();
+        assert_eq!(queued, order);
+
+        // So does a standby importing the mempool
+        let mut standby = new_verifier();
+        assert_eq!(standby.import_mempool(&verifier.export_mempool()).unwrap(), 2);
+        let queued: Vec<F> = standby.pending_batch.transactions.iter().map(VerifiedTransaction::private_id).collect();
+        assert_eq!(queued, order);
+
+        for verifier in [&mut verifier, &mut standby] {
+            let header = verifier.process_batch().unwrap();
+            assert_eq!(header.new_roots, state.get_roots());
+            assert!(verifier.state.nullifier_exists(nf));
+        }
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let (vk, proof) = create_mock_key_and_proof();
+        let new_verifier = || ServerVerifier::new(
+            StateManager::new(32),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+        );
+        let roots = StateManager::new(32).get_roots();
+        let mint = |i: u64| {
+            let mut tx = mock_mint(roots.clone(), proof.clone());
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(100 * i),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(100 * i), F::from(i), i),
+            };
+            tx
+        };
+        
+        // Produce a block from two mints
+        let mut txs = vec![mint(1), mint(2)];
+        declare_chained_roots(StateManager::new(32), &mut txs);
+        let mut producer = new_verifier();
+        for tx in &txs {
+            producer.add_transaction(tx.clone()).unwrap();
+        }
+        let header = producer.process_batch().unwrap();
+        
+        // An auditor at the previous roots accepts the block without changing state
+        let auditor = new_verifier();
+        auditor.verify_block(&header, &txs).unwrap();
+        assert_eq!(auditor.get_current_roots(), roots);
+        
+        // Swapping in a different transaction breaks the root transition
+        let mut swapped = txs.clone();
+        swapped[0] = mint(3);
+        assert!(matches!(auditor.verify_block(&header, &swapped), Err(FluxeError::Other(_))));
+        
+        // The block does not chain off the producer's advanced state
+        assert!(producer.verify_block(&header, &txs).is_err());
+        
+        println!("✓ Block verification test passed");
+    }
+
+    #[test]
+    fn test_roots_checked_per_transaction() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let mint = |i: u64| {
+            let mut tx = mock_mint(roots.clone(), proof.clone());
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(100 * i),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(100 * i), F::from(i), i),
+            };
+            tx
+        };
+        let mut txs = vec![mint(1), mint(2)];
+        let expected = declare_chained_roots(verifier.state.clone(), &mut txs);
+        
+        // The first transaction claims the post-batch roots; the final roots still match
+        txs[0].new_roots = expected.get_roots();
+        for tx in &txs {
+            verifier.add_transaction(tx.clone()).unwrap();
+        }
+        assert!(matches!(verifier.process_batch(), Err(FluxeError::Other(_))));
+        assert_eq!(verifier.get_current_roots(), roots);
+        
+        println!("✓ Per-transaction root check test passed");
+    }
+
+    #[test]
+    fn test_process_batch_records_events() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let mint = |roots: StateRoots, i: u64| {
+            let mut tx = mock_mint(roots, proof.clone());
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(100 * i),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(100 * i), F::from(i), i),
+            };
+            tx
+        };
+        
+        // Two mints in the first batch, one in the second
+        for (batch, count) in [(0u64, 2u64), (1, 1)] {
+            let roots = verifier.get_current_roots();
+            let mut txs: Vec<_> = (1..=count).map(|i| mint(roots.clone(), 10 * batch + i)).collect();
+            declare_chained_roots(verifier.state.clone(), &mut txs);
+            for tx in txs {
+                verifier.add_transaction(tx).unwrap();
+            }
+            verifier.process_batch().unwrap();
+            assert_eq!(verifier.state.batch_id, batch + 1);
+        }
+        
+        // One event per applied transaction, under the batch that applied it
+        let batch_ids: Vec<u64> = verifier.state.events.iter().map(|event| event.batch_id).collect();
+        assert_eq!(batch_ids, vec![0, 0, 1]);
+        assert_eq!(verifier.state.events[2].transition.new_roots, verifier.get_current_roots());
+        
+        // A failed batch leaves neither events nor a batch bump behind; admission refuses
+        // the stale roots, so the transaction is queued directly
+        let stale = mint(StateManager::new(32).get_roots(), 99);
+        verifier.pending_batch.transactions.push(stale);
+        assert!(verifier.process_batch().is_err());
+        assert_eq!(verifier.state.events.len(), 3);
+        assert_eq!(verifier.state.batch_id, 2);
+        
+        // Maintenance keeping one batch back drops the first batch's events
+        let config = MaintenanceConfig { keep_batches: 1, ..MaintenanceConfig::default() };
+        assert_eq!(verifier.maintenance(&config).events_pruned, 2);
+        assert!(verifier.state.events.iter().all(|event| event.batch_id == 1));
+        
+        println!("✓ Batch event log test passed");
+    }
+
+    #[test]
+    fn test_inclusion_receipt() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let mint = |i: u64| {
+            let mut tx = mock_mint(roots.clone(), proof.clone());
+            tx.transaction_data = TransactionData::Mint {
+                asset_type: 1,
+                amount: Amount::from(100 * i),
+                notes_out: vec![],
+                ingress_receipt: IngressReceipt::new(1, Amount::from(100 * i), F::from(i), i),
+            };
+            tx
+        };
+        
+        let mut txs = vec![mint(1), mint(2), mint(3)];
+        declare_chained_roots(StateManager::new(32), &mut txs);
+        for tx in &txs {
+            verifier.add_transaction(tx.clone()).unwrap();
+        }
+        
+        // No receipt while the transaction is still pending
+        assert!(verifier.inclusion_receipt(&txs[0].private_id()).is_none());
+        let header = verifier.process_batch().unwrap();
+        
+        // Every batched transaction gets a receipt verifying against the header's tx root
+        for tx in &txs {
+            let receipt = verifier.inclusion_receipt(&tx.private_id()).unwrap();
+            assert_eq!(receipt.batch_id, header.batch_id);
+            assert!(receipt.verify(&header));
+        }
+        
+        // A receipt does not verify for another transaction or another block
+        let mut forged = verifier.inclusion_receipt(&txs[0].private_id()).unwrap().clone();
+        forged.tx_private_id = F::from(999u64);
+        forged.merkle_path_to_batch_txs.leaf = forged.tx_private_id;
+        assert!(!forged.verify(&header));
+        let receipt = verifier.inclusion_receipt(&txs[0].private_id()).unwrap();
+        let mut other_block = header.clone();
+        other_block.batch_id += 1;
+        assert!(!receipt.verify(&other_block));
+        
+        println!("✓ Inclusion receipt test passed");
+    }
+
+    #[test]
+    fn test_partition_independent() {
+        let (mut verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let transfer = |nullifiers: Vec<u64>| {
+            TransactionBuilder::new_transfer(roots.clone(), roots.clone()).build(
+                proof.clone(),
+                vec![],
+                TransactionData::Transfer {
+                    nullifiers: nullifiers.into_iter().map(F::from).collect(),
+                    notes_out: vec![],
+                },
+            )
+        };
+        
+        // 0 and 2 share a nullifier; 3 chains onto 2; 1 is independent
+        let txs = vec![transfer(vec![1]), transfer(vec![5]), transfer(vec![1, 2]), transfer(vec![2])];
+        assert!(txs[0].conflicts_with(&txs[2]));
+        assert!(!txs[0].conflicts_with(&txs[1]));
+        assert_eq!(
+            ServerVerifier::partition_independent(&txs),
+            vec![vec![0, 2, 3], vec![1]]
+        );
+        
+        // The conflicting group applies serially and exposes the double spend
+        assert!(matches!(
+            verifier.apply_groups_parallel(&txs),
+            Err(FluxeError::DoubleSpend(nf)) if nf == F::from(1u64)
+        ));
+        
+        // Independent transactions each get their own group and apply cleanly
+        let independent = vec![transfer(vec![1]), transfer(vec![5]), transfer(vec![7])];
+        assert_eq!(ServerVerifier::partition_independent(&independent).len(), 3);
+        verifier.apply_groups_parallel(&independent).unwrap();
+        assert_eq!(verifier.get_current_roots(), roots);
+        
+        // Batch processing runs the same dry run, so the conflicting batch is refused
+        // before the live state or its event log moves
+        let events = verifier.state.events.len();
+        verifier.pending_batch.transactions = txs;
+        assert!(matches!(verifier.process_batch(), Err(FluxeError::DoubleSpend(_))));
+        assert_eq!(verifier.get_current_roots(), roots);
+        assert_eq!(verifier.state.events.len(), events);
+        
+        println!("✓ Independent partition test passed");
+    }
+
+    #[test]
+    fn test_nft_stats_after_burns() {
+        let (mut verifier, _) = create_mock_verifier_and_proof();
+        let mock_burn = mock_burn_prover(&mut verifier);
+        verifier.state.supply.insert(1, Amount::from(1000u64));
+        let roots = verifier.get_current_roots();
+        
+        let mut burns: Vec<_> = (1..=3u64)
+            .map(|i| mock_burn(roots.clone(), F::from(100 + i), F::from(200 + i), i))
+            .collect();
+        declare_chained_roots(verifier.state.clone(), &mut burns);
+        
+        // Only the key-0 sentinel before any burn
+        assert_eq!(verifier.nft_stats().entries, 0);
+        assert_eq!(verifier.nft_stats().leaf_count, 1);
+        
+        for tx in burns {
+            verifier.add_transaction(tx).unwrap();
+        }
+        verifier.process_batch().unwrap();
+        
+        let stats = verifier.nft_stats();
+        assert_eq!(stats.entries, 3);
+        assert_eq!(stats.leaf_count, 4);
+        assert_eq!(stats.capacity, Some(1 << 32));
+        assert!(stats.utilization > 0.0);
+        
+        println!("✓ NFT stats test passed");
+    }
+
+    #[test]
+    fn test_batch_nullifiers_staged() {
+        let (vk, _) = create_mock_key_and_proof();
+        let mut verifier = ServerVerifier::new(
+            StateManager::new(32).with_confirmation_delay(1),
+            vk.clone(),
+            vk.clone(),
+            vk.clone(),
+            vk,
+        );
+        let mock_burn = mock_burn_prover(&mut verifier);
+        verifier.state.supply.insert(1, Amount::from(1000u64));
+        let empty_nft_root = verifier.state.nft_tree.root();
+        let burn = |roots: StateRoots, i: u64| mock_burn(roots, F::from(500u64), F::from(600 + i), i);
+        
+        // The batch's nullifier moves the NFT root, as its proof claims, and is journaled
+        let mut txs = vec![burn(verifier.get_current_roots(), 1)];
+        declare_chained_roots(verifier.state.clone(), &mut txs);
+        let spent_nft_root = txs[0].new_roots.nft_root;
+        assert_ne!(spent_nft_root, empty_nft_root);
+        verifier.add_transaction(txs.remove(0)).unwrap();
+        verifier.process_batch().unwrap();
+        assert_eq!(verifier.state.nft_tree.root(), spent_nft_root);
+        assert_eq!(verifier.state.staged_nullifiers.len(), 1);
+        
+        // Journaled, it blocks a second spend
+        let roots = verifier.get_current_roots();
+        assert!(matches!(verifier.add_transaction(burn(roots, 2)), Err(FluxeError::DoubleSpend(_))));
+        
+        // Once the next batch confirms it, it is final and the root stays put
+        assert_eq!(verifier.state.batch_id, 1);
+        assert_eq!(verifier.state.finalize_nullifiers(0), 1);
+        assert!(verifier.state.staged_nullifiers.is_empty());
+        assert_eq!(verifier.state.nft_tree.root(), spent_nft_root);
+        
+        println!("✓ Staged batch nullifiers test passed");
+    }
+
+    #[test]
+    fn test_same_note_burned_twice_in_batch() {
+        let (mut verifier, _) = create_mock_verifier_and_proof();
+        let mock_burn = mock_burn_prover(&mut verifier);
+        verifier.state.supply.insert(1, Amount::from(1000u64));
+        let roots = verifier.get_current_roots();
+        
+        // Two burns of one note commitment under different nullifiers
+        let consumed_cm = F::from(77u64);
+        let mut burns: Vec<_> = (1..=2u64)
+            .map(|i| mock_burn(roots.clone(), F::from(300 + i), consumed_cm, i))
+            .collect();
+        declare_chained_roots(verifier.state.clone(), &mut burns);
+        for tx in burns {
+            verifier.add_transaction(tx).unwrap();
+        }
+        
+        let result = verifier.process_batch();
+        assert!(matches!(result, Err(FluxeError::Other(ref msg)) if msg.contains("burned twice")));
+        
+        // Rejected before touching the live state
+        assert_eq!(verifier.get_current_roots(), roots);
+        
+        println!("✓ Same-note double burn test passed");
+    }
+
+    #[test]
+    fn test_missing_verifying_key() {
+        let (vk_mint, _, vk_transfer, _) = create_mock_verifying_keys();
+        let mut verifier = ServerVerifier::with_keys(
+            StateManager::new(32),
+            Some(vk_mint),
+            None,
+            Some(vk_transfer),
+            None,
+        );
+        assert_eq!(
+            verifier.loaded_circuit_types(),
+            vec![TransactionType::Mint, TransactionType::Transfer]
+        );
+
+        let (_, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let tx = VerifiedTransaction {
+            tx_type: TransactionType::ObjectUpdate,
+            proof,
+            public_inputs: vec![],
+            old_roots: roots.clone(),
+            new_roots: roots,
+            transaction_data: TransactionData::ObjectUpdate {
+                old_object_cm: F::from(1u64),
+                new_object_cm: F::from(2u64),
+                callback_ops: vec![],
+                owner: None,
+            },
+        };
+
+        match verifier.add_transaction(tx) {
+            Err(FluxeError::Other(msg)) => assert_eq!(msg, "no verifying key for ObjectUpdate"),
+            other => panic!("expected missing key error, got {:?}", other),
+        }
+        assert!(verifier.pending_batch.transactions.is_empty());
+
+        println!("✓ Missing verifying key test passed");
+    }
+
+    /// Circuit that only exposes the given public inputs
+    #[derive(Clone)]
+    struct InputsCircuit(Vec<F>);
+    
+    impl ark_relations::r1cs::ConstraintSynthesizer<F> for InputsCircuit {
+        fn generate_constraints(
+            self,
+            cs: ark_relations::r1cs::ConstraintSystemRef<F>,
+        ) -> Result<(), ark_relations::r1cs::SynthesisError> {
+            for input in self.0 {
+                cs.new_input_variable(|| Ok(input))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = thread_rng();
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            InputsCircuit(vec![F::from(0u64); 2]), &mut rng
+        ).unwrap();
+        let (mint_vk, mint_proof) = create_mock_key_and_proof();
+        let verifier = ServerVerifier::with_keys(StateManager::new(32), Some(mint_vk), None, Some(vk), None);
+        let roots = verifier.get_current_roots();
+        
+        let transfer = |inputs: Vec<F>, rng: &mut rand::rngs::ThreadRng| {
+            let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, InputsCircuit(inputs.clone()), rng).unwrap();
+            TransactionBuilder::new_transfer(roots.clone(), roots.clone()).build(
+                proof,
+                inputs,
+                TransactionData::Transfer { nullifiers: vec![], notes_out: vec![] },
+            )
+        };
+        let mut txs: Vec<_> = (1..=4u64)
+            .map(|i| transfer(vec![F::from(i), F::from(i * 10)], &mut rng))
+            .collect();
+        txs.push(mock_mint(roots.clone(), mint_proof));
+        
+        // Valid proofs of two circuits pass together
+        verifier.verify_batch(&txs).unwrap();
+        for tx in &txs {
+            assert!(verifier.verify_transaction(tx).unwrap());
+        }
+        
+        // One proof bound to different public inputs rejects the whole batch
+        let mut bad = txs.clone();
+        bad[2].public_inputs[1] = F::from(99u64);
+        assert!(matches!(verifier.verify_batch(&bad), Err(FluxeError::InvalidProof(_))));
+        
+        // So does a proof from another transaction
+        let mut swapped = txs.clone();
+        swapped[0].proof = txs[1].proof.clone();
+        assert!(matches!(verifier.verify_batch(&swapped), Err(FluxeError::InvalidProof(_))));
+        
+        println!("✓ Batch verification test passed");
+    }
+
+    #[test]
+    fn test_mint_minters_root() {
+        use crate::merkle::SortedTree;
+
+        let mut rng = thread_rng();
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(
+            InputsCircuit(vec![F::from(0u64); 10]), &mut rng
+        ).unwrap();
+        let mut minters = SortedTree::new(16);
+        minters.insert(F::from(5u64)).unwrap();
+        let mut state = StateManager::new(32);
+        state.update_authorized_minters_root(minters.root());
+        let verifier = ServerVerifier::with_keys(state, Some(vk), None, None, None);
+        let roots = verifier.get_current_roots();
+        
+        // [.., authorized_minters_root (9)]
+        let mint = |minters_root: F, rng: &mut rand::rngs::ThreadRng| {
+            let mut inputs = vec![F::from(0u64); 10];
+            inputs[9] = minters_root;
+            let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, InputsCircuit(inputs.clone()), rng).unwrap();
+            let mut tx = mock_mint(roots.clone(), proof);
+            tx.public_inputs = inputs;
+            tx
+        };
+        
+        // A minter proven against the live tree is admitted
+        verifier.validate_only(&mint(minters.root(), &mut rng)).unwrap();
+        
+        // One proven against a foreign minters tree is not, though its proof verifies
+        let mut foreign = SortedTree::new(16);
+        foreign.insert(F::from(6u64)).unwrap();
+        let forged = mint(foreign.root(), &mut rng);
+        assert!(verifier.verify_transaction(&forged).unwrap());
+        assert!(matches!(verifier.validate_only(&forged), Err(FluxeError::ComplianceViolation(_))));
+        
+        // Nor is a mint that leaves the root out of its inputs
+        let mut short = mint(minters.root(), &mut rng);
+        short.public_inputs.truncate(9);
+        assert!(matches!(verifier.validate_only(&short), Err(FluxeError::InvalidProof(_))));
+        
+        println!("✓ Mint minters root test passed");
+    }
+
+    #[test]
+    fn test_risk_tags() {
+        use crate::crypto::pedersen::{PedersenCommitment, PedersenParams, PedersenRandomness};
+
+        let mut rng = thread_rng();
+        let state = StateManager::new(32);
+        let roots = state.get_roots();
+        // [cmt_old, cmt_new, nft_old, nft_new, sanctions, pool_rules, nf, n_out, fee, sanctions fallbacks]
+        let public_inputs = vec![
+            roots.cmt_root, F::from(1u64), roots.nft_root, F::from(2u64),
+            roots.sanctions_root, roots.pool_rules_root, F::from(9u64), F::from(0u64), F::from(0u64),
+            F::from(2u64),
+        ];
+        let circuit = InputsCircuit(public_inputs.clone());
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+
+        // The default layout reads the count the setup's transfer key publishes
+        let layout = TransferLayout::default();
+        let mut verifier = ServerVerifier::with_keys(state, None, None, Some(vk), None)
+            .with_transfer_layout(layout.clone());
+        let tx = TransactionBuilder::new_transfer(roots.clone(), roots).build(
+            proof,
+            public_inputs,
+            TransactionData::Transfer { nullifiers: vec![F::from(9u64)], notes_out: vec![] },
+        );
+        let tx_id = tx.private_id();
+        assert_eq!(layout.sanctions_fallbacks(&tx), Some(F::from(2u64)));
+        verifier.add_transaction(tx.clone()).unwrap();
+
+        let flagged = verifier.flagged_transactions();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].tx_id, tx_id);
+        assert_eq!(flagged[0].risk_tags, vec![RiskTag::MissingSanctionsProof]);
+
+        // Every address cleared against the list, or a key that does not publish the count
+        let mut cleared = tx.clone();
+        *cleared.public_inputs.last_mut().unwrap() = F::from(0u64);
+        assert!(RiskPolicy::default().tags_for(&cleared, &layout).is_empty());
+        let unpublished = TransferLayout { sanctions_fallbacks: false, ..TransferLayout::default() };
+        assert!(RiskPolicy::default().tags_for(&tx, &unpublished).is_empty());
+
+        // Large mints are tagged under a policy; small ones are not flagged at all
+        let policy = RiskPolicy { high_risk_pools: vec![], large_amount: Amount::from(1000u64) };
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let mut verifier = verifier.with_risk_policy(policy.clone());
+        let roots = verifier.get_current_roots();
+        verifier.add_transaction(mock_mint(roots.clone(), proof.clone())).unwrap();
+        assert_eq!(verifier.flagged_transactions()[0].risk_tags, vec![RiskTag::LargeAmount]);
+        let mut small = mock_mint(roots, proof);
+        if let TransactionData::Mint { amount, .. } = &mut small.transaction_data {
+            *amount = Amount::from(10u64);
+        }
+        assert!(policy.tags_for(&small, &TransferLayout::default()).is_empty());
+
+        // Each high-risk pool is tagged once, however its notes are interleaved
+        let policy = RiskPolicy { high_risk_pools: vec![3, 5], ..RiskPolicy::default() };
+        let params = PedersenParams::setup_value_commitment();
+        let v_comm = PedersenCommitment::commit(&params, 10, &PedersenRandomness::new(&mut rng));
+        if let TransactionData::Mint { notes_out, .. } = &mut small.transaction_data {
+            *notes_out = [5, 3, 5, 1]
+                .into_iter()
+                .map(|pool_id| Note::new(1, v_comm.clone(), F::from(11u64), [3u8; 32], pool_id))
+                .collect();
+        }
+        assert_eq!(
+            policy.tags_for(&small, &TransferLayout::default()),
+            vec![RiskTag::HighRiskPool(3), RiskTag::HighRiskPool(5)]
+        );
+    }
+
+    #[test]
+    fn test_stale_sanctions_root_rejected() {
+        let mut rng = thread_rng();
+        let mut state = StateManager::new(32);
+        state.update_sanctions_root(F::from(100u64));
+        let roots = state.get_roots();
+        // [cmt_old, cmt_new, nft_old, nft_new, sanctions, pool_rules, nf, n_out, fee]
+        let public_inputs = vec![
+            roots.cmt_root, F::from(1u64), roots.nft_root, F::from(2u64),
+            roots.sanctions_root, roots.pool_rules_root, F::from(9u64), F::from(0u64), F::from(0u64),
+        ];
+        let circuit = InputsCircuit(public_inputs.clone());
+        let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+        let tx = TransactionBuilder::new_transfer(roots.clone(), roots).build(
+            proof,
+            public_inputs,
+            TransactionData::Transfer { nullifiers: vec![F::from(9u64)], notes_out: vec![] },
+        );
+
+        // The transaction starts from the verifier's pending roots; only the sanctions root
+        // its proof is built against lags behind
+        let rebased = |verifier: &ServerVerifier| {
+            let mut tx = tx.clone();
+            tx.old_roots = verifier.pending_roots();
+            tx.new_roots = tx.old_roots.clone();
+            tx
+        };
+
+        // Current root only: accepted until the sanctions list changes
+        let mut verifier = ServerVerifier::with_keys(state.clone(), None, None, Some(vk.clone()), None)
+            .with_sanctions_freshness(0);
+        assert!(verifier.add_transaction(rebased(&verifier)).is_ok());
+        verifier.update_sanctions_root(F::from(200u64));
+        match verifier.add_transaction(rebased(&verifier)) {
+            Err(FluxeError::ComplianceViolation(reason)) => assert!(reason.contains("stale sanctions root")),
+            other => panic!("expected stale sanctions root rejection, got {:?}", other),
+        }
+
+        // A window of one tolerates the previous root, but not the one before it
+        let mut verifier = ServerVerifier::with_keys(state.clone(), None, None, Some(vk.clone()), None)
+            .with_sanctions_freshness(1);
+        verifier.update_sanctions_root(F::from(200u64));
+        assert!(verifier.add_transaction(rebased(&verifier)).is_ok());
+        verifier.update_sanctions_root(F::from(300u64));
+        assert!(verifier.add_transaction(rebased(&verifier)).is_err());
+
+        // Without the policy the stale root is not checked at admission
+        let mut verifier = ServerVerifier::with_keys(state, None, None, Some(vk), None);
+        verifier.update_sanctions_root(F::from(200u64));
+        assert!(verifier.add_transaction(rebased(&verifier)).is_ok());
+    }
+
+    #[test]
+    fn test_stale_owner_index_root_rejected() {
+        let mut rng = thread_rng();
+        let mut state = StateManager::new(32);
+        state.index_object(F::from(5u64), F::from(50u64));
+        let roots = state.get_roots();
+        let transfer = |owner_index_root: F| {
+            // [cmt_old, cmt_new, nft_old, nft_new, sanctions, pool_rules, nf, n_out, fee, owner_index_root]
+            let public_inputs = vec![
+                roots.cmt_root, F::from(1u64), roots.nft_root, F::from(2u64),
+                roots.sanctions_root, roots.pool_rules_root, F::from(9u64), F::from(0u64), F::from(0u64),
+                owner_index_root,
+            ];
+            let circuit = InputsCircuit(public_inputs.clone());
+            let (pk, vk) = Groth16::<ark_bls12_381::Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+            let proof = Groth16::<ark_bls12_381::Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+            let tx = TransactionBuilder::new_transfer(roots.clone(), roots.clone()).build(
+                proof,
+                public_inputs,
+                TransactionData::Transfer { nullifiers: vec![F::from(9u64)], notes_out: vec![] },
+            );
+            (tx, vk)
+        };
+        let layout = TransferLayout { owner_index_root: true, ..TransferLayout::default() };
+
+        // Proven against the live owner index
+        let (tx, vk) = transfer(state.owner_index_root());
+        let mut verifier = ServerVerifier::with_keys(state.clone(), None, None, Some(vk), None)
+            .with_transfer_layout(layout.clone());
+        assert_eq!(layout.owner_index_root(&tx), Some(state.owner_index_root()));
+        assert!(verifier.add_transaction(tx).is_ok());
+
+        // A mismatched root would let a frozen recipient through
+        let (tx, vk) = transfer(F::from(12345u64));
+        let mut verifier = ServerVerifier::with_keys(state.clone(), None, None, Some(vk), None)
+            .with_transfer_layout(layout.clone());
+        match verifier.add_transaction(tx) {
+            Err(FluxeError::ComplianceViolation(reason)) => assert!(reason.contains("stale owner index root")),
+            other => panic!("expected stale owner index root rejection, got {:?}", other),
+        }
+
+        // Freezing another owner moves the index, and the earlier proof goes stale
+        let (tx, vk) = transfer(state.owner_index_root());
+        let mut verifier = ServerVerifier::with_keys(state, None, None, Some(vk), None)
+            .with_transfer_layout(layout);
+        verifier.state.index_object(F::from(6u64), F::from(60u64));
+        assert!(verifier.check_proof_context(&tx).is_err());
+    }
+
+    #[test]
+    fn test_review_window_pinned() {
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let transfer = |current_time: F, max_review_age: F| {
+            // [cmt_old, cmt_new, nft_old, nft_new, sanctions, pool_rules, nf, n_out, fee,
+            //  current_time, max_review_age]
+            let public_inputs = vec![
+                roots.cmt_root, F::from(1u64), roots.nft_root, F::from(2u64),
+                roots.sanctions_root, roots.pool_rules_root, F::from(9u64), F::from(0u64), F::from(0u64),
+                current_time, max_review_age,
+            ];
+            TransactionBuilder::new_transfer(roots.clone(), roots.clone()).build(
+                proof.clone(),
+                public_inputs,
+                TransactionData::Transfer { nullifiers: vec![F::from(9u64)], notes_out: vec![] },
+            )
+        };
+        let verifier = verifier.with_transfer_layout(TransferLayout {
+            review_window: true,
+            ..TransferLayout::default()
+        });
+        let fresh = transfer(F::from(now), F::from(86_400u64));
+        
+        // Without a policy the window can't be trusted
+        assert!(verifier.check_proof_context(&fresh).is_err());
+        
+        let verifier = verifier.with_review_policy(ReviewPolicy { max_review_age: 86_400, clock_skew: 60 });
+        assert_eq!(
+            verifier.transfer_layout.review_window(&fresh),
+            Some((F::from(now), F::from(86_400u64)))
+        );
+        assert!(verifier.check_proof_context(&fresh).is_ok());
+        
+        // A looser age limit than the policy's
+        match verifier.check_proof_context(&transfer(F::from(now), F::from(u64::MAX))) {
+            Err(FluxeError::ComplianceViolation(reason)) => assert!(reason.contains("max_review_age")),
+            other => panic!("expected max_review_age rejection, got {:?}", other),
+        }
+        
+        // A backdated clock, when every input's review was still fresh
+        match verifier.check_proof_context(&transfer(F::from(now - 3600), F::from(86_400u64))) {
+            Err(FluxeError::ComplianceViolation(reason)) => assert!(reason.contains("current_time")),
+            other => panic!("expected current_time rejection, got {:?}", other),
+        }
+        assert!(verifier.check_proof_context(&transfer(-F::from(1u64), F::from(86_400u64))).is_err());
+    }
+
+    #[test]
+    fn test_callback_ops_cap() {
+        let (verifier, proof) = create_mock_verifier_and_proof();
+        let mut verifier = verifier.with_max_callback_ops(2);
+        let roots = verifier.get_current_roots();
+        let object_update = |n: u64| VerifiedTransaction {
+            tx_type: TransactionType::ObjectUpdate,
+            proof: proof.clone(),
+            public_inputs: vec![],
+            old_roots: roots.clone(),
+            new_roots: roots.clone(),
+            transaction_data: TransactionData::ObjectUpdate {
+                old_object_cm: F::from(1u64),
+                new_object_cm: F::from(2u64),
+                callback_ops: (0..n).map(|i| CallbackOperation::Process(F::from(i))).collect(),
+                owner: None,
+            },
+        };
+        
+        assert!(verifier.add_transaction(object_update(3)).is_err());
+        verifier.add_transaction(object_update(2)).unwrap();
+        
+        // Padding fills the remaining slots with no-ops
+        let ops = [CallbackOperation::Process(F::from(5u64))];
+        let padded = CallbackOperation::padded_hashes(&ops, MAX_CALLBACK_OPS).unwrap();
+        assert_eq!(padded.len(), MAX_CALLBACK_OPS);
+        assert_eq!(padded[0], ops[0].hash());
+        assert!(padded[1..].iter().all(|h| *h == F::from(0u64)));
+    }
+
+    #[test]
+    fn test_rotate_keys() {
+        let (mut verifier, old_proof) = create_mock_verifier_and_proof();
+        let roots = verifier.get_current_roots();
+        let old_fingerprints = verifier.active_key_fingerprints().to_vec();
+        assert_eq!(old_fingerprints.len(), 4);
+        assert!(verifier.validate_only(&mock_mint(roots.clone(), old_proof.clone())).is_ok());
+
+        let (new_vk, new_proof) = create_mock_key_and_proof();
+        verifier.rotate_keys(VerifyingKeySet {
+            mint: Some(new_vk),
+            ..Default::default()
+        });
+
+        // Only the new mint key is active
+        let fingerprints = verifier.active_key_fingerprints();
+        assert_eq!(fingerprints.len(), 1);
+        assert_eq!(fingerprints[0].0, TransactionType::Mint);
+        assert_ne!(fingerprints[0].1, old_fingerprints[0].1);
+        assert_eq!(verifier.loaded_circuit_types(), vec![TransactionType::Mint]);
+
+        // Proofs under the old key are rejected, new-key proofs accepted
+        assert!(verifier.add_transaction(mock_mint(roots.clone(), old_proof)).is_err());
+        assert!(verifier.add_transaction(mock_mint(roots, new_proof)).is_ok());
+
+        println!("✓ Key rotation test passed");
+    }
+
     #[test]
     fn test_server_verifier_creation() {
         let state = StateManager::new(32);
@@ -1,12 +1,85 @@
+use crate::bloom::FieldBloom;
 use crate::crypto::poseidon_hash;
-use crate::data_structures::{IngressReceipt, ExitReceipt, CallbackInvocation};
-use crate::merkle::{IncrementalTree, SortedTree, MerklePath, TreeParams};
+use crate::data_structures::{IngressReceipt, ExitReceipt, ZkObject};
+use crate::merkle::{IncrementalTree, SortedTree, SortedMembershipProof, MerklePath, TreeConfig, TreeParams};
 use crate::types::{*, StateRoots};
 use ark_bls12_381::Fr as F;
 use ark_ff::Zero;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+
+/// Tree depth of the canonical deployment (and of `StateManager::empty_roots`)
+pub const DEFAULT_TREE_DEPTH: usize = 32;
+
+/// Nullifiers the initial bloom filter is sized for
+pub const INITIAL_BLOOM_CAPACITY: usize = 4096;
+
+/// Default bloom filter false-positive target
+pub const DEFAULT_BLOOM_FP_RATE: f64 = 0.01;
+
+/// CMT roots a proof may still be built against by default
+pub const DEFAULT_ROOT_HISTORY: usize = 128;
+
+/// Knobs for `StateManager::maintenance`, run periodically by a node
+#[derive(Clone, Debug)]
+pub struct MaintenanceConfig {
+    /// How often the API server runs maintenance
+    pub interval: std::time::Duration,
+    
+    /// Batches of event log kept behind the current batch
+    pub keep_batches: u64,
+    
+    /// False-positive rate the rebuilt nullifier bloom filter targets
+    pub bloom_fp_rate: f64,
+    
+    /// Headroom the rebuilt filter is sized for, as a multiple of the current nullifier count
+    pub bloom_growth: usize,
+    
+    /// Most recent rejections kept in the verifier's rejection log
+    pub max_rejections: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            interval: std::time::Duration::from_secs(600),
+            keep_batches: 1000,
+            bloom_fp_rate: DEFAULT_BLOOM_FP_RATE,
+            bloom_growth: 2,
+            max_rejections: 10_000,
+        }
+    }
+}
+
+/// What a maintenance pass freed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Event-log entries pruned
+    pub events_pruned: usize,
+    
+    /// Bloom filter size before and after the rebuild
+    pub bloom_bytes_before: usize,
+    pub bloom_bytes_after: usize,
+    
+    /// Rejection-log entries trimmed (verifier only)
+    pub rejections_trimmed: usize,
+}
+
+/// Point-in-time copy of a `StateManager`: trees, roots, supply, logs and staging
+/// Taken with `StateManager::snapshot`, put back with `StateManager::restore`
+#[derive(Clone)]
+pub struct StateSnapshot {
+    state: Box<StateManager>,
+}
+
+impl StateSnapshot {
+    /// Roots the snapshot restores
+    pub fn roots(&self) -> StateRoots {
+        self.state.get_roots()
+    }
+}
 
 /// Global state manager for Fluxe protocol
+#[derive(Clone)]
 pub struct StateManager {
     /// Commitment Tree (append-only) for note commitments
     pub cmt_tree: IncrementalTree,
@@ -29,33 +102,147 @@ pub struct StateManager {
     /// Reference roots (updated out-of-band)
     pub sanctions_root: MerkleRoot,
     pub pool_rules_root: MerkleRoot,
+    pub exit_allow_root: MerkleRoot,
+    pub authorized_minters_root: MerkleRoot,
     
     /// Supply accounting per asset type
     pub supply: HashMap<AssetType, Amount>,
     
     /// Tree parameters
     pub params: TreeParams,
+    
+    /// Height of each tree, which provers must build their witnesses for
+    pub tree_config: TreeConfig,
+    
+    /// Batch that new events are recorded under
+    pub batch_id: u64,
+    
+    /// Log of applied transitions, oldest first (prunable; roots do not depend on it)
+    pub events: Vec<StateEvent>,
+    
+    /// Batches a spent nullifier stays reversible before it is final (0 = final immediately)
+    pub confirmation_delay: u64,
+    
+    /// Rollback journal: nullifiers spent within the confirmation delay, in spend order,
+    /// with the batch that spent them. They are already in the NFT
+    pub staged_nullifiers: Vec<(u64, Nullifier)>,
+    
+    /// Bloom filter over spent nullifiers, a fast negative for `nullifier_exists`;
+    /// insert into the NFT through `insert_nullifier` to keep it in sync
+    pub nf_bloom: FieldBloom,
+    
+    /// Most recent CMT roots, oldest first, capped at `root_history_len`
+    pub cmt_root_history: VecDeque<MerkleRoot>,
+    
+    /// How many CMT roots `is_recent_cmt_root` accepts
+    pub root_history_len: usize,
+    
+    /// Latest object commitment of each indexed owner, followed through object updates
+    pub owner_objects: HashMap<AuthAddr, Commitment>,
+    
+    /// Owner of each indexed object commitment (reverse of `owner_objects`)
+    pub object_owners: HashMap<Commitment, AuthAddr>,
+    
+    /// Authenticated `owner_objects`: one `ZkObject::owner_index_key` per indexed owner,
+    /// replaced when their object changes so only current objects are members
+    pub owner_index: SortedTree,
 }
 
 impl StateManager {
-    /// Create a new state manager
+    /// Create a new state manager with every tree at `tree_depth`
     pub fn new(tree_depth: usize) -> Self {
-        let params = TreeParams::new(tree_depth);
+        Self::from_tree_config(TreeConfig::uniform(tree_depth))
+    }
+    
+    /// Create a new state manager with each tree at its configured height
+    pub fn from_tree_config(tree_config: TreeConfig) -> Self {
+        let params = TreeParams::new(tree_config.cmt);
+        let cmt_tree = IncrementalTree::new(tree_config.cmt);
+        let cmt_tree_root = cmt_tree.root();
         
         Self {
-            cmt_tree: IncrementalTree::new(tree_depth),
-            nft_tree: SortedTree::new(tree_depth),
-            obj_tree: IncrementalTree::new(tree_depth),
-            cb_tree: SortedTree::new(tree_depth),
-            ingress_tree: IncrementalTree::new(tree_depth),
-            exit_tree: IncrementalTree::new(tree_depth),
+            cmt_tree,
+            nft_tree: SortedTree::new(tree_config.nft),
+            obj_tree: IncrementalTree::new(tree_config.object),
+            cb_tree: SortedTree::new(tree_config.cb),
+            ingress_tree: IncrementalTree::new(tree_config.ingress),
+            exit_tree: IncrementalTree::new(tree_config.exit),
             sanctions_root: F::zero(),
             pool_rules_root: F::zero(),
+            exit_allow_root: F::zero(),
+            authorized_minters_root: F::zero(),
             supply: HashMap::new(),
             params,
+            tree_config,
+            batch_id: 0,
+            events: Vec::new(),
+            confirmation_delay: 0,
+            staged_nullifiers: Vec::new(),
+            nf_bloom: FieldBloom::with_rate(INITIAL_BLOOM_CAPACITY, DEFAULT_BLOOM_FP_RATE),
+            cmt_root_history: VecDeque::from([cmt_tree_root]),
+            root_history_len: DEFAULT_ROOT_HISTORY,
+            owner_objects: HashMap::new(),
+            object_owners: HashMap::new(),
+            owner_index: SortedTree::new(tree_config.object),
+        }
+    }
+    
+    /// Capture the full state so a failed batch can be rolled back
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            state: Box::new(self.clone()),
         }
     }
     
+    /// Roll back to `snapshot`, discarding everything applied since it was taken
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        *self = *snapshot.state;
+    }
+    
+    /// Accept proofs built against any of the last `n` CMT roots (at least the current one)
+    pub fn with_root_history(mut self, n: usize) -> Self {
+        self.root_history_len = n.max(1);
+        self.trim_root_history();
+        self
+    }
+    
+    /// Record the current CMT root in the history window; call after CMT appends
+    pub fn checkpoint_cmt_root(&mut self) {
+        let root = self.cmt_tree.root();
+        if self.cmt_root_history.back() != Some(&root) {
+            self.cmt_root_history.push_back(root);
+            self.trim_root_history();
+        }
+    }
+    
+    fn trim_root_history(&mut self) {
+        while self.cmt_root_history.len() > self.root_history_len {
+            self.cmt_root_history.pop_front();
+        }
+    }
+    
+    /// Whether `root` is the current CMT root or one of the recent roots in the window
+    pub fn is_recent_cmt_root(&self, root: MerkleRoot) -> bool {
+        root == self.cmt_tree.root() || self.cmt_root_history.contains(&root)
+    }
+    
+    /// Keep spent nullifiers reversible for `batches` batches, so a reorg can roll them back.
+    /// They enter the NFT, and move NFT_ROOT, when spent; `finalize_nullifiers` only drops
+    /// them from the rollback journal
+    pub fn with_confirmation_delay(mut self, batches: u64) -> Self {
+        self.confirmation_delay = batches;
+        self
+    }
+    
+    /// Canonical initial roots at DEFAULT_TREE_DEPTH, for conformance testing
+    ///
+    /// - cmt, obj, ingress, exit: root of an empty append-only tree (all leaves zero)
+    /// - nft, cb: root of a sorted tree holding only the key-0 sentinel leaf
+    /// - sanctions, pool_rules, exit_allow: zero until set out-of-band
+    pub fn empty_roots() -> StateRoots {
+        Self::from_tree_config(TreeConfig::default()).get_roots()
+    }
+    
     /// Get current state roots
     pub fn get_roots(&self) -> StateRoots {
         StateRoots {
@@ -67,6 +254,8 @@ impl StateManager {
             exit_root: self.exit_tree.root(),
             sanctions_root: self.sanctions_root,
             pool_rules_root: self.pool_rules_root,
+            exit_allow_root: self.exit_allow_root,
+            authorized_minters_root: self.authorized_minters_root,
         }
     }
     
@@ -80,12 +269,15 @@ impl StateManager {
         
         // Add ingress receipt
         let ingress_hash = ingress_receipt.hash();
+        let ingress_leaves = self.ingress_tree.num_leaves();
         self.ingress_tree.append(ingress_hash);
+        Self::assert_append_only("INGRESS", ingress_leaves, self.ingress_tree.num_leaves());
         
         // Add output commitments to CMT tree
         for &cm in output_commitments {
             self.cmt_tree.append(cm);
         }
+        self.checkpoint_cmt_root();
         
         // Update supply
         let supply_entry = self.supply.entry(ingress_receipt.asset_type).or_insert(Amount::zero());
@@ -93,14 +285,14 @@ impl StateManager {
         
         let new_roots = self.get_roots();
         
-        Ok(TransitionProof {
+        Ok(self.record(TransitionProof {
             old_roots,
             new_roots,
             operations: vec![
                 StateOperation::IngressAppend(ingress_hash),
                 StateOperation::CmtAppend(output_commitments.to_vec()),
             ],
-        })
+        }))
     }
     
     /// Process a burn transaction (boundary-out)
@@ -112,16 +304,18 @@ impl StateManager {
         let old_roots = self.get_roots();
         
         // Check nullifier doesn't exist (prevent double spend)
-        if self.nft_tree.contains(&nullifier) {
+        if self.nullifier_exists(nullifier) {
             return Err(StateError::DoubleSpend(nullifier));
         }
         
         // Add nullifier to NFT tree
-        self.nft_tree.insert(nullifier)?;
+        self.spend_nullifier(nullifier)?;
         
         // Add exit receipt
         let exit_hash = exit_receipt.hash();
+        let exit_leaves = self.exit_tree.num_leaves();
         self.exit_tree.append(exit_hash);
+        Self::assert_append_only("EXIT", exit_leaves, self.exit_tree.num_leaves());
         
         // Update supply
         let supply_entry = self.supply.get_mut(&exit_receipt.asset_type)
@@ -135,14 +329,14 @@ impl StateManager {
         
         let new_roots = self.get_roots();
         
-        Ok(TransitionProof {
+        Ok(self.record(TransitionProof {
             old_roots,
             new_roots,
             operations: vec![
                 StateOperation::NftInsert(nullifier),
                 StateOperation::ExitAppend(exit_hash),
             ],
-        })
+        }))
     }
     
     /// Process a transfer transaction (in-protocol)
@@ -155,62 +349,211 @@ impl StateManager {
         
         // Check all nullifiers are fresh
         for &nf in input_nullifiers {
-            if self.nft_tree.contains(&nf) {
+            if self.nullifier_exists(nf) {
                 return Err(StateError::DoubleSpend(nf));
             }
         }
         
         // Add nullifiers to NFT tree
         for &nf in input_nullifiers {
-            self.nft_tree.insert(nf)?;
+            self.spend_nullifier(nf)?;
         }
         
         // Add output commitments to CMT tree
         for &cm in output_commitments {
             self.cmt_tree.append(cm);
         }
+        self.checkpoint_cmt_root();
         
         let new_roots = self.get_roots();
         
-        Ok(TransitionProof {
+        Ok(self.record(TransitionProof {
             old_roots,
             new_roots,
             operations: vec![
                 StateOperation::NftBatchInsert(input_nullifiers.to_vec()),
                 StateOperation::CmtAppend(output_commitments.to_vec()),
             ],
-        })
+        }))
     }
     
     /// Process an object update
+    /// A Freeze escalation appends the object committing to the frozen state
+    /// (see `ZkObject::apply_freeze`) and is logged against the owner
     pub fn process_object_update(
         &mut self,
         new_object_commitment: Commitment,
-        callback_invocation: Option<&CallbackInvocation>,
+        callback_op: Option<&CallbackOperation>,
     ) -> Result<TransitionProof, StateError> {
         let old_roots = self.get_roots();
         
         // Add new object commitment
         self.obj_tree.append(new_object_commitment);
         
-        // If there's a callback invocation, add it to CB tree
-        if let Some(invocation) = callback_invocation {
-            let cb_hash = invocation.hash();
-            self.cb_tree.insert(cb_hash)?;
+        let mut operations = vec![StateOperation::ObjAppend(new_object_commitment)];
+        match callback_op {
+            // If there's a callback invocation, add it to CB tree
+            Some(CallbackOperation::Add(invocation)) => {
+                let cb_hash = invocation.hash();
+                self.cb_tree.insert(cb_hash)?;
+                operations.push(StateOperation::CbInsert(cb_hash));
+            }
+            Some(CallbackOperation::Freeze(owner_addr)) => {
+                self.index_object(*owner_addr, new_object_commitment);
+                operations.push(StateOperation::Freeze(*owner_addr));
+            }
+            Some(CallbackOperation::Process(_)) | None => {}
         }
         
         let new_roots = self.get_roots();
         
-        let mut operations = vec![StateOperation::ObjAppend(new_object_commitment)];
-        if let Some(invocation) = callback_invocation {
-            operations.push(StateOperation::CbInsert(invocation.hash()));
-        }
-        
-        Ok(TransitionProof {
+        Ok(self.record(TransitionProof {
             old_roots,
             new_roots,
             operations,
-        })
+        }))
+    }
+    
+    /// Record `object_cm` as the owner's current object, replacing any earlier one
+    pub fn index_object(&mut self, owner_addr: AuthAddr, object_cm: Commitment) {
+        if let Some(previous) = self.owner_objects.insert(owner_addr, object_cm) {
+            self.object_owners.remove(&previous);
+            // Present whenever owner_objects held it
+            let _ = self.owner_index.remove(ZkObject::owner_index_key(owner_addr, previous));
+        }
+        self.object_owners.insert(object_cm, owner_addr);
+        let _ = self.owner_index.insert(ZkObject::owner_index_key(owner_addr, object_cm));
+    }
+    
+    /// Follow an object update: the owner of `old_object_cm`, if indexed, now holds `new_object_cm`
+    pub fn reindex_object(&mut self, old_object_cm: Commitment, new_object_cm: Commitment) {
+        if let Some(owner_addr) = self.object_owners.get(&old_object_cm).copied() {
+            self.index_object(owner_addr, new_object_cm);
+        }
+    }
+    
+    /// Current object commitment of an owner, if their object is indexed
+    pub fn object_for_owner(&self, owner_addr: AuthAddr) -> Option<Commitment> {
+        self.owner_objects.get(&owner_addr).copied()
+    }
+    
+    /// Root of the owner index, which transfers blocking frozen recipients prove against
+    pub fn owner_index_root(&self) -> MerkleRoot {
+        self.owner_index.root()
+    }
+    
+    /// Membership proof of an owner's current object in the owner index
+    pub fn prove_owner_object(&self, owner_addr: AuthAddr) -> Option<SortedMembershipProof> {
+        let object_cm = self.object_for_owner(owner_addr)?;
+        self.owner_index.prove_key_membership(ZkObject::owner_index_key(owner_addr, object_cm))
+    }
+    
+    /// Insert a spent nullifier into the NFT, journaling it for rollback if a confirmation
+    /// delay is set. The NFT root moves either way, as the spend's proof claims
+    /// Batches spend through this; `insert_nullifier` is never journaled
+    pub fn spend_nullifier(&mut self, nullifier: Nullifier) -> Result<(), StateError> {
+        self.insert_nullifier(nullifier)?;
+        if self.confirmation_delay > 0 {
+            self.staged_nullifiers.push((self.batch_id, nullifier));
+        }
+        Ok(())
+    }
+    
+    /// Insert a nullifier straight into the NFT, keeping the bloom filter in sync
+    pub fn insert_nullifier(&mut self, nullifier: Nullifier) -> Result<MerklePath, String> {
+        let path = self.nft_tree.insert(nullifier)?;
+        self.nf_bloom.insert(&nullifier);
+        Ok(path)
+    }
+    
+    /// Make journaled nullifiers spent at or before `up_to_batch` final, dropping them
+    /// from the rollback journal; the NFT is unchanged
+    /// Nullifiers younger than the confirmation delay stay reversible regardless.
+    /// Returns the number finalized.
+    pub fn finalize_nullifiers(&mut self, up_to_batch: u64) -> usize {
+        let confirmed_batch = self.batch_id.saturating_sub(self.confirmation_delay);
+        let up_to_batch = up_to_batch.min(confirmed_batch);
+        
+        let before = self.staged_nullifiers.len();
+        self.staged_nullifiers.retain(|(batch_id, _)| *batch_id > up_to_batch);
+        before - self.staged_nullifiers.len()
+    }
+    
+    /// Un-spend journaled nullifiers from `from_batch` onward (e.g. after a reorg),
+    /// removing them from the NFT newest first so its root returns to before their spends
+    /// Finalized nullifiers are permanent and unaffected. Returns the number removed.
+    pub fn rollback_nullifiers(&mut self, from_batch: u64) -> Result<usize, StateError> {
+        let keep = self.staged_nullifiers
+            .iter()
+            .position(|(batch_id, _)| *batch_id >= from_batch)
+            .unwrap_or(self.staged_nullifiers.len());
+        let removed: Vec<_> = self.staged_nullifiers.drain(keep..).collect();
+        for &(_, nf) in removed.iter().rev() {
+            self.nft_tree.remove(nf)?; // left in the bloom filter until the next rebuild
+        }
+        Ok(removed.len())
+    }
+    
+    /// Invariant: the boundary trees are append-only, every transition adds leaves
+    /// Checked in debug builds only
+    fn assert_append_only(tree: &str, leaves_before: usize, leaves_after: usize) {
+        debug_assert!(
+            leaves_after > leaves_before,
+            "{} tree must only grow: {} leaves before, {} after",
+            tree,
+            leaves_before,
+            leaves_after
+        );
+    }
+    
+    /// Append a transition to the event log under the current batch
+    pub fn record(&mut self, transition: TransitionProof) -> TransitionProof {
+        self.events.push(StateEvent {
+            batch_id: self.batch_id,
+            transition: transition.clone(),
+        });
+        transition
+    }
+    
+    /// Drop event-log entries recorded before `batch_id`
+    /// Trees, roots and supply are untouched, so current proofs stay valid.
+    /// Returns the number of events removed.
+    pub fn prune_before(&mut self, batch_id: u64) -> usize {
+        let before = self.events.len();
+        self.events.retain(|event| event.batch_id >= batch_id);
+        before - self.events.len()
+    }
+    
+    /// Periodic upkeep: prune the event log to the last `keep_batches` batches and rebuild
+    /// the nullifier bloom filter for the current nullifier count at the target rate,
+    /// dropping stale entries from rolled-back nullifiers. Roots and spent-checks are unchanged
+    pub fn maintenance(&mut self, config: &MaintenanceConfig) -> MaintenanceReport {
+        let events_pruned = self.prune_before(self.batch_id.saturating_sub(config.keep_batches));
+        
+        let bloom_bytes_before = self.nf_bloom.memory_bytes();
+        let nullifiers: Vec<Nullifier> = self.nft_tree
+            .keys()
+            .into_iter()
+            .filter(|key| !key.is_zero())
+            .collect();
+        let capacity = nullifiers.len() * config.bloom_growth.max(1);
+        let mut bloom = FieldBloom::with_rate(capacity, config.bloom_fp_rate);
+        for nf in &nullifiers {
+            bloom.insert(nf);
+        }
+        self.nf_bloom = bloom;
+        
+        MaintenanceReport {
+            events_pruned,
+            bloom_bytes_before,
+            bloom_bytes_after: self.nf_bloom.memory_bytes(),
+            rejections_trimmed: 0,
+        }
+    }
+    
+    /// Approximate heap held by the prunable structures (event log and bloom filter)
+    pub fn maintenance_footprint(&self) -> usize {
+        self.events.len() * std::mem::size_of::<StateEvent>() + self.nf_bloom.memory_bytes()
     }
     
     /// Get Merkle proof for a commitment
@@ -223,8 +566,11 @@ impl StateManager {
         self.nft_tree.get_non_membership_proof(nullifier)
     }
     
-    /// Check if a nullifier exists
+    /// Check if a nullifier is spent, whether final or still within the confirmation delay
     pub fn nullifier_exists(&self, nullifier: Nullifier) -> bool {
+        if !self.nf_bloom.may_contain(&nullifier) {
+            return false;
+        }
         self.nft_tree.contains(&nullifier)
     }
     
@@ -242,9 +588,26 @@ impl StateManager {
     pub fn update_pool_rules_root(&mut self, new_root: MerkleRoot) {
         self.pool_rules_root = new_root;
     }
+    
+    /// Update approved exit destinations root (admin operation)
+    pub fn update_exit_allow_root(&mut self, new_root: MerkleRoot) {
+        self.exit_allow_root = new_root;
+    }
+    
+    /// Update authorized minters root (admin operation)
+    pub fn update_authorized_minters_root(&mut self, new_root: MerkleRoot) {
+        self.authorized_minters_root = new_root;
+    }
 }
 
 
+/// Event-log entry: a transition applied during a batch
+#[derive(Clone, Debug)]
+pub struct StateEvent {
+    pub batch_id: u64,
+    pub transition: TransitionProof,
+}
+
 /// State transition proof
 #[derive(Clone, Debug)]
 pub struct TransitionProof {
@@ -264,8 +627,8 @@ impl TransitionProof {
     /// Get the state transition hash
     pub fn hash(&self) -> F {
         poseidon_hash(&[
-            self.old_roots.hash(),
-            self.new_roots.hash(),
+            self.old_roots.digest(),
+            self.new_roots.digest(),
             F::from(self.operations.len() as u64),
         ])
     }
@@ -281,6 +644,7 @@ pub enum StateOperation {
     CbInsert(F),
     IngressAppend(F),
     ExitAppend(F),
+    Freeze(AuthAddr),
 }
 
 /// State manager errors
@@ -304,6 +668,17 @@ impl From<String> for StateError {
     }
 }
 
+impl From<StateError> for FluxeError {
+    fn from(e: StateError) -> Self {
+        match e {
+            StateError::DoubleSpend(nullifier) => FluxeError::DoubleSpend(nullifier),
+            StateError::InsufficientSupply => FluxeError::InsufficientBalance,
+            StateError::TreeError(msg) => FluxeError::Other(msg),
+            StateError::InvalidProof => FluxeError::InvalidProof("State transition proof rejected".to_string()),
+        }
+    }
+}
+
 /// Non-membership proof for nullifiers
 #[derive(Clone, Debug)]
 pub struct NonMembershipProof {
@@ -382,6 +757,191 @@ mod tests {
         println!("✓ Double spend prevention test passed");
     }
     
+    #[test]
+    fn test_staged_nullifiers() {
+        let mut manager = StateManager::new(32).with_confirmation_delay(2);
+        let mut rng = thread_rng();
+        let empty_nft_root = manager.nft_tree.root();
+        
+        // Nullifiers spent in batches 1 and 2 enter the NFT at once and are journaled
+        manager.batch_id = 1;
+        let kept = F::rand(&mut rng);
+        let reorged = F::rand(&mut rng);
+        manager.process_transfer(&[kept], &[F::rand(&mut rng)]).unwrap();
+        let kept_nft_root = manager.nft_tree.root();
+        assert_ne!(kept_nft_root, empty_nft_root);
+        manager.batch_id = 2;
+        manager.process_transfer(&[reorged], &[F::rand(&mut rng)]).unwrap();
+        assert_eq!(manager.staged_nullifiers.len(), 2);
+        
+        // A journaled nullifier blocks a double spend
+        let result = manager.process_transfer(&[reorged], &[F::rand(&mut rng)]);
+        assert!(matches!(result, Err(StateError::DoubleSpend(_))));
+        
+        // Batch 2 is reorged away: its nullifier leaves the NFT and can be spent again
+        assert_eq!(manager.rollback_nullifiers(2).unwrap(), 1);
+        assert!(!manager.nullifier_exists(reorged));
+        assert!(manager.nullifier_exists(kept));
+        assert_eq!(manager.nft_tree.root(), kept_nft_root);
+        
+        // Batch 1 is not yet two batches deep
+        assert_eq!(manager.finalize_nullifiers(1), 0);
+        manager.batch_id = 3;
+        assert_eq!(manager.finalize_nullifiers(1), 1);
+        assert!(manager.nft_tree.contains(&kept));
+        assert_eq!(manager.nft_tree.root(), kept_nft_root);
+        
+        // Finalized nullifiers survive a rollback and keep blocking double spends
+        assert_eq!(manager.rollback_nullifiers(0).unwrap(), 0);
+        let result = manager.process_transfer(&[kept], &[F::rand(&mut rng)]);
+        assert!(matches!(result, Err(StateError::DoubleSpend(_))));
+        
+        println!("✓ Staged nullifiers test passed");
+    }
+    
+    #[test]
+    fn test_prune_before() {
+        let mut manager = StateManager::new(32);
+        let mut rng = thread_rng();
+        
+        let receipt = IngressReceipt {
+            asset_type: 1,
+            amount: Amount::from(1000u64),
+            beneficiary_cm: F::rand(&mut rng),
+            nonce: 1,
+            aux: F::zero(),
+        };
+        let minted = F::rand(&mut rng);
+        manager.process_mint(&receipt, &[minted]).unwrap();
+        
+        // One transfer in each of the next two batches
+        for batch_id in 1..=2 {
+            manager.batch_id = batch_id;
+            manager.process_transfer(&[F::rand(&mut rng)], &[F::rand(&mut rng)]).unwrap();
+        }
+        assert_eq!(manager.events.len(), 3);
+        
+        let roots = manager.get_roots();
+        let path = manager.get_commitment_proof(minted).unwrap();
+        
+        assert_eq!(manager.prune_before(2), 2);
+        assert_eq!(manager.events.len(), 1);
+        assert_eq!(manager.events[0].batch_id, 2);
+        
+        // Roots, supply and membership proofs survive pruning
+        assert_eq!(manager.get_roots(), roots);
+        assert_eq!(manager.get_supply(1), Amount::from(1000u64));
+        let path_after = manager.get_commitment_proof(minted).unwrap();
+        assert_eq!(path_after.siblings, path.siblings);
+        assert!(path_after.verify(&roots.cmt_root, &manager.params));
+        
+        println!("✓ Prune before test passed");
+    }
+    
+    #[test]
+    fn test_maintenance() {
+        let mut manager = StateManager::new(32);
+        let mut rng = thread_rng();
+        
+        let mut spent = Vec::new();
+        for batch_id in 0..20 {
+            manager.batch_id = batch_id;
+            let nf = F::rand(&mut rng);
+            manager.process_transfer(&[nf], &[F::rand(&mut rng)]).unwrap();
+            spent.push(nf);
+        }
+        let roots = manager.get_roots();
+        let footprint = manager.maintenance_footprint();
+        
+        let config = MaintenanceConfig {
+            keep_batches: 5,
+            ..MaintenanceConfig::default()
+        };
+        let report = manager.maintenance(&config);
+        assert_eq!(report.events_pruned, 14);
+        assert!(report.bloom_bytes_after < report.bloom_bytes_before);
+        assert!(manager.maintenance_footprint() < footprint);
+        
+        // Roots unchanged, every spent nullifier is still caught, fresh ones still pass
+        assert_eq!(manager.get_roots(), roots);
+        for nf in &spent {
+            assert!(manager.nullifier_exists(*nf));
+            let result = manager.process_transfer(&[*nf], &[F::rand(&mut rng)]);
+            assert!(matches!(result, Err(StateError::DoubleSpend(_))));
+        }
+        assert!(manager.process_transfer(&[F::rand(&mut rng)], &[F::rand(&mut rng)]).is_ok());
+        
+        println!("✓ Maintenance test passed");
+    }
+    
+    #[test]
+    fn test_root_history() {
+        let mut state = StateManager::new(16).with_root_history(3);
+        let mut roots = vec![state.cmt_tree.root()];
+        
+        for i in 0..4u64 {
+            let receipt = IngressReceipt::new(1, Amount::from(10u64), F::from(i + 1), i);
+            state.process_mint(&receipt, &[F::from(100 + i)]).unwrap();
+            roots.push(state.cmt_tree.root());
+        }
+        
+        // Only the last three roots remain acceptable
+        assert_eq!(state.cmt_root_history.len(), 3);
+        assert!(!state.is_recent_cmt_root(roots[0]));
+        assert!(!state.is_recent_cmt_root(roots[1]));
+        for root in &roots[2..] {
+            assert!(state.is_recent_cmt_root(*root));
+        }
+        
+        // Shrinking the window keeps the newest roots
+        let state = state.with_root_history(1);
+        assert!(state.is_recent_cmt_root(roots[4]));
+        assert!(!state.is_recent_cmt_root(roots[3]));
+        
+        // The default window holds DEFAULT_ROOT_HISTORY roots
+        assert_eq!(StateManager::new(16).root_history_len, DEFAULT_ROOT_HISTORY);
+    }
+
+    #[test]
+    fn test_from_tree_config() {
+        use crate::merkle::MerkleTree;
+        
+        let config = TreeConfig { nft: 16, cb: 20, ..TreeConfig::default() };
+        let mut manager = StateManager::from_tree_config(config);
+        assert_eq!(manager.tree_config, config);
+        assert_eq!(manager.cmt_tree.height(), DEFAULT_TREE_DEPTH);
+        assert_eq!(MerkleTree::height(&manager.nft_tree), 16);
+        assert_eq!(MerkleTree::height(&manager.cb_tree), 20);
+        
+        // Paths handed to provers have the configured length
+        let path = manager.cmt_tree.append(F::from(7u64));
+        assert!(TreeConfig::check_siblings("CMT", config.cmt, path.siblings.len()).is_ok());
+        
+        println!("✓ Tree config test passed");
+    }
+    
+    #[test]
+    fn test_empty_roots() {
+        let empty = StateManager::empty_roots();
+        
+        // A fresh manager starts at the canonical roots, and they are stable
+        assert_eq!(StateManager::new(DEFAULT_TREE_DEPTH).get_roots(), empty);
+        assert_eq!(StateManager::empty_roots(), empty);
+        
+        // Trees of the same kind share the same empty root
+        assert_eq!(empty.obj_root, empty.cmt_root);
+        assert_eq!(empty.ingress_root, empty.cmt_root);
+        assert_eq!(empty.exit_root, empty.cmt_root);
+        assert_eq!(empty.cb_root, empty.nft_root);
+        assert_ne!(empty.nft_root, empty.cmt_root); // sentinel leaf
+        assert_eq!(empty.sanctions_root, F::zero());
+        assert_eq!(empty.pool_rules_root, F::zero());
+        assert_eq!(empty.exit_allow_root, F::zero());
+        assert_eq!(empty.authorized_minters_root, F::zero());
+        
+        println!("✓ Empty roots test passed");
+    }
+    
     #[test]
     fn test_state_roots_hash() {
         let roots1 = StateRoots {
@@ -393,6 +953,8 @@ mod tests {
             exit_root: F::from(6u64),
             sanctions_root: F::from(7u64),
             pool_rules_root: F::from(8u64),
+            exit_allow_root: F::from(9u64),
+            authorized_minters_root: F::from(10u64),
         };
         
         let roots2 = roots1.clone();
@@ -408,4 +970,51 @@ mod tests {
         
         println!("✓ State roots hash test passed");
     }
+    
+    #[test]
+    fn test_state_roots_digest() {
+        let roots1 = StateRoots {
+            cmt_root: F::from(1u64),
+            nft_root: F::from(2u64),
+            obj_root: F::from(3u64),
+            cb_root: F::from(4u64),
+            ingress_root: F::from(5u64),
+            exit_root: F::from(6u64),
+            sanctions_root: F::from(7u64),
+            pool_rules_root: F::from(8u64),
+            exit_allow_root: F::from(9u64),
+            authorized_minters_root: F::from(10u64),
+        };
+        
+        let roots2 = roots1.clone();
+        
+        // Same roots should have same digest
+        assert_eq!(roots1.digest(), roots2.digest());
+        
+        // Changing any single root changes the digest
+        let setters: [fn(&mut StateRoots); 10] = [
+            |r| r.cmt_root = F::from(100u64),
+            |r| r.nft_root = F::from(100u64),
+            |r| r.obj_root = F::from(100u64),
+            |r| r.cb_root = F::from(100u64),
+            |r| r.ingress_root = F::from(100u64),
+            |r| r.exit_root = F::from(100u64),
+            |r| r.sanctions_root = F::from(100u64),
+            |r| r.pool_rules_root = F::from(100u64),
+            |r| r.exit_allow_root = F::from(100u64),
+            |r| r.authorized_minters_root = F::from(100u64),
+        ];
+        for set in setters {
+            let mut changed = roots1.clone();
+            set(&mut changed);
+            assert_ne!(roots1.digest(), changed.digest());
+        }
+        
+        // Roots that are a permutation of each other digest differently
+        let mut swapped = roots1.clone();
+        std::mem::swap(&mut swapped.cmt_root, &mut swapped.nft_root);
+        assert_ne!(roots1.digest(), swapped.digest());
+        
+        println!("✓ State roots digest test passed");
+    }
 }
\ No newline at end of file
@@ -1,12 +1,13 @@
 use ark_bls12_381::Fr as F;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use ark_serialize::{Compress, Read, SerializationError, Validate, Write};
 // Using ark_serialize for cryptographic types
 
 pub mod field_wrapper;
 pub use field_wrapper::FieldElement;
 
 pub mod amount_wrapper;
-pub use amount_wrapper::Amount;
+pub use amount_wrapper::{Amount, DEFAULT_DECIMALS, MAX_DECIMALS};
 
 /// Asset type identifier (e.g., USDC, USDT)
 pub type AssetType = u32;
@@ -98,6 +99,10 @@ impl Supply {
 pub struct BlockHeader {
     pub prev_roots: StateRoots,
     pub new_roots: StateRoots,
+    /// Digest of new_roots, for comparing and transmitting the post-state compactly
+    pub roots_digest: F,
+    /// Root of the tree committing to the batch's transaction private ids, in block order
+    pub tx_root: MerkleRoot,
     pub batch_id: u64,
     pub agg_proof: Vec<u8>, // Placeholder for aggregated proof
     pub timestamp: Time,
@@ -114,6 +119,8 @@ pub struct StateRoots {
     pub exit_root: MerkleRoot,
     pub sanctions_root: MerkleRoot,
     pub pool_rules_root: MerkleRoot,
+    pub exit_allow_root: MerkleRoot,
+    pub authorized_minters_root: MerkleRoot,
 }
 
 impl Default for StateRoots {
@@ -133,11 +140,14 @@ impl StateRoots {
             exit_root: F::from(0),
             sanctions_root: F::from(0),
             pool_rules_root: F::from(0),
+            exit_allow_root: F::from(0),
+            authorized_minters_root: F::from(0),
         }
     }
     
-    /// Compute hash of all roots
-    pub fn hash(&self) -> F {
+    /// Single digest of all roots, Poseidon over them in declaration order:
+    /// cmt, nft, obj, cb, ingress, exit, sanctions, pool_rules, exit_allow, authorized_minters
+    pub fn digest(&self) -> F {
         use crate::crypto::poseidon_hash;
         poseidon_hash(&[
             self.cmt_root,
@@ -148,12 +158,19 @@ impl StateRoots {
             self.exit_root,
             self.sanctions_root,
             self.pool_rules_root,
+            self.exit_allow_root,
+            self.authorized_minters_root,
         ])
     }
+    
+    /// Compute hash of all roots (the same value as `digest`)
+    pub fn hash(&self) -> F {
+        self.digest()
+    }
 }
 
 /// Transaction types
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TransactionType {
     Mint,
     Burn,
@@ -161,6 +178,48 @@ pub enum TransactionType {
     ObjectUpdate,
 }
 
+impl CanonicalSerialize for TransactionType {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let tag: u8 = match self {
+            TransactionType::Mint => 0,
+            TransactionType::Burn => 1,
+            TransactionType::Transfer => 2,
+            TransactionType::ObjectUpdate => 3,
+        };
+        tag.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        1
+    }
+}
+
+impl Valid for TransactionType {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TransactionType {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(TransactionType::Mint),
+            1 => Ok(TransactionType::Burn),
+            2 => Ok(TransactionType::Transfer),
+            3 => Ok(TransactionType::ObjectUpdate),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
 /// Result type for Fluxe operations
 pub type FluxeResult<T> = Result<T, FluxeError>;
 
@@ -185,6 +244,9 @@ pub enum FluxeError {
     #[error("Serialization error: {0}")]
     SerializationError(String),
     
+    #[error("Tree error: {0}")]
+    Tree(crate::merkle::TreeError),
+    
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -201,8 +263,17 @@ impl From<&str> for FluxeError {
     }
 }
 
+impl From<crate::merkle::TreeError> for FluxeError {
+    fn from(e: crate::merkle::TreeError) -> Self {
+        FluxeError::Tree(e)
+    }
+}
+
 // CallbackInvocation is defined in data_structures::zk_object - avoiding duplicate definition
 
+/// Maximum callback operations per object update; the object-update circuit's op list has this fixed shape
+pub const MAX_CALLBACK_OPS: usize = 4;
+
 /// Operations on callbacks
 #[derive(Clone, Debug)]
 pub enum CallbackOperation {
@@ -210,4 +281,90 @@ pub enum CallbackOperation {
     Add(crate::data_structures::zk_object::CallbackInvocation),
     /// Process/mark as processed a callback by ticket
     Process(F),
+    /// Escalation: force-freeze the compliance state of this owner
+    Freeze(AuthAddr),
+}
+
+impl CallbackOperation {
+    /// Hash of the operation, tagged by kind so it is never zero (zero marks a no-op slot)
+    pub fn hash(&self) -> F {
+        use crate::crypto::poseidon_hash;
+        match self {
+            CallbackOperation::Add(invocation) => poseidon_hash(&[F::from(1u64), invocation.hash()]),
+            CallbackOperation::Process(ticket) => poseidon_hash(&[F::from(2u64), *ticket]),
+            CallbackOperation::Freeze(owner_addr) => poseidon_hash(&[F::from(3u64), *owner_addr]),
+        }
+    }
+    
+    /// Hashes of `ops` padded with no-ops to `max_ops` slots
+    pub fn padded_hashes(ops: &[CallbackOperation], max_ops: usize) -> Result<Vec<F>, FluxeError> {
+        if ops.len() > max_ops {
+            return Err(FluxeError::Other(format!(
+                "{} callback operations exceed the cap of {}",
+                ops.len(),
+                max_ops
+            )));
+        }
+        let mut hashes: Vec<F> = ops.iter().map(CallbackOperation::hash).collect();
+        hashes.resize(max_ops, F::from(0u64));
+        Ok(hashes)
+    }
+}
+
+impl CanonicalSerialize for CallbackOperation {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            CallbackOperation::Add(invocation) => {
+                0u8.serialize_with_mode(&mut writer, compress)?;
+                invocation.serialize_with_mode(&mut writer, compress)
+            }
+            CallbackOperation::Process(ticket) => {
+                1u8.serialize_with_mode(&mut writer, compress)?;
+                ticket.serialize_with_mode(&mut writer, compress)
+            }
+            CallbackOperation::Freeze(owner_addr) => {
+                2u8.serialize_with_mode(&mut writer, compress)?;
+                owner_addr.serialize_with_mode(&mut writer, compress)
+            }
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        1 + match self {
+            CallbackOperation::Add(invocation) => invocation.serialized_size(compress),
+            CallbackOperation::Process(ticket) => ticket.serialized_size(compress),
+            CallbackOperation::Freeze(owner_addr) => owner_addr.serialized_size(compress),
+        }
+    }
+}
+
+impl Valid for CallbackOperation {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for CallbackOperation {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            0 => Ok(CallbackOperation::Add(CanonicalDeserialize::deserialize_with_mode(
+                &mut reader, compress, validate,
+            )?)),
+            1 => Ok(CallbackOperation::Process(F::deserialize_with_mode(
+                &mut reader, compress, validate,
+            )?)),
+            2 => Ok(CallbackOperation::Freeze(F::deserialize_with_mode(
+                &mut reader, compress, validate,
+            )?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
 }
\ No newline at end of file
@@ -3,7 +3,17 @@ use ark_serialize::{SerializationError, Read, Write};
 use std::fmt;
 use ark_bls12_381::Fr as F;
 
+/// Decimals used for stablecoin assets (1 unit = 10^-6 of a token)
+pub const DEFAULT_DECIMALS: u8 = 6;
+
+/// Largest decimals value whose scale 10^decimals fits in a u128
+pub const MAX_DECIMALS: u8 = 38;
+
 /// Wrapper for u128 amounts with ark_serialize support
+///
+/// Amounts are fixed-point: the inner value counts base units, and an asset
+/// with `decimals` places has 10^decimals base units per whole token. All
+/// arithmetic, including in-circuit value conservation, works on base units.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct Amount(pub u128);
 
@@ -20,10 +30,58 @@ impl Amount {
         self.0
     }
     
-    /// Convert to field element (may truncate if amount is too large)
+    /// Convert to field element; lossless, since every u128 is below the field modulus
     pub fn to_field(&self) -> F {
-        // Safe for amounts up to ~2^64 which is sufficient for real-world use
-        F::from(self.0 as u64)
+        F::from(self.0)
+    }
+    
+    /// Parse a decimal string such as "1.250000" into base units for an asset
+    /// with the given number of decimals
+    pub fn from_decimal_str(s: &str, decimals: u8) -> Result<Self, String> {
+        if decimals > MAX_DECIMALS {
+            return Err(format!("Unsupported decimals: {}", decimals));
+        }
+        
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && frac.is_empty()) || !is_digits(whole) || !is_digits(frac) {
+            return Err(format!("Invalid decimal amount: {}", s));
+        }
+        if frac.len() > decimals as usize {
+            return Err(format!("Amount {} has more than {} decimal places", s, decimals));
+        }
+        
+        let scale = 10u128.pow(decimals as u32);
+        let whole_units: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| format!("Amount {} overflows", s))?
+        };
+        let frac_units: u128 = if frac.is_empty() {
+            0
+        } else {
+            frac.parse::<u128>().map_err(|_| format!("Invalid decimal amount: {}", s))?
+                * 10u128.pow((decimals as usize - frac.len()) as u32)
+        };
+        
+        whole_units
+            .checked_mul(scale)
+            .and_then(|units| units.checked_add(frac_units))
+            .map(Self)
+            .ok_or_else(|| format!("Amount {} overflows", s))
+    }
+    
+    /// Format base units as a decimal string with exactly `decimals` places
+    pub fn to_decimal_str(&self, decimals: u8) -> Result<String, String> {
+        if decimals > MAX_DECIMALS {
+            return Err(format!("Unsupported decimals: {}", decimals));
+        }
+        if decimals == 0 {
+            return Ok(self.0.to_string());
+        }
+        
+        let scale = 10u128.pow(decimals as u32);
+        Ok(format!("{}.{:0width$}", self.0 / scale, self.0 % scale, width = decimals as usize))
     }
 }
 
@@ -138,4 +196,41 @@ impl std::ops::Div<u128> for Amount {
     fn div(self, scalar: u128) -> Self {
         Self(self.0 / scalar)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        let amount = Amount::from_decimal_str("1.250000", DEFAULT_DECIMALS).unwrap();
+        assert_eq!(amount.value(), 1_250_000);
+        assert_eq!(amount.to_decimal_str(DEFAULT_DECIMALS).unwrap(), "1.250000");
+        
+        // Short fractions are scaled up, whole numbers need no point
+        assert_eq!(Amount::from_decimal_str("1.25", DEFAULT_DECIMALS).unwrap(), amount);
+        assert_eq!(Amount::from_decimal_str("3", DEFAULT_DECIMALS).unwrap().value(), 3_000_000);
+        assert_eq!(Amount::from(42u64).to_decimal_str(DEFAULT_DECIMALS).unwrap(), "0.000042");
+        assert_eq!(Amount::from(42u64).to_decimal_str(0).unwrap(), "42");
+        
+        // A misconfigured asset is an error, not a panic
+        assert!(amount.to_decimal_str(MAX_DECIMALS + 1).is_err());
+    }
+
+    #[test]
+    fn test_decimal_rejects_excess_precision() {
+        assert!(Amount::from_decimal_str("1.2500001", DEFAULT_DECIMALS).is_err());
+        assert!(Amount::from_decimal_str("1.5", 0).is_err());
+        assert!(Amount::from_decimal_str("1.2.3", DEFAULT_DECIMALS).is_err());
+        assert!(Amount::from_decimal_str("-1", DEFAULT_DECIMALS).is_err());
+        assert!(Amount::from_decimal_str(".", DEFAULT_DECIMALS).is_err());
+    }
+
+    #[test]
+    fn test_to_field_is_lossless() {
+        let wide = Amount::new((1u128 << 64) + 5);
+        assert_ne!(wide.to_field(), Amount::from(5u64).to_field());
+        assert_eq!(wide.to_field(), F::from(1u128 << 64) + F::from(5u64));
+    }
+}
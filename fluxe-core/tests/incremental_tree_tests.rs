@@ -226,4 +226,114 @@ fn test_different_tree_heights() {
         // Path should verify
         assert!(path.verify(&tree.root(), &params));
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_batch_append_onto_existing_tree() {
+    let mut batched = IncrementalTree::new(5);
+    let mut sequential = IncrementalTree::new(5);
+    let params = TreeParams::new(5);
+    let mut rng = thread_rng();
+    
+    // Start mid-subtree so the batch straddles existing nodes
+    let prefix: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+    batched.append_batch(&prefix);
+    for leaf in &prefix {
+        sequential.append(*leaf);
+    }
+    
+    let leaves: Vec<F> = (0..13).map(|_| F::rand(&mut rng)).collect();
+    let paths = batched.append_batch(&leaves);
+    for leaf in &leaves {
+        sequential.append(*leaf);
+    }
+    
+    assert_eq!(batched.root(), sequential.root());
+    for (offset, path) in paths.iter().enumerate() {
+        assert_eq!(path.leaf_index, 3 + offset);
+        assert!(path.verify(&batched.root(), &params));
+    }
+    
+    // Empty batch is a no-op
+    assert!(batched.append_batch(&[]).is_empty());
+    assert_eq!(batched.root(), sequential.root());
+}
+
+#[test]
+fn test_append_witnesses_batch() {
+    let mut tree = IncrementalTree::new(4);
+    let params = TreeParams::new(4);
+    let mut rng = thread_rng();
+    tree.append(F::rand(&mut rng));
+    
+    let leaves: Vec<F> = (0..5).map(|_| F::rand(&mut rng)).collect();
+    let witnesses = tree.generate_append_witnesses_batch(&leaves);
+    
+    // Witness generation leaves the tree untouched
+    assert_eq!(tree.num_leaves(), 1);
+    
+    // Each witness chains from the root left by the one before it
+    for (leaf, witness) in leaves.iter().zip(&witnesses) {
+        assert_eq!(witness.leaf_index, tree.num_leaves());
+        assert_eq!(witness.compute_old_root(&params), tree.root());
+        tree.append(*leaf);
+        assert_eq!(witness.compute_new_root(&params), tree.root());
+    }
+}
+
+#[test]
+fn test_frontier_tree_matches_full_tree() {
+    let mut full = IncrementalTree::new(6);
+    let mut frontier = IncrementalTree::new_frontier(6);
+    let params = TreeParams::new(6);
+    let mut rng = thread_rng();
+    
+    for i in 0..40 {
+        let leaf = F::rand(&mut rng);
+        
+        // Append witnesses agree before the append
+        let full_witness = full.generate_append_witness(leaf);
+        let frontier_witness = frontier.generate_append_witness(leaf);
+        assert_eq!(frontier_witness.pre_siblings, full_witness.pre_siblings);
+        assert_eq!(frontier_witness.last_leaf_path, full_witness.last_leaf_path);
+        
+        full.append(leaf);
+        let path = frontier.append(leaf);
+        assert_eq!(frontier.root(), full.root());
+        assert!(path.verify(&frontier.root(), &params));
+        
+        // At most two nodes per level survive
+        assert!(frontier.nodes().len() <= 2 * (params.height + 1));
+        
+        // Only the latest leaf keeps a path
+        if i > 0 {
+            assert!(frontier.get_path(i - 1).is_none());
+        }
+        assert_eq!(frontier.get_path(i), full.get_path(i));
+    }
+    
+    // Batch appends prune the same way
+    let leaves: Vec<F> = (0..17).map(|_| F::rand(&mut rng)).collect();
+    full.append_batch(&leaves);
+    frontier.append_batch(&leaves);
+    assert_eq!(frontier.root(), full.root());
+    assert_eq!(frontier.recompute_root(), full.recompute_root());
+    assert!(frontier.nodes().len() <= 2 * (params.height + 1));
+}
+
+#[test]
+fn test_frontier_tree_memory_is_bounded() {
+    let mut full = IncrementalTree::new(32);
+    let mut frontier = IncrementalTree::new_frontier(32);
+    
+    for chunk in 0..10u64 {
+        let leaves: Vec<F> = (0..200u64).map(|i| F::from(chunk * 200 + i + 1)).collect();
+        full.append_batch(&leaves);
+        frontier.append_batch(&leaves);
+        assert!(frontier.nodes().len() <= 2 * 33);
+    }
+    
+    assert_eq!(frontier.num_leaves(), 2000);
+    assert_eq!(frontier.root(), full.root());
+    assert!(full.nodes().len() > 2000);
+}